@@ -0,0 +1,397 @@
+//! This module applies the responses to prompts of the `patch` preset: unified diffs whose hunks
+//! name the anchor of the line they start at. Every hunk is checked against the anchors and the
+//! current content of its file before any file is changed, so a response written against other
+//! content, or misquoting it, is rejected instead of corrupting the code.
+
+use crate::messages::{message, message_with, Message};
+use crate::output::print_success;
+use crate::text::line_anchor;
+use crate::workspace::write_atomic;
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+/// The header of a hunk, with the anchor of its first line after it.
+static HUNK_HEADER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@\s*(\S*)").unwrap());
+
+/// The anchor of a line copied along with the line, such as `  12:3fa | `.
+static COPIED_ANCHOR: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*\d+:[0-9a-f]{3} \| ").unwrap());
+
+/// The path of the empty side of the diff of a new file.
+const DEV_NULL: &str = "/dev/null";
+
+/// The changes of a response to one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    /// The path of the file, as listed in the prompt.
+    pub path: String,
+    /// Whether the file is created, diffed against `/dev/null`.
+    pub creates: bool,
+    /// The hunks of the changes, in order.
+    pub hunks: Vec<Hunk>,
+}
+
+/// A hunk of a unified diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// The number of the first line of the hunk in the file, starting at 1.
+    pub old_start: usize,
+    /// The number of lines of the file the hunk covers.
+    pub old_count: usize,
+    /// The number of lines the hunk replaces them with.
+    pub new_count: usize,
+    /// The anchor of the first line of the hunk, if the header names one.
+    pub anchor: Option<String>,
+    /// The lines of the hunk.
+    pub lines: Vec<HunkLine>,
+}
+
+/// A line of a hunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    /// A line kept as is.
+    Context(String),
+    /// A line removed from the file.
+    Remove(String),
+    /// A line added to the file.
+    Add(String),
+}
+
+impl Hunk {
+    /// Returns the lines of the file the hunk covers.
+    ///
+    /// # Returns
+    ///
+    /// * `impl Iterator<Item = &str>` - The kept and removed lines, in order.
+    pub fn old_lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().filter_map(|line| match line {
+            HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+            HunkLine::Add(_) => None,
+        })
+    }
+
+    /// Returns the lines the hunk leaves in the file.
+    ///
+    /// # Returns
+    ///
+    /// * `impl Iterator<Item = &str>` - The kept and added lines, in order.
+    pub fn new_lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().filter_map(|line| match line {
+            HunkLine::Context(text) | HunkLine::Add(text) => Some(text.as_str()),
+            HunkLine::Remove(_) => None,
+        })
+    }
+
+    /// Checks whether the hunk holds as many lines as its header gives.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - Whether the hunk is complete.
+    fn is_complete(&self) -> bool {
+        self.old_lines().count() >= self.old_count && self.new_lines().count() >= self.new_count
+    }
+
+    /// Returns the index of the first line of the file the hunk covers, or inserts after.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The index of the line, starting at 0.
+    fn start_index(&self) -> usize {
+        // A hunk adding lines only starts after the line it names
+        if self.old_count == 0 {
+            self.old_start
+        } else {
+            self.old_start.saturating_sub(1)
+        }
+    }
+}
+
+/// Parses the unified diffs of a response, skipping the prose and code fences around them.
+///
+/// # Arguments
+///
+/// * `response` - The response of the model.
+///
+/// # Returns
+///
+/// * `Result<Vec<FilePatch>>` - The changes by file, in order, or an error if a hunk header is malformed.
+pub fn parse_patches(response: &str) -> Result<Vec<FilePatch>> {
+    let mut patches: Vec<FilePatch> = Vec::new();
+    let mut lines = response.lines().peekable();
+    while let Some(line) = lines.next() {
+        // Removed lines can start with `--- ` too, such as SQL comments
+        let in_hunk = patches
+            .last()
+            .and_then(|patch| patch.hunks.last())
+            .is_some_and(|hunk| !hunk.is_complete());
+        if let Some(old) = line.strip_prefix("--- ").filter(|_| !in_hunk) {
+            let Some(new) = lines.peek().and_then(|next| next.strip_prefix("+++ ")) else {
+                continue;
+            };
+            let (old, new) = (diff_path(old), diff_path(new));
+            lines.next();
+            patches.push(FilePatch {
+                path: if new == DEV_NULL { old } else { new },
+                creates: old == DEV_NULL,
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(patch) = patches.last_mut() else {
+            continue;
+        };
+        if line.starts_with("@@") {
+            patch.hunks.push(parse_hunk_header(line, &patch.path)?);
+            continue;
+        }
+        let Some(hunk) = patch.hunks.last_mut().filter(|hunk| !hunk.is_complete()) else {
+            continue;
+        };
+        let hunk_line = match line.chars().next() {
+            Some(' ') => HunkLine::Context(strip_copied_anchor(&line[1..])),
+            Some('-') => HunkLine::Remove(strip_copied_anchor(&line[1..])),
+            Some('+') => HunkLine::Add(strip_copied_anchor(&line[1..])),
+            // Empty context lines often lose their leading space on the way
+            None => HunkLine::Context(String::new()),
+            // Such as the `\ No newline at end of file` markers
+            Some(_) => continue,
+        };
+        hunk.lines.push(hunk_line);
+    }
+    Ok(patches)
+}
+
+/// Applies the hunks of a patch to the content of its file, after checking each against the
+/// anchor it names and the lines it covers.
+///
+/// # Arguments
+///
+/// * `patch` - The changes to the file.
+/// * `content` - The current content of the file, empty for a new file.
+///
+/// # Returns
+///
+/// * `Result<String>` - The patched content, or an error if a hunk does not match the file.
+pub fn apply_patch(patch: &FilePatch, content: &str) -> Result<String> {
+    let mut lines: Vec<String> = content.lines().map(|line| line.trim_end_matches('\r').to_string()).collect();
+    let mut end = 0;
+    for hunk in &patch.hunks {
+        let start = hunk.start_index();
+        if start < end {
+            bail!(message_with(Message::ApplyHunksOverlap, &[("path", &patch.path), ("line", &hunk.old_start)]));
+        }
+        if !patch.creates {
+            check_anchor(patch, hunk, &lines)?;
+        }
+        let old: Vec<&str> = hunk.old_lines().collect();
+        let matches = lines
+            .get(start..start + old.len())
+            .is_some_and(|current| current.iter().zip(&old).all(|(current, old)| current.trim_end() == old.trim_end()));
+        if !matches {
+            bail!(message_with(Message::ApplyContextMismatch, &[("path", &patch.path), ("line", &hunk.old_start)]));
+        }
+        end = start + old.len();
+    }
+
+    for hunk in patch.hunks.iter().rev() {
+        let start = hunk.start_index();
+        let old = hunk.old_lines().count();
+        lines.splice(start..start + old, hunk.new_lines().map(str::to_string));
+    }
+    let eol = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut patched = lines.join(eol);
+    if !patched.is_empty() && (content.is_empty() || content.ends_with('\n')) {
+        patched.push_str(eol);
+    }
+    Ok(patched)
+}
+
+/// Runs `codexio apply`: checks every hunk of a response against the files of a codebase, and
+/// applies them unless only checking. No file is changed unless every hunk matches.
+///
+/// # Arguments
+///
+/// * `root` - The canonical path of the codebase the prompt was generated from.
+/// * `response` - The file holding the response, or `-` to read it from stdin.
+/// * `check` - Whether to only check the hunks, without changing any file.
+///
+/// # Returns
+///
+/// * `Result<()>` - An error if the response cannot be read, or a hunk does not match its file.
+pub fn run_apply(root: &Path, response: &Path, check: bool) -> Result<()> {
+    let read_failed = || message_with(Message::ApplyReadFailed, &[("path", &response.display())]);
+    let text = if response == Path::new("-") {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text).with_context(read_failed)?;
+        text
+    } else {
+        fs::read_to_string(response).with_context(read_failed)?
+    };
+    let patches = parse_patches(&text)?;
+    if patches.iter().all(|patch| patch.hunks.is_empty()) {
+        bail!(message(Message::ApplyNoPatches));
+    }
+
+    let mut patched = Vec::new();
+    for patch in &patches {
+        let path = resolve_path(root, &patch.path)?;
+        let content = match (patch.creates, path.exists()) {
+            (true, false) => String::new(),
+            (true, true) => bail!(message_with(Message::ApplyFileExists, &[("path", &patch.path)])),
+            (false, true) => fs::read_to_string(&path)
+                .with_context(|| message_with(Message::ApplyReadFailed, &[("path", &path.display())]))?,
+            (false, false) => bail!(message_with(Message::ApplyFileNotFound, &[("path", &patch.path)])),
+        };
+        patched.push((path, apply_patch(patch, &content)?));
+    }
+
+    let hunks: usize = patches.iter().map(|patch| patch.hunks.len()).sum();
+    let files = patched.len();
+    if check {
+        print_success(&message_with(Message::ApplyChecked, &[("hunks", &hunks), ("files", &files)]));
+        return Ok(());
+    }
+    for (path, content) in &patched {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| message_with(Message::ApplyWriteFailed, &[("path", &path.display())]))?;
+        }
+        write_atomic(path, content.as_bytes())
+            .with_context(|| message_with(Message::ApplyWriteFailed, &[("path", &path.display())]))?;
+    }
+    print_success(&message_with(Message::ApplyApplied, &[("hunks", &hunks), ("files", &files)]));
+    Ok(())
+}
+
+/// Checks that a hunk names the anchor of the line it starts at, as the file holds it now.
+///
+/// # Arguments
+///
+/// * `patch` - The changes to the file.
+/// * `hunk` - The hunk.
+/// * `lines` - The current lines of the file.
+///
+/// # Returns
+///
+/// * `Result<()>` - An error if the anchor is missing or does not match the file.
+fn check_anchor(patch: &FilePatch, hunk: &Hunk, lines: &[String]) -> Result<()> {
+    // Lines added at the very top have no line to be anchored to
+    if hunk.old_start == 0 {
+        return Ok(());
+    }
+    let Some(anchor) = &hunk.anchor else {
+        bail!(message_with(Message::ApplyAnchorMissing, &[("path", &patch.path), ("line", &hunk.old_start)]));
+    };
+    let expected = lines.get(hunk.old_start - 1).map(|line| line_anchor(hunk.old_start, line));
+    if expected.as_ref() != Some(anchor) {
+        bail!(message_with(Message::ApplyAnchorMismatch, &[("path", &patch.path), ("anchor", anchor)]));
+    }
+    Ok(())
+}
+
+/// Parses the header of a hunk.
+///
+/// # Arguments
+///
+/// * `line` - The header, such as `@@ -12,3 +12,4 @@ 12:3fa`.
+/// * `path` - The path of the file, for the error.
+///
+/// # Returns
+///
+/// * `Result<Hunk>` - The hunk, without lines, or an error if the header is malformed.
+fn parse_hunk_header(line: &str, path: &str) -> Result<Hunk> {
+    let Some(captures) = HUNK_HEADER.captures(line) else {
+        bail!(message_with(Message::ApplyInvalidHunk, &[("path", &path), ("header", &line)]));
+    };
+    let number = |index: usize| {
+        captures
+            .get(index)
+            .map_or(Ok(1), |number| number.as_str().parse::<usize>())
+    };
+    let parsed = (number(1), number(2), number(4));
+    let (Ok(old_start), Ok(old_count), Ok(new_count)) = parsed else {
+        bail!(message_with(Message::ApplyInvalidHunk, &[("path", &path), ("header", &line)]));
+    };
+    Ok(Hunk {
+        old_start,
+        old_count,
+        new_count,
+        anchor: captures
+            .get(5)
+            .map(|anchor| anchor.as_str().to_string())
+            .filter(|anchor| !anchor.is_empty()),
+        lines: Vec::new(),
+    })
+}
+
+/// Returns the path of a side of a diff, without its `a/` or `b/` prefix and timestamp.
+///
+/// # Arguments
+///
+/// * `header` - The path of the `---` or `+++` line.
+///
+/// # Returns
+///
+/// * `String` - The path.
+fn diff_path(header: &str) -> String {
+    let path = header.split('\t').next().unwrap_or(header).trim();
+    path.strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path)
+        .to_string()
+}
+
+/// Removes the anchor a line was copied with from the prompt, if any.
+///
+/// # Arguments
+///
+/// * `line` - The line of the hunk, without its prefix.
+///
+/// # Returns
+///
+/// * `String` - The line without the anchor.
+fn strip_copied_anchor(line: &str) -> String {
+    COPIED_ANCHOR.replace(line, "").into_owned()
+}
+
+/// Resolves the path of a patched file against the codebase, refusing paths outside of it.
+///
+/// Paths are listed as absolute paths in the prompt, or below the label of their root with
+/// `--relative-paths`.
+///
+/// # Arguments
+///
+/// * `root` - The canonical path of the codebase.
+/// * `path` - The path of the file, as named by the diff.
+///
+/// # Returns
+///
+/// * `Result<PathBuf>` - The path of the file, or an error if it is outside the codebase.
+fn resolve_path(root: &Path, path: &str) -> Result<PathBuf> {
+    let given = Path::new(path);
+    if given.components().any(|component| component == Component::ParentDir) {
+        bail!(message_with(Message::ApplyUnsafePath, &[("path", &path)]));
+    }
+    let resolved = if given.is_absolute() {
+        given.to_path_buf()
+    } else {
+        let direct = root.join(given);
+        let labeled = root
+            .file_name()
+            .and_then(|label| given.strip_prefix(label).ok())
+            .map(|relative| root.join(relative));
+        match labeled {
+            Some(labeled) if !direct.exists() => labeled,
+            _ => direct,
+        }
+    };
+    if !resolved.starts_with(root) {
+        bail!(message_with(Message::ApplyUnsafePath, &[("path", &path)]));
+    }
+    Ok(resolved)
+}
@@ -0,0 +1,256 @@
+//! This module runs batch files, which list several prompts to generate in one run, such as the
+//! context packages of every service of a monorepo built by a nightly pipeline.
+//!
+//! The jobs run in parallel in the same process, sharing the tokenizers, the parsed templates
+//! and the workspace remote sources are fetched into.
+
+use crate::config::{FileConfig, PresetName};
+use crate::input::layer_config;
+use crate::messages::{message_with, Message};
+use crate::options::Options;
+use crate::output::{
+    print_error, print_info, print_summary, print_warning, set_spinner_hidden, write_stats_file, write_to_file,
+};
+use crate::processing::{process_codebase, ProcessedCodebase};
+use crate::source::fetch_sources;
+use crate::split::write_parts;
+use crate::workspace::Workspace;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// A batch file, listing the jobs to run in order.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct BatchFile {
+    /// The jobs of the batch.
+    pub jobs: Vec<BatchJob>,
+}
+
+/// The codebase paths of a job, given as a single path or a list.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum JobPaths {
+    /// A single codebase path.
+    One(PathBuf),
+    /// Several codebase paths, packed into one prompt.
+    Many(Vec<PathBuf>),
+}
+
+/// A job of a batch file, generating one prompt.
+///
+/// Besides its own keys, a job takes every key of the configuration files, such as `include`,
+/// `template` or `output`.
+#[derive(Deserialize, Debug)]
+pub struct BatchJob {
+    /// The name of the job in messages, defaulting to its output file.
+    pub name: Option<String>,
+    /// The codebase paths, defaulting to the directory of the batch file.
+    pub path: Option<JobPaths>,
+    /// The saved selection of the project configuration file to apply.
+    pub selection: Option<String>,
+    /// The profile of the project configuration file to apply.
+    pub profile: Option<String>,
+    /// The built-in preset to apply.
+    pub preset: Option<PresetName>,
+    /// The options of the job.
+    #[serde(flatten)]
+    pub config: FileConfig,
+}
+
+impl BatchJob {
+    /// Returns the name of the job in messages.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The position of the job in the batch file, starting at 0.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The name of the job, its output file, or its position.
+    pub fn label(&self, index: usize) -> String {
+        self.name
+            .clone()
+            .or_else(|| self.config.output.clone())
+            .unwrap_or_else(|| format!("#{}", index + 1))
+    }
+
+    /// Layers the job onto the command-line options.
+    ///
+    /// The command-line options take precedence, followed by the keys of the job, its saved
+    /// selection, profile and preset, and the configuration files of its codebase. The `path`,
+    /// `template` and `output` of the job are relative to the directory of the batch file.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The options given on the command line.
+    /// * `batch_dir` - The directory of the batch file.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Options>` - The options of the job, or an error if its configuration is invalid.
+    pub fn options(self, base: &Options, batch_dir: &Path) -> Result<Options> {
+        let mut options = base.clone();
+        options.path = match self.path {
+            Some(JobPaths::One(path)) => vec![batch_dir.join(path)],
+            Some(JobPaths::Many(paths)) => paths.into_iter().map(|path| batch_dir.join(path)).collect(),
+            None => vec![batch_dir.to_path_buf()],
+        };
+        let mut config = self.config;
+        config.template = config.template.map(|template| batch_dir.join(template));
+        config.output = config
+            .output
+            .map(|output| batch_dir.join(output).to_string_lossy().into_owned());
+        options.merge_file_config(config);
+        layer_config(&mut options, self.selection.as_deref(), self.profile.as_deref(), self.preset)?;
+        options.no_clipboard = true;
+        Ok(options)
+    }
+}
+
+/// Reads and parses a batch file.
+///
+/// # Arguments
+///
+/// * `path` - The path to the YAML batch file.
+///
+/// # Returns
+///
+/// * `Result<BatchFile>` - The jobs of the batch, or an error if the file cannot be read or parsed.
+pub fn load_batch(path: &Path) -> Result<BatchFile> {
+    let content = fs::read_to_string(path)
+        .with_context(|| message_with(Message::BatchReadFailed, &[("path", &path.display())]))?;
+    serde_yaml::from_str(&content)
+        .with_context(|| message_with(Message::BatchReadFailed, &[("path", &path.display())]))
+}
+
+/// Runs the jobs of a batch file, writing each prompt to the output file of its job.
+///
+/// Up to `concurrency` jobs run at once. Each job prints its report once it finishes, so the
+/// reports of parallel jobs do not interleave. A failing job is reported and the others still run.
+///
+/// # Arguments
+///
+/// * `path` - The path to the YAML batch file.
+/// * `base` - The options given on the command line, applied to every job.
+/// * `concurrency` - The number of jobs to run at once, defaulting to the number of CPUs.
+///
+/// # Returns
+///
+/// * `Result<()>` - An error if the batch file is invalid or any job failed.
+pub fn run_batch(path: &Path, base: &Options, concurrency: Option<usize>) -> Result<()> {
+    let batch = load_batch(path)?;
+    let batch_dir = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let workspace = Workspace::new()?;
+
+    let total = batch.jobs.len();
+    let concurrency = concurrency
+        .or_else(|| thread::available_parallelism().ok().map(NonZeroUsize::get))
+        .unwrap_or(1)
+        .clamp(1, total.max(1));
+    // Spinners of parallel jobs would draw over each other
+    set_spinner_hidden(concurrency > 1 || base.quiet);
+
+    let queue = Mutex::new(batch.jobs.into_iter().enumerate());
+    let console = Mutex::new(());
+    let failed = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..concurrency {
+            scope.spawn(|| loop {
+                let Some((index, job)) = queue.lock().unwrap().next() else {
+                    break;
+                };
+                let label = job.label(index);
+                let result = run_job(job, &label, base, batch_dir, &workspace);
+
+                let _console = console.lock().unwrap();
+                print_info(&message_with(
+                    Message::BatchJobStarted,
+                    &[("job", &label), ("index", &(index + 1)), ("total", &total)],
+                ));
+                match result {
+                    Ok(report) => report.print(),
+                    Err(e) => {
+                        print_error(&message_with(
+                            Message::BatchJobFailed,
+                            &[("job", &label), ("error", &format!("{:#}", e))],
+                        ));
+                        failed.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+
+    let failed = failed.into_inner();
+    if failed > 0 {
+        bail!(message_with(Message::BatchFailed, &[("failed", &failed), ("total", &total)]));
+    }
+    Ok(())
+}
+
+/// The outcome of a finished job, printed once the job is done.
+struct JobReport {
+    /// The processed codebase of the job.
+    processed: ProcessedCodebase,
+    /// The output file the prompt was written to, or its parts when split.
+    destinations: Vec<String>,
+    /// The token budget of the job, if any.
+    budget: Option<usize>,
+}
+
+impl JobReport {
+    /// Prints the warnings and the summary line of the job.
+    fn print(&self) {
+        for warning in &self.processed.warnings {
+            print_warning(warning);
+        }
+        print_summary(&self.processed, self.budget, &self.destinations);
+    }
+}
+
+/// Runs a single job of a batch file.
+///
+/// # Arguments
+///
+/// * `job` - The job to run.
+/// * `label` - The name of the job in messages.
+/// * `base` - The options given on the command line.
+/// * `batch_dir` - The directory of the batch file.
+/// * `workspace` - The workspace remote sources are fetched into.
+///
+/// # Returns
+///
+/// * `Result<JobReport>` - The report of the job, or an error if it has no output file or fails to run.
+fn run_job(job: BatchJob, label: &str, base: &Options, batch_dir: &Path, workspace: &Workspace) -> Result<JobReport> {
+    let mut options = job.options(base, batch_dir)?;
+    let output = options
+        .output
+        .clone()
+        .with_context(|| message_with(Message::BatchJobWithoutOutput, &[("job", &label)]))?;
+    fetch_sources(&mut options, workspace)?;
+
+    let processed = process_codebase(&options)?;
+    let destinations = if processed.parts.is_empty() {
+        write_to_file(&output, &processed.rendered, options.compress)?;
+        vec![output.clone()]
+    } else {
+        write_parts(&output, &processed.parts, options.compress)?
+    };
+    if options.stats {
+        write_stats_file(&output, &processed, &options)?;
+    }
+    Ok(JobReport {
+        processed,
+        destinations,
+        budget: options.budget,
+    })
+}
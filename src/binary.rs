@@ -0,0 +1,83 @@
+//! This module detects binary files by sniffing their content, so they can be listed in the
+//! prompt as placeholders instead of being dropped.
+
+use crate::config::FileSize;
+
+/// The number of leading bytes searched for a NUL byte, the same heuristic as git.
+const SNIFF_LENGTH: usize = 8000;
+
+/// Magic numbers of common binary formats, with the name shown in placeholders.
+const SIGNATURES: [(&[u8], &str); 22] = [
+    (b"\x89PNG\r\n\x1a\n", "PNG"),
+    (b"\xff\xd8\xff", "JPEG"),
+    (b"GIF87a", "GIF"),
+    (b"GIF89a", "GIF"),
+    (b"%PDF-", "PDF"),
+    (b"PK\x03\x04", "ZIP"),
+    (b"\x1f\x8b", "GZIP"),
+    (b"BZh", "BZIP2"),
+    (b"\xfd7zXZ\x00", "XZ"),
+    (b"\x28\xb5\x2f\xfd", "ZSTD"),
+    (b"7z\xbc\xaf\x27\x1c", "7Z"),
+    (b"\x7fELF", "ELF"),
+    (b"\x00asm", "WASM"),
+    (b"MZ", "EXE"),
+    (b"\xcf\xfa\xed\xfe", "Mach-O"),
+    (b"\xca\xfe\xba\xbe", "Java class"),
+    (b"SQLite format 3\x00", "SQLite"),
+    (b"wOFF", "WOFF"),
+    (b"wOF2", "WOFF2"),
+    (b"OggS", "OGG"),
+    (b"ID3", "MP3"),
+    (b"RIFF", "RIFF"),
+];
+
+/// Checks whether file content is binary.
+///
+/// Content is binary when its first bytes contain a NUL byte, or when it is not valid UTF-8
+/// and starts with the magic number of a known binary format.
+///
+/// # Arguments
+///
+/// * `bytes` - The content of the file.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the content is binary.
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(SNIFF_LENGTH)].contains(&0)
+        || (binary_kind(bytes).is_some() && std::str::from_utf8(bytes).is_err())
+}
+
+/// Returns the name of the binary format of file content, from its magic number.
+///
+/// # Arguments
+///
+/// * `bytes` - The content of the file.
+///
+/// # Returns
+///
+/// * `Option<&'static str>` - The name of the format, such as `PNG`, or `None` if it is not recognized.
+pub fn binary_kind(bytes: &[u8]) -> Option<&'static str> {
+    SIGNATURES
+        .iter()
+        .find(|(signature, _)| bytes.starts_with(signature))
+        .map(|(_, name)| *name)
+}
+
+/// Returns the placeholder listed in the prompt instead of the content of a binary file.
+///
+/// # Arguments
+///
+/// * `bytes` - The content of the file.
+///
+/// # Returns
+///
+/// * `String` - The placeholder, e.g. `<binary file, 1.2 MB, PNG>`.
+pub fn binary_placeholder(bytes: &[u8]) -> String {
+    let size = FileSize(bytes.len() as u64);
+    match binary_kind(bytes) {
+        Some(kind) => format!("<binary file, {}, {}>", size, kind),
+        None => format!("<binary file, {}>", size),
+    }
+}
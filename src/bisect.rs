@@ -0,0 +1,169 @@
+//! This module gathers what a regression is tracked down from: the commits between a good and a
+//! bad revision, the diff of each of them, and the files they touched as they are checked out, so
+//! the model can tell which commit likely introduced the regression.
+
+use crate::dashboard::format_day;
+use crate::messages::{message_with, Message};
+use crate::release::ChangedFile;
+use anyhow::{anyhow, Context, Result};
+use git2::{Commit, Delta, DiffOptions, Repository, Sort};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use tracing::instrument;
+
+/// A commit that may have introduced the regression.
+#[derive(Debug, Clone, Serialize)]
+pub struct SuspectCommit {
+    /// The abbreviated id of the commit.
+    pub id: String,
+    /// The first line of the commit message.
+    pub summary: String,
+    /// The rest of the commit message, if any.
+    pub body: Option<String>,
+    /// The name of the author.
+    pub author: String,
+    /// The day the commit was made, such as `2024-05-01`.
+    pub date: String,
+    /// The files the commit changed.
+    pub changed_files: Vec<ChangedFile>,
+    /// The diff of the commit against its parent.
+    pub diff: String,
+}
+
+/// What a regression is tracked down from.
+#[derive(Debug, Clone, Serialize)]
+pub struct Bisect {
+    /// The last revision known to work.
+    pub good: String,
+    /// The first revision known to have the regression.
+    pub bad: String,
+    /// The commits between the two revisions, oldest first, without merge commits.
+    pub commits: Vec<SuspectCommit>,
+    /// The paths of the files touched by the commits, relative to the repository.
+    pub touched_files: Vec<String>,
+    /// The absolute paths of the touched files that are checked out, which are packed.
+    #[serde(skip)]
+    pub checked_out: Vec<PathBuf>,
+}
+
+/// Gathers the commits between a good and a bad revision, with their diffs and the files they touched.
+///
+/// # Arguments
+///
+/// * `repo_path` - A path inside the git repository.
+/// * `good` - The last revision known to work.
+/// * `bad` - The first revision known to have the regression.
+///
+/// # Returns
+///
+/// * `Result<Bisect>` - What the regression is tracked down from, or an error if a revision does not exist.
+#[instrument(name = "git", skip_all)]
+pub fn gather_bisect(repo_path: &Path, good: &str, bad: &str) -> Result<Bisect> {
+    let repo = Repository::discover(repo_path).context("Failed to open repository")?;
+    let workdir = repo
+        .workdir()
+        .context("Repository has no working directory")?
+        .canonicalize()
+        .context("Failed to canonicalize working directory")?;
+    let resolve = |revision: &str| -> Result<Commit> {
+        repo.revparse_single(revision)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|_| anyhow!(message_with(Message::RevisionNotFound, &[("revision", &revision)])))
+    };
+    let good_commit = resolve(good)?;
+    let bad_commit = resolve(bad)?;
+
+    // ~~~ Commits reachable from the bad revision but not from the good one ~~~
+    let mut revwalk = repo.revwalk().context("Failed to create revwalk")?;
+    revwalk.push(bad_commit.id()).context("Failed to push commit to revwalk")?;
+    revwalk.hide(good_commit.id()).context("Failed to hide commit from revwalk")?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    let mut commits = Vec::new();
+    let mut touched = BTreeSet::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?).context("Failed to find commit")?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        let suspect = suspect_commit(&repo, &commit)?;
+        touched.extend(suspect.changed_files.iter().map(|file| file.path.clone()));
+        commits.push(suspect);
+    }
+
+    let touched_files: Vec<String> = touched.into_iter().collect();
+    let checked_out = touched_files
+        .iter()
+        .map(|path| workdir.join(path))
+        .filter(|path| path.is_file())
+        .collect();
+    Ok(Bisect {
+        good: good.to_string(),
+        bad: bad.to_string(),
+        commits,
+        touched_files,
+        checked_out,
+    })
+}
+
+/// Reads a commit with the files it changed and its diff against its parent.
+///
+/// # Arguments
+///
+/// * `repo` - The repository of the commit.
+/// * `commit` - The commit to read.
+///
+/// # Returns
+///
+/// * `Result<SuspectCommit>` - The commit as rendered in the prompt, or an error if its diff cannot be generated.
+fn suspect_commit(repo: &Repository, commit: &Commit) -> Result<SuspectCommit> {
+    let tree = commit.tree().context("Failed to get commit tree")?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree().context("Failed to get commit tree")?),
+        Err(_) => None,
+    };
+    let diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(DiffOptions::new().ignore_whitespace(true)))
+        .context("Failed to generate diff")?;
+    let changed_files = diff
+        .deltas()
+        .filter_map(|delta| {
+            let file = if delta.status() == Delta::Deleted {
+                delta.old_file()
+            } else {
+                delta.new_file()
+            };
+            Some(ChangedFile {
+                path: file.path()?.to_string_lossy().replace('\\', "/"),
+                status: match delta.status() {
+                    Delta::Added => "added",
+                    Delta::Deleted => "deleted",
+                    Delta::Modified => "modified",
+                    _ => "changed",
+                },
+            })
+        })
+        .collect();
+    let mut diff_text = Vec::new();
+    diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            diff_text.push(line.origin() as u8);
+        }
+        diff_text.extend_from_slice(line.content());
+        true
+    })
+    .context("Failed to print diff")?;
+
+    Ok(SuspectCommit {
+        id: commit.id().to_string()[..7].to_string(),
+        summary: commit.summary().unwrap_or_default().trim().to_string(),
+        body: commit
+            .body()
+            .map(|body| body.trim().to_string())
+            .filter(|body| !body.is_empty()),
+        author: commit.author().name().unwrap_or_default().to_string(),
+        date: format_day(commit.time().seconds().max(0) as u64),
+        changed_files,
+        diff: String::from_utf8_lossy(&diff_text).into_owned(),
+    })
+}
@@ -0,0 +1,141 @@
+//! This module detects boilerplate repeated at the top of many files, such as license headers
+//! and codegen preambles, so it can be rendered once as a common header instead of in every file.
+
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// The number of leading bytes of each file searched for a header.
+const SNIFF_LENGTH: u64 = 4096;
+
+/// The minimum number of lines of a header.
+const MIN_HEADER_LINES: usize = 3;
+
+/// The minimum number of files sharing a header for it to be factored out.
+const MIN_SHARED_FILES: usize = 3;
+
+/// The prefixes of comment lines, once leading whitespace is trimmed.
+const COMMENT_PREFIXES: [&str; 8] = ["//", "/*", "*", "--", ";;", "<!--", "-->", "\"\"\""];
+
+/// A header shared by several files, rendered once before the files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CommonHeader {
+    /// The number by which files refer to the header, starting at 1.
+    pub id: usize,
+    /// The header, wrapped in a code block like the files.
+    pub code: String,
+    /// The number of files the header was removed from.
+    pub files: usize,
+}
+
+/// Returns the block of comment lines a file starts with, if it is long enough to be a header.
+///
+/// Lines starting with `#` count as comments only when followed by a space or another `#`, so
+/// attributes and preprocessor directives such as `#[derive]` or `#include` end the block.
+///
+/// # Arguments
+///
+/// * `text` - The content of the file.
+///
+/// # Returns
+///
+/// * `Option<&str>` - The leading comment lines, or `None` if there are fewer than `MIN_HEADER_LINES`.
+pub fn leading_comment_block(text: &str) -> Option<&str> {
+    let mut end = 0;
+    let mut lines = 0;
+    for line in text.split_inclusive('\n') {
+        if !is_comment_line(line) {
+            break;
+        }
+        end += line.len();
+        lines += 1;
+    }
+    (lines >= MIN_HEADER_LINES).then(|| &text[..end])
+}
+
+/// Removes a header from the start of a file, along with the blank lines following it.
+///
+/// # Arguments
+///
+/// * `text` - The content of the file.
+/// * `header` - The header to remove.
+///
+/// # Returns
+///
+/// * `Option<(&str, usize)>` - The rest of the file and the number of lines removed, or `None` if the file does not start with the header.
+pub fn strip_header<'a>(text: &'a str, header: &str) -> Option<(&'a str, usize)> {
+    let rest = text.strip_prefix(header)?;
+    let body = rest.trim_start_matches(['\n', '\r']);
+    let removed = header.matches('\n').count() + rest[..rest.len() - body.len()].matches('\n').count();
+    Some((body, removed))
+}
+
+/// Finds the headers shared by enough files to be factored out.
+///
+/// Only the first bytes of each file are read, so this pass stays cheap on large codebases.
+///
+/// # Arguments
+///
+/// * `paths` - The paths of the files to analyze.
+///
+/// # Returns
+///
+/// * `Vec<String>` - The shared headers, in the order of the first file starting with each one.
+pub fn find_common_headers<P: AsRef<Path> + Sync>(paths: &[P]) -> Vec<String> {
+    let headers: Vec<Option<String>> = paths.par_iter().map(|path| read_header(path.as_ref())).collect();
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for header in headers.iter().flatten() {
+        *counts.entry(header).or_default() += 1;
+    }
+    let mut common = Vec::new();
+    for header in headers.iter().flatten() {
+        if counts.get(header.as_str()).is_some_and(|count| *count >= MIN_SHARED_FILES)
+            && !common.contains(header)
+        {
+            common.push(header.clone());
+        }
+    }
+    common
+}
+
+/// Reads the header of a file from its first bytes.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file.
+///
+/// # Returns
+///
+/// * `Option<String>` - The header, or `None` if the file has none or it may continue past the bytes read.
+fn read_header(path: &Path) -> Option<String> {
+    let mut head = Vec::new();
+    File::open(path).ok()?.take(SNIFF_LENGTH).read_to_end(&mut head).ok()?;
+    // The last character may have been cut off by the read
+    let text = match std::str::from_utf8(&head) {
+        Ok(text) => text,
+        Err(e) => std::str::from_utf8(&head[..e.valid_up_to()]).ok()?,
+    };
+    let header = leading_comment_block(text)?;
+    (header.len() < text.len()).then(|| header.to_string())
+}
+
+/// Checks whether a line is a comment line.
+///
+/// # Arguments
+///
+/// * `line` - The line, with its line ending.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the line is a comment.
+fn is_comment_line(line: &str) -> bool {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix('#') {
+        return rest.is_empty() || rest.starts_with([' ', '#']);
+    }
+    COMMENT_PREFIXES.iter().any(|prefix| line.starts_with(prefix))
+}
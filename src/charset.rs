@@ -0,0 +1,145 @@
+//! This module detects the character encoding of text files that are not UTF-8, such as
+//! Latin-1, Shift-JIS or UTF-16 sources, so they can be transcoded instead of dropped.
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use std::borrow::Cow;
+
+/// The number of leading bytes inspected to recognize UTF-16 without a byte order mark.
+const SNIFF_LENGTH: usize = 8000;
+
+/// Text decoded from the content of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedText<'a> {
+    /// The text, transcoded to UTF-8.
+    pub text: Cow<'a, str>,
+    /// The encoding the content was decoded from.
+    pub encoding: &'static Encoding,
+    /// Whether invalid bytes were replaced with `U+FFFD`.
+    pub lossy: bool,
+}
+
+/// Detects the encoding of UTF-16 content, from its byte order mark or the NUL bytes of ASCII
+/// characters.
+///
+/// UTF-16 content is detected first, as its NUL bytes would otherwise mark it as binary.
+///
+/// # Arguments
+///
+/// * `bytes` - The content of the file.
+///
+/// # Returns
+///
+/// * `Option<&'static Encoding>` - `UTF-16LE` or `UTF-16BE`, or `None` if the content is not UTF-16.
+pub fn detect_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return (encoding != UTF_8).then_some(encoding);
+    }
+
+    let sample = &bytes[..bytes.len().min(SNIFF_LENGTH) & !1];
+    if sample.is_empty() {
+        return None;
+    }
+    let units = sample.len() / 2;
+    let even_nuls = sample.iter().step_by(2).filter(|byte| **byte == 0).count();
+    let odd_nuls = sample.iter().skip(1).step_by(2).filter(|byte| **byte == 0).count();
+
+    // ASCII characters encoded as UTF-16 have a NUL high byte, mostly on one side only
+    if even_nuls == 0 && odd_nuls * 10 >= units * 9 {
+        Some(UTF_16LE)
+    } else if odd_nuls == 0 && even_nuls * 10 >= units * 9 {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Decodes UTF-16 content, skipping its byte order mark.
+///
+/// # Arguments
+///
+/// * `bytes` - The content of the file.
+///
+/// # Returns
+///
+/// * `Option<DecodedText>` - The decoded text, or `None` if the content is not valid UTF-16.
+pub fn decode_utf16(bytes: &[u8]) -> Option<DecodedText<'_>> {
+    let encoding = detect_utf16(bytes)?;
+    decode_with(bytes, encoding)
+}
+
+/// Decodes text content, detecting its encoding when it is not valid UTF-8.
+///
+/// Content with a byte order mark is decoded with the encoding it names. Otherwise, valid
+/// UTF-8 is kept as is, and any other content is decoded with the legacy encoding guessed by
+/// `chardetng`, such as `windows-1252` or `Shift_JIS`.
+///
+/// # Arguments
+///
+/// * `bytes` - The content of the file.
+///
+/// # Returns
+///
+/// * `Option<DecodedText>` - The decoded text, or `None` if the content is malformed in the detected encoding.
+pub fn decode_text(bytes: &[u8]) -> Option<DecodedText<'_>> {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return decode_with(bytes, encoding);
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Some(DecodedText {
+            text: Cow::Borrowed(text),
+            encoding: UTF_8,
+            lossy: false,
+        });
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    decode_with(bytes, detector.guess(None, false))
+}
+
+/// Decodes text content as UTF-8, replacing invalid bytes with `U+FFFD`.
+///
+/// Content with a byte order mark is still decoded with the encoding it names.
+///
+/// # Arguments
+///
+/// * `bytes` - The content of the file.
+///
+/// # Returns
+///
+/// * `DecodedText` - The decoded text, marked as lossy if bytes were replaced.
+pub fn decode_lossy(bytes: &[u8]) -> DecodedText<'_> {
+    if let Some(decoded) = Encoding::for_bom(bytes).and_then(|(encoding, _)| decode_with(bytes, encoding)) {
+        return decoded;
+    }
+    let text = String::from_utf8_lossy(bytes);
+    DecodedText {
+        lossy: matches!(text, Cow::Owned(_)),
+        text,
+        encoding: UTF_8,
+    }
+}
+
+/// Decodes content with an encoding, skipping a byte order mark.
+///
+/// # Arguments
+///
+/// * `bytes` - The content of the file.
+/// * `encoding` - The encoding of the content.
+///
+/// # Returns
+///
+/// * `Option<DecodedText>` - The decoded text, or `None` if the content is malformed.
+fn decode_with(bytes: &[u8], encoding: &'static Encoding) -> Option<DecodedText<'_>> {
+    let bytes = match Encoding::for_bom(bytes) {
+        Some((bom_encoding, length)) if bom_encoding == encoding => &bytes[length..],
+        _ => bytes,
+    };
+    let text = encoding.decode_without_bom_handling_and_without_replacement(bytes)?;
+    Some(DecodedText {
+        text,
+        encoding,
+        lossy: false,
+    })
+}
@@ -0,0 +1,258 @@
+//! This module copies the prompt to the clipboard through pluggable backends: the system
+//! clipboard, the OSC 52 terminal escape sequence, the `wl-copy` and `xclip` commands, or none,
+//! so headless machines and remote sessions can copy as well as desktops.
+
+use crate::config::ClipboardBackend;
+use crate::messages::{message, message_with, Message};
+use anyhow::{anyhow, Result};
+use arboard::Clipboard;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// A destination the prompt can be copied to.
+pub trait ClipboardSink {
+    /// Returns the name of the backend, as given to `--clipboard`.
+    ///
+    /// # Returns
+    ///
+    /// * `&'static str` - The name of the backend.
+    fn name(&self) -> &'static str;
+
+    /// Copies a text to the clipboard.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to copy.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An empty result indicating success or an error.
+    fn copy(&self, text: &str) -> Result<()>;
+
+    /// Checks that the backend can copy, without changing the clipboard, for `codexio doctor`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - An empty result if the backend is available, or the error a copy would fail with.
+    fn probe(&self) -> Result<()>;
+}
+
+/// The system clipboard, through the arboard library.
+#[derive(Debug, Default)]
+pub struct ArboardSink;
+
+impl ClipboardSink for ArboardSink {
+    fn name(&self) -> &'static str {
+        "arboard"
+    }
+
+    fn copy(&self, text: &str) -> Result<()> {
+        let mut clipboard = Clipboard::new()
+            .map_err(|e| anyhow!(message_with(Message::ClipboardInitFailed, &[("error", &e)])))?;
+        clipboard
+            .set_text(text.to_string())
+            .map_err(|e| anyhow!(message_with(Message::ClipboardCopyFailed, &[("error", &e)])))
+    }
+
+    fn probe(&self) -> Result<()> {
+        Clipboard::new()
+            .map(|_| ())
+            .map_err(|e| anyhow!(message_with(Message::ClipboardInitFailed, &[("error", &e)])))
+    }
+}
+
+/// The OSC 52 escape sequence, which asks the terminal to set its clipboard, including when the
+/// terminal is on another machine than the shell.
+#[derive(Debug, Default)]
+pub struct Osc52Sink;
+
+impl ClipboardSink for Osc52Sink {
+    fn name(&self) -> &'static str {
+        "osc52"
+    }
+
+    fn copy(&self, text: &str) -> Result<()> {
+        let sequence = osc52_sequence(text, env::var_os("TMUX").is_some());
+        // The terminal is written to directly, so the sequence does not end up in piped output
+        if let Ok(mut tty) = OpenOptions::new().write(true).open("/dev/tty") {
+            tty.write_all(sequence.as_bytes())?;
+            tty.flush()?;
+            return Ok(());
+        }
+        let mut stderr = io::stderr();
+        if !stderr.is_terminal() {
+            return Err(anyhow!(message(Message::ClipboardNoTerminal)));
+        }
+        stderr.write_all(sequence.as_bytes())?;
+        stderr.flush()?;
+        Ok(())
+    }
+
+    fn probe(&self) -> Result<()> {
+        if OpenOptions::new().write(true).open("/dev/tty").is_ok() || io::stderr().is_terminal() {
+            Ok(())
+        } else {
+            Err(anyhow!(message(Message::ClipboardNoTerminal)))
+        }
+    }
+}
+
+/// A command reading the text to copy from its standard input, such as `wl-copy` or `xclip`.
+#[derive(Debug)]
+pub struct CommandSink {
+    /// The name of the backend.
+    name: &'static str,
+    /// The program to run.
+    program: &'static str,
+    /// The arguments of the program.
+    args: &'static [&'static str],
+}
+
+impl CommandSink {
+    /// The `wl-copy` command of Wayland.
+    pub const WL_COPY: CommandSink = CommandSink {
+        name: "wl-copy",
+        program: "wl-copy",
+        args: &[],
+    };
+
+    /// The `xclip` command of X11, writing to the clipboard selection.
+    pub const XCLIP: CommandSink = CommandSink {
+        name: "xclip",
+        program: "xclip",
+        args: &["-selection", "clipboard"],
+    };
+}
+
+impl ClipboardSink for CommandSink {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn copy(&self, text: &str) -> Result<()> {
+        let failed = |error: &dyn std::fmt::Display| {
+            anyhow!(message_with(
+                Message::ClipboardCommandFailed,
+                &[("command", &self.program), ("error", error)],
+            ))
+        };
+        let mut child = Command::new(self.program)
+            .args(self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| failed(&e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes()).map_err(|e| failed(&e))?;
+        }
+        let status = child.wait().map_err(|e| failed(&e))?;
+        if !status.success() {
+            return Err(failed(&status));
+        }
+        Ok(())
+    }
+
+    fn probe(&self) -> Result<()> {
+        if on_path(self.program) {
+            Ok(())
+        } else {
+            Err(anyhow!(message_with(Message::ClipboardCommandMissing, &[("command", &self.program)])))
+        }
+    }
+}
+
+/// A backend that copies nothing, for runs that only write files.
+#[derive(Debug, Default)]
+pub struct NoopSink;
+
+impl ClipboardSink for NoopSink {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn copy(&self, _text: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn probe(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Returns the sink of a clipboard backend, detecting it from the environment for `auto`.
+///
+/// # Arguments
+///
+/// * `backend` - The backend given with `--clipboard` or in a configuration file.
+///
+/// # Returns
+///
+/// * `Box<dyn ClipboardSink>` - The sink to copy the prompt with.
+pub fn clipboard_sink(backend: ClipboardBackend) -> Box<dyn ClipboardSink> {
+    match backend {
+        ClipboardBackend::Auto => clipboard_sink(detect_backend()),
+        ClipboardBackend::Arboard => Box::new(ArboardSink),
+        ClipboardBackend::Osc52 => Box::new(Osc52Sink),
+        ClipboardBackend::WlCopy => Box::new(CommandSink::WL_COPY),
+        ClipboardBackend::Xclip => Box::new(CommandSink::XCLIP),
+        ClipboardBackend::None => Box::new(NoopSink),
+    }
+}
+
+/// Picks the clipboard backend suited to the environment.
+///
+/// Remote sessions without a display forward the copy to the local terminal with OSC 52, and
+/// Wayland sessions use `wl-copy` when it is installed. The system clipboard is used otherwise.
+///
+/// # Returns
+///
+/// * `ClipboardBackend` - The detected backend, never `Auto`.
+pub fn detect_backend() -> ClipboardBackend {
+    let set = |name: &str| env::var_os(name).is_some_and(|value| !value.is_empty());
+    let remote = set("SSH_TTY") || set("SSH_CONNECTION");
+    let display = set("DISPLAY") || set("WAYLAND_DISPLAY");
+    if remote && !display {
+        ClipboardBackend::Osc52
+    } else if cfg!(target_os = "linux") && set("WAYLAND_DISPLAY") && on_path("wl-copy") {
+        ClipboardBackend::WlCopy
+    } else {
+        ClipboardBackend::Arboard
+    }
+}
+
+/// Builds the OSC 52 sequence setting the clipboard to a text.
+///
+/// # Arguments
+///
+/// * `text` - The text to copy.
+/// * `tmux` - Whether to wrap the sequence so tmux passes it through to the outer terminal.
+///
+/// # Returns
+///
+/// * `String` - The escape sequence.
+pub fn osc52_sequence(text: &str, tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    if tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+/// Checks whether a program is found in one of the directories of `PATH`.
+///
+/// # Arguments
+///
+/// * `program` - The name of the program.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the program is installed.
+fn on_path(program: &str) -> bool {
+    env::var_os("PATH").is_some_and(|path| env::split_paths(&path).any(|directory| directory.join(program).is_file()))
+}
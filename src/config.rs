@@ -1,79 +1,1236 @@
 //! This module defines the configuration options for the application.
 
-use clap::Parser;
+use clap::builder::BoolishValueParser;
+use crate::options::Options;
+use clap::{Parser, Subcommand, ValueEnum};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject, SubschemaValidation};
+use schemars::{schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
 
-/// Configuration options for the application.
-#[derive(Parser, Debug)]
+/// The command-line arguments of the application, mapped into `Options` once parsed.
+///
+/// Every option can also be set through a `CODEXIO_<OPTION>` environment variable
+/// (e.g. `CODEXIO_EXCLUDE`), which takes precedence over the configuration files.
+#[derive(Parser, Serialize, Debug)]
 #[clap(name = "codexio", version = "2.0.0", author = "Mufeed VH")]
+#[command(args_override_self = true)]
 pub struct Config {
-    /// Path to the codebase directory.
-    #[arg()]
-    pub path: PathBuf,
+    /// Subcommand to run instead of generating the whole prompt.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Paths to the codebase directories or files.
+    ///
+    /// A single file produces a prompt containing just that file. Git URLs are cloned, and
+    /// `.zip`, `.tar` and `.tar.gz` archives are extracted to a temporary directory.
+    #[arg(default_value = ".", env = "CODEXIO_PATH")]
+    pub path: Vec<PathBuf>,
+
+    /// Optional URL of a remote git repository to clone and use instead of the paths.
+    ///
+    /// Paths that look like git URLs are cloned as well.
+    #[clap(long, value_name = "URL", conflicts_with = "path", env = "CODEXIO_REMOTE")]
+    pub remote: Option<String>,
+
+    /// Optional GitHub repository to download as a snapshot, without requiring git.
+    #[clap(
+        long,
+        value_name = "OWNER/REPO[@REF]",
+        conflicts_with_all = ["path", "remote"],
+        env = "CODEXIO_GITHUB"
+    )]
+    pub github: Option<String>,
+
+    /// Optional branch or tag to check out when cloning remote repositories.
+    #[clap(long, value_name = "REF", env = "CODEXIO_BRANCH")]
+    pub branch: Option<String>,
+
+    /// Optional file listing the paths to pack, one per line, or `-` to read them from stdin.
+    ///
+    /// The listed files are packed in the given order, without applying any patterns.
+    #[clap(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["path", "remote", "github"],
+        env = "CODEXIO_FILES_FROM"
+    )]
+    pub files_from: Option<String>,
+
+    /// Optional workspace file listing several roots to pack together, each with its own patterns.
+    ///
+    /// Files are listed under the label of their root, and the other keys of the file apply to all the roots.
+    #[clap(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["path", "remote", "github", "files_from"],
+        env = "CODEXIO_WORKSPACE_FILE"
+    )]
+    pub workspace_file: Option<PathBuf>,
 
     /// Patterns to include.
-    #[clap(long)]
+    #[clap(long, env = "CODEXIO_INCLUDE")]
     pub include: Option<String>,
 
     /// Patterns to exclude.
-    #[clap(long)]
+    #[clap(long, env = "CODEXIO_EXCLUDE")]
     pub exclude: Option<String>,
 
+    /// Files of patterns to include, one glob per line, with `#` starting a comment line.
+    #[clap(long, value_name = "FILE", env = "CODEXIO_INCLUDE_FROM")]
+    pub include_from: Vec<PathBuf>,
+
+    /// Files of patterns to exclude, one glob per line, with `#` starting a comment line.
+    #[clap(long, value_name = "FILE", env = "CODEXIO_EXCLUDE_FROM")]
+    pub exclude_from: Vec<PathBuf>,
+
     /// Include files in case of conflict between include and exclude patterns.
-    #[clap(long)]
+    #[clap(long, env = "CODEXIO_INCLUDE_PRIORITY", value_parser = BoolishValueParser::new())]
     pub include_priority: bool,
 
+    /// Only pack files whose content matches this regular expression, such as `TODO|FIXME` or a function name.
+    #[clap(long, value_name = "REGEX", env = "CODEXIO_INCLUDE_CONTAINING")]
+    pub include_containing: Option<String>,
+
+    /// Skip files whose content matches this regular expression, such as `DO NOT SUBMIT` or `PRIVATE KEY`, even if they match the include patterns.
+    #[clap(long, value_name = "REGEX", env = "CODEXIO_EXCLUDE_CONTAINING")]
+    pub exclude_containing: Option<String>,
+
+    /// Only pack files in these languages or with these extensions (comma-separated), such as `rust,python,makefile`.
+    ///
+    /// The language of extensionless files is detected from their name, modelines or shebang.
+    #[clap(long, value_name = "LANGUAGES", env = "CODEXIO_LANG")]
+    pub lang: Option<String>,
+
+    /// Only pack files selected by this expression, such as `(lang:rust & changed:7d) | path:docs/**`.
+    ///
+    /// Filters are `path:GLOB`, `lang:NAME`, `ext:EXT`, `contains:REGEX` and `changed:DURATION`, combined with `&`, `|`, `!` and parentheses.
+    #[clap(long, value_name = "EXPRESSION", env = "CODEXIO_SELECT")]
+    pub select: Option<String>,
+
+    /// Pack files matched by any ignore file, including `.gitignore`, `.ignore` and `.codexioignore`.
+    #[clap(long, env = "CODEXIO_NO_IGNORE", value_parser = BoolishValueParser::new())]
+    pub no_ignore: bool,
+
+    /// Pack files ignored by git, while still honoring `.ignore` and `.codexioignore`.
+    #[clap(long, env = "CODEXIO_NO_GITIGNORE", value_parser = BoolishValueParser::new())]
+    pub no_gitignore: bool,
+
+    /// Pack files marked `linguist-generated` or `linguist-vendored` in `.gitattributes`, which are skipped by default.
+    #[clap(long, env = "CODEXIO_NO_GITATTRIBUTES", value_parser = BoolishValueParser::new())]
+    pub no_gitattributes: bool,
+
+    /// Pack generated and minified files, such as `*.min.js`, `*.pb.go` and files marked `Code generated ... DO NOT EDIT`, which are skipped by default.
+    #[clap(long, env = "CODEXIO_INCLUDE_GENERATED", value_parser = BoolishValueParser::new())]
+    pub include_generated: bool,
+
+    /// Pack dependency, build output and cache directories such as `node_modules` and `target`, and lock files, which are skipped by default.
+    #[clap(long, env = "CODEXIO_NO_DEFAULT_EXCLUDES", value_parser = BoolishValueParser::new())]
+    pub no_default_excludes: bool,
+
+    /// Pack `.env` files, private keys, keystores and cloud credentials, which are skipped even when an include pattern matches them.
+    #[clap(long, env = "CODEXIO_ALLOW_SENSITIVE", value_parser = BoolishValueParser::new())]
+    pub allow_sensitive: bool,
+
+    /// Pack hidden files and directories, such as dotfiles. The `.git` directory is always skipped.
+    #[clap(long, env = "CODEXIO_HIDDEN", value_parser = BoolishValueParser::new())]
+    pub hidden: bool,
+
+    /// Follow symlinked directories during traversal, skipping symlink cycles.
+    #[clap(long, env = "CODEXIO_FOLLOW_SYMLINKS", value_parser = BoolishValueParser::new())]
+    pub follow_symlinks: bool,
+
+    /// Pack the targets of symlinked files pointing outside the root, under the path of the link.
+    ///
+    /// Such links are skipped with a warning by default.
+    #[clap(long, env = "CODEXIO_INCLUDE_EXTERNAL_SYMLINKS", value_parser = BoolishValueParser::new())]
+    pub include_external_symlinks: bool,
+
+    /// Only traverse this many levels below each root, in both the tree and the files.
+    #[clap(long, value_name = "DEPTH", env = "CODEXIO_MAX_DEPTH")]
+    pub max_depth: Option<usize>,
+
+    /// Skip files larger than this size, such as `200KB` or `1MB`, reporting them as warnings.
+    #[clap(long, value_name = "SIZE", env = "CODEXIO_MAX_FILE_SIZE")]
+    pub max_file_size: Option<FileSize>,
+
+    /// Pack files that are not valid UTF-8 by replacing their invalid bytes, instead of detecting their encoding.
+    #[clap(long, env = "CODEXIO_LOSSY", value_parser = BoolishValueParser::new())]
+    pub lossy: bool,
+
+    /// Render the license headers and other leading comment blocks shared by several files once, as common headers referenced by the files.
+    #[clap(long, env = "CODEXIO_DEDUPE_HEADERS", value_parser = BoolishValueParser::new())]
+    pub dedupe_headers: bool,
+
+    /// Replace API keys, tokens, private keys and connection string passwords found in files with
+    /// placeholders such as `[REDACTED:aws-access-key]`. This is the default.
+    #[clap(long, overrides_with = "no_redact", env = "CODEXIO_REDACT", value_parser = BoolishValueParser::new())]
+    pub redact: bool,
+
+    /// Pack files as they are, without redacting the secrets found in them.
+    #[clap(long, overrides_with = "redact", env = "CODEXIO_NO_REDACT", value_parser = BoolishValueParser::new())]
+    pub no_redact: bool,
+
+    /// Pack git repositories nested inside the codebase, which are skipped by default.
+    ///
+    /// Submodules are always packed.
+    #[clap(long, env = "CODEXIO_INCLUDE_NESTED_REPOS", value_parser = BoolishValueParser::new())]
+    pub include_nested_repos: bool,
+
+    /// Report how many files were left out because git did not check them out.
+    ///
+    /// Files outside a sparse checkout or marked skip-worktree are never packed.
+    #[clap(long, env = "CODEXIO_REPORT_SPARSE", value_parser = BoolishValueParser::new())]
+    pub report_sparse: bool,
+
     /// Exclude files/folders from the source tree based on exclude patterns.
-    #[clap(long)]
+    #[clap(long, env = "CODEXIO_EXCLUDE_FROM_TREE", value_parser = BoolishValueParser::new())]
     pub exclude_from_tree: bool,
 
+    /// Show the size and token count of each file in the source tree, and their totals on directories.
+    #[clap(long, env = "CODEXIO_ANNOTATE_TREE", value_parser = BoolishValueParser::new())]
+    pub annotate_tree: bool,
+
+    /// Render only the source tree, without the file contents.
+    ///
+    /// Filters still apply, and the tree only shows the files they select.
+    #[clap(long, env = "CODEXIO_TREE_ONLY", value_parser = BoolishValueParser::new())]
+    pub tree_only: bool,
+
+    /// Leave the source tree out of the prompt, to save its tokens on large codebases.
+    #[clap(long, env = "CODEXIO_NO_TREE", value_parser = BoolishValueParser::new(), conflicts_with = "tree_only")]
+    pub no_tree: bool,
+
+    /// How to draw the source tree, defaulting to `ascii` with `--ascii` and to `unicode` otherwise.
+    #[clap(long, value_enum, env = "CODEXIO_TREE_STYLE")]
+    pub tree_style: Option<TreeStyle>,
+
     /// Display the token count of the generated prompt.
-    #[clap(long)]
+    #[clap(long, env = "CODEXIO_TOKENS", value_parser = BoolishValueParser::new())]
     pub tokens: bool,
 
     /// Optional tokenizer to use for token count.
     ///
     /// Supported tokenizers: cl100k (default), p50k, p50k_edit, r50k, gpt2.
-    #[clap(short = 'c', long)]
+    #[clap(short = 'c', long, env = "CODEXIO_ENCODING")]
     pub encoding: Option<String>,
 
-    /// Optional output file path.
-    #[clap(short, long)]
+    /// Optional output file path, or `-` to write the prompt to stdout.
+    #[clap(short, long, env = "CODEXIO_OUTPUT")]
     pub output: Option<String>,
 
+    /// Compress the output file with `gzip` or `zstd`, appending `.gz` or `.zst` to its path when missing.
+    ///
+    /// An output path ending with `.gz` or `.zst` selects the compression by itself.
+    #[clap(long, value_enum, env = "CODEXIO_COMPRESS")]
+    pub compress: Option<Compression>,
+
+    /// Split the prompt into parts of at most this many tokens, numbered before the extension of
+    /// the output file, such as `prompt.part1.md` and `prompt.part2.md`.
+    ///
+    /// Files are only cut between lines when a file does not fit in a part on its own.
+    #[clap(long, value_name = "TOKENS", env = "CODEXIO_SPLIT_TOKENS")]
+    pub split_tokens: Option<usize>,
+
+    /// Optional token budget the prompt is measured against in the summary line.
+    #[clap(long, value_name = "TOKENS", env = "CODEXIO_BUDGET")]
+    pub budget: Option<usize>,
+
+    /// Estimate the token count of each file from its size before reading it.
+    ///
+    /// With `--budget`, the files whose estimates do not fit are left out without being read, lowest priority tier first, and sorting by tokens uses the estimates.
+    #[clap(long, env = "CODEXIO_ESTIMATE", value_parser = BoolishValueParser::new())]
+    pub estimate: bool,
+
+    /// Write a `<output>.stats.json` file with the file list, per-file tokens and warnings next to the output file.
+    #[clap(long, env = "CODEXIO_STATS", value_parser = BoolishValueParser::new())]
+    pub stats: bool,
+
+    /// Print how long each stage of the run took to stderr.
+    #[clap(long, env = "CODEXIO_TIMINGS", value_parser = BoolishValueParser::new())]
+    pub timings: bool,
+
     /// Include git diff.
-    #[clap(short, long)]
+    #[clap(short, long, env = "CODEXIO_DIFF", value_parser = BoolishValueParser::new())]
     pub diff: bool,
 
     /// Generate git diff between two branches.
-    #[clap(long, value_name = "BRANCHES")]
+    #[clap(long, value_name = "BRANCHES", env = "CODEXIO_GIT_DIFF_BRANCH")]
     pub git_diff_branch: Option<String>,
 
     /// Retrieve git log between two branches.
-    #[clap(long, value_name = "BRANCHES")]
+    #[clap(long, value_name = "BRANCHES", env = "CODEXIO_GIT_LOG_BRANCH")]
     pub git_log_branch: Option<String>,
 
     /// Add line numbers to the source code.
-    #[clap(short, long)]
+    #[clap(short, long, env = "CODEXIO_LINE_NUMBER", value_parser = BoolishValueParser::new())]
     pub line_number: bool,
 
+    /// Add line numbers only to the files matching these patterns (comma-separated), such as `**/src/**`.
+    #[clap(long, value_name = "PATTERNS", env = "CODEXIO_LINE_NUMBER_FOR")]
+    pub line_number_for: Option<String>,
+
+    /// Add line numbers only to the files changed in the git diff of the prompt, between the
+    /// `--git-diff-branch` branches if given, or staged otherwise.
+    #[clap(long, env = "CODEXIO_LINE_NUMBER_DIFF", value_parser = BoolishValueParser::new())]
+    pub line_number_diff: bool,
+
+    /// Only pack the files with merge conflicts, with the base, ours and theirs versions of each from
+    /// the git index, and the merge, rebase, cherry-pick or revert in progress.
+    #[clap(long, env = "CODEXIO_CONFLICTS", value_parser = BoolishValueParser::new())]
+    pub conflicts: bool,
+
+    /// Prefix each line of the files with a stable anchor, its number and a hash of its content,
+    /// which `codexio apply` checks the hunks of a response against.
+    #[clap(long, env = "CODEXIO_LINE_ANCHORS", value_parser = BoolishValueParser::new())]
+    pub line_anchors: bool,
+
     /// Disable wrapping code inside markdown code blocks.
-    #[clap(long)]
+    #[clap(long, env = "CODEXIO_NO_CODEBLOCK", value_parser = BoolishValueParser::new())]
     pub no_codeblock: bool,
 
+    /// Format of the prompt: the Markdown of the template, a JSON or YAML document wrapping it,
+    /// XML documents, JSON Lines with one object per file, or an OpenAI chat completions or
+    /// Anthropic Messages request.
+    ///
+    /// Defaults to `json` with `--json`, and to `markdown` otherwise.
+    #[clap(long, value_enum, env = "CODEXIO_FORMAT")]
+    pub format: Option<OutputFormat>,
+
+    /// Section of the template rendered as the system message of the chat formats, `--format
+    /// openai` and `--format anthropic`, the rest of the prompt being the user message.
+    ///
+    /// Defaults to `instructions`.
+    #[clap(long, value_enum, env = "CODEXIO_SYSTEM_SECTION")]
+    pub system_section: Option<Section>,
+
+    /// Model named in the payloads of the chat formats.
+    ///
+    /// Defaults to `gpt-4o` with `--format openai` and `claude-sonnet-4-5` with `--format anthropic`.
+    #[clap(long, env = "CODEXIO_MODEL")]
+    pub model: Option<String>,
+
+    /// Section of the template after which `--format anthropic` places its prompt caching breakpoint,
+    /// so repeated runs reuse what comes before it: `tree`, `files`, `diff`, `instructions` or `all`
+    /// for the whole prompt.
+    ///
+    /// Defaults to `files`.
+    #[clap(long, value_enum, env = "CODEXIO_CACHE_BREAKPOINT")]
+    pub cache_breakpoint: Option<Section>,
+
     /// Use relative paths instead of absolute paths, including the parent directory.
-    #[clap(long)]
+    #[clap(long, env = "CODEXIO_RELATIVE_PATHS", value_parser = BoolishValueParser::new())]
     pub relative_paths: bool,
 
+    /// Order of the files in the prompt: `path`, `size`, `modified` or `tokens`, ascending, or descending with `-desc`, e.g. `tokens-desc`.
+    ///
+    /// Defaults to the order of the source tree.
+    #[clap(long, value_enum, env = "CODEXIO_SORT")]
+    pub sort: Option<SortOrder>,
+
     /// Optional Disable copying to clipboard.
-    #[clap(long)]
+    #[clap(long, env = "CODEXIO_NO_CLIPBOARD", value_parser = BoolishValueParser::new())]
     pub no_clipboard: bool,
 
+    /// Print only warnings and errors, without the spinner or status lines, and never ask for
+    /// template variables, failing instead when one has no value; for scripts and CI.
+    #[clap(short, long, env = "CODEXIO_QUIET", value_parser = BoolishValueParser::new())]
+    pub quiet: bool,
+
+    /// When to color the status output: `auto` (default) colors it on terminals unless `NO_COLOR`
+    /// is set, `always` and `never` override the detection.
+    #[clap(long, value_enum, env = "CODEXIO_COLOR")]
+    pub color: Option<ColorChoice>,
+
+    /// Clipboard backend to copy the prompt with: `auto`, `arboard`, `osc52`, `wl-copy`, `xclip` or `none`.
+    ///
+    /// Defaults to `auto`, which uses OSC 52 over SSH without a display, `wl-copy` on Wayland, and the system clipboard otherwise.
+    #[clap(long, value_enum, env = "CODEXIO_CLIPBOARD")]
+    pub clipboard: Option<ClipboardBackend>,
+
+    /// Optional chat interface the prompt will be pasted into, e.g. `chatgpt` or `claude-web`.
+    ///
+    /// A warning is printed when the copied content exceeds the paste limit of the target.
+    #[clap(long, env = "CODEXIO_TARGET")]
+    pub target: Option<String>,
+
+    /// Copy only the selected section of the prompt to the clipboard.
+    #[clap(long, value_enum, default_value_t = Section::All, env = "CODEXIO_COPY_SECTION")]
+    pub copy_section: Section,
+
     /// Optional Path to a custom Handlebars template.
-    #[clap(short, long)]
+    #[clap(short, long, env = "CODEXIO_TEMPLATE")]
     pub template: Option<PathBuf>,
 
-    /// Print output as JSON.
-    #[clap(long)]
+    /// Optional path to a YAML or JSON file answering the variables and forms of the template, instead of asking for them.
+    #[clap(long, env = "CODEXIO_ANSWERS")]
+    pub answers: Option<PathBuf>,
+
+    /// Value of a template variable, as `NAME=VALUE`, which also replaces `{{NAME}}` in the
+    /// patterns and paths of the configuration. Can be given several times.
+    #[clap(long, value_name = "NAME=VALUE")]
+    pub var: Vec<String>,
+
+    /// Color theme for status output and HTML pages: `default` or `high-contrast`.
+    #[clap(long, value_enum, env = "CODEXIO_THEME")]
+    pub theme: Option<ThemeName>,
+
+    /// Color scheme of HTML pages such as the `stats` dashboard: `auto` (default) follows the
+    /// `prefers-color-scheme` of the browser, `light` and `dark` force one.
+    #[clap(long, value_enum, env = "CODEXIO_HTML_THEME")]
+    pub html_theme: Option<HtmlTheme>,
+
+    /// Use ASCII characters only in status output, the spinner and the source tree.
+    #[clap(long, env = "CODEXIO_ASCII", value_parser = BoolishValueParser::new())]
+    pub ascii: bool,
+
+    /// Print output as JSON, the prompt as with `--format json`.
+    #[clap(long, env = "CODEXIO_JSON", value_parser = BoolishValueParser::new())]
     pub json: bool,
-}
\ No newline at end of file
+
+    /// Format of the diagnostic logs written to stderr: `text` (default), or `json` for one JSON
+    /// object per line, with the included and excluded files, the stage timings and the warnings.
+    ///
+    /// The levels are selected with `RUST_LOG`.
+    #[clap(long, value_enum, default_value_t = LogFormat::Text, env = "CODEXIO_LOG_FORMAT")]
+    pub log_format: LogFormat,
+
+    /// List the files that would be packed, with their size and token count, without rendering
+    /// the prompt or copying it.
+    #[clap(long, env = "CODEXIO_LIST", value_parser = BoolishValueParser::new())]
+    pub list: bool,
+
+    /// Explain why a path is packed or left out, naming the pattern, ignore rule or limit
+    /// that decides, without rendering the prompt. Can be given several times.
+    #[clap(long, value_name = "PATH")]
+    pub explain: Vec<PathBuf>,
+
+    /// Optional language of status messages, e.g. `es` or `de`.
+    ///
+    /// Defaults to the locale given by `LC_ALL`, `LC_MESSAGES` or `LANG`.
+    #[clap(long, env = "CODEXIO_LOCALE")]
+    pub locale: Option<String>,
+
+    /// Optional instructions appended to the prompt.
+    #[clap(long, env = "CODEXIO_INSTRUCTIONS")]
+    pub instructions: Option<String>,
+
+    /// Optional selection saved with `save-selection` to apply, taking precedence over profiles.
+    #[clap(long, value_name = "NAME", env = "CODEXIO_SELECTION")]
+    pub selection: Option<String>,
+
+    /// Optional named profile from the configuration file to apply.
+    #[clap(long, env = "CODEXIO_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Optional built-in preset to apply, selecting the files relevant to a kind of prompt.
+    ///
+    /// Its values take precedence over the configuration files, but not over a profile.
+    #[clap(long, value_enum, env = "CODEXIO_PRESET")]
+    pub preset: Option<PresetName>,
+}
+
+impl Config {
+    /// Maps the parsed command line into the options of the run.
+    ///
+    /// The subcommand, the saved selection, the profile and the preset are left out, since they
+    /// select what to run and which configuration to load rather than how to run it.
+    ///
+    /// # Returns
+    ///
+    /// * `(Option<Command>, Options)` - The subcommand, if one was given, and the options.
+    pub fn into_options(self) -> (Option<Command>, Options) {
+        let mut options = Options {
+            path: self.path,
+            remote: self.remote,
+            github: self.github,
+            branch: self.branch,
+            files_from: self.files_from,
+            workspace_file: self.workspace_file,
+            workspace_roots: Vec::new(),
+            include: self.include,
+            exclude: self.exclude,
+            include_from: self.include_from,
+            exclude_from: self.exclude_from,
+            include_priority: self.include_priority,
+            include_containing: self.include_containing,
+            exclude_containing: self.exclude_containing,
+            lang: self.lang,
+            select: self.select,
+            no_ignore: self.no_ignore,
+            no_gitignore: self.no_gitignore,
+            no_gitattributes: self.no_gitattributes,
+            include_generated: self.include_generated,
+            no_default_excludes: self.no_default_excludes,
+            allow_sensitive: self.allow_sensitive,
+            hidden: self.hidden,
+            follow_symlinks: self.follow_symlinks,
+            include_external_symlinks: self.include_external_symlinks,
+            max_depth: self.max_depth,
+            max_file_size: self.max_file_size,
+            lossy: self.lossy,
+            dedupe_headers: self.dedupe_headers,
+            redact: (self.redact || self.no_redact).then_some(!self.no_redact),
+            include_nested_repos: self.include_nested_repos,
+            report_sparse: self.report_sparse,
+            exclude_from_tree: self.exclude_from_tree,
+            annotate_tree: self.annotate_tree,
+            tree_only: self.tree_only,
+            no_tree: self.no_tree,
+            tree_style: self.tree_style,
+            tokens: self.tokens,
+            encoding: self.encoding,
+            output: self.output,
+            compress: self.compress,
+            split_tokens: self.split_tokens,
+            budget: self.budget,
+            estimate: self.estimate,
+            stats: self.stats,
+            timings: self.timings,
+            diff: self.diff,
+            git_diff_branch: self.git_diff_branch,
+            git_log_branch: self.git_log_branch,
+            line_number: self.line_number,
+            line_number_for: self.line_number_for,
+            line_number_diff: self.line_number_diff,
+            conflicts: self.conflicts,
+            line_anchors: self.line_anchors,
+            no_codeblock: self.no_codeblock,
+            format: self.format,
+            system_section: self.system_section,
+            model: self.model,
+            cache_breakpoint: self.cache_breakpoint,
+            relative_paths: self.relative_paths,
+            sort: self.sort,
+            no_clipboard: self.no_clipboard,
+            quiet: self.quiet,
+            color: self.color,
+            clipboard: self.clipboard,
+            target: self.target,
+            paste_limits: HashMap::new(),
+            tiers: HashMap::new(),
+            copy_section: self.copy_section,
+            template: self.template,
+            answers: self.answers,
+            var: self.var,
+            theme: self.theme,
+            html_theme: self.html_theme,
+            colors: None,
+            ascii: self.ascii,
+            json: self.json,
+            log_format: self.log_format,
+            list: self.list,
+            explain: self.explain,
+            release: None,
+            bisect: None,
+            preset: None,
+            locale: self.locale,
+            instructions: self.instructions,
+            section: Section::All,
+            count_file_tokens: false,
+            settled_flags: HashSet::new(),
+        };
+        match &self.command {
+            Some(Command::Render { section }) => options.section = *section,
+            Some(Command::ReleaseNotes { from, to }) => {
+                options.release = Some(ReleaseRange {
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+            Some(Command::BisectContext { good, bad }) => {
+                options.bisect = Some(BisectRange {
+                    good: good.clone(),
+                    bad: bad.clone(),
+                });
+            }
+            // Suggestions group files by their directories below the roots
+            Some(Command::Suggest) => {
+                options.count_file_tokens = true;
+                options.relative_paths = true;
+            }
+            _ => {}
+        }
+        (self.command, options)
+    }
+}
+
+/// Defaults loaded from the user and project configuration files.
+///
+/// Every field mirrors the command-line option of the same name. Values given on the
+/// command line always take precedence over the ones loaded from a file.
+#[derive(Deserialize, JsonSchema, Debug, Default)]
+#[serde(default)]
+pub struct FileConfig {
+    /// Patterns to include.
+    pub include: Option<Vec<String>>,
+    /// Patterns to exclude.
+    pub exclude: Option<Vec<String>>,
+    /// Files of patterns to include.
+    pub include_from: Option<Vec<PathBuf>>,
+    /// Files of patterns to exclude.
+    pub exclude_from: Option<Vec<PathBuf>>,
+    /// Include files in case of conflict between include and exclude patterns.
+    pub include_priority: Option<bool>,
+    /// Only pack files whose content matches this regular expression.
+    pub include_containing: Option<String>,
+    /// Skip files whose content matches this regular expression.
+    pub exclude_containing: Option<String>,
+    /// Only pack files in these languages or with these extensions (comma-separated).
+    pub lang: Option<String>,
+    /// Only pack files selected by this expression.
+    pub select: Option<String>,
+    /// Pack files matched by any ignore file.
+    pub no_ignore: Option<bool>,
+    /// Pack files ignored by git.
+    pub no_gitignore: Option<bool>,
+    /// Pack files marked `linguist-generated` or `linguist-vendored` in `.gitattributes`.
+    pub no_gitattributes: Option<bool>,
+    /// Pack generated and minified files, such as `*.min.js` and `*.pb.go`.
+    pub include_generated: Option<bool>,
+    /// Pack the directories and lock files that are skipped by default.
+    pub no_default_excludes: Option<bool>,
+    /// Pack `.env` files, private keys and other credential files.
+    pub allow_sensitive: Option<bool>,
+    /// Pack hidden files and directories.
+    pub hidden: Option<bool>,
+    /// Follow symlinked directories during traversal.
+    pub follow_symlinks: Option<bool>,
+    /// Pack the targets of symlinked files pointing outside the root.
+    pub include_external_symlinks: Option<bool>,
+    /// The number of levels traversed below each root.
+    pub max_depth: Option<usize>,
+    /// The size above which files are skipped.
+    pub max_file_size: Option<FileSize>,
+    /// Pack files that are not valid UTF-8 by replacing their invalid bytes.
+    pub lossy: Option<bool>,
+    /// Render the leading comment blocks shared by several files once.
+    pub dedupe_headers: Option<bool>,
+    /// Replace the secrets found in files with placeholders.
+    pub redact: Option<bool>,
+    /// Pack git repositories nested inside the codebase.
+    pub include_nested_repos: Option<bool>,
+    /// Exclude files/folders from the source tree based on exclude patterns.
+    pub exclude_from_tree: Option<bool>,
+    /// Show the size and token count of each file in the source tree.
+    pub annotate_tree: Option<bool>,
+    /// Whether to render only the source tree, without the file contents.
+    pub tree_only: Option<bool>,
+    /// Whether to leave the source tree out of the prompt.
+    pub no_tree: Option<bool>,
+    /// How to draw the source tree.
+    pub tree_style: Option<TreeStyle>,
+    /// Display the token count of the generated prompt.
+    pub tokens: Option<bool>,
+    /// Tokenizer to use for token count.
+    pub encoding: Option<String>,
+    /// Output file path.
+    pub output: Option<String>,
+    /// Compression of the output file.
+    pub compress: Option<Compression>,
+    /// Token cap of each part the prompt is split into.
+    pub split_tokens: Option<usize>,
+    /// Token budget the prompt is measured against.
+    pub budget: Option<usize>,
+    /// Whether to estimate the token count of each file from its size before reading it.
+    pub estimate: Option<bool>,
+    /// Add line numbers to the source code.
+    pub line_number: Option<bool>,
+    /// Add line numbers to the files matching these patterns (comma-separated).
+    pub line_number_for: Option<String>,
+    /// Add line numbers to the files changed in the git diff.
+    pub line_number_diff: Option<bool>,
+    /// Only pack the files with merge conflicts, with their versions from the git index.
+    pub conflicts: Option<bool>,
+    /// Prefix each line of the files with an anchor of its number and content.
+    pub line_anchors: Option<bool>,
+    /// Disable wrapping code inside markdown code blocks.
+    pub no_codeblock: Option<bool>,
+    /// Format of the prompt, `markdown`, `json`, `xml`, `yaml`, `jsonl`, `openai` or `anthropic`.
+    pub format: Option<OutputFormat>,
+    /// Section of the template rendered as the system message of the chat formats.
+    pub system_section: Option<Section>,
+    /// Model named in the payloads of the chat formats.
+    pub model: Option<String>,
+    /// Section of the template after which `--format anthropic` places its prompt caching breakpoint.
+    pub cache_breakpoint: Option<Section>,
+    /// Use relative paths instead of absolute paths.
+    pub relative_paths: Option<bool>,
+    /// Order of the files in the prompt.
+    pub sort: Option<SortOrder>,
+    /// Disable copying to clipboard.
+    pub no_clipboard: Option<bool>,
+    /// Print only warnings and errors and never ask for template variables.
+    pub quiet: Option<bool>,
+    /// When to color the status output: `auto`, `always` or `never`.
+    pub color: Option<ColorChoice>,
+    /// Clipboard backend to copy the prompt with.
+    pub clipboard: Option<ClipboardBackend>,
+    /// Chat interface the prompt will be pasted into.
+    pub target: Option<String>,
+    /// Paste limits by target, overriding the built-in ones.
+    pub paste_limits: HashMap<String, PasteLimit>,
+    /// Patterns of priority tiers, by name from `tier1`, fitting the files to the budget tier by tier.
+    pub tiers: HashMap<String, Vec<String>>,
+    /// Path to a custom Handlebars template.
+    pub template: Option<PathBuf>,
+    /// Path to a YAML or JSON file answering the variables and forms of the template.
+    pub answers: Option<PathBuf>,
+    /// Instructions appended to the prompt.
+    pub instructions: Option<String>,
+    /// Color theme for status output and HTML pages.
+    pub theme: Option<ThemeName>,
+    /// Color scheme of HTML pages: `auto`, `light` or `dark`.
+    pub html_theme: Option<HtmlTheme>,
+    /// Color overrides for status output.
+    pub colors: Option<ColorsConfig>,
+    /// Language of status messages.
+    pub locale: Option<String>,
+    /// Named profiles, each overriding the values above when selected with `--profile`.
+    pub profiles: HashMap<String, FileConfig>,
+    /// Named file selections saved with `save-selection`, each applied with `--selection`.
+    pub selections: HashMap<String, FileConfig>,
+}
+
+/// A file size, given as a number of bytes with an optional `B`, `KB`, `MB` or `GB` unit.
+///
+/// Units are binary, so `1KB` is 1024 bytes, and are case-insensitive. Configuration files
+/// accept either a string or a plain number of bytes.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "RawFileSize")]
+pub struct FileSize(pub u64);
+
+/// A file size as written in a configuration file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawFileSize {
+    Bytes(u64),
+    Text(String),
+}
+
+impl TryFrom<RawFileSize> for FileSize {
+    type Error = String;
+
+    fn try_from(raw: RawFileSize) -> Result<Self, Self::Error> {
+        match raw {
+            RawFileSize::Bytes(bytes) => Ok(FileSize(bytes)),
+            RawFileSize::Text(text) => text.parse(),
+        }
+    }
+}
+
+impl FromStr for FileSize {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        let split = value
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(value.len());
+        let (number, unit) = value.split_at(split);
+        let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+            "" | "B" => 1,
+            "K" | "KB" | "KIB" => 1 << 10,
+            "M" | "MB" | "MIB" => 1 << 20,
+            "G" | "GB" | "GIB" => 1 << 30,
+            _ => return Err(format!("invalid size unit in '{}'", value)),
+        };
+        let number: f64 = number
+            .parse()
+            .map_err(|_| format!("invalid size '{}'", value))?;
+        Ok(FileSize((number * multiplier as f64) as u64))
+    }
+}
+
+impl JsonSchema for FileSize {
+    fn schema_name() -> String {
+        "FileSize".to_string()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        let text = SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(schemars::schema::StringValidation {
+                pattern: Some(r"^\s*[0-9.]+\s*([kKmMgG]([iI]?[bB])?|[bB])?\s*$".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        SchemaObject {
+            metadata: Some(Box::new(schemars::schema::Metadata {
+                description: Some("A number of bytes, or a size such as \"200KB\" or \"1MB\".".to_string()),
+                ..Default::default()
+            })),
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![gen.subschema_for::<u64>(), text.into()]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl fmt::Display for FileSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+        if self.0 < 1 << 10 {
+            return write!(f, "{} B", self.0);
+        }
+        let mut size = self.0 as f64;
+        let mut unit = UNITS[0];
+        for candidate in UNITS {
+            size /= 1024.0;
+            unit = candidate;
+            if size < 1024.0 {
+                break;
+            }
+        }
+        write!(f, "{:.1} {}", size, unit)
+    }
+}
+
+/// The size above which a chat interface may truncate or reject a paste.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(default)]
+pub struct PasteLimit {
+    /// The maximum number of bytes.
+    pub bytes: Option<usize>,
+    /// The maximum number of characters.
+    pub characters: Option<usize>,
+}
+
+/// Color overrides for status output, by role.
+///
+/// Colors are given by name, e.g. `"bright cyan"`.
+#[derive(Deserialize, Serialize, JsonSchema, Debug, Default, Clone)]
+#[serde(default)]
+pub struct ColorsConfig {
+    /// The color of the brackets around status symbols.
+    pub bracket: Option<String>,
+    /// The color of success symbols and messages.
+    pub success: Option<String>,
+    /// The color of informational symbols.
+    pub info: Option<String>,
+    /// The color of error symbols and messages.
+    pub error: Option<String>,
+    /// The color of highlighted values, such as token counts.
+    pub highlight: Option<String>,
+}
+
+/// The built-in color themes.
+#[derive(ValueEnum, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    /// The standard colors.
+    #[default]
+    Default,
+    /// Brighter colors for higher contrast.
+    HighContrast,
+}
+
+/// The color scheme of HTML pages, selected with `--html-theme`.
+#[derive(ValueEnum, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum HtmlTheme {
+    /// Light or dark, following the `prefers-color-scheme` of the browser.
+    #[default]
+    Auto,
+    /// Dark text on a light background.
+    Light,
+    /// Light text on a dark background.
+    Dark,
+}
+
+/// When to color the status output, selected with `--color`.
+#[derive(ValueEnum, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorChoice {
+    /// On terminals, unless the `NO_COLOR` environment variable is set.
+    #[default]
+    Auto,
+    /// Always, even when the output is piped.
+    Always,
+    /// Never.
+    Never,
+}
+
+/// The formats of the diagnostic logs, selected with `--log-format`.
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+/// The compressions of the output file, selected with `--compress`.
+#[derive(ValueEnum, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Compression {
+    /// gzip, which any archive tool reads.
+    Gzip,
+    /// Zstandard, which is smaller and faster.
+    Zstd,
+}
+
+impl Compression {
+    /// Returns the extension of the files compressed this way.
+    ///
+    /// # Returns
+    ///
+    /// * `&'static str` - The extension, without the leading dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gz",
+            Compression::Zstd => "zst",
+        }
+    }
+
+    /// Returns the compression of a path from its extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the file.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Compression>` - The compression, or `None` if the path ends with neither `.gz` nor `.zst`.
+    pub fn from_path(path: &str) -> Option<Self> {
+        Compression::value_variants()
+            .iter()
+            .copied()
+            .find(|compression| path.ends_with(&format!(".{}", compression.extension())))
+    }
+}
+
+/// The styles the source tree can be drawn in.
+#[derive(ValueEnum, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TreeStyle {
+    /// With box-drawing characters, such as `├──`.
+    #[default]
+    Unicode,
+    /// With ASCII characters only, such as `|--`.
+    Ascii,
+    /// With two spaces of indentation per level.
+    Indent,
+}
+
+/// The formats the prompt can be rendered in.
+#[derive(ValueEnum, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// The Markdown of the template.
+    #[default]
+    Markdown,
+    /// A JSON document with the Markdown prompt, its token count and files, printed to stdout.
+    Json,
+    /// The `<documents>` structure Anthropic recommends for packing documents into a prompt,
+    /// with a `<document>` per file.
+    Xml,
+    /// The document of `json` as YAML.
+    Yaml,
+    /// One JSON object per line and file, with its path, language, content and tokens.
+    Jsonl,
+    /// A request body of the OpenAI chat completions API, with the `--system-section` of the
+    /// template as the system message and the rest of the prompt as the user message.
+    Openai,
+    /// A request body of the Anthropic Messages API, split like `openai`, with a prompt caching
+    /// breakpoint after the `--cache-breakpoint` section.
+    Anthropic,
+}
+
+impl OutputFormat {
+    /// Checks whether the prompt of this format is the Markdown of the template, on its own,
+    /// wrapped in the document of `json` or `yaml`, or split into the messages of a chat format.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` for `markdown`, `json`, `yaml` and the chat formats.
+    pub fn renders_template(self) -> bool {
+        matches!(self, OutputFormat::Markdown | OutputFormat::Json | OutputFormat::Yaml) || self.is_chat()
+    }
+
+    /// Checks whether this format is the request body of a chat API, splitting the prompt into
+    /// a system and a user message.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` for `openai` and `anthropic`.
+    pub fn is_chat(self) -> bool {
+        matches!(self, OutputFormat::Openai | OutputFormat::Anthropic)
+    }
+}
+
+/// The orders the files of the prompt can be sorted in.
+#[derive(ValueEnum, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortOrder {
+    /// By path, from A to Z.
+    Path,
+    /// By path, from Z to A.
+    PathDesc,
+    /// By size, smallest first.
+    Size,
+    /// By size, largest first.
+    SizeDesc,
+    /// By modification time, oldest first.
+    Modified,
+    /// By modification time, most recently modified first.
+    ModifiedDesc,
+    /// By token count, fewest first.
+    Tokens,
+    /// By token count, most first.
+    TokensDesc,
+    /// By the first include pattern each file matches, in the order the patterns were given.
+    Include,
+}
+
+impl SortOrder {
+    /// Checks whether the order compares token counts, which requires counting the tokens of each file.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` for `tokens` and `tokens-desc`.
+    pub fn needs_tokens(self) -> bool {
+        matches!(self, SortOrder::Tokens | SortOrder::TokensDesc)
+    }
+}
+
+/// The backends the prompt can be copied to the clipboard with.
+#[derive(ValueEnum, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClipboardBackend {
+    /// Detect the backend from the environment.
+    #[default]
+    Auto,
+    /// The system clipboard, through the arboard library.
+    Arboard,
+    /// The OSC 52 escape sequence, copying through the terminal, including over SSH.
+    Osc52,
+    /// The `wl-copy` command of Wayland.
+    WlCopy,
+    /// The `xclip` command of X11.
+    Xclip,
+    /// Do not copy the prompt.
+    None,
+}
+
+/// The built-in presets.
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PresetName {
+    /// Dockerfiles, Terraform, Kubernetes manifests, Helm charts and GitHub Actions workflows.
+    Infra,
+    /// Authentication, authorization, input parsing, cryptography and dependency manifests, with
+    /// a summary of the dependencies and a security review template.
+    Audit,
+    /// READMEs, build configurations and entry points, with the languages and the dependency
+    /// graph of the codebase and an architecture overview template.
+    Onboard,
+    /// Every line anchored by its number and content, with a template asking for unified diffs
+    /// against the anchors, which `codexio apply` checks before applying them.
+    Patch,
+    /// The files with merge conflicts, with the versions of each from the git index and a
+    /// conflict resolution template.
+    Resolve,
+}
+
+/// The revisions a regression is looked for between.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BisectRange {
+    /// The last revision known to work.
+    pub good: String,
+    /// The first revision known to have the regression.
+    pub bad: String,
+}
+
+/// The revisions release notes are generated between.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseRange {
+    /// The revision the release starts after.
+    pub from: String,
+    /// The revision the release ends at.
+    pub to: String,
+}
+
+/// Subcommands of the application.
+#[derive(Subcommand, Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum Command {
+    /// Render a single section of the prompt.
+    Render {
+        /// The section to render.
+        #[clap(long, value_enum)]
+        section: Section,
+    },
+    /// Re-run the last invocation made in the current directory.
+    Again {
+        /// Options added to the saved ones, taking precedence over them.
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        overrides: Vec<String>,
+    },
+    /// Analyze the codebase and suggest patterns and flags to fit the `--budget`.
+    Suggest,
+    /// Save the patterns, filters and budget of this run under a name in `.codexio.toml`, to reuse
+    /// them with `--selection`.
+    SaveSelection {
+        /// The name of the selection.
+        name: String,
+    },
+    /// Generate a security review prompt, the same as `--preset audit`.
+    Audit,
+    /// Generate a prompt explaining the codebase to a new engineer, the same as `--preset onboard`.
+    Onboard,
+    /// Generate a prompt resolving the merge conflicts in progress, the same as `--preset resolve`.
+    Resolve,
+    /// Generate a prompt for writing release notes, from the commits, changed files and diff
+    /// between two revisions, following the format of the existing changelog.
+    ReleaseNotes {
+        /// The revision the release starts after, such as the tag of the previous release.
+        #[clap(long)]
+        from: String,
+        /// The revision the release ends at.
+        #[clap(long, default_value = "HEAD")]
+        to: String,
+    },
+    /// Generate a prompt finding the commit that likely introduced a regression, from the commits
+    /// between a good and a bad revision, their diffs and the current version of the files they touched.
+    BisectContext {
+        /// The last revision known to work.
+        good: String,
+        /// The first revision known to have the regression.
+        #[clap(default_value = "HEAD")]
+        bad: String,
+    },
+    /// Print the sizes of the prompts generated in this repository over time, their token trend
+    /// and the files included most often, to notice the context growing.
+    Stats {
+        /// Write the dashboard as an HTML page to this file.
+        #[clap(long, value_name = "FILE")]
+        html: Option<PathBuf>,
+        /// Serve the dashboard as an HTML page at this address, such as `127.0.0.1:8080`.
+        #[clap(long, value_name = "ADDRESS")]
+        serve: Option<String>,
+    },
+    /// Apply the unified diffs of a response to a prompt of the `patch` preset, after checking
+    /// every hunk against the anchors and the current content of its file.
+    Apply {
+        /// The file holding the response, or `-` to read it from stdin.
+        response: PathBuf,
+        /// Only check the hunks against their files, without changing any.
+        #[clap(long)]
+        check: bool,
+    },
+    /// Generate several prompts in one run, from the jobs of a YAML batch file.
+    Batch {
+        /// The batch file listing the jobs.
+        file: PathBuf,
+        /// The number of jobs to run at once, defaulting to the number of CPUs.
+        #[clap(long, short = 'j', value_name = "N")]
+        jobs: Option<usize>,
+    },
+    /// Check git, the clipboard backend, the terminal, the configuration files, the template and
+    /// the cache directory, printing what fails and how to fix it.
+    Doctor,
+    /// Inspect the configuration files.
+    Config {
+        /// The configuration action to run.
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Work on custom templates.
+    Template {
+        /// The template action to run.
+        #[command(subcommand)]
+        action: TemplateAction,
+    },
+}
+
+impl Command {
+    /// Checks whether this subcommand generates prompts, so `codexio again` can replay it.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` for `render`, `batch` and the subcommands selecting a preset.
+    pub fn generates_prompt(&self) -> bool {
+        matches!(
+            self,
+            Command::Render { .. }
+                | Command::Audit
+                | Command::Onboard
+                | Command::Resolve
+                | Command::ReleaseNotes { .. }
+                | Command::BisectContext { .. }
+                | Command::Batch { .. }
+        )
+    }
+}
+
+/// Actions of the `config` subcommand.
+#[derive(Subcommand, Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfigAction {
+    /// Print the JSON Schema of `.codexio.toml`, for editor validation and autocompletion.
+    Schema,
+}
+
+/// Actions of the `template` subcommand.
+#[derive(Subcommand, Serialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum TemplateAction {
+    /// Render a template against built-in sample data, without traversing a codebase.
+    Preview {
+        /// The Handlebars template file to preview.
+        template: PathBuf,
+    },
+}
+
+/// Returns the JSON Schema of the configuration files.
+///
+/// # Returns
+///
+/// * `Result<String, serde_json::Error>` - The pretty-printed schema, describing every key of `FileConfig`.
+pub fn config_schema() -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&schema_for!(FileConfig))
+}
+
+/// Sections of the generated prompt that can be rendered or copied on their own.
+#[derive(ValueEnum, Serialize, Deserialize, JsonSchema, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Section {
+    /// The file contents.
+    Files,
+    /// The git diff and log output.
+    Diff,
+    /// The source tree.
+    Tree,
+    /// The instructions.
+    Instructions,
+    /// The whole rendered prompt.
+    All,
+}
+
+impl Section {
+    /// Returns the name of the partial that renders this section.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&'static str>` - The partial name, or `None` for the whole prompt.
+    pub fn partial_name(self) -> Option<&'static str> {
+        match self {
+            Section::Files => Some("files"),
+            Section::Diff => Some("diff"),
+            Section::Tree => Some("tree"),
+            Section::Instructions => Some("instructions"),
+            Section::All => None,
+        }
+    }
+}
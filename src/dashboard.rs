@@ -0,0 +1,343 @@
+//! This module builds the dashboard of `codexio stats` from the prompts recorded for a
+//! repository: their sizes over time and the files included most often, so teams notice
+//! their context growing before it outgrows the model.
+
+use crate::config::{HtmlTheme, ThemeName};
+use crate::history::{load_runs, RunRecord};
+use crate::messages::{message, message_with, Message};
+use crate::output::{print_dashboard, print_info, print_success};
+use crate::text::escape_xml;
+use crate::theme::html_theme_css;
+use crate::workspace::write_atomic;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::Path;
+
+/// The number of days with runs shown in the token trend.
+const TREND_DAYS: usize = 30;
+
+/// The number of files listed among the most included ones.
+const TOP_FILES: usize = 10;
+
+/// The number of seconds in a day.
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// The dashboard of the prompts recorded for a repository.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Dashboard {
+    /// The number of recorded prompts.
+    pub runs: usize,
+    /// The day of the first recorded prompt, such as `2024-05-01`.
+    pub first_day: Option<String>,
+    /// The day of the last recorded prompt.
+    pub last_day: Option<String>,
+    /// The number of tokens of the last prompt.
+    pub latest_tokens: usize,
+    /// The average number of tokens of the prompts.
+    pub average_tokens: usize,
+    /// The number of tokens of the largest prompt.
+    pub peak_tokens: usize,
+    /// The change of the average number of tokens from the first to the last day of the trend,
+    /// in percent.
+    pub growth_percent: Option<i64>,
+    /// The prompts of the last days with runs, oldest first.
+    pub trend: Vec<TrendDay>,
+    /// The files included most often, most included first.
+    pub top_files: Vec<FileUsage>,
+}
+
+/// The prompts generated on one day.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TrendDay {
+    /// The day, such as `2024-05-01`.
+    pub day: String,
+    /// The number of prompts generated that day.
+    pub runs: usize,
+    /// The average number of tokens of the prompts of that day.
+    pub average_tokens: usize,
+}
+
+/// How often a file was included in the recorded prompts.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct FileUsage {
+    /// The path of the file, as listed in the prompts.
+    pub path: String,
+    /// The number of prompts including the file.
+    pub runs: usize,
+}
+
+/// Builds the dashboard of the recorded prompts.
+///
+/// Prompts whose tokens were not counted are estimated at four bytes per token.
+///
+/// # Arguments
+///
+/// * `runs` - The recorded prompts, oldest first.
+///
+/// # Returns
+///
+/// * `Dashboard` - The sizes, trend and most included files of the prompts.
+pub fn build_dashboard(runs: &[RunRecord]) -> Dashboard {
+    let tokens: Vec<usize> = runs.iter().map(RunRecord::tokens).collect();
+
+    let mut days: BTreeMap<u64, (usize, usize)> = BTreeMap::new();
+    for (run, tokens) in runs.iter().zip(&tokens) {
+        let day = days.entry(run.timestamp / SECONDS_PER_DAY).or_default();
+        day.0 += 1;
+        day.1 += tokens;
+    }
+    let skipped = days.len().saturating_sub(TREND_DAYS);
+    let trend: Vec<TrendDay> = days
+        .into_iter()
+        .skip(skipped)
+        .map(|(day, (runs, tokens))| TrendDay {
+            day: format_day(day * SECONDS_PER_DAY),
+            runs,
+            average_tokens: tokens / runs,
+        })
+        .collect();
+    let growth_percent = match (trend.first(), trend.last()) {
+        (Some(first), Some(last)) if trend.len() > 1 && first.average_tokens > 0 => Some(
+            (last.average_tokens as i64 - first.average_tokens as i64) * 100 / first.average_tokens as i64,
+        ),
+        _ => None,
+    };
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for run in runs {
+        for path in &run.files {
+            *counts.entry(path.as_str()).or_default() += 1;
+        }
+    }
+    let mut top_files: Vec<FileUsage> = counts
+        .into_iter()
+        .map(|(path, runs)| FileUsage {
+            path: path.to_string(),
+            runs,
+        })
+        .collect();
+    top_files.sort_by(|a, b| b.runs.cmp(&a.runs).then_with(|| a.path.cmp(&b.path)));
+    top_files.truncate(TOP_FILES);
+
+    Dashboard {
+        runs: runs.len(),
+        first_day: runs.first().map(|run| format_day(run.timestamp)),
+        last_day: runs.last().map(|run| format_day(run.timestamp)),
+        latest_tokens: tokens.last().copied().unwrap_or(0),
+        average_tokens: tokens.iter().sum::<usize>() / tokens.len().max(1),
+        peak_tokens: tokens.iter().copied().max().unwrap_or(0),
+        growth_percent,
+        trend,
+        top_files,
+    }
+}
+
+/// Runs `codexio stats` for a repository, printing its dashboard, and writing or serving it as
+/// an HTML page when asked to.
+///
+/// # Arguments
+///
+/// * `directory` - The canonical path of the repository the prompts were generated from.
+/// * `html` - The file to write the HTML page to, if any.
+/// * `serve` - The address to serve the HTML page at, if any.
+/// * `json` - Whether to print the dashboard as JSON instead of text.
+/// * `html_theme` - The color scheme of the HTML page.
+/// * `theme` - The built-in theme the colors of the HTML page are taken from.
+///
+/// # Returns
+///
+/// * `Result<()>` - An error if the runs cannot be read, or the page cannot be written or served.
+pub fn run_stats(
+    directory: &Path,
+    html: Option<&Path>,
+    serve: Option<&str>,
+    json: bool,
+    html_theme: HtmlTheme,
+    theme: ThemeName,
+) -> Result<()> {
+    let runs = load_runs(directory)?;
+    if runs.is_empty() {
+        print_info(&message_with(Message::StatsNoRuns, &[("path", &directory.display())]));
+        return Ok(());
+    }
+    let dashboard = build_dashboard(&runs);
+    print_dashboard(&dashboard, json)?;
+
+    let page = render_dashboard_html(&dashboard, &directory.display().to_string(), html_theme, theme);
+    if let Some(path) = html {
+        write_atomic(path, page.as_bytes())
+            .with_context(|| message_with(Message::StatsHtmlWriteFailed, &[("path", &path.display())]))?;
+        print_success(&message_with(Message::StatsHtmlWritten, &[("path", &path.display())]));
+    }
+    if let Some(address) = serve {
+        serve_page(address, &page)?;
+    }
+    Ok(())
+}
+
+/// Renders the dashboard as a self-contained HTML page.
+///
+/// # Arguments
+///
+/// * `dashboard` - The dashboard.
+/// * `title` - The name of the repository, shown as the heading.
+/// * `html_theme` - Whether the page is light, dark, or follows `prefers-color-scheme`.
+/// * `theme` - The built-in theme the colors of the page are taken from.
+///
+/// # Returns
+///
+/// * `String` - The HTML page.
+pub fn render_dashboard_html(dashboard: &Dashboard, title: &str, html_theme: HtmlTheme, theme: ThemeName) -> String {
+    let peak = dashboard.trend.iter().map(|day| day.average_tokens).max().unwrap_or(0).max(1);
+    let trend: String = dashboard
+        .trend
+        .iter()
+        .map(|day| {
+            format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td><div class=\"bar\" style=\"width: {}%\"></div></td></tr>\n",
+                day.day,
+                day.runs,
+                day.average_tokens,
+                day.average_tokens * 100 / peak
+            )
+        })
+        .collect();
+    let files: String = dashboard
+        .top_files
+        .iter()
+        .map(|file| format!("<tr><td>{}</td><td><code>{}</code></td></tr>\n", file.runs, escape_xml(&file.path)))
+        .collect();
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>codexio stats: {title}</title>\n<style>\n{palette}\
+         body {{ font-family: sans-serif; margin: 2em; background: var(--background); color: var(--text); }}\n\
+         table {{ border-collapse: collapse; margin-bottom: 2em; }}\n\
+         td, th {{ padding: 0.2em 0.8em; text-align: left; border-bottom: 1px solid var(--border); }}\n\
+         td:last-child {{ width: 20em; }}\n\
+         .bar {{ background: var(--bar); height: 0.8em; }}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n<p>{overview}</p>\n<p>{tokens}</p>\n\
+         <h2>{trend_heading}</h2>\n<table>\n<tr><th>{day}</th><th>{runs}</th><th>{average}</th><th></th></tr>\n{trend}</table>\n\
+         <h2>{files_heading}</h2>\n<table>\n<tr><th>{runs}</th><th>{file}</th></tr>\n{files}</table>\n</body>\n</html>\n",
+        palette = html_theme_css(html_theme, theme),
+        title = escape_xml(title),
+        overview = escape_xml(&overview(dashboard)),
+        tokens = escape_xml(&token_summary(dashboard)),
+        trend_heading = escape_xml(message(Message::StatsTrend)),
+        files_heading = escape_xml(message(Message::StatsTopFiles)),
+        day = escape_xml(message(Message::StatsDay)),
+        runs = escape_xml(message(Message::StatsRuns)),
+        average = escape_xml(message(Message::StatsAverageTokens)),
+        file = escape_xml(message(Message::ListFile)),
+        trend = trend,
+        files = files,
+    )
+}
+
+/// Returns the line giving the number of prompts and the days they span.
+///
+/// # Arguments
+///
+/// * `dashboard` - The dashboard.
+///
+/// # Returns
+///
+/// * `String` - The overview line.
+pub fn overview(dashboard: &Dashboard) -> String {
+    message_with(
+        Message::StatsOverview,
+        &[
+            ("runs", &dashboard.runs),
+            ("first", &dashboard.first_day.as_deref().unwrap_or("-")),
+            ("last", &dashboard.last_day.as_deref().unwrap_or("-")),
+        ],
+    )
+}
+
+/// Returns the line giving the sizes of the prompts, and how their average changed.
+///
+/// # Arguments
+///
+/// * `dashboard` - The dashboard.
+///
+/// # Returns
+///
+/// * `String` - The token summary line.
+pub fn token_summary(dashboard: &Dashboard) -> String {
+    let mut summary = message_with(
+        Message::StatsTokens,
+        &[
+            ("latest", &dashboard.latest_tokens),
+            ("average", &dashboard.average_tokens),
+            ("peak", &dashboard.peak_tokens),
+        ],
+    );
+    if let (Some(percent), Some(first)) = (dashboard.growth_percent, dashboard.trend.first()) {
+        let percent = format!("{:+}", percent);
+        summary = format!(
+            "{} {}",
+            summary,
+            message_with(Message::StatsGrowth, &[("percent", &percent), ("day", &first.day)])
+        );
+    }
+    summary
+}
+
+/// Formats the day of a timestamp as an ISO 8601 date, in UTC.
+///
+/// # Arguments
+///
+/// * `timestamp` - The number of seconds since the Unix epoch.
+///
+/// # Returns
+///
+/// * `String` - The date, such as `2024-05-01`.
+pub fn format_day(timestamp: u64) -> String {
+    // Converts days since the epoch to a civil date, in eras of 400 years starting on March 1st
+    let days = (timestamp / SECONDS_PER_DAY) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Serves an HTML page at an address until the process is stopped.
+///
+/// # Arguments
+///
+/// * `address` - The address to listen on, such as `127.0.0.1:8080`.
+/// * `page` - The HTML page returned for every request.
+///
+/// # Returns
+///
+/// * `Result<()>` - An error if the address cannot be listened on.
+fn serve_page(address: &str, page: &str) -> Result<()> {
+    let listener = TcpListener::bind(address)
+        .with_context(|| message_with(Message::StatsServeFailed, &[("address", &address)]))?;
+    print_info(&message_with(Message::StatsServing, &[("address", &address)]));
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        page.len(),
+        page
+    );
+    for stream in listener.incoming() {
+        let Ok(mut stream) = stream else {
+            continue;
+        };
+        // The request is read and ignored, as every path gets the same page
+        let mut request = [0; 4096];
+        let _ = stream.read(&mut request);
+        let _ = stream.write_all(response.as_bytes());
+    }
+    Ok(())
+}
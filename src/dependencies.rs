@@ -0,0 +1,345 @@
+//! This module summarizes the dependencies declared in the package manifests of a codebase,
+//! such as `Cargo.toml` and `package.json`, so a security review can check what is pulled in
+//! without reading every manifest.
+
+use crate::path::codebase_files;
+use tracing::debug;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use toml_edit::{DocumentMut, Item};
+
+/// The file names of the package manifests that are summarized.
+pub const MANIFEST_FILES: [&str; 5] = ["Cargo.toml", "package.json", "pyproject.toml", "requirements.txt", "go.mod"];
+
+/// A dependency declared in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Dependency {
+    /// The name of the package.
+    pub name: String,
+    /// The version requirement, or where the package comes from when it is not a registry version.
+    pub version: Option<String>,
+    /// What the dependency is used for: `runtime`, `dev`, `build`, `optional` or `peer`.
+    pub kind: &'static str,
+    /// Whether the dependency is another package of the codebase, referenced by path or workspace.
+    pub local: bool,
+}
+
+/// The dependencies declared in one manifest.
+#[derive(Debug, Clone, Serialize)]
+pub struct Manifest {
+    /// The path of the manifest relative to its root, with `/` separators.
+    pub path: String,
+    /// The package ecosystem of the manifest, such as `cargo` or `npm`.
+    pub ecosystem: &'static str,
+    /// The declared dependencies, in the order of the manifest.
+    pub dependencies: Vec<Dependency>,
+}
+
+/// Summarizes the dependencies declared in the manifests found below the roots.
+///
+/// Manifests in ignored, hidden and default excluded directories, such as `node_modules`, are
+/// left out, as are manifests that cannot be read or parsed.
+///
+/// # Arguments
+///
+/// * `roots` - The root paths of the codebase.
+///
+/// # Returns
+///
+/// * `Vec<Manifest>` - The manifests declaring at least one dependency, sorted by path within each root.
+pub fn dependency_summary(roots: &[PathBuf]) -> Vec<Manifest> {
+    let mut manifests = Vec::new();
+    for root in roots {
+        for path in codebase_files(root) {
+            let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+            if !is_manifest(file_name) {
+                continue;
+            }
+            let Some((ecosystem, dependencies)) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| parse_manifest(file_name, &content))
+            else {
+                debug!("Skipped unreadable manifest: {}", path.display());
+                continue;
+            };
+            if dependencies.is_empty() {
+                continue;
+            }
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            manifests.push(Manifest {
+                path: relative.to_string_lossy().replace('\\', "/"),
+                ecosystem,
+                dependencies,
+            });
+        }
+    }
+    manifests
+}
+
+/// Checks whether a file name is one of a summarized manifest, including `requirements-dev.txt`
+/// and the other requirement files.
+///
+/// # Arguments
+///
+/// * `file_name` - The name of the file.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the file is a manifest.
+pub fn is_manifest(file_name: &str) -> bool {
+    MANIFEST_FILES.contains(&file_name) || (file_name.starts_with("requirements") && file_name.ends_with(".txt"))
+}
+
+/// Parses the dependencies declared in a manifest.
+///
+/// # Arguments
+///
+/// * `file_name` - The name of the manifest, which gives its format.
+/// * `content` - The content of the manifest.
+///
+/// # Returns
+///
+/// * `Option<(&'static str, Vec<Dependency>)>` - The ecosystem and the dependencies, or `None` if the file is not a manifest or cannot be parsed.
+pub fn parse_manifest(file_name: &str, content: &str) -> Option<(&'static str, Vec<Dependency>)> {
+    match file_name {
+        "Cargo.toml" => Some(("cargo", parse_cargo(content)?)),
+        "package.json" => Some(("npm", parse_package_json(content)?)),
+        "pyproject.toml" => Some(("pypi", parse_pyproject(content)?)),
+        "go.mod" => Some(("go", parse_go_mod(content))),
+        name if is_manifest(name) => Some(("pypi", parse_requirements(content))),
+        _ => None,
+    }
+}
+
+/// Parses the dependency tables of a `Cargo.toml`, including the workspace dependencies.
+///
+/// # Arguments
+///
+/// * `content` - The content of the manifest.
+///
+/// # Returns
+///
+/// * `Option<Vec<Dependency>>` - The dependencies, or `None` if the manifest is not valid TOML.
+fn parse_cargo(content: &str) -> Option<Vec<Dependency>> {
+    let document: DocumentMut = content.parse().ok()?;
+    let mut dependencies = Vec::new();
+    let tables = [
+        (document.get("dependencies"), "runtime"),
+        (document.get("dev-dependencies"), "dev"),
+        (document.get("build-dependencies"), "build"),
+        (document.get("workspace").and_then(|workspace| workspace.get("dependencies")), "runtime"),
+    ];
+    for (table, kind) in tables {
+        let Some(table) = table.and_then(Item::as_table_like) else {
+            continue;
+        };
+        for (name, item) in table.iter() {
+            // A dependency is either a version string or a table with a version, path or git source
+            let version = item.as_str().map(str::to_string).or_else(|| {
+                let table = item.as_table_like()?;
+                ["version", "git", "path"]
+                    .iter()
+                    .find_map(|key| table.get(key).and_then(Item::as_str))
+                    .map(str::to_string)
+                    .or_else(|| table.get("workspace").and_then(Item::as_bool).map(|_| "workspace".to_string()))
+            });
+            let flag = |key: &str| {
+                item.as_table_like()
+                    .and_then(|table| table.get(key))
+                    .and_then(Item::as_bool)
+                    .unwrap_or(false)
+            };
+            let has_path = item.as_table_like().is_some_and(|table| table.contains_key("path"));
+            dependencies.push(Dependency {
+                name: name.to_string(),
+                version,
+                kind: if flag("optional") { "optional" } else { kind },
+                local: has_path || flag("workspace"),
+            });
+        }
+    }
+    Some(dependencies)
+}
+
+/// Parses the dependency objects of a `package.json`.
+///
+/// # Arguments
+///
+/// * `content` - The content of the manifest.
+///
+/// # Returns
+///
+/// * `Option<Vec<Dependency>>` - The dependencies, or `None` if the manifest is not valid JSON.
+fn parse_package_json(content: &str) -> Option<Vec<Dependency>> {
+    let package: serde_json::Value = serde_json::from_str(content).ok()?;
+    let mut dependencies = Vec::new();
+    let objects = [
+        ("dependencies", "runtime"),
+        ("devDependencies", "dev"),
+        ("optionalDependencies", "optional"),
+        ("peerDependencies", "peer"),
+    ];
+    for (key, kind) in objects {
+        let Some(object) = package[key].as_object() else {
+            continue;
+        };
+        for (name, version) in object {
+            let version = version.as_str().map(str::to_string);
+            let local = version
+                .as_deref()
+                .is_some_and(|version| ["file:", "link:", "workspace:"].iter().any(|prefix| version.starts_with(prefix)));
+            dependencies.push(Dependency {
+                name: name.clone(),
+                version,
+                kind,
+                local,
+            });
+        }
+    }
+    Some(dependencies)
+}
+
+/// Parses the dependencies of a `pyproject.toml`, from the standard `project` table or from Poetry.
+///
+/// # Arguments
+///
+/// * `content` - The content of the manifest.
+///
+/// # Returns
+///
+/// * `Option<Vec<Dependency>>` - The dependencies, or `None` if the manifest is not valid TOML.
+fn parse_pyproject(content: &str) -> Option<Vec<Dependency>> {
+    let document: DocumentMut = content.parse().ok()?;
+    let mut dependencies = Vec::new();
+    let requirements = |item: Option<&Item>, kind: &'static str| -> Vec<Dependency> {
+        item.and_then(Item::as_array)
+            .into_iter()
+            .flat_map(|array| array.iter())
+            .filter_map(|value| parse_requirement(value.as_str()?, kind))
+            .collect()
+    };
+    let project = document.get("project");
+    dependencies.extend(requirements(project.and_then(|project| project.get("dependencies")), "runtime"));
+    let extras = project
+        .and_then(|project| project.get("optional-dependencies"))
+        .and_then(Item::as_table_like);
+    for (_, extra) in extras.into_iter().flat_map(|extras| extras.iter()) {
+        dependencies.extend(requirements(Some(extra), "optional"));
+    }
+    let poetry = document.get("tool").and_then(|tool| tool.get("poetry"));
+    let tables = [
+        (poetry.and_then(|poetry| poetry.get("dependencies")), "runtime"),
+        (poetry.and_then(|poetry| poetry.get("dev-dependencies")), "dev"),
+    ];
+    for (table, kind) in tables {
+        let Some(table) = table.and_then(Item::as_table_like) else {
+            continue;
+        };
+        for (name, item) in table.iter().filter(|(name, _)| *name != "python") {
+            let version = item
+                .as_str()
+                .or_else(|| item.as_table_like()?.get("version")?.as_str())
+                .map(str::to_string);
+            dependencies.push(Dependency {
+                name: name.to_string(),
+                version,
+                kind,
+                local: item.as_table_like().is_some_and(|table| table.contains_key("path")),
+            });
+        }
+    }
+    Some(dependencies)
+}
+
+/// Parses the lines of a `requirements.txt`, skipping comments, options and includes.
+///
+/// # Arguments
+///
+/// * `content` - The content of the requirements file.
+///
+/// # Returns
+///
+/// * `Vec<Dependency>` - The required packages.
+fn parse_requirements(content: &str) -> Vec<Dependency> {
+    content
+        .lines()
+        .map(|line| line.split(" #").next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with('-'))
+        .filter_map(|line| parse_requirement(line, "runtime"))
+        .collect()
+}
+
+/// Parses a requirement such as `requests[socks]>=2.31; python_version > "3.8"`.
+///
+/// # Arguments
+///
+/// * `line` - The requirement.
+/// * `kind` - What the dependency is used for.
+///
+/// # Returns
+///
+/// * `Option<Dependency>` - The required package, or `None` if the line does not start with a name.
+fn parse_requirement(line: &str, kind: &'static str) -> Option<Dependency> {
+    let line = line.split(';').next()?.trim();
+    let end = line
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(line.len());
+    let name = &line[..end];
+    if name.is_empty() {
+        return None;
+    }
+    let rest = line[end..].trim_start();
+    // Extras are part of what is installed, but not of the version requirement
+    let rest = match rest.strip_prefix('[') {
+        Some(extras) => extras.split_once(']').map_or("", |(_, rest)| rest).trim(),
+        None => rest,
+    };
+    Some(Dependency {
+        name: name.to_string(),
+        version: (!rest.is_empty()).then(|| rest.to_string()),
+        kind,
+        local: false,
+    })
+}
+
+/// Parses the `require` directives of a `go.mod`, in their single-line and block forms.
+///
+/// # Arguments
+///
+/// * `content` - The content of the module file.
+///
+/// # Returns
+///
+/// * `Vec<Dependency>` - The required modules.
+fn parse_go_mod(content: &str) -> Vec<Dependency> {
+    let mut dependencies = Vec::new();
+    let mut in_block = false;
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let requirement = if in_block {
+            if line == ")" {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if line == "require (" {
+            in_block = true;
+            continue;
+        } else if let Some(requirement) = line.strip_prefix("require ") {
+            requirement
+        } else {
+            continue;
+        };
+        let mut parts = requirement.split_whitespace();
+        if let Some(name) = parts.next() {
+            dependencies.push(Dependency {
+                name: name.to_string(),
+                version: parts.next().map(str::to_string),
+                kind: "runtime",
+                local: false,
+            });
+        }
+    }
+    dependencies
+}
@@ -0,0 +1,294 @@
+//! This module runs `codexio doctor`, which probes what a run depends on: the git repository of
+//! the codebase, the clipboard backend, the terminal, the configuration files, the template and
+//! the cache directory, so a run that silently did nothing can be told apart from a broken setup.
+
+use crate::clipboard::clipboard_sink;
+use crate::history::{cache_dir, probe_cache};
+use crate::input::{layer_config, user_config_path, PROJECT_CONFIG_FILE};
+use crate::messages::{message, message_with, Message};
+use crate::options::Options;
+use crate::template::handlebars_setup;
+use clap::ValueEnum;
+use git2::{ErrorCode, Repository};
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+
+/// The outcome of a check.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// The check passed.
+    Pass,
+    /// The check passed, but some features are unavailable.
+    Warn,
+    /// The check failed, so runs will fail or do nothing.
+    Fail,
+}
+
+/// A check of `codexio doctor`.
+#[derive(Serialize, Debug, Clone)]
+pub struct Check {
+    /// The name of what is checked, such as `git` or `clipboard`.
+    pub name: &'static str,
+    /// The outcome of the check.
+    pub status: Status,
+    /// What was found.
+    pub detail: String,
+    /// How to fix a warning or a failure.
+    pub fix: Option<String>,
+}
+
+impl Check {
+    /// Creates a passed check.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of what is checked.
+    /// * `detail` - What was found.
+    ///
+    /// # Returns
+    ///
+    /// * `Check` - The check, without a fix.
+    fn pass(name: &'static str, detail: String) -> Self {
+        Check {
+            name,
+            status: Status::Pass,
+            detail,
+            fix: None,
+        }
+    }
+
+    /// Creates a check that warned or failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of what is checked.
+    /// * `status` - Whether the check warned or failed.
+    /// * `detail` - What was found.
+    /// * `fix` - How to fix it.
+    ///
+    /// # Returns
+    ///
+    /// * `Check` - The check, with its fix.
+    fn problem(name: &'static str, status: Status, detail: String, fix: String) -> Self {
+        Check {
+            name,
+            status,
+            detail,
+            fix: Some(fix),
+        }
+    }
+}
+
+/// Runs the checks of `codexio doctor`.
+///
+/// The configuration files are layered onto the options first, the way a run would, so the
+/// clipboard and template checks probe what a run would use.
+///
+/// # Arguments
+///
+/// * `options` - The command-line options, before the configuration files are layered onto them.
+///
+/// # Returns
+///
+/// * `Vec<Check>` - The checks, in the order they are printed.
+pub fn run_checks(options: &Options) -> Vec<Check> {
+    let mut layered = options.clone();
+    let config = check_config(&mut layered);
+    let options = if config.status == Status::Fail { options } else { &layered };
+    vec![
+        check_git(options),
+        check_clipboard(options),
+        check_terminal(),
+        config,
+        check_template(options),
+        check_cache(),
+    ]
+}
+
+/// Checks that the codebase is in a git repository that can be opened.
+///
+/// # Arguments
+///
+/// * `options` - The options of the run.
+///
+/// # Returns
+///
+/// * `Check` - A warning outside a repository, and a failure if the repository is broken.
+fn check_git(options: &Options) -> Check {
+    let root = options.primary_dir();
+    match Repository::discover(root) {
+        Ok(repo) => {
+            let path = repo.workdir().unwrap_or(repo.path()).display().to_string();
+            let branch = repo
+                .head()
+                .ok()
+                .and_then(|head| head.shorthand().map(str::to_string))
+                .unwrap_or_else(|| "HEAD".to_string());
+            Check::pass(
+                "git",
+                message_with(Message::DoctorGitRepository, &[("path", &path), ("branch", &branch)]),
+            )
+        }
+        Err(error) if error.code() == ErrorCode::NotFound => Check::problem(
+            "git",
+            Status::Warn,
+            message_with(Message::DoctorGitNotRepository, &[("path", &root.display())]),
+            message(Message::DoctorGitNotRepositoryFix).to_string(),
+        ),
+        Err(error) => Check::problem(
+            "git",
+            Status::Fail,
+            message_with(Message::DoctorGitBroken, &[("error", &error.message())]),
+            message(Message::DoctorGitBrokenFix).to_string(),
+        ),
+    }
+}
+
+/// Checks that the clipboard backend of the run can copy, without changing the clipboard.
+///
+/// # Arguments
+///
+/// * `options` - The options of the run.
+///
+/// # Returns
+///
+/// * `Check` - A failure if the backend is unavailable.
+fn check_clipboard(options: &Options) -> Check {
+    if options.no_clipboard {
+        return Check::pass("clipboard", message(Message::DoctorClipboardDisabled).to_string());
+    }
+    let sink = clipboard_sink(options.clipboard.unwrap_or_default());
+    match sink.probe() {
+        Ok(()) => Check::pass(
+            "clipboard",
+            message_with(Message::DoctorClipboardReady, &[("backend", &sink.name())]),
+        ),
+        Err(error) => Check::problem(
+            "clipboard",
+            Status::Fail,
+            error.to_string(),
+            message(Message::DoctorClipboardFix).to_string(),
+        ),
+    }
+}
+
+/// Checks that the terminal can be asked for template variables and renders escape sequences.
+///
+/// # Returns
+///
+/// * `Check` - A warning if nothing can be asked, or if `TERM` cannot render colors.
+fn check_terminal() -> Check {
+    let term = env::var("TERM").unwrap_or_default();
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        Check::problem(
+            "terminal",
+            Status::Warn,
+            message(Message::DoctorTerminalNotInteractive).to_string(),
+            message(Message::DoctorTerminalNotInteractiveFix).to_string(),
+        )
+    } else if term.is_empty() || term == "dumb" {
+        Check::problem(
+            "terminal",
+            Status::Warn,
+            message(Message::DoctorTerminalDumb).to_string(),
+            message(Message::DoctorTerminalDumbFix).to_string(),
+        )
+    } else {
+        Check::pass("terminal", message_with(Message::DoctorTerminalReady, &[("term", &term)]))
+    }
+}
+
+/// Checks that the user and project configuration files parse, by layering them onto the options.
+///
+/// # Arguments
+///
+/// * `options` - The options to layer the configuration files onto.
+///
+/// # Returns
+///
+/// * `Check` - A failure naming the invalid key or file.
+fn check_config(options: &mut Options) -> Check {
+    if let Err(error) = layer_config(options, None, None, None) {
+        return Check::problem(
+            "config",
+            Status::Fail,
+            format!("{:#}", error),
+            message(Message::DoctorConfigFix).to_string(),
+        );
+    }
+    let paths: Vec<String> = user_config_path()
+        .into_iter()
+        .chain([options.primary_dir().join(PROJECT_CONFIG_FILE)])
+        .filter(|path| path.is_file())
+        .map(|path| path.display().to_string())
+        .collect();
+    if paths.is_empty() {
+        Check::pass("config", message(Message::DoctorConfigDefaults).to_string())
+    } else {
+        Check::pass(
+            "config",
+            message_with(Message::DoctorConfigLoaded, &[("paths", &paths.join(", "))]),
+        )
+    }
+}
+
+/// Checks that the template of the run can be read and compiled.
+///
+/// # Arguments
+///
+/// * `options` - The options of the run.
+///
+/// # Returns
+///
+/// * `Check` - A failure if the custom template is missing or has a syntax error.
+fn check_template(options: &Options) -> Check {
+    let Some(path) = &options.template else {
+        let name = options
+            .preset
+            .and_then(|preset| preset.to_possible_value())
+            .map_or_else(|| "default".to_string(), |value| value.get_name().to_string());
+        return Check::pass("template", message_with(Message::DoctorTemplateBuiltin, &[("name", &name)]));
+    };
+    let compiled = fs::read_to_string(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|content| handlebars_setup(&content, "custom"));
+    match compiled {
+        Ok(_) => Check::pass(
+            "template",
+            message_with(Message::DoctorTemplateValid, &[("path", &path.display())]),
+        ),
+        Err(error) => Check::problem(
+            "template",
+            Status::Fail,
+            message_with(
+                Message::DoctorTemplateInvalid,
+                &[("path", &path.display()), ("error", &format!("{:#}", error))],
+            ),
+            message_with(Message::DoctorTemplateFix, &[("path", &path.display())]),
+        ),
+    }
+}
+
+/// Checks that the cache directory, where `codexio again` and `codexio stats` keep their
+/// history, can be written to and that its files parse.
+///
+/// # Returns
+///
+/// * `Check` - A failure naming what is wrong with the cache directory.
+fn check_cache() -> Check {
+    match probe_cache() {
+        Ok(dir) => Check::pass("cache", message_with(Message::DoctorCacheHealthy, &[("path", &dir.display())])),
+        Err(error) => {
+            let dir = cache_dir().map_or_else(|| "~/.cache/codexio".to_string(), |dir| dir.display().to_string());
+            Check::problem(
+                "cache",
+                Status::Fail,
+                format!("{:#}", error),
+                message_with(Message::DoctorCacheFix, &[("path", &dir)]),
+            )
+        }
+    }
+}
@@ -0,0 +1,392 @@
+//! This module explains why a path is packed or left out, replaying the checks of the traversal
+//! in order and naming the pattern, ignore rule or limit that decides.
+
+use crate::binary::is_binary;
+use crate::charset::{decode_lossy, decode_text, decode_utf16};
+use crate::filter::{
+    is_generated_content, is_generated_file, is_sensitive_file, last_match, last_matching_pattern,
+    should_include_file,
+};
+use crate::gitattributes::{GitAttributes, Linguist};
+use crate::language::{detect_language, matches_language};
+use crate::messages::{message, message_with, Message};
+use crate::path::{
+    external_symlink_target, is_default_excluded_file, is_generated_excluded, oversized_file, TraversalOptions,
+    DEFAULT_EXCLUDED_DIRECTORIES, IGNORE_FILE,
+};
+use crate::select::Candidate;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One check of an explanation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    /// Whether the path passed the check.
+    pub passed: bool,
+    /// What the check found, localized.
+    pub detail: String,
+}
+
+/// Why a path is packed or left out.
+#[derive(Debug, Clone)]
+pub struct Explanation {
+    /// The path as given.
+    pub path: PathBuf,
+    /// Whether the path is packed.
+    pub included: bool,
+    /// The checks the path went through, in order, up to the first one it failed.
+    pub steps: Vec<Step>,
+}
+
+/// The checks of an explanation, collected until one fails.
+#[derive(Debug, Default)]
+struct Steps(Vec<Step>);
+
+impl Steps {
+    /// Records a check the path passed.
+    fn pass(&mut self, detail: String) {
+        self.0.push(Step { passed: true, detail });
+    }
+
+    /// Records the check the path failed, and returns the steps to end the explanation with.
+    fn fail(mut self, detail: String) -> Vec<Step> {
+        self.0.push(Step { passed: false, detail });
+        self.0
+    }
+}
+
+/// Explains why a path is packed or left out of a run.
+///
+/// The checks follow the order of the traversal: the directories leading to the path, the
+/// ignore files, the include and exclude patterns, the default excludes and limits, and the
+/// filters applied to the content of the file.
+///
+/// # Arguments
+///
+/// * `path` - The path to explain, relative to the current directory or absolute.
+/// * `roots` - The codebase paths of the run.
+/// * `options` - The traversal options of the run.
+///
+/// # Returns
+///
+/// * `Explanation` - The verdict and the checks leading to it.
+pub fn explain_path(path: &Path, roots: &[PathBuf], options: &TraversalOptions) -> Explanation {
+    let steps = explain_steps(path, roots, options);
+    Explanation {
+        path: path.to_path_buf(),
+        included: steps.iter().all(|step| step.passed),
+        steps,
+    }
+}
+
+/// Runs the checks of an explanation, stopping at the first one the path fails.
+///
+/// # Arguments
+///
+/// * `path` - The path to explain.
+/// * `roots` - The codebase paths of the run.
+/// * `options` - The traversal options of the run.
+///
+/// # Returns
+///
+/// * `Vec<Step>` - The checks the path went through.
+fn explain_steps(path: &Path, roots: &[PathBuf], options: &TraversalOptions) -> Vec<Step> {
+    let mut steps = Steps::default();
+
+    // ~~~ Locate the path as the walk sees it, without resolving a final symlink ~~~
+    let Some(path) = walk_path(path) else {
+        return steps.fail(message(Message::ExplainMissing));
+    };
+    let Some(root) = roots
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .find(|root| path.starts_with(root))
+    else {
+        return steps.fail(message(Message::ExplainOutsideRoots));
+    };
+    if path.is_dir() {
+        return steps.fail(message(Message::ExplainDirectory));
+    }
+    if options.skip_paths.iter().any(|skip| *skip == path) {
+        return steps.fail(message(Message::ExplainOutputFile));
+    }
+    if options.skip_worktree.contains(&path) {
+        return steps.fail(message(Message::ExplainSkipWorktree));
+    }
+
+    // ~~~ The entries leading to the file, from the root down ~~~
+    let relative = path.strip_prefix(&root).unwrap_or(Path::new(""));
+    let entries: Vec<PathBuf> = relative
+        .components()
+        .scan(root.clone(), |entry, component| {
+            entry.push(component);
+            Some(entry.clone())
+        })
+        .collect();
+    for (depth, entry) in entries.iter().enumerate() {
+        let name = entry.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let is_dir = depth + 1 < entries.len();
+        if name == ".git" {
+            return steps.fail(message(Message::ExplainGitDirectory));
+        }
+        if is_dir && options.default_excludes && DEFAULT_EXCLUDED_DIRECTORIES.contains(&name) {
+            return steps.fail(message_with(
+                Message::ExplainDefaultExcludedDirectory,
+                &[("directory", &entry.display())],
+            ));
+        }
+        if is_dir && !options.include_nested_repos && entry.join(".git").is_dir() {
+            return steps.fail(message_with(Message::ExplainNestedRepo, &[("directory", &entry.display())]));
+        }
+        if !options.hidden && name.starts_with('.') {
+            return steps.fail(message_with(Message::ExplainHidden, &[("name", &name)]));
+        }
+    }
+    if let Some(max_depth) = options.max_depth {
+        if entries.len() > max_depth {
+            return steps.fail(message_with(
+                Message::ExplainMaxDepth,
+                &[("depth", &entries.len()), ("max_depth", &max_depth)],
+            ));
+        }
+    }
+
+    // ~~~ Ignore files, checked for every entry, as ignored directories are not walked ~~~
+    if options.no_ignore {
+        steps.pass(message(Message::ExplainIgnoreDisabled));
+    } else {
+        let mut decided = None;
+        for (depth, entry) in entries.iter().enumerate() {
+            match ignore_rule(entry, depth + 1 < entries.len(), options) {
+                Some(rule) if !rule.whitelisted => {
+                    return steps.fail(message_with(
+                        Message::ExplainIgnored,
+                        &[("pattern", &rule.pattern), ("file", &rule.file.display())],
+                    ));
+                }
+                Some(rule) if depth + 1 == entries.len() => decided = Some(rule),
+                _ => {}
+            }
+        }
+        match decided {
+            Some(rule) => steps.pass(message_with(
+                Message::ExplainWhitelisted,
+                &[("pattern", &rule.pattern), ("file", &rule.file.display())],
+            )),
+            None => steps.pass(message(Message::ExplainNotIgnored)),
+        }
+    }
+
+    // ~~~ Include and exclude patterns, matched against the canonical path ~~~
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+    let canonical = canonical.to_string_lossy();
+    let include = last_matching_pattern(&options.include, &canonical);
+    let exclude = last_matching_pattern(&options.exclude, &canonical);
+    let included = should_include_file(&path, &options.include, &options.exclude, options.include_priority);
+    match (include, exclude) {
+        (Some((include, true)), Some((exclude, true))) => {
+            steps.pass(message_with(Message::ExplainIncludeMatched, &[("pattern", &include)]));
+            if !included {
+                return steps.fail(message_with(Message::ExplainExcludePriority, &[("pattern", &exclude)]));
+            }
+            steps.pass(message_with(Message::ExplainIncludePriority, &[("pattern", &exclude)]));
+        }
+        (_, Some((exclude, true))) => {
+            return steps.fail(message_with(Message::ExplainExcludeMatched, &[("pattern", &exclude)]));
+        }
+        (Some((include, false)), _) => {
+            return steps.fail(message_with(Message::ExplainIncludeNegated, &[("pattern", &include)]));
+        }
+        (None, _) if !included => return steps.fail(message(Message::ExplainIncludeMissed)),
+        (include, exclude) => {
+            if let Some((include, _)) = include {
+                steps.pass(message_with(Message::ExplainIncludeMatched, &[("pattern", &include)]));
+            }
+            if let Some((exclude, _)) = exclude {
+                steps.pass(message_with(Message::ExplainExcludeNegated, &[("pattern", &exclude)]));
+            }
+        }
+    }
+
+    // ~~~ Default excludes and limits ~~~
+    if is_default_excluded_file(&path, options) {
+        return steps.fail(message(Message::ExplainLockFile));
+    }
+    if options.linguist_excludes && last_match(&options.include, &path.to_string_lossy()) != Some(true) {
+        if let Some(linguist) = GitAttributes::default().linguist(&path) {
+            let attribute = match linguist {
+                Linguist::Generated => "linguist-generated",
+                Linguist::Vendored => "linguist-vendored",
+            };
+            return steps.fail(message_with(Message::ExplainLinguist, &[("attribute", &attribute)]));
+        }
+    }
+    if is_generated_excluded(&path, options, is_generated_file(&path)) {
+        return steps.fail(message(Message::ExplainGenerated));
+    }
+    if !options.allow_sensitive && is_sensitive_file(&path) {
+        return steps.fail(message(Message::ExplainSensitive));
+    }
+    if !options.include_external_symlinks {
+        if let Some(target) = external_symlink_target(&path, &root) {
+            return steps.fail(message_with(Message::ExplainExternalSymlink, &[("target", &target.display())]));
+        }
+    }
+    if let Some((size, limit)) = oversized_file(&path, options.max_file_size).zip(options.max_file_size) {
+        return steps.fail(message_with(Message::ExplainTooLarge, &[("size", &size), ("limit", &limit)]));
+    }
+
+    // ~~~ Content filters ~~~
+    let Ok(bytes) = fs::read(&path) else {
+        return steps.fail(message(Message::ExplainMissing));
+    };
+    let decoded = match decode_utf16(&bytes) {
+        Some(decoded) => Some(decoded),
+        None if is_binary(&bytes) => None,
+        None if options.lossy => Some(decode_lossy(&bytes)),
+        None => match decode_text(&bytes) {
+            Some(decoded) => Some(decoded),
+            None => return steps.fail(message(Message::ExplainUndetectedEncoding)),
+        },
+    };
+    let text = decoded.as_ref().map(|decoded| decoded.text.as_ref());
+    let language = match text {
+        Some(text) => {
+            if text.trim().is_empty() {
+                return steps.fail(message(Message::ExplainEmpty));
+            }
+            if options.exclude_containing.as_ref().is_some_and(|pattern| pattern.is_match(text)) {
+                return steps.fail(message(Message::ExplainContentExcluded));
+            }
+            if is_generated_excluded(&path, options, is_generated_content(text)) {
+                return steps.fail(message(Message::ExplainGenerated));
+            }
+            detect_language(&path, text)
+        }
+        None => {
+            steps.pass(message(Message::ExplainBinary));
+            detect_language(&path, "")
+        }
+    };
+    if options
+        .include_containing
+        .as_ref()
+        .is_some_and(|pattern| !text.is_some_and(|text| pattern.is_match(text)))
+    {
+        return steps.fail(message(Message::ExplainContentMissing));
+    }
+    if !options.languages.is_empty() && !matches_language(&path, language.as_deref(), &options.languages) {
+        return steps.fail(match &language {
+            Some(language) => message_with(Message::ExplainLanguage, &[("language", language)]),
+            None => message(Message::ExplainNoLanguage),
+        });
+    }
+    if let Some(selection) = &options.selection {
+        let relative_path = relative.to_string_lossy().replace('\\', "/");
+        let candidate = Candidate {
+            relative_path: &relative_path,
+            absolute_path: &path.to_string_lossy(),
+            language: language.as_deref(),
+            text,
+            age: options.change_ages.get(&path).copied(),
+        };
+        if !selection.matches(&candidate) {
+            return steps.fail(message(Message::ExplainNotSelected));
+        }
+    }
+    steps.0
+}
+
+/// Returns the absolute path of an entry as the walk yields it, with its directories resolved
+/// but not the entry itself, so a symlinked file keeps its own path.
+///
+/// # Arguments
+///
+/// * `path` - The path, relative to the current directory or absolute.
+///
+/// # Returns
+///
+/// * `Option<PathBuf>` - The absolute path, or `None` if the path does not exist.
+fn walk_path(path: &Path) -> Option<PathBuf> {
+    fs::symlink_metadata(path).ok()?;
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => {
+            let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+            Some(parent.canonicalize().ok()?.join(name))
+        }
+        _ => path.canonicalize().ok(),
+    }
+}
+
+/// An ignore file rule matching an entry.
+#[derive(Debug)]
+struct IgnoreRule {
+    /// The pattern as written in the ignore file.
+    pattern: String,
+    /// The ignore file the pattern was read from.
+    file: PathBuf,
+    /// Whether the pattern is negated, keeping the entry.
+    whitelisted: bool,
+}
+
+/// Finds the ignore file rule deciding whether an entry is walked.
+///
+/// As in the walk, `.codexioignore` files take precedence over `.ignore` files, which take
+/// precedence over `.gitignore` files, and within each kind the file closest to the entry
+/// wins. The `.git/info/exclude` file of the repository and the global gitignore come last.
+///
+/// # Arguments
+///
+/// * `entry` - The absolute path of the entry.
+/// * `is_dir` - Whether the entry is a directory.
+/// * `options` - The traversal options.
+///
+/// # Returns
+///
+/// * `Option<IgnoreRule>` - The deciding rule, or `None` if no rule matches the entry.
+fn ignore_rule(entry: &Path, is_dir: bool, options: &TraversalOptions) -> Option<IgnoreRule> {
+    let use_gitignore = !options.no_gitignore;
+    let directories: Vec<&Path> = entry.ancestors().skip(1).collect();
+    let repository = directories.iter().find(|directory| directory.join(".git").exists());
+
+    let mut matchers: Vec<Gitignore> = Vec::new();
+    for name in [IGNORE_FILE, ".ignore", ".gitignore"] {
+        if name == ".gitignore" && !use_gitignore {
+            continue;
+        }
+        for directory in &directories {
+            if name == ".gitignore" && !repository.is_some_and(|repository| directory.starts_with(repository)) {
+                continue;
+            }
+            let file = directory.join(name);
+            if file.is_file() {
+                matchers.push(Gitignore::new(&file).0);
+            }
+        }
+    }
+    if use_gitignore {
+        if let Some(repository) = repository {
+            let mut builder = GitignoreBuilder::new(repository);
+            builder.add(repository.join(".git").join("info").join("exclude"));
+            if let Ok(exclude) = builder.build() {
+                matchers.push(exclude);
+            }
+        }
+        matchers.push(Gitignore::global().0);
+    }
+
+    matchers.iter().find_map(|matcher| {
+        let (glob, whitelisted) = match matcher.matched(entry, is_dir) {
+            Match::None => return None,
+            Match::Ignore(glob) => (glob, false),
+            Match::Whitelist(glob) => (glob, true),
+        };
+        Some(IgnoreRule {
+            pattern: glob.original().to_string(),
+            file: glob.from().map(Path::to_path_buf).unwrap_or_else(|| matcher.path().to_path_buf()),
+            whitelisted,
+        })
+    })
+}
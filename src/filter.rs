@@ -1,13 +1,169 @@
 //! This module contains the logic for filtering files based on include and exclude patterns.
 
 use colored::*;
-use glob::Pattern;
-use log::{debug, error};
+use glob::{MatchOptions, Pattern};
 use std::fs;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use std::path::Path;
+use tracing::{debug, error, instrument};
+
+/// Patterns of the files holding credentials, never packed unless `--allow-sensitive` is given,
+/// matched against the end of their path.
+pub const SENSITIVE_PATTERNS: [&str; 27] = [
+    ".env",
+    ".env.*",
+    "*.env",
+    "*.pem",
+    "*.key",
+    "*.p12",
+    "*.pfx",
+    "*.keystore",
+    "*.jks",
+    "id_rsa*",
+    "id_dsa*",
+    "id_ecdsa*",
+    "id_ed25519*",
+    ".netrc",
+    ".npmrc",
+    ".pypirc",
+    ".git-credentials",
+    ".htpasswd",
+    "*.tfstate",
+    "*.tfstate.*",
+    ".aws/credentials",
+    ".azure/accessTokens.json",
+    ".config/gcloud/**/*credentials*.json",
+    ".docker/config.json",
+    ".kube/config",
+    "service-account*.json",
+    "credentials.json",
+];
+
+/// Templates of sensitive files, which document the expected keys without holding their values.
+const SENSITIVE_TEMPLATE_SUFFIXES: [&str; 4] = [".example", ".sample", ".template", ".dist"];
+
+/// The compiled sensitive patterns.
+static SENSITIVE_GLOBS: Lazy<Vec<Pattern>> =
+    Lazy::new(|| SENSITIVE_PATTERNS.iter().map(|pattern| Pattern::new(pattern).unwrap()).collect());
+
+/// Determines whether a file holds credentials, such as a `.env` file or a private key.
+///
+/// Templates such as `.env.example` are not sensitive, as they document the keys a project
+/// expects without holding their values.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the file matches one of the `SENSITIVE_PATTERNS`.
+pub fn is_sensitive_file(path: &Path) -> bool {
+    let path = path.to_string_lossy().replace('\\', "/");
+    if SENSITIVE_TEMPLATE_SUFFIXES.iter().any(|suffix| path.ends_with(suffix)) {
+        return false;
+    }
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    // Patterns match at any depth, so they are tried against every trailing part of the path
+    let components: Vec<&str> = path.split('/').collect();
+    (0..components.len()).any(|start| {
+        let suffix = components[start..].join("/");
+        SENSITIVE_GLOBS.iter().any(|glob| glob.matches_with(&suffix, options))
+    })
+}
+
+/// File name patterns of the output of minifiers, bundlers and code generators, skipped unless
+/// `--include-generated` is given.
+pub const GENERATED_PATTERNS: [&str; 22] = [
+    "*.min.js",
+    "*.min.mjs",
+    "*.min.css",
+    "*.js.map",
+    "*.mjs.map",
+    "*.css.map",
+    "*.d.ts.map",
+    "*.pb.go",
+    "*.pb.gw.go",
+    "*.pb.cc",
+    "*.pb.h",
+    "*.pb.swift",
+    "*.pb.dart",
+    "*_pb2.py",
+    "*_pb2.pyi",
+    "*_pb2_grpc.py",
+    "*_pb.js",
+    "*_pb.d.ts",
+    "*_grpc_pb.js",
+    "*.g.dart",
+    "*.freezed.dart",
+    "zz_generated.*",
+];
+
+/// The number of leading lines searched for a generated-code marker.
+const GENERATED_MARKER_LINES: usize = 10;
+
+/// The average line length, in bytes, above which a large text file is considered minified.
+const MINIFIED_LINE_LENGTH: usize = 1000;
+
+/// The size, in bytes, above which a text file may be considered minified.
+const MINIFIED_MIN_SIZE: usize = 4096;
+
+/// The compiled generated patterns.
+static GENERATED_GLOBS: Lazy<Vec<Pattern>> =
+    Lazy::new(|| GENERATED_PATTERNS.iter().map(|pattern| Pattern::new(pattern).unwrap()).collect());
+
+/// The comments code generators put at the top of their output, such as Go's
+/// `// Code generated by protoc-gen-go. DO NOT EDIT.` or `@generated`.
+static GENERATED_MARKER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)code generated .*do not edit|@generated\b|<auto-?generated|auto-?generated by .*do not (edit|modify)").unwrap()
+});
+
+/// Determines whether a file is generated, from its name.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the file name matches one of the `GENERATED_PATTERNS`.
+pub fn is_generated_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| GENERATED_GLOBS.iter().any(|glob| glob.matches(name)))
+}
+
+/// Determines whether the content of a file is generated, from a generated-code marker in its
+/// first lines, or minified, from lines averaging over `MINIFIED_LINE_LENGTH` bytes.
+///
+/// # Arguments
+///
+/// * `text` - The content of the file.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the content looks generated or minified.
+pub fn is_generated_content(text: &str) -> bool {
+    if text
+        .lines()
+        .take(GENERATED_MARKER_LINES)
+        .any(|line| GENERATED_MARKER.is_match(line))
+    {
+        return true;
+    }
+    text.len() > MINIFIED_MIN_SIZE && text.len() / text.lines().count().max(1) > MINIFIED_LINE_LENGTH
+}
 
 /// Determines whether a file should be included based on include and exclude patterns.
 ///
+/// Patterns starting with `!` are negated, as in `.gitignore`: within each list, the last
+/// matching pattern decides, so `**/tests/**,!**/tests/fixtures/important.json` excludes the
+/// tests except one fixture. A leading `\!` matches a literal `!`.
+///
 /// # Arguments
 ///
 /// * `path` - The path to the file to be checked.
@@ -18,6 +174,7 @@ use std::path::Path;
 /// # Returns
 ///
 /// * `bool` - `true` if the file should be included, `false` otherwise.
+#[instrument(name = "filter", skip_all)]
 pub fn should_include_file(
     path: &Path,
     include_patterns: &[String],
@@ -35,23 +192,21 @@ pub fn should_include_file(
     let path_str = canonical_path.to_str().unwrap();
 
     // ~~~ Check glob patterns ~~~
-    let included = include_patterns
-        .iter()
-        .any(|pattern| Pattern::new(pattern).unwrap().matches(path_str));
-    let excluded = exclude_patterns
-        .iter()
-        .any(|pattern| Pattern::new(pattern).unwrap().matches(path_str));
+    let included = last_match(include_patterns, path_str);
+    let excluded = last_match(exclude_patterns, path_str) == Some(true);
+    let only_negated_includes = include_patterns.iter().all(|pattern| pattern.starts_with('!'));
 
     // ~~~ Decision ~~~
     let result = match (included, excluded) {
-        (true, true) => include_priority, // If both include and exclude patterns match, use the include_priority flag
-        (true, false) => true,            // If the path is included and not excluded, include it
-        (false, true) => false,           // If the path is excluded, exclude it
-        (false, false) => include_patterns.is_empty(), // If no include patterns are provided, include everything
+        (Some(true), true) => include_priority, // If both include and exclude patterns match, use the include_priority flag
+        (Some(true), false) => true,            // If the path is included and not excluded, include it
+        (_, true) => false,                     // If the path is excluded, exclude it
+        (Some(false), false) => false,          // If the path is matched by a negated include pattern, exclude it
+        (None, false) => only_negated_includes, // If no include patterns are provided, include everything
     };
 
     debug!(
-        "Checking path: {:?}, {}: {}, {}: {}, decision: {}",
+        "Checking path: {:?}, {}: {:?}, {}: {}, decision: {}",
         path_str,
         "included".bold().green(),
         included,
@@ -61,3 +216,67 @@ pub fn should_include_file(
     );
     result
 }
+
+/// Returns the outcome of the last pattern of a list matching a path.
+///
+/// # Arguments
+///
+/// * `patterns` - The glob patterns, where a leading `!` negates a pattern.
+/// * `path` - The path to match.
+///
+/// # Returns
+///
+/// * `Option<bool>` - `Some(true)` if the last matching pattern is positive, `Some(false)` if it is negated, `None` if no pattern matches.
+pub fn last_match(patterns: &[String], path: &str) -> Option<bool> {
+    last_matching_pattern(patterns, path).map(|(_, positive)| positive)
+}
+
+/// Returns the position of the first positive pattern of a list matching a path, counting only
+/// the positive patterns, so files can be grouped in the order their patterns were given.
+///
+/// # Arguments
+///
+/// * `patterns` - The glob patterns, where a leading `!` negates a pattern.
+/// * `path` - The path to match.
+///
+/// # Returns
+///
+/// * `Option<usize>` - The position of the first matching positive pattern, or `None` if none matches.
+pub fn first_matching_pattern(patterns: &[String], path: &str) -> Option<usize> {
+    patterns
+        .iter()
+        .filter(|pattern| !pattern.starts_with('!'))
+        .position(|pattern| {
+            let glob = match pattern.strip_prefix('\\') {
+                Some(literal) if literal.starts_with('!') => literal,
+                _ => pattern.as_str(),
+            };
+            Pattern::new(glob).unwrap().matches(path)
+        })
+}
+
+/// Returns the last pattern of a list matching a path, with its outcome.
+///
+/// # Arguments
+///
+/// * `patterns` - The glob patterns, where a leading `!` negates a pattern.
+/// * `path` - The path to match.
+///
+/// # Returns
+///
+/// * `Option<(&str, bool)>` - The pattern as given, and `true` if it is positive, or `None` if no pattern matches.
+pub fn last_matching_pattern<'a>(patterns: &'a [String], path: &str) -> Option<(&'a str, bool)> {
+    patterns.iter().rev().find_map(|pattern| {
+        let (glob, negated) = match pattern.strip_prefix('!') {
+            Some(glob) => (glob, true),
+            None => match pattern.strip_prefix('\\') {
+                Some(literal) if literal.starts_with('!') => (literal, false),
+                _ => (pattern.as_str(), false),
+            },
+        };
+        Pattern::new(glob)
+            .unwrap()
+            .matches(path)
+            .then_some((pattern.as_str(), !negated))
+    })
+}
@@ -0,0 +1,276 @@
+//! This module handles the forms templates declare for structured variables, such as a task
+//! with a title, reproduction steps and acceptance criteria. Each form is collected field by
+//! field, interactively or from an answers file, and rendered as a well-formed section.
+//!
+//! Forms are declared in a Handlebars comment starting with `forms:`, which is not rendered:
+//!
+//! ```handlebars
+//! {{!--
+//! forms:
+//!   - name: task
+//!     title: Task
+//!     fields:
+//!       - name: title
+//!       - name: steps
+//!         label: Steps to reproduce
+//!         kind: steps
+//! --}}
+//! {{ task.section }}
+//! ```
+
+use crate::messages::{message, message_with, Message};
+use anyhow::{anyhow, bail, Result};
+use inquire::{required, Text};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+/// The Handlebars comments of a template, which may declare forms.
+static COMMENT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)\{\{!--(?P<body>.*?)--\}\}").unwrap());
+
+/// A form declared by a template, collected into the variable of its name.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Form {
+    /// The name of the variable holding the answers, such as `task`.
+    pub name: String,
+    /// The heading of the rendered section, defaulting to the name.
+    pub title: Option<String>,
+    /// The fields of the form, in the order they are asked for and rendered.
+    pub fields: Vec<FormField>,
+}
+
+/// A field of a form.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FormField {
+    /// The name of the field in the variable of the form, such as `steps`.
+    pub name: String,
+    /// The label the field is asked for with and rendered under, defaulting to the name.
+    pub label: Option<String>,
+    /// What the field holds, which sets how it is asked for and rendered.
+    #[serde(default)]
+    pub kind: FieldKind,
+    /// Whether the field has to be filled in.
+    #[serde(default)]
+    pub required: bool,
+    /// A hint shown while the field is asked for.
+    pub help: Option<String>,
+}
+
+impl FormField {
+    /// Returns the label of the field.
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - The label, or the name if the field has none.
+    pub fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// What a form field holds.
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FieldKind {
+    /// A single line of text, rendered as a paragraph.
+    #[default]
+    Text,
+    /// Items rendered as a bulleted list.
+    List,
+    /// Items rendered as a numbered list, such as reproduction steps.
+    Steps,
+    /// Items rendered as a checklist, such as acceptance criteria.
+    Checklist,
+}
+
+/// The declarations of a comment starting with `forms:`.
+#[derive(Deserialize)]
+struct FormsBlock {
+    /// The declared forms.
+    forms: Vec<Form>,
+}
+
+/// Parses the forms declared in the comments of a template.
+///
+/// # Arguments
+///
+/// * `template` - The Handlebars template string.
+///
+/// # Returns
+///
+/// * `Result<Vec<Form>>` - The declared forms, in order, or an error if a declaration is invalid.
+pub fn parse_forms(template: &str) -> Result<Vec<Form>> {
+    let mut forms = Vec::new();
+    for captures in COMMENT.captures_iter(template) {
+        let body = &captures["body"];
+        if !body.trim_start().starts_with("forms:") {
+            continue;
+        }
+        let block: FormsBlock = serde_yaml::from_str(body)
+            .map_err(|e| anyhow!(message_with(Message::FormInvalid, &[("error", &e)])))?;
+        forms.extend(block.forms);
+    }
+    Ok(forms)
+}
+
+/// Collects the answers to a form, from the answers file if one was given, or interactively.
+///
+/// # Arguments
+///
+/// * `form` - The form to collect.
+/// * `answers` - The answers to the form from the answers file, if one was given.
+///
+/// # Returns
+///
+/// * `Result<Value>` - The answers by field, with the rendered form as `section`, or an error if a required field is missing from the answers.
+pub fn collect_form(form: &Form, answers: Option<&Value>) -> Result<Value> {
+    let mut values = Map::new();
+    for field in &form.fields {
+        let value = match answers {
+            Some(answers) => answer_field(field, answers.get(&field.name))?,
+            None => ask_field(field)?,
+        };
+        values.insert(field.name.clone(), value);
+    }
+    let section = render_form(form, &values);
+    values.insert("section".to_string(), Value::String(section));
+    Ok(Value::Object(values))
+}
+
+/// Fills a form with placeholders naming its fields, for `codexio template preview`.
+///
+/// # Arguments
+///
+/// * `form` - The form to fill.
+///
+/// # Returns
+///
+/// * `Value` - The placeholders by field, with the rendered form as `section`.
+pub fn placeholder_form(form: &Form) -> Value {
+    let mut values = Map::new();
+    for field in &form.fields {
+        let placeholder = format!("<{}>", field.label());
+        let value = match field.kind {
+            FieldKind::Text => json!(placeholder),
+            FieldKind::List | FieldKind::Steps | FieldKind::Checklist => json!([placeholder]),
+        };
+        values.insert(field.name.clone(), value);
+    }
+    let section = render_form(form, &values);
+    values.insert("section".to_string(), Value::String(section));
+    Value::Object(values)
+}
+
+/// Renders the answers to a form as a Markdown section, with a heading per filled-in field.
+///
+/// # Arguments
+///
+/// * `form` - The form.
+/// * `values` - The answers by field.
+///
+/// # Returns
+///
+/// * `String` - The rendered section.
+pub fn render_form(form: &Form, values: &Map<String, Value>) -> String {
+    let mut section = format!("## {}\n", form.title.as_deref().unwrap_or(&form.name));
+    for field in &form.fields {
+        let body = match (field.kind, values.get(&field.name)) {
+            (FieldKind::Text, Some(Value::String(text))) if !text.trim().is_empty() => text.trim().to_string(),
+            (kind, Some(Value::Array(items))) if !items.is_empty() => items
+                .iter()
+                .filter_map(Value::as_str)
+                .enumerate()
+                .map(|(index, item)| match kind {
+                    FieldKind::Steps => format!("{}. {}", index + 1, item),
+                    FieldKind::Checklist => format!("- [ ] {}", item),
+                    FieldKind::Text | FieldKind::List => format!("- {}", item),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => continue,
+        };
+        section.push_str(&format!("\n### {}\n\n{}\n", field.label(), body));
+    }
+    section
+}
+
+/// Reads the answer to a field from the answers file.
+///
+/// List fields take a list or a single string, and text fields a string or a list of lines.
+///
+/// # Arguments
+///
+/// * `field` - The field.
+/// * `answer` - The answer given in the file, if any.
+///
+/// # Returns
+///
+/// * `Result<Value>` - The answer, or an error if a required field has none.
+fn answer_field(field: &FormField, answer: Option<&Value>) -> Result<Value> {
+    let lines: Vec<String> = match answer {
+        Some(Value::Array(items)) => items.iter().map(scalar_text).collect(),
+        Some(Value::Null) | None => Vec::new(),
+        Some(value) => vec![scalar_text(value)],
+    };
+    let lines: Vec<String> = lines.into_iter().filter(|line| !line.trim().is_empty()).collect();
+    if field.required && lines.is_empty() {
+        bail!(message_with(Message::AnswerMissing, &[("name", &field.name)]));
+    }
+    Ok(match field.kind {
+        FieldKind::Text => Value::String(lines.join("\n")),
+        FieldKind::List | FieldKind::Steps | FieldKind::Checklist => json!(lines),
+    })
+}
+
+/// Asks for a field interactively, item by item for list fields.
+///
+/// # Arguments
+///
+/// * `field` - The field.
+///
+/// # Returns
+///
+/// * `Result<Value>` - The answer.
+fn ask_field(field: &FormField) -> Result<Value> {
+    if field.kind == FieldKind::Text {
+        let mut prompt = Text::new(field.label());
+        if let Some(help) = &field.help {
+            prompt = prompt.with_help_message(help);
+        }
+        if field.required {
+            prompt = prompt.with_validator(required!(message(Message::FormFieldRequired)));
+        }
+        return Ok(Value::String(prompt.prompt()?));
+    }
+
+    let help = field.help.as_deref().unwrap_or(message(Message::FillFormList));
+    let mut items = Vec::new();
+    loop {
+        let prompt = format!("{} ({})", field.label(), items.len() + 1);
+        let item = Text::new(&prompt).with_help_message(help).prompt()?;
+        if !item.trim().is_empty() {
+            items.push(item.trim().to_string());
+        } else if field.required && items.is_empty() {
+            eprintln!("{}", message(Message::FormFieldRequired));
+        } else {
+            break;
+        }
+    }
+    Ok(json!(items))
+}
+
+/// Returns the text of a scalar answer, such as a string or a number.
+///
+/// # Arguments
+///
+/// * `value` - The answer.
+///
+/// # Returns
+///
+/// * `String` - The answer as text.
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
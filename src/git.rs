@@ -1,9 +1,13 @@
 //! This module handles git operations.
 
+use crate::messages::{message, message_with, Message};
 use anyhow::{Context, Result};
-use git2::{DiffOptions, Repository};
-use log::info;
-use std::path::Path;
+use git2::{DiffOptions, IndexEntry, IndexEntryExtendedFlag, Repository, RepositoryState, Sort, Status, StatusOptions};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, instrument};
 
 /// Generates a git diff for the repository at the provided path
 ///
@@ -14,11 +18,13 @@ use std::path::Path;
 /// # Returns
 ///
 /// * `Result<String, git2::Error>` - The generated git diff as a string or an error
+#[instrument(name = "git", skip_all)]
 pub fn get_git_diff(repo_path: &Path) -> Result<String> {
     info!("Opening repository at path: {:?}", repo_path);
-    let repo = Repository::open(repo_path).context("Failed to open repository")?;
-    let head = repo.head().context("Failed to get repository head")?;
-    let head_tree = head.peel_to_tree().context("Failed to peel to tree")?;
+    let repo = Repository::open(repo_path)
+        .with_context(|| message_with(Message::GitOpenFailed, &[("path", &repo_path.display())]))?;
+    let head = repo.head().with_context(|| message(Message::GitHeadFailed))?;
+    let head_tree = head.peel_to_tree().with_context(|| message(Message::GitHeadFailed))?;
 
     let diff = repo
         .diff_tree_to_index(
@@ -26,14 +32,14 @@ pub fn get_git_diff(repo_path: &Path) -> Result<String> {
             None,
             Some(DiffOptions::new().ignore_whitespace(true)),
         )
-        .context("Failed to generate diff")?;
+        .with_context(|| message(Message::GitDiffGenerateFailed))?;
 
     let mut diff_text = Vec::new();
     diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
         diff_text.extend_from_slice(line.content());
         true
     })
-        .context("Failed to print diff")?;
+        .with_context(|| message(Message::GitDiffPrintFailed))?;
 
     info!("Generated git diff successfully");
     Ok(String::from_utf8_lossy(&diff_text).into_owned())
@@ -50,17 +56,19 @@ pub fn get_git_diff(repo_path: &Path) -> Result<String> {
 /// # Returns
 ///
 /// * `Result<String, git2::Error>` - The generated git diff as a string or an error
+#[instrument(name = "git", skip_all)]
 pub fn get_git_diff_between_branches(
     repo_path: &Path,
     branch1: &str,
     branch2: &str,
 ) -> Result<String> {
     info!("Opening repository at path: {:?}", repo_path);
-    let repo = Repository::open(repo_path).context("Failed to open repository")?;
+    let repo = Repository::open(repo_path)
+        .with_context(|| message_with(Message::GitOpenFailed, &[("path", &repo_path.display())]))?;
 
     for branch in [branch1, branch2].iter() {
         if !branch_exists(&repo, branch) {
-            return Err(anyhow::anyhow!("Branch {} doesn't exist!", branch));
+            return Err(anyhow::anyhow!(message_with(Message::BranchNotFound, &[("branch", &branch)])));
         }
     }
 
@@ -76,19 +84,62 @@ pub fn get_git_diff_between_branches(
             Some(&branch2_tree),
             Some(DiffOptions::new().ignore_whitespace(true)),
         )
-        .context("Failed to generate diff between branches")?;
+        .with_context(|| message(Message::GitDiffGenerateFailed))?;
 
     let mut diff_text = Vec::new();
     diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
         diff_text.extend_from_slice(line.content());
         true
     })
-        .context("Failed to print diff")?;
+        .with_context(|| message(Message::GitDiffPrintFailed))?;
 
     info!("Generated git diff between branches successfully");
     Ok(String::from_utf8_lossy(&diff_text).into_owned())
 }
 
+/// Returns the files changed in a git diff for the repository at the provided path, between
+/// two branches if given, or between the head and the index otherwise
+///
+/// # Arguments
+///
+/// * `repo_path` - A reference to the path of the git repository
+/// * `branches` - The names of the branches to diff, if any
+///
+/// # Returns
+///
+/// * `Result<Vec<PathBuf>>` - The absolute paths of the changed files, or an error if the diff cannot be generated
+#[instrument(name = "git", skip_all)]
+pub fn get_diff_paths(repo_path: &Path, branches: Option<(&str, &str)>) -> Result<Vec<PathBuf>> {
+    let repo = Repository::open(repo_path)
+        .with_context(|| message_with(Message::GitOpenFailed, &[("path", &repo_path.display())]))?;
+    let workdir = repo
+        .workdir()
+        .with_context(|| message(Message::GitNoWorkdir))?
+        .canonicalize()
+        .with_context(|| message(Message::GitWorkdirFailed))?;
+
+    let diff = match branches {
+        Some((branch1, branch2)) => {
+            let branch1_tree = repo.revparse_single(branch1)?.peel_to_commit()?.tree()?;
+            let branch2_tree = repo.revparse_single(branch2)?.peel_to_commit()?.tree()?;
+            repo.diff_tree_to_tree(Some(&branch1_tree), Some(&branch2_tree), None)
+        }
+        None => {
+            let head = repo.head().with_context(|| message(Message::GitHeadFailed))?;
+            let head_tree = head.peel_to_tree().with_context(|| message(Message::GitHeadFailed))?;
+            repo.diff_tree_to_index(Some(&head_tree), None, None)
+        }
+    }
+    .with_context(|| message(Message::GitDiffGenerateFailed))?;
+
+    let paths = diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().map(|path| workdir.join(path)))
+        .collect::<Vec<_>>();
+    info!("Found {} files in the diff", paths.len());
+    Ok(paths)
+}
+
 /// Retrieves the git log between two branches for the repository at the provided path
 ///
 /// # Arguments
@@ -100,32 +151,34 @@ pub fn get_git_diff_between_branches(
 /// # Returns
 ///
 /// * `Result<String, git2::Error>` - The git log as a string or an error
+#[instrument(name = "git", skip_all)]
 pub fn get_git_log(repo_path: &Path, branch1: &str, branch2: &str) -> Result<String> {
     info!("Opening repository at path: {:?}", repo_path);
-    let repo = Repository::open(repo_path).context("Failed to open repository")?;
+    let repo = Repository::open(repo_path)
+        .with_context(|| message_with(Message::GitOpenFailed, &[("path", &repo_path.display())]))?;
 
     for branch in [branch1, branch2].iter() {
         if !branch_exists(&repo, branch) {
-            return Err(anyhow::anyhow!("Branch {} doesn't exist!", branch));
+            return Err(anyhow::anyhow!(message_with(Message::BranchNotFound, &[("branch", &branch)])));
         }
     }
 
     let branch1_commit = repo.revparse_single(branch1)?.peel_to_commit()?;
     let branch2_commit = repo.revparse_single(branch2)?.peel_to_commit()?;
 
-    let mut revwalk = repo.revwalk().context("Failed to create revwalk")?;
+    let mut revwalk = repo.revwalk().with_context(|| message(Message::GitHistoryFailed))?;
     revwalk
         .push(branch2_commit.id())
-        .context("Failed to push branch2 commit to revwalk")?;
+        .with_context(|| message(Message::GitHistoryFailed))?;
     revwalk
         .hide(branch1_commit.id())
-        .context("Failed to hide branch1 commit from revwalk")?;
+        .with_context(|| message(Message::GitHistoryFailed))?;
     revwalk.set_sorting(git2::Sort::REVERSE)?;
 
     let mut log_text = String::new();
     for oid in revwalk {
-        let oid = oid.context("Failed to get OID from revwalk")?;
-        let commit = repo.find_commit(oid).context("Failed to find commit")?;
+        let oid = oid.with_context(|| message(Message::GitHistoryFailed))?;
+        let commit = repo.find_commit(oid).with_context(|| message(Message::GitHistoryFailed))?;
         log_text.push_str(&format!(
             "{} - {}\n",
             &commit.id().to_string()[..7],
@@ -152,4 +205,197 @@ fn branch_exists(repo: &Repository, branch_name: &str) -> bool {
         Ok(_) => true,
         Err(_) => false,
     }
-}
\ No newline at end of file
+}
+/// Returns the files of the repository containing the provided path that are not checked
+/// out, because of a sparse checkout or the skip-worktree bit
+///
+/// # Arguments
+///
+/// * `path` - A path inside the repository, which does not have to exist
+///
+/// # Returns
+///
+/// * `Result<Vec<PathBuf>>` - The absolute paths of the skip-worktree files, or an error if no repository is found
+#[instrument(name = "git", skip_all)]
+pub fn get_skip_worktree_paths(path: &Path) -> Result<Vec<PathBuf>> {
+    let existing = path
+        .ancestors()
+        .find(|ancestor| ancestor.exists() && !ancestor.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let repo = Repository::discover(existing)
+        .with_context(|| message_with(Message::GitOpenFailed, &[("path", &existing.display())]))?;
+    let workdir = repo
+        .workdir()
+        .with_context(|| message(Message::GitNoWorkdir))?
+        .canonicalize()
+        .with_context(|| message(Message::GitWorkdirFailed))?;
+    let index = repo.index().with_context(|| message(Message::GitIndexFailed))?;
+
+    let skip_worktree = IndexEntryExtendedFlag::SKIP_WORKTREE.bits();
+    let paths = index
+        .iter()
+        .filter(|entry| entry.flags_extended & skip_worktree != 0)
+        .map(|entry| workdir.join(String::from_utf8_lossy(&entry.path).as_ref()))
+        .collect::<Vec<_>>();
+    info!("Found {} skip-worktree entries", paths.len());
+    Ok(paths)
+}
+
+/// Returns how long ago the files of the repository containing the provided path changed,
+/// for the files committed within a window or changed in the working tree
+///
+/// # Arguments
+///
+/// * `path` - A path inside the repository
+/// * `window` - The number of seconds of history to read
+///
+/// # Returns
+///
+/// * `Result<HashMap<PathBuf, u64>>` - The number of seconds since each recently changed file last changed, by absolute path, with `0` for uncommitted changes
+#[instrument(name = "git", skip_all)]
+pub fn get_change_ages(path: &Path, window: u64) -> Result<HashMap<PathBuf, u64>> {
+    let repo = Repository::discover(path)
+        .with_context(|| message_with(Message::GitOpenFailed, &[("path", &path.display())]))?;
+    let workdir = repo
+        .workdir()
+        .with_context(|| message(Message::GitNoWorkdir))?
+        .canonicalize()
+        .with_context(|| message(Message::GitWorkdirFailed))?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64);
+
+    let mut ages = HashMap::new();
+    let statuses = repo
+        .statuses(Some(StatusOptions::new().include_untracked(true).recurse_untracked_dirs(true)))
+        .with_context(|| message(Message::GitStatusFailed))?;
+    for entry in statuses.iter() {
+        if entry.status() != Status::CURRENT && !entry.status().is_ignored() {
+            if let Some(path) = entry.path() {
+                ages.insert(workdir.join(path), 0);
+            }
+        }
+    }
+
+    let mut revwalk = repo.revwalk().with_context(|| message(Message::GitHistoryFailed))?;
+    revwalk.set_sorting(Sort::TIME).with_context(|| message(Message::GitHistoryFailed))?;
+    if revwalk.push_head().is_err() {
+        // A repository without commits only has uncommitted changes
+        return Ok(ages);
+    }
+    for oid in revwalk {
+        let oid = oid.with_context(|| message(Message::GitHistoryFailed))?;
+        let commit = repo.find_commit(oid).with_context(|| message(Message::GitHistoryFailed))?;
+        let age = now.saturating_sub(commit.time().seconds()).max(0) as u64;
+        if age > window {
+            break;
+        }
+        let tree = commit.tree().with_context(|| message(Message::GitHistoryFailed))?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree().with_context(|| message(Message::GitHistoryFailed))?),
+            Err(_) => None,
+        };
+        let diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+            .with_context(|| message(Message::GitDiffGenerateFailed))?;
+        for delta in diff.deltas() {
+            if let Some(path) = delta.new_file().path() {
+                ages.entry(workdir.join(path)).or_insert(age);
+            }
+        }
+    }
+    info!("Found {} files changed within {} seconds", ages.len(), window);
+    Ok(ages)
+}
+
+/// The merge, rebase, cherry-pick or revert in progress in a repository, with its conflicts.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct MergeState {
+    /// The operation in progress: `merge`, `rebase`, `cherry-pick` or `revert`, if any.
+    pub operation: Option<String>,
+    /// The branch checked out, if `HEAD` is not detached.
+    pub head: Option<String>,
+    /// The conflicted files, in the order of the index.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// A file with merge conflicts, with the versions of the git index stages.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    /// The path of the file, relative to the repository.
+    pub path: String,
+    /// The absolute path of the file in the working tree.
+    #[serde(skip)]
+    pub absolute_path: PathBuf,
+    /// The version of the common ancestor, stage 1, unless the file was added on both sides.
+    pub base: Option<String>,
+    /// The version of `HEAD`, stage 2, unless the file was deleted there.
+    pub ours: Option<String>,
+    /// The version being merged in, stage 3, unless the file was deleted there.
+    pub theirs: Option<String>,
+}
+
+/// Returns the operation in progress in the repository containing the provided path and the
+/// files it left conflicted
+///
+/// # Arguments
+///
+/// * `path` - A path inside the repository
+///
+/// # Returns
+///
+/// * `Result<MergeState>` - The operation in progress and the conflicted files, or an error if no repository is found
+#[instrument(name = "git", skip_all)]
+pub fn get_merge_state(path: &Path) -> Result<MergeState> {
+    let repo = Repository::discover(path)
+        .with_context(|| message_with(Message::GitOpenFailed, &[("path", &path.display())]))?;
+    let workdir = repo
+        .workdir()
+        .with_context(|| message(Message::GitNoWorkdir))?
+        .canonicalize()
+        .with_context(|| message(Message::GitWorkdirFailed))?;
+    let operation = match repo.state() {
+        RepositoryState::Merge => Some("merge"),
+        RepositoryState::Rebase | RepositoryState::RebaseInteractive | RepositoryState::RebaseMerge => Some("rebase"),
+        RepositoryState::CherryPick | RepositoryState::CherryPickSequence => Some("cherry-pick"),
+        RepositoryState::Revert | RepositoryState::RevertSequence => Some("revert"),
+        _ => None,
+    };
+    let head = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.shorthand().map(String::from));
+
+    // Each side of a conflict is a stage of the index, missing where the file does not exist
+    let index = repo.index().with_context(|| message(Message::GitIndexFailed))?;
+    let blob = |entry: Option<&IndexEntry>| -> Result<Option<String>> {
+        entry
+            .map(|entry| {
+                let blob = repo.find_blob(entry.id).with_context(|| message(Message::GitIndexFailed))?;
+                Ok(String::from_utf8_lossy(blob.content()).into_owned())
+            })
+            .transpose()
+    };
+    let mut conflicts = Vec::new();
+    for conflict in index.conflicts().with_context(|| message(Message::GitIndexFailed))? {
+        let conflict = conflict.with_context(|| message(Message::GitIndexFailed))?;
+        let Some(entry) = conflict.our.as_ref().or(conflict.their.as_ref()).or(conflict.ancestor.as_ref()) else {
+            continue;
+        };
+        let path = String::from_utf8_lossy(&entry.path).into_owned();
+        conflicts.push(Conflict {
+            absolute_path: workdir.join(&path),
+            path,
+            base: blob(conflict.ancestor.as_ref())?,
+            ours: blob(conflict.our.as_ref())?,
+            theirs: blob(conflict.their.as_ref())?,
+        });
+    }
+    info!("Found {} conflicted files", conflicts.len());
+    Ok(MergeState {
+        operation: operation.map(String::from),
+        head,
+        conflicts,
+    })
+}
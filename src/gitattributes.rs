@@ -0,0 +1,174 @@
+//! This module reads the `linguist-generated` and `linguist-vendored` attributes of files from
+//! `.gitattributes`, so the files GitHub hides from diffs and language statistics are left out
+//! of prompts as well.
+
+use glob::{MatchOptions, Pattern};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// The name of the attribute files looked up in each directory.
+pub const ATTRIBUTES_FILE: &str = ".gitattributes";
+
+/// The reason a file is marked by linguist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linguist {
+    /// The file is marked `linguist-generated`.
+    Generated,
+    /// The file is marked `linguist-vendored`.
+    Vendored,
+}
+
+/// A line of an attribute file, with the linguist attributes it sets.
+#[derive(Debug, Clone)]
+struct AttributeRule {
+    /// The pattern selecting the files.
+    pattern: Pattern,
+    /// Whether the pattern matches the file name at any depth, rather than the path relative to
+    /// the directory of the attribute file.
+    name_only: bool,
+    /// The value given to `linguist-generated`, where `None` leaves it unchanged.
+    generated: Option<Option<bool>>,
+    /// The value given to `linguist-vendored`, where `None` leaves it unchanged.
+    vendored: Option<Option<bool>>,
+}
+
+/// Parses the lines of an attribute file setting linguist attributes.
+///
+/// Attributes are set with `attr` or `attr=true`, unset with `-attr` or `attr=false`, and
+/// returned to unspecified with `!attr`. Lines that do not mention a linguist attribute are
+/// left out.
+///
+/// # Arguments
+///
+/// * `content` - The content of the attribute file.
+///
+/// # Returns
+///
+/// * `Vec<AttributeRule>` - The rules, in the order of the file.
+fn parse_gitattributes(content: &str) -> Vec<AttributeRule> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let mut fields = line.split_whitespace();
+            let pattern = fields.next()?;
+            // Negative patterns are forbidden in attribute files, and directories do not pass
+            // their attributes to the files they contain
+            if pattern.starts_with('!') || pattern.ends_with('/') {
+                return None;
+            }
+
+            let mut rule = AttributeRule {
+                name_only: !pattern.contains('/'),
+                pattern: Pattern::new(pattern.trim_start_matches('/')).ok()?,
+                generated: None,
+                vendored: None,
+            };
+            for attribute in fields {
+                let (name, state) = match (attribute.strip_prefix('-'), attribute.strip_prefix('!')) {
+                    (Some(name), _) => (name, Some(false)),
+                    (_, Some(name)) => (name, None),
+                    _ => match attribute.split_once('=') {
+                        Some((name, value)) => (name, Some(value != "false")),
+                        None => (attribute, Some(true)),
+                    },
+                };
+                match name {
+                    "linguist-generated" => rule.generated = Some(state),
+                    "linguist-vendored" => rule.vendored = Some(state),
+                    _ => {}
+                }
+            }
+            (rule.generated.is_some() || rule.vendored.is_some()).then_some(rule)
+        })
+        .collect()
+}
+
+/// The attribute files of the directories seen during a walk, read once per directory.
+#[derive(Debug, Default)]
+pub struct GitAttributes {
+    /// The rules of the attribute file of each directory, empty if it has none.
+    directories: HashMap<PathBuf, Rc<Vec<AttributeRule>>>,
+}
+
+impl GitAttributes {
+    /// Returns whether a file is marked generated or vendored by linguist.
+    ///
+    /// The attribute files of the directories from the repository root down to the file are
+    /// applied in order, followed by `.git/info/attributes`, so the deepest and last matching
+    /// line wins, as in git.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The absolute path to the file.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Linguist>` - Why the file is marked, or `None` if it is not.
+    pub fn linguist(&mut self, path: &Path) -> Option<Linguist> {
+        let mut sources = Vec::new();
+        let mut repository_root = None;
+        for directory in path.ancestors().skip(1) {
+            sources.push((directory.to_path_buf(), self.rules(directory, &directory.join(ATTRIBUTES_FILE))));
+            if directory.join(".git").exists() {
+                repository_root = Some(directory);
+                break;
+            }
+        }
+        sources.reverse();
+        if let Some(root) = repository_root {
+            let info = root.join(".git").join("info").join("attributes");
+            sources.push((root.to_path_buf(), self.rules(&info, &info)));
+        }
+
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+        let file_name = path.file_name()?.to_str()?;
+        let mut generated = None;
+        let mut vendored = None;
+        for (directory, rules) in &sources {
+            let Some(relative) = path.strip_prefix(directory).ok().and_then(Path::to_str) else {
+                continue;
+            };
+            for rule in rules.iter() {
+                let subject = if rule.name_only { file_name } else { relative };
+                if rule.pattern.matches_with(subject, options) {
+                    generated = rule.generated.unwrap_or(generated);
+                    vendored = rule.vendored.unwrap_or(vendored);
+                }
+            }
+        }
+        match (generated, vendored) {
+            (Some(true), _) => Some(Linguist::Generated),
+            (_, Some(true)) => Some(Linguist::Vendored),
+            _ => None,
+        }
+    }
+
+    /// Returns the rules of an attribute file, reading it the first time.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The directory or file the rules are cached under.
+    /// * `file` - The path to the attribute file.
+    ///
+    /// # Returns
+    ///
+    /// * `Rc<Vec<AttributeRule>>` - The rules, empty if the file does not exist.
+    fn rules(&mut self, key: &Path, file: &Path) -> Rc<Vec<AttributeRule>> {
+        Rc::clone(self.directories.entry(key.to_path_buf()).or_insert_with(|| {
+            Rc::new(
+                fs::read_to_string(file)
+                    .map(|content| parse_gitattributes(&content))
+                    .unwrap_or_default(),
+            )
+        }))
+    }
+}
@@ -0,0 +1,366 @@
+//! This module persists the arguments of previous invocations so they can be re-run
+//! with `codexio again`, the prompts they generated for `codexio stats`, and the last prompt
+//! written to each output file, to report what changed when it is overwritten.
+
+use crate::messages::{message, message_with, Message};
+use crate::workspace::{with_file_lock, write_atomic};
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The name of the file holding the saved invocations.
+const HISTORY_FILE: &str = "history.json";
+
+/// The name of the file holding the recorded runs.
+const RUNS_FILE: &str = "runs.json";
+
+/// The name of the file holding the last prompt written to each output file.
+const OUTPUTS_FILE: &str = "outputs.json";
+
+/// The number of runs kept per directory, the oldest being dropped first.
+const MAX_RUNS: usize = 500;
+
+/// A prompt generated by a previous run, recorded for `codexio stats`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RunRecord {
+    /// When the run was made, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// The number of tokens of the prompt, if they were counted.
+    pub token_count: Option<usize>,
+    /// The size of the prompt in bytes.
+    pub bytes: usize,
+    /// The paths of the files included in the prompt.
+    pub files: Vec<String>,
+}
+
+impl RunRecord {
+    /// Creates the record of a prompt generated now.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_count` - The number of tokens of the prompt, if they were counted.
+    /// * `bytes` - The size of the prompt in bytes.
+    /// * `files` - The paths of the files included in the prompt.
+    ///
+    /// # Returns
+    ///
+    /// * `RunRecord` - The record, stamped with the current time.
+    pub fn new(token_count: Option<usize>, bytes: usize, files: Vec<String>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        RunRecord {
+            timestamp,
+            token_count,
+            bytes,
+            files,
+        }
+    }
+
+    /// Returns the number of tokens of the prompt, estimated at four bytes per token when they
+    /// were not counted.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of tokens.
+    pub fn tokens(&self) -> usize {
+        self.token_count.unwrap_or_else(|| self.bytes.div_ceil(4))
+    }
+}
+
+/// Returns the directory where codexio keeps its cache files.
+///
+/// # Returns
+///
+/// * `Option<PathBuf>` - The `codexio` directory inside `$XDG_CACHE_HOME` or `~/.cache`.
+pub fn cache_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|dir| dir.join("codexio"))
+}
+
+/// Reads all saved invocations, keyed by the directory they were made in.
+///
+/// # Arguments
+///
+/// * `cache` - The cache directory.
+///
+/// # Returns
+///
+/// * `Result<BTreeMap<String, Vec<String>>>` - The saved arguments per directory.
+fn read_history(cache: &Path) -> Result<BTreeMap<String, Vec<String>>> {
+    read_store(cache, HISTORY_FILE)
+}
+
+/// Reads a file of the cache directory holding values keyed by directory.
+///
+/// # Arguments
+///
+/// * `cache` - The cache directory.
+/// * `name` - The name of the file.
+///
+/// # Returns
+///
+/// * `Result<BTreeMap<String, T>>` - The values per directory, empty if the file does not exist.
+fn read_store<T: DeserializeOwned>(cache: &Path, name: &str) -> Result<BTreeMap<String, T>> {
+    let path = cache.join(name);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| message_with(Message::HistoryReadFailed, &[("path", &path.display())]))?;
+    serde_json::from_str(&content)
+        .with_context(|| message_with(Message::HistoryParseFailed, &[("path", &path.display())]))
+}
+
+/// Saves the arguments of an invocation made in the given directory.
+///
+/// The history file is updated under a lock, so concurrent invocations all get recorded.
+///
+/// # Arguments
+///
+/// * `directory` - The directory the invocation was made in.
+/// * `args` - The command-line arguments, without the program name.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+pub fn save_invocation(directory: &Path, args: &[String]) -> Result<()> {
+    let cache = cache_dir().with_context(|| message(Message::CacheDirUnknown))?;
+    save_invocation_in(&cache, directory, args)
+}
+
+/// Saves the arguments of an invocation made in the given directory to a cache directory.
+///
+/// # Arguments
+///
+/// * `cache` - The cache directory.
+/// * `directory` - The directory the invocation was made in.
+/// * `args` - The command-line arguments, without the program name.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+pub fn save_invocation_in(cache: &Path, directory: &Path, args: &[String]) -> Result<()> {
+    fs::create_dir_all(cache)
+        .with_context(|| message_with(Message::CacheDirCreateFailed, &[("path", &cache.display())]))?;
+
+    let path = cache.join(HISTORY_FILE);
+    with_file_lock(&path, || {
+        let mut history = read_history(cache)?;
+        history.insert(directory.display().to_string(), args.to_vec());
+        write_atomic(&path, serde_json::to_string_pretty(&history)?.as_bytes())
+            .with_context(|| message_with(Message::HistoryWriteFailed, &[("path", &path.display())]))
+    })
+}
+
+/// Loads the arguments of the last invocation made in the given directory.
+///
+/// # Arguments
+///
+/// * `directory` - The directory to look up.
+///
+/// # Returns
+///
+/// * `Result<Option<Vec<String>>>` - The saved arguments, or `None` if nothing was recorded.
+pub fn load_invocation(directory: &Path) -> Result<Option<Vec<String>>> {
+    match cache_dir() {
+        Some(cache) => load_invocation_in(&cache, directory),
+        None => Ok(None),
+    }
+}
+
+/// Loads the arguments of the last invocation made in the given directory from a cache directory.
+///
+/// # Arguments
+///
+/// * `cache` - The cache directory.
+/// * `directory` - The directory to look up.
+///
+/// # Returns
+///
+/// * `Result<Option<Vec<String>>>` - The saved arguments, or `None` if nothing was recorded.
+pub fn load_invocation_in(cache: &Path, directory: &Path) -> Result<Option<Vec<String>>> {
+    Ok(read_history(cache)?.remove(&directory.display().to_string()))
+}
+
+/// Records a prompt generated in the given directory, for `codexio stats`.
+///
+/// The runs file is updated under a lock, and only the last runs of each directory are kept.
+///
+/// # Arguments
+///
+/// * `directory` - The directory the prompt was generated from.
+/// * `run` - The record of the prompt.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+pub fn record_run(directory: &Path, run: RunRecord) -> Result<()> {
+    let cache = cache_dir().with_context(|| message(Message::CacheDirUnknown))?;
+    record_run_in(&cache, directory, run)
+}
+
+/// Records a prompt generated in the given directory in a cache directory.
+///
+/// # Arguments
+///
+/// * `cache` - The cache directory.
+/// * `directory` - The directory the prompt was generated from.
+/// * `run` - The record of the prompt.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+pub fn record_run_in(cache: &Path, directory: &Path, run: RunRecord) -> Result<()> {
+    fs::create_dir_all(cache)
+        .with_context(|| message_with(Message::CacheDirCreateFailed, &[("path", &cache.display())]))?;
+
+    let path = cache.join(RUNS_FILE);
+    with_file_lock(&path, || {
+        let mut runs: BTreeMap<String, Vec<RunRecord>> = read_store(cache, RUNS_FILE)?;
+        let recorded = runs.entry(directory.display().to_string()).or_default();
+        recorded.push(run);
+        let excess = recorded.len().saturating_sub(MAX_RUNS);
+        recorded.drain(..excess);
+        write_atomic(&path, serde_json::to_string(&runs)?.as_bytes())
+            .with_context(|| message_with(Message::HistoryWriteFailed, &[("path", &path.display())]))
+    })
+}
+
+/// Loads the prompts recorded for the given directory.
+///
+/// # Arguments
+///
+/// * `directory` - The directory to look up.
+///
+/// # Returns
+///
+/// * `Result<Vec<RunRecord>>` - The recorded runs, oldest first.
+pub fn load_runs(directory: &Path) -> Result<Vec<RunRecord>> {
+    match cache_dir() {
+        Some(cache) => load_runs_in(&cache, directory),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Loads the prompts recorded for the given directory from a cache directory.
+///
+/// # Arguments
+///
+/// * `cache` - The cache directory.
+/// * `directory` - The directory to look up.
+///
+/// # Returns
+///
+/// * `Result<Vec<RunRecord>>` - The recorded runs, oldest first.
+pub fn load_runs_in(cache: &Path, directory: &Path) -> Result<Vec<RunRecord>> {
+    let mut runs: BTreeMap<String, Vec<RunRecord>> = read_store(cache, RUNS_FILE)?;
+    Ok(runs.remove(&directory.display().to_string()).unwrap_or_default())
+}
+
+/// Records the prompt written to an output file, replacing the one written to it before.
+///
+/// # Arguments
+///
+/// * `output` - The path of the output file.
+/// * `run` - The record of the prompt.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+pub fn record_output(output: &Path, run: RunRecord) -> Result<()> {
+    let cache = cache_dir().with_context(|| message(Message::CacheDirUnknown))?;
+    record_output_in(&cache, output, run)
+}
+
+/// Records the prompt written to an output file in a cache directory.
+///
+/// # Arguments
+///
+/// * `cache` - The cache directory.
+/// * `output` - The path of the output file.
+/// * `run` - The record of the prompt.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+pub fn record_output_in(cache: &Path, output: &Path, run: RunRecord) -> Result<()> {
+    fs::create_dir_all(cache)
+        .with_context(|| message_with(Message::CacheDirCreateFailed, &[("path", &cache.display())]))?;
+
+    let path = cache.join(OUTPUTS_FILE);
+    with_file_lock(&path, || {
+        let mut outputs: BTreeMap<String, RunRecord> = read_store(cache, OUTPUTS_FILE)?;
+        outputs.insert(output_key(output), run);
+        write_atomic(&path, serde_json::to_string(&outputs)?.as_bytes())
+            .with_context(|| message_with(Message::HistoryWriteFailed, &[("path", &path.display())]))
+    })
+}
+
+/// Loads the prompt last written to an output file.
+///
+/// # Arguments
+///
+/// * `output` - The path of the output file.
+///
+/// # Returns
+///
+/// * `Result<Option<RunRecord>>` - The record of the prompt, or `None` if none was recorded.
+pub fn load_output(output: &Path) -> Result<Option<RunRecord>> {
+    match cache_dir() {
+        Some(cache) => load_output_in(&cache, output),
+        None => Ok(None),
+    }
+}
+
+/// Loads the prompt last written to an output file from a cache directory.
+///
+/// # Arguments
+///
+/// * `cache` - The cache directory.
+/// * `output` - The path of the output file.
+///
+/// # Returns
+///
+/// * `Result<Option<RunRecord>>` - The record of the prompt, or `None` if none was recorded.
+pub fn load_output_in(cache: &Path, output: &Path) -> Result<Option<RunRecord>> {
+    let mut outputs: BTreeMap<String, RunRecord> = read_store(cache, OUTPUTS_FILE)?;
+    Ok(outputs.remove(&output_key(output)))
+}
+
+/// Returns the key an output file is recorded under: its absolute path, so runs from other
+/// directories find it.
+///
+/// # Arguments
+///
+/// * `output` - The path of the output file.
+///
+/// # Returns
+///
+/// * `String` - The key of the output file.
+fn output_key(output: &Path) -> String {
+    output.canonicalize().unwrap_or_else(|_| output.to_path_buf()).display().to_string()
+}
+
+/// Checks that the cache directory can be written to and that its files parse, for `codexio doctor`.
+///
+/// # Returns
+///
+/// * `Result<PathBuf>` - The cache directory, or an error naming what is wrong with it.
+pub fn probe_cache() -> Result<PathBuf> {
+    let dir = cache_dir().with_context(|| message(Message::CacheDirUnknown))?;
+    fs::create_dir_all(&dir).with_context(|| message_with(Message::CacheDirCreateFailed, &[("path", &dir.display())]))?;
+    tempfile::NamedTempFile::new_in(&dir)
+        .with_context(|| message_with(Message::CacheWriteFailed, &[("path", &dir.display())]))?;
+    read_history(&dir)?;
+    read_store::<Vec<RunRecord>>(&dir, RUNS_FILE)?;
+    read_store::<RunRecord>(&dir, OUTPUTS_FILE)?;
+    Ok(dir)
+}
@@ -1,26 +1,338 @@
 //! This module handles the input operations, including parsing command-line arguments,
 //! reading files, and interacting with the user for undefined variables.
 
-use crate::config::Config;
-use crate::path::label;
+use crate::config::{Command, Compression, Config, FileConfig, PresetName};
+use crate::form::{collect_form, parse_forms};
+use crate::options::Options;
+use crate::history::{load_invocation, save_invocation};
+use crate::messages::{message, message_with, Message};
+use crate::path::root_labels;
+use crate::preset::preset_config;
 use crate::template::extract_undefined_variables;
-use anyhow::{Context, Result};
+use crate::workspace_file::apply_workspace_file;
+use anyhow::{anyhow, bail, Context, Result};
 use inquire::Text;
-use log::debug;
+use tracing::{debug, warn};
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
 use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::env;
 use std::fs;
-use std::path::PathBuf;
-use clap::Parser;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use clap::{CommandFactory, Parser};
 
-/// Parses the command-line arguments and returns a `Config` struct.
+/// The name of the project configuration file, looked up in the codebase directory.
+pub const PROJECT_CONFIG_FILE: &str = ".codexio.toml";
+
+/// A `{{name}}` variable in a pattern or path.
+static VARIABLE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_-]*)\s*\}\}").unwrap());
+
+/// Parses the command-line arguments into the subcommand and the options of the run.
+///
+/// Options missing from the command line are filled in from the project configuration
+/// file (`.codexio.toml`) and then the user configuration file (`~/.config/codexio/config.toml`).
+/// When `--selection` is given, the values of that saved selection come first. When `--profile`
+/// is given, the values of that profile take precedence over the top-level ones,
+/// and the values of a built-in `--preset` come right after them. The keys of a
+/// `--workspace-file` come right after the command line.
+/// The arguments of the runs generating prompts are saved per working directory so
+/// `codexio again` can replay them, with the options given to `again` inserted before the
+/// saved subcommand.
+///
+/// # Returns
+///
+/// * `Result<(Option<Command>, Options)>` - The subcommand, if one was given, and the options.
+pub fn parse_config() -> Result<(Option<Command>, Options)> {
+    let mut args: Vec<String> = env::args().collect();
+    let mut config = Config::parse_from(&args);
+    let current_dir = env::current_dir().context("Failed to get current directory")?;
+
+    if let Some(Command::Again { overrides }) = &config.command {
+        let saved = load_invocation(&current_dir)?
+            .with_context(|| message(Message::NoPreviousInvocation))?;
+        let before_again = subcommand_index(&args[1..]).map_or(&args[1..], |index| &args[1..=index]);
+        // The overrides go before the saved subcommand, whose own arguments would not take them
+        let split = subcommand_index(&saved).unwrap_or(saved.len());
+        let mut replayed = vec![args[0].clone()];
+        replayed.extend_from_slice(&saved[..split]);
+        replayed.extend_from_slice(before_again);
+        replayed.extend(overrides.iter().cloned());
+        replayed.extend_from_slice(&saved[split..]);
+        args = replayed;
+        config = Config::parse_from(&args);
+    }
+    // Only the runs generating prompts are replayed, not `doctor`, `stats` or the other tools
+    if config.command.as_ref().map_or(true, Command::generates_prompt) {
+        if let Err(e) = save_invocation(&current_dir, &args[1..]) {
+            warn!("Failed to save invocation: {}", e);
+        }
+    }
+
+    let selection = config.selection.take();
+    let profile = config.profile.take();
+    let preset = config
+        .preset
+        .take()
+        .or(match config.command {
+            Some(Command::Audit) => Some(PresetName::Audit),
+            Some(Command::Onboard) => Some(PresetName::Onboard),
+            Some(Command::Resolve) => Some(PresetName::Resolve),
+            _ => None,
+        });
+    let (command, mut options) = config.into_options();
+    // Batch jobs are layered onto the command-line options one by one
+    if let Some(Command::Batch { .. }) = &command {
+        if selection.is_some() || profile.is_some() || preset.is_some() {
+            bail!(message(Message::BatchLayersUnsupported));
+        }
+        return Ok((command, options));
+    }
+    // The doctor layers the configuration files itself, to report them when they are invalid
+    if let Some(Command::Doctor) = &command {
+        return Ok((command, options));
+    }
+    if let Some(path) = options.workspace_file.clone() {
+        apply_workspace_file(&mut options, &path)?;
+    }
+    layer_config(&mut options, selection.as_deref(), profile.as_deref(), preset)?;
+    debug!("Parsed options: {:?}", options);
+    Ok((command, options))
+}
+
+/// Returns the position of the subcommand in command-line arguments, skipping the values of
+/// the options before it.
+///
+/// # Arguments
+///
+/// * `args` - The command-line arguments, without the program name.
+///
+/// # Returns
+///
+/// * `Option<usize>` - The index of the subcommand, or `None` if there is none.
+fn subcommand_index(args: &[String]) -> Option<usize> {
+    let command = Config::command();
+    let takes_value = |flag: &str| {
+        command.get_arguments().any(|arg| {
+            let named = match flag.strip_prefix("--") {
+                Some(long) => arg.get_long() == Some(long),
+                None => flag.len() == 2 && arg.get_short() == flag.chars().nth(1),
+            };
+            named && arg.get_action().takes_values()
+        })
+    };
+    let mut index = 0;
+    while index < args.len() {
+        let arg = args[index].as_str();
+        if arg == "--" {
+            return None;
+        }
+        if command.find_subcommand(arg).is_some() {
+            return Some(index);
+        }
+        if arg.starts_with('-') && !arg.contains('=') && takes_value(arg) {
+            index += 1;
+        }
+        index += 1;
+    }
+    None
+}
+
+/// Fills in the options that were not given on the command line from a saved selection, a
+/// profile, a built-in preset and the configuration files, in this order of precedence.
+///
+/// # Arguments
+///
+/// * `options` - The options to fill in, whose first path is where the project configuration file is looked up.
+/// * `selection` - The name of the saved selection to apply, if any.
+/// * `profile` - The name of the profile to apply, if any.
+/// * `preset` - The built-in preset to apply, if any.
+///
+/// # Returns
+///
+/// * `Result<()>` - An error if a configuration file is invalid or the selection or profile does not exist.
+pub fn layer_config(
+    options: &mut Options,
+    selection: Option<&str>,
+    profile: Option<&str>,
+    preset: Option<PresetName>,
+) -> Result<()> {
+    let mut file_config = load_file_config(options.primary_dir())?;
+    if let Some(name) = selection {
+        let selection = file_config
+            .selections
+            .remove(name)
+            .with_context(|| message_with(Message::SelectionNotFound, &[("name", &name)]))?;
+        options.merge_file_config(selection);
+    }
+    if let Some(name) = profile {
+        let profile = file_config
+            .profiles
+            .remove(name)
+            .with_context(|| message_with(Message::ProfileNotFound, &[("name", &name)]))?;
+        options.merge_file_config(profile);
+    }
+    if let Some(name) = preset {
+        options.preset = Some(name);
+        options.merge_file_config(preset_config(name));
+    }
+    options.merge_file_config(file_config);
+    resolve_variables(options)?;
+    resolve_compression(options);
+    Ok(())
+}
+
+/// Pairs the compression of the output file with its extension: the extension of `--compress`
+/// is appended to an output path lacking it, and an output path ending with `.gz` or `.zst`
+/// selects the compression without `--compress`.
+///
+/// # Arguments
+///
+/// * `options` - The layered options whose output file and compression are resolved.
+fn resolve_compression(options: &mut Options) {
+    let Some(output) = options.output_file().map(str::to_string) else {
+        return;
+    };
+    match options.compress {
+        Some(compression) if Compression::from_path(&output) != Some(compression) => {
+            options.output = Some(format!("{}.{}", output, compression.extension()));
+        }
+        Some(_) => {}
+        None => options.compress = Compression::from_path(&output),
+    }
+}
+
+/// Replaces the `{{name}}` variables of the patterns and paths of the options with their values,
+/// so one profile such as `include = "src/{{service}}/**"` can serve several directories.
+///
+/// Values come from `--var` first, then from the scalar entries of the answers file.
+///
+/// # Arguments
+///
+/// * `options` - The layered options whose patterns and paths are resolved.
+///
+/// # Returns
+///
+/// * `Result<()>` - An error if a `--var` is invalid or a variable has no value.
+fn resolve_variables(options: &mut Options) -> Result<()> {
+    let mut variables = parse_variables(&options.var)?;
+    if let Some(path) = &options.answers {
+        if let Value::Object(answers) = load_answers(path)? {
+            for (name, value) in answers {
+                let value = match value {
+                    Value::String(value) => value,
+                    Value::Number(_) | Value::Bool(_) => value.to_string(),
+                    _ => continue,
+                };
+                variables.entry(name).or_insert(value);
+            }
+        }
+    }
+
+    let resolve = |pattern: &mut String| -> Result<()> {
+        *pattern = interpolate_variables(pattern, &variables)?;
+        Ok(())
+    };
+    let resolve_path = |path: &mut PathBuf| -> Result<()> {
+        *path = PathBuf::from(interpolate_variables(&path.to_string_lossy(), &variables)?);
+        Ok(())
+    };
+    for pattern in [&mut options.include, &mut options.exclude, &mut options.line_number_for]
+        .into_iter()
+        .flatten()
+    {
+        resolve(pattern)?;
+    }
+    for pattern in options.tiers.values_mut().flatten() {
+        resolve(pattern)?;
+    }
+    for path in options.include_from.iter_mut().chain(options.exclude_from.iter_mut()) {
+        resolve_path(path)?;
+    }
+    if let Some(path) = options.template.as_mut() {
+        resolve_path(path)?;
+    }
+    Ok(())
+}
+
+/// Parses the `--var` values into a map of variable names to values.
+///
+/// # Arguments
+///
+/// * `vars` - The values, each of the form `NAME=VALUE`.
 ///
 /// # Returns
 ///
-/// * `Result<Config>` - The parsed configuration options.
-pub fn parse_config() -> Result<Config> {
-    let config = Config::parse();
-    debug!("Parsed config: {:?}", config);
-    Ok(config)
+/// * `Result<HashMap<String, String>>` - The values by name, or an error if one has no `=` or no name.
+pub fn parse_variables(vars: &[String]) -> Result<HashMap<String, String>> {
+    vars.iter()
+        .map(|var| match var.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_string(), value.to_string())),
+            _ => Err(anyhow!(message_with(Message::VarInvalid, &[("value", &var)]))),
+        })
+        .collect()
+}
+
+/// Replaces the `{{name}}` variables of a pattern or path with their values.
+///
+/// Single braces, as in the glob alternatives `*.{rs,toml}`, are left as they are.
+///
+/// # Arguments
+///
+/// * `text` - The pattern or path.
+/// * `variables` - The values of the variables, by name.
+///
+/// # Returns
+///
+/// * `Result<String>` - The resolved text, or an error naming the first variable without a value.
+pub fn interpolate_variables(text: &str, variables: &HashMap<String, String>) -> Result<String> {
+    let mut missing = None;
+    let resolved = VARIABLE.replace_all(text, |captures: &Captures| match variables.get(&captures[1]) {
+        Some(value) => value.clone(),
+        None => {
+            missing.get_or_insert_with(|| captures[1].to_string());
+            String::new()
+        }
+    });
+    if let Some(name) = missing {
+        bail!(message_with(Message::PatternVariableMissing, &[("name", &name), ("pattern", &text)]));
+    }
+    Ok(resolved.into_owned())
+}
+
+/// Returns the path of the user configuration file.
+///
+/// # Returns
+///
+/// * `Option<PathBuf>` - The path to `codexio/config.toml` inside `$XDG_CONFIG_HOME` or `~/.config`.
+pub fn user_config_path() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("codexio").join("config.toml"))
+}
+
+/// Loads and layers the user and project configuration files.
+///
+/// # Arguments
+///
+/// * `root_path` - The codebase directory, where the project configuration file is looked up.
+///
+/// # Returns
+///
+/// * `Result<FileConfig>` - The merged defaults, with project values overriding user values.
+pub fn load_file_config(root_path: &Path) -> Result<FileConfig> {
+    let mut builder = ::config::Config::builder();
+    if let Some(user_path) = user_config_path() {
+        builder = builder.add_source(::config::File::from(user_path).required(false));
+    }
+    builder = builder.add_source(::config::File::from(root_path.join(PROJECT_CONFIG_FILE)).required(false));
+
+    builder
+        .build()
+        .and_then(|settings| settings.try_deserialize())
+        .with_context(|| message(Message::ConfigLoadFailed))
 }
 
 /// Reads the content of a file at the given path.
@@ -36,27 +348,105 @@ pub fn read_file(path: &PathBuf) -> Result<String> {
     fs::read_to_string(path).context("Failed to read file")
 }
 
+/// Reads a list of file paths, one per line, from a file or from stdin.
+///
+/// Empty lines are ignored.
+///
+/// # Arguments
+///
+/// * `source` - The path to the file, or `-` for stdin.
+///
+/// # Returns
+///
+/// * `Result<Vec<PathBuf>>` - The listed paths, in the given order.
+pub fn read_file_list(source: &str) -> Result<Vec<PathBuf>> {
+    let content = if source == "-" {
+        let mut content = String::new();
+        io::stdin()
+            .read_to_string(&mut content)
+            .context("Failed to read file list from stdin")?;
+        content
+    } else {
+        fs::read_to_string(source).context("Failed to read file list")?
+    };
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Reads the answers file given to `--answers`, a YAML or JSON mapping of template variables
+/// and forms to their values.
+///
+/// # Arguments
+///
+/// * `path` - The path of the answers file.
+///
+/// # Returns
+///
+/// * `Result<Value>` - The answers, or an error if the file cannot be read or parsed.
+pub fn load_answers(path: &Path) -> Result<Value> {
+    let read_failed = || message_with(Message::AnswersReadFailed, &[("path", &path.display())]);
+    let content = fs::read_to_string(path).with_context(read_failed)?;
+    let answers: Value = serde_yaml::from_str(&content).with_context(read_failed)?;
+    Ok(answers)
+}
+
 /// Prompts the user for values of undefined variables in the template.
 ///
+/// The forms the template declares are collected first, field by field. When an answers file
+/// is given, values are taken from it instead and nothing is asked.
+///
 /// # Arguments
 ///
 /// * `data` - The JSON data object to be populated with user-defined variables.
 /// * `template_content` - The template content string.
+/// * `answers` - The answers from `--answers`, if given.
+/// * `interactive` - Whether values missing without an answers file may be asked for, which `--quiet` turns off.
 ///
 /// # Returns
 ///
-/// * `Result<()>` - An empty result indicating success or an error.
-pub fn prompt_for_undefined_variables(data: &mut Value, template_content: &str) -> Result<()> {
+/// * `Result<()>` - An empty result indicating success or an error, such as a value that cannot be asked for.
+pub fn prompt_for_undefined_variables(
+    data: &mut Value,
+    template_content: &str,
+    answers: Option<&Value>,
+    interactive: bool,
+) -> Result<()> {
+    let unanswered = |name: &str| anyhow!(message_with(Message::VariableUnanswered, &[("name", &name)]));
     let undefined_variables = extract_undefined_variables(template_content);
     let mut user_defined_vars = Map::new();
 
+    for form in parse_forms(template_content)? {
+        if !data.as_object().unwrap().contains_key(&form.name) {
+            let form_answers = answers.map(|answers| answers.get(&form.name).unwrap_or(&Value::Null));
+            if form_answers.is_none() && !interactive {
+                return Err(unanswered(&form.name));
+            }
+            let values = collect_form(&form, form_answers)?;
+            user_defined_vars.insert(form.name, values);
+        }
+    }
+
     for var in undefined_variables {
-        if !data.as_object().unwrap().contains_key(&var) {
-            let prompt = format!("Enter value for '{}': ", var);
-            let answer = Text::new(&prompt)
-                .with_help_message("Fill user defined variable in template")
-                .prompt()?;
-            user_defined_vars.insert(var, Value::String(answer));
+        if !data.as_object().unwrap().contains_key(&var) && !user_defined_vars.contains_key(&var) {
+            let answer = match answers {
+                Some(answers) => answers
+                    .get(&var)
+                    .cloned()
+                    .ok_or_else(|| anyhow!(message_with(Message::AnswerMissing, &[("name", &var)])))?,
+                None if !interactive => return Err(unanswered(&var)),
+                None => {
+                    let prompt = message_with(Message::EnterValueFor, &[("name", &var)]);
+                    let answer = Text::new(&prompt)
+                        .with_help_message(message(Message::FillTemplateVariable))
+                        .prompt()?;
+                    Value::String(answer)
+                }
+            };
+            user_defined_vars.insert(var, answer);
         }
     }
 
@@ -71,6 +461,9 @@ pub fn prompt_for_undefined_variables(data: &mut Value, template_content: &str)
 
 /// Creates the initial JSON data object based on the provided configuration.
 ///
+/// The `instructions` key is only set when instructions were given, so custom templates
+/// can still ask for them interactively. With `--no-tree`, `source_tree` is left empty.
+///
 /// # Arguments
 ///
 /// * `config` - The application configuration.
@@ -84,21 +477,25 @@ pub fn prompt_for_undefined_variables(data: &mut Value, template_content: &str)
 ///
 /// * `Value` - The initial JSON data object.
 pub fn create_initial_data(
-    config: &Config,
+    config: &Options,
     tree: String,
     files: Vec<Value>,
     git_diff: String,
     git_diff_branch: String,
     git_log_branch: String,
 ) -> Value {
-    json!({
-        "absolute_code_path": label(&config.path),
-        "source_tree": tree,
+    let mut data = json!({
+        "absolute_code_path": root_labels(&config.path).join(", "),
+        "source_tree": if config.no_tree { String::new() } else { tree },
         "files": files,
         "git_diff": git_diff,
         "git_diff_branch": git_diff_branch,
         "git_log_branch": git_log_branch
-    })
+    });
+    if let Some(instructions) = &config.instructions {
+        data["instructions"] = json!(instructions);
+    }
+    data
 }
 
 /// Parses comma-separated patterns into a vector of strings.
@@ -120,4 +517,47 @@ pub fn parse_patterns(patterns: &Option<String>) -> Result<Vec<String>> {
         }
         _ => Ok(vec![]),
     }
+}
+
+/// Parses comma-separated patterns and appends the patterns loaded from pattern files.
+///
+/// # Arguments
+///
+/// * `patterns` - An optional string containing comma-separated patterns.
+/// * `pattern_files` - The paths to pattern files, as read by `read_pattern_file`.
+///
+/// # Returns
+///
+/// * `Result<Vec<String>>` - A vector of all patterns, or an error if a file cannot be read.
+pub fn parse_patterns_with_files(
+    patterns: &Option<String>,
+    pattern_files: &[PathBuf],
+) -> Result<Vec<String>> {
+    let mut all_patterns = parse_patterns(patterns)?;
+    for path in pattern_files {
+        all_patterns.extend(read_pattern_file(path)?);
+    }
+    Ok(all_patterns)
+}
+
+/// Reads patterns from a file containing one glob per line.
+///
+/// Empty lines and lines starting with `#` are ignored.
+///
+/// # Arguments
+///
+/// * `path` - The path to the pattern file.
+///
+/// # Returns
+///
+/// * `Result<Vec<String>>` - The patterns in the file, or an error if it cannot be read.
+pub fn read_pattern_file(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| message_with(Message::PatternFileReadFailed, &[("path", &path.display())]))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
 }
\ No newline at end of file
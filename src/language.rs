@@ -0,0 +1,315 @@
+//! This module detects the programming language of files, from their extension, their name,
+//! editor modelines or their shebang, so extensionless files such as `Makefile` or shell
+//! scripts get a code fence language and can be selected with `--lang`.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Languages of common file extensions.
+const EXTENSIONS: [(&str, &str); 47] = [
+    ("rs", "rust"),
+    ("py", "python"),
+    ("pyi", "python"),
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("jsx", "javascript"),
+    ("ts", "typescript"),
+    ("tsx", "typescript"),
+    ("go", "go"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("scala", "scala"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cc", "cpp"),
+    ("cpp", "cpp"),
+    ("cxx", "cpp"),
+    ("hpp", "cpp"),
+    ("cs", "csharp"),
+    ("swift", "swift"),
+    ("rb", "ruby"),
+    ("php", "php"),
+    ("pl", "perl"),
+    ("lua", "lua"),
+    ("sh", "bash"),
+    ("bash", "bash"),
+    ("zsh", "zsh"),
+    ("fish", "fish"),
+    ("ps1", "powershell"),
+    ("r", "r"),
+    ("sql", "sql"),
+    ("html", "html"),
+    ("css", "css"),
+    ("scss", "scss"),
+    ("md", "markdown"),
+    ("json", "json"),
+    ("yaml", "yaml"),
+    ("yml", "yaml"),
+    ("toml", "toml"),
+    ("xml", "xml"),
+    ("tf", "terraform"),
+    ("tfvars", "terraform"),
+    ("hcl", "hcl"),
+    ("dockerfile", "dockerfile"),
+    ("hbs", "handlebars"),
+];
+
+/// Languages of files recognized by their name.
+const FILE_NAMES: [(&str, &str); 20] = [
+    ("Makefile", "makefile"),
+    ("GNUmakefile", "makefile"),
+    ("makefile", "makefile"),
+    ("Dockerfile", "dockerfile"),
+    ("Containerfile", "dockerfile"),
+    ("Jenkinsfile", "groovy"),
+    ("Rakefile", "ruby"),
+    ("Gemfile", "ruby"),
+    ("Vagrantfile", "ruby"),
+    ("Podfile", "ruby"),
+    ("CMakeLists.txt", "cmake"),
+    ("BUILD", "starlark"),
+    ("WORKSPACE", "starlark"),
+    ("Justfile", "just"),
+    ("justfile", "just"),
+    (".bashrc", "bash"),
+    (".bash_profile", "bash"),
+    (".zshrc", "zsh"),
+    (".profile", "sh"),
+    (".envrc", "bash"),
+];
+
+/// Languages of shebang interpreters.
+const INTERPRETERS: [(&str, &str); 17] = [
+    ("python", "python"),
+    ("python3", "python"),
+    ("python2", "python"),
+    ("bash", "bash"),
+    ("sh", "sh"),
+    ("dash", "sh"),
+    ("zsh", "zsh"),
+    ("fish", "fish"),
+    ("node", "javascript"),
+    ("deno", "typescript"),
+    ("ruby", "ruby"),
+    ("perl", "perl"),
+    ("php", "php"),
+    ("lua", "lua"),
+    ("pwsh", "powershell"),
+    ("Rscript", "r"),
+    ("make", "makefile"),
+];
+
+/// Matches vim modelines such as `vim: set ft=python:` and emacs ones such as `-*- mode: ruby -*-`.
+static MODELINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:vim?:.*?\b(?:ft|filetype|syntax)=(?P<vim>[\w+-]+))|(?:-\*-\s*(?:.*?mode:\s*)?(?P<emacs>[\w+-]+)\s*(?:;.*?)?-\*-)")
+        .unwrap()
+});
+
+/// The number of lines at the start and the end of a file searched for a modeline.
+const MODELINE_LINES: usize = 5;
+
+/// Detects the language of a file.
+///
+/// Well-known file names are checked first, then the extension, then editor modelines and the shebang.
+/// Unknown extensions are returned as is, so they can still be used as a code fence language.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file.
+/// * `text` - The content of the file.
+///
+/// # Returns
+///
+/// * `Option<String>` - The language, such as `rust` or `makefile`, or `None` if it is unknown.
+pub fn detect_language(path: &Path, text: &str) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    if let Some(language) = lookup(&FILE_NAMES, file_name) {
+        return Some(language.to_string());
+    }
+    // Variants such as `Dockerfile.dev`, whose extension names a stage rather than a language
+    if let Some((name, _)) = file_name.split_once('.') {
+        if matches!(name, "Dockerfile" | "Containerfile") {
+            return Some("dockerfile".to_string());
+        }
+    }
+    if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
+        return Some(extension_language(extension));
+    }
+    modeline_language(text).or_else(|| shebang_language(text).map(str::to_string))
+}
+
+/// Returns the language of a file extension.
+///
+/// # Arguments
+///
+/// * `extension` - The file extension, without the leading dot.
+///
+/// # Returns
+///
+/// * `String` - The language of the extension, or the lowercase extension itself if it is unknown.
+pub fn extension_language(extension: &str) -> String {
+    let extension = extension.to_lowercase();
+    lookup(&EXTENSIONS, &extension)
+        .map(str::to_string)
+        .unwrap_or(extension)
+}
+
+/// Checks whether the language of a file is one of the selected languages.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file.
+/// * `language` - The detected language of the file, if any.
+/// * `languages` - The selected languages or extensions, in lowercase.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the language or the extension of the file is selected.
+pub fn matches_language(path: &Path, language: Option<&str>, languages: &[String]) -> bool {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase);
+    languages.iter().any(|selected| {
+        language.is_some_and(|language| language == selected) || extension.as_deref() == Some(selected.as_str())
+    })
+}
+
+/// The share of a language in a codebase.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LanguageStats {
+    /// The language, such as `rust`.
+    pub language: String,
+    /// The number of files in the language.
+    pub files: usize,
+    /// The total size of the files in bytes.
+    pub bytes: u64,
+    /// The share of the language in the total size, in percent with one decimal.
+    pub percent: f64,
+}
+
+/// Breaks a codebase down by language, from the names and extensions of its files.
+///
+/// # Arguments
+///
+/// * `paths` - The paths of the files of the codebase.
+///
+/// # Returns
+///
+/// * `Vec<LanguageStats>` - The languages, largest first, leaving out the files of unknown languages.
+pub fn language_stats(paths: &[PathBuf]) -> Vec<LanguageStats> {
+    let mut totals: HashMap<String, (usize, u64)> = HashMap::new();
+    for path in paths {
+        let Some(language) = detect_language(path, "") else {
+            continue;
+        };
+        let bytes = fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0);
+        let total = totals.entry(language).or_default();
+        total.0 += 1;
+        total.1 += bytes;
+    }
+    let size: u64 = totals.values().map(|(_, bytes)| bytes).sum();
+    let mut stats: Vec<LanguageStats> = totals
+        .into_iter()
+        .map(|(language, (files, bytes))| LanguageStats {
+            language,
+            files,
+            bytes,
+            percent: if size == 0 {
+                0.0
+            } else {
+                (bytes as f64 * 1000.0 / size as f64).round() / 10.0
+            },
+        })
+        .collect();
+    stats.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.language.cmp(&b.language)));
+    stats
+}
+
+/// Returns the language named by a vim or emacs modeline near the start or the end of a file.
+///
+/// # Arguments
+///
+/// * `text` - The content of the file.
+///
+/// # Returns
+///
+/// * `Option<String>` - The language of the modeline, in lowercase.
+fn modeline_language(text: &str) -> Option<String> {
+    let lines: Vec<&str> = text.lines().collect();
+    let tail = lines.len().saturating_sub(MODELINE_LINES).max(MODELINE_LINES.min(lines.len()));
+    lines[..MODELINE_LINES.min(lines.len())]
+        .iter()
+        .chain(&lines[tail..])
+        .find_map(|line| {
+            let captures = MODELINE.captures(line)?;
+            let language = captures.name("vim").or_else(|| captures.name("emacs"))?;
+            Some(normalize_modeline(&language.as_str().to_lowercase()))
+        })
+}
+
+/// Maps the names used by editor modelines to code fence languages.
+///
+/// # Arguments
+///
+/// * `name` - The lowercase name given by the modeline.
+///
+/// # Returns
+///
+/// * `String` - The language.
+fn normalize_modeline(name: &str) -> String {
+    match name {
+        "sh" | "shell-script" => "sh",
+        "make" => "makefile",
+        "js" => "javascript",
+        "c++" => "cpp",
+        other => other,
+    }
+    .to_string()
+}
+
+/// Returns the language of the interpreter named by a file's shebang.
+///
+/// # Arguments
+///
+/// * `text` - The content of the file.
+///
+/// # Returns
+///
+/// * `Option<&'static str>` - The language of the interpreter, if it is known.
+fn shebang_language(text: &str) -> Option<&'static str> {
+    let shebang = text.lines().next()?.strip_prefix("#!")?;
+    let mut words = shebang.split_whitespace();
+    let mut interpreter = words.next()?.rsplit('/').next()?;
+    if interpreter == "env" {
+        interpreter = words.find(|word| !word.starts_with('-'))?;
+    }
+    lookup(&INTERPRETERS, interpreter).or_else(|| {
+        // Versioned interpreters such as `python3.12`
+        let name = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        lookup(&INTERPRETERS, name)
+    })
+}
+
+/// Looks up a key in a table of languages.
+///
+/// # Arguments
+///
+/// * `table` - The table of keys and languages.
+/// * `key` - The key to look up.
+///
+/// # Returns
+///
+/// * `Option<&'static str>` - The language of the key, if it is in the table.
+fn lookup(table: &[(&str, &'static str)], key: &str) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, language)| *language)
+}
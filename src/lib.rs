@@ -1,9 +1,42 @@
+pub mod apply;
+pub mod batch;
+pub mod binary;
+pub mod bisect;
+pub mod boilerplate;
+pub mod charset;
+pub mod clipboard;
 pub mod config;
+pub mod dashboard;
+pub mod dependencies;
+pub mod doctor;
+pub mod explain;
 pub mod filter;
+pub mod form;
 pub mod git;
+pub mod gitattributes;
+pub mod history;
 pub mod input;
+pub mod language;
+pub mod logging;
+pub mod messages;
+pub mod options;
 pub mod output;
+pub mod paste;
 pub mod path;
+pub mod preset;
 pub mod processing;
+pub mod release;
+pub mod secrets;
+pub mod select;
+pub mod selection;
+pub mod source;
+pub mod split;
+pub mod suggest;
 pub mod template;
-pub mod token;
\ No newline at end of file
+pub mod text;
+pub mod theme;
+pub mod tiers;
+pub mod timings;
+pub mod token;
+pub mod workspace;
+pub mod workspace_file;
\ No newline at end of file
@@ -0,0 +1,60 @@
+//! This module installs the subscriber of the `tracing` events and spans, which writes the
+//! diagnostic logs to stderr in the `--log-format` and records the `--timings` breakdown.
+//!
+//! The logs are filtered with `RUST_LOG`. Without it, text logs show only errors, while JSON logs
+//! show the warnings, one event per included or excluded file, and one event per completed stage
+//! with its duration in the `time.busy` field, so wrapper tools can follow a run.
+
+use crate::config::LogFormat;
+use crate::messages::{message_with, Message};
+use crate::output::colors_enabled;
+use crate::timings::{Timings, TimingsLayer};
+use anyhow::{anyhow, Result};
+use std::io;
+use tracing_subscriber::filter::EnvFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::{Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// The filter of the JSON logs without `RUST_LOG`: the file events of the traversal, the stage
+/// spans and the warnings.
+const JSON_DEFAULT_FILTER: &str = "warn,codexio=info,codexio::path=debug,included_files=debug";
+
+/// Installs the global subscriber, which also forwards the records of the `log` crate.
+///
+/// # Arguments
+///
+/// * `format` - The format of the logs.
+/// * `record_timings` - Whether to record the time spent in each stage, for `--timings`.
+///
+/// # Returns
+///
+/// * `Result<Option<Timings>>` - The handle to read the timings from if they are recorded, or an
+///   error if a subscriber is already installed.
+pub fn install_subscriber(format: LogFormat, record_timings: bool) -> Result<Option<Timings>> {
+    let (default_filter, logs) = match format {
+        LogFormat::Text => (
+            "error",
+            tracing_subscriber::fmt::layer()
+                .with_writer(io::stderr)
+                .with_ansi(colors_enabled())
+                .boxed(),
+        ),
+        LogFormat::Json => (
+            JSON_DEFAULT_FILTER,
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_writer(io::stderr)
+                .with_span_events(FmtSpan::CLOSE)
+                .boxed(),
+        ),
+    };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_filter));
+    let timings = record_timings.then(Timings::default);
+    tracing_subscriber::registry()
+        .with(logs.with_filter(filter))
+        .with(timings.clone().map(TimingsLayer::new))
+        .try_init()
+        .map_err(|e| anyhow!(message_with(Message::SubscriberInstallFailed, &[("error", &e)])))?;
+    Ok(timings)
+}
@@ -1,51 +1,223 @@
+use codexio::apply::run_apply;
+use codexio::batch::run_batch;
+use codexio::clipboard::clipboard_sink;
+use codexio::config::{config_schema, ClipboardBackend, Command, ConfigAction, OutputFormat, TemplateAction};
+use codexio::dashboard::run_stats;
+use codexio::doctor::{run_checks, Status};
+use codexio::history::{record_output, record_run, RunRecord};
 use codexio::input::parse_config;
+use codexio::logging::install_subscriber;
 use codexio::output::{
-    copy_to_clipboard, print_json_output, print_to_console, print_token_info, write_to_file,
+    copy_to_clipboard, formatter, print_checks, print_error, print_explanations, print_file_list, print_json_error, print_structured_output, print_output_delta, print_success, print_summary,
+    print_suggestions, print_timings, print_to_console, print_token_info, print_warning, read_previous_output,
+    set_ascii_output, set_color_choice, set_quiet, set_status_to_stderr, stdout_piped, write_stats_file, write_to_file,
 };
-use codexio::processing::process_codebase;
-use anyhow::Result;
-use colored::Colorize;
-use codexio::path::label;
+use codexio::messages::{message, message_with, set_locale, Locale, Message};
+use codexio::options::STDOUT_OUTPUT;
+use codexio::processing::{explain_paths, process_codebase};
+use codexio::selection::save_selection;
+use codexio::source::fetch_sources;
+use codexio::split::write_parts;
+use codexio::suggest::suggest;
+use codexio::template::preview_template;
+use codexio::workspace::Workspace;
+use anyhow::{bail, Context, Result};
+use codexio::paste::{check_paste_limit, paste_limit};
+use codexio::path::root_labels;
+use codexio::theme::{set_theme, Theme};
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
 
 fn main() -> Result<()> {
-    env_logger::init();
+    let start = Instant::now();
 
     // Parse Configuration
-    let config = parse_config()?;
+    let (command, mut config) = parse_config()?;
+    if let Some(Command::Config { action: ConfigAction::Schema }) = &command {
+        println!("{}", config_schema()?);
+        return Ok(());
+    }
+    if let Some(Command::Template { action: TemplateAction::Preview { template } }) = &command {
+        let content = fs::read_to_string(template).with_context(|| message(Message::TemplateReadFailed))?;
+        println!("{}", preview_template(&content)?);
+        return Ok(());
+    }
+    set_locale(
+        config
+            .locale
+            .as_deref()
+            .and_then(Locale::from_tag)
+            .unwrap_or_else(Locale::detect),
+    );
+    set_ascii_output(config.ascii);
+    set_quiet(config.quiet);
+    set_color_choice(config.color.unwrap_or_default());
+    let timings = install_subscriber(config.log_format, config.timings)?;
+    set_theme(Theme::from_config(config.theme.unwrap_or_default(), config.colors.as_ref())?);
+    if let Some(Command::SaveSelection { name }) = &command {
+        let path = save_selection(config.primary_dir(), name, &config)?;
+        print_success(&message_with(Message::SelectionSaved, &[("name", name), ("path", &path.display())]));
+        return Ok(());
+    }
+    if let Some(Command::Batch { file, jobs }) = &command {
+        return run_batch(file, &config, *jobs);
+    }
+    if let Some(Command::Apply { response, check }) = &command {
+        let root = config
+            .primary_dir()
+            .canonicalize()
+            .with_context(|| message_with(Message::CanonicalizeFailed, &[("path", &config.primary_dir().display())]))?;
+        return run_apply(&root, response, *check);
+    }
+    if let Some(Command::Stats { html, serve }) = &command {
+        let directory = config
+            .primary_dir()
+            .canonicalize()
+            .with_context(|| message_with(Message::CanonicalizeFailed, &[("path", &config.primary_dir().display())]))?;
+        return run_stats(
+            &directory,
+            html.as_deref(),
+            serve.as_deref(),
+            config.json,
+            config.html_theme.unwrap_or_default(),
+            config.theme.unwrap_or_default(),
+        );
+    }
+    if let Some(Command::Doctor) = &command {
+        let checks = run_checks(&config);
+        print_checks(&checks, config.json)?;
+        if checks.iter().any(|check| check.status == Status::Fail) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    // Piped runs print the prompt rather than copying it, unless a clipboard backend is configured
+    if config.output.is_none() && stdout_piped() {
+        config.output = Some(STDOUT_OUTPUT.to_string());
+        config.no_clipboard |= config.clipboard.is_none();
+    }
+    set_status_to_stderr(config.writes_to_stdout());
+    if config.split_tokens.is_some() && config.output_file().is_none() {
+        bail!(message(Message::SplitTokensWithoutOutput));
+    }
+
+    let paste_target = match &config.target {
+        Some(target) => Some((target.clone(), paste_limit(target, &config.paste_limits)?)),
+        None => None,
+    };
+
+    // Prompts are recorded for `codexio stats` under the local repository they come from
+    let stats_directory = if config.remote.is_none() && config.github.is_none() {
+        config.primary_dir().canonicalize().ok()
+    } else {
+        None
+    };
+
+    // Fetch Remote Sources into a workspace deleted at the end of the run
+    let workspace = Workspace::new()?;
+    fetch_sources(&mut config, &workspace)?;
+
+    // Explain the selection of the given paths instead of rendering the prompt
+    if !config.explain.is_empty() {
+        return print_explanations(&explain_paths(&config)?, config.json);
+    }
 
     // Process Codebase
-    let (rendered, token_count, model_info, paths) = process_codebase(&config)?;
+    let processed = match process_codebase(&config) {
+        Ok(processed) => processed,
+        Err(error) if config.json || config.output_format() == OutputFormat::Json => {
+            print_json_error(&error)?;
+            std::process::exit(1);
+        }
+        Err(error) => return Err(error),
+    };
+    for warning in &processed.warnings {
+        print_warning(warning);
+    }
+    let rendered = &processed.rendered;
+    let generates_prompt = !config.list && !matches!(command, Some(Command::Suggest));
+    if let Some(directory) = stats_directory.filter(|_| generates_prompt) {
+        let run = RunRecord::new(processed.token_count, rendered.len(), processed.paths.clone());
+        if let Err(e) = record_run(&directory, run) {
+            tracing::warn!(error = %e, "Failed to record the prompt");
+        }
+    }
 
     // Output Handling
-    if config.json {
-        print_json_output(
-            &rendered,
-            &label(&config.path),
-            token_count,
-            &model_info,
-            paths,
+    if config.list {
+        let files = processed.data["files"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+        print_file_list(files, config.json)?;
+    } else if let Some(Command::Suggest) = &command {
+        let files = processed.data["files"].as_array().map(Vec::as_slice).unwrap_or(&[]);
+        print_suggestions(&suggest(files, processed.token_count.unwrap_or(0), config.budget));
+    } else if let Some(formatter) =
+        formatter(config.output_format()).filter(|_| matches!(config.output_format(), OutputFormat::Json | OutputFormat::Yaml))
+    {
+        // The json and yaml documents wrap the prompt, while the chat formats were rendered into it
+        let stages = timings.as_ref().map(|timings| timings.stages()).unwrap_or_default();
+        print_structured_output(
+            formatter.as_ref(),
+            &processed,
+            &config,
+            &root_labels(&config.path).join(", "),
+            start.elapsed(),
+            &stages,
         )?;
     } else {
         if config.tokens {
-            print_token_info(token_count, &model_info);
+            print_token_info(processed.token_count.unwrap_or(0), &processed.model_info);
         }
 
-        if !config.no_clipboard {
-            if let Err(e) = copy_to_clipboard(&rendered) {
-                eprintln!(
-                    "{}{}{} {}",
-                    "[".bold().white(),
-                    "!".bold().red(),
-                    "]".bold().white(),
-                    format!("Failed to copy to clipboard: {}", e).red()
-                );
-                print_to_console(&rendered);
+        let mut destinations = Vec::new();
+        let backend = config.clipboard.unwrap_or_default();
+        if !config.no_clipboard && backend != ClipboardBackend::None {
+            if let Err(e) = copy_to_clipboard(&processed.clipboard, clipboard_sink(backend).as_ref()) {
+                print_error(&e.to_string());
+                if !config.writes_to_stdout() {
+                    print_to_console(&processed.clipboard);
+                    destinations.push(message(Message::DestinationStdout).to_string());
+                }
+            } else {
+                destinations.push(message(Message::DestinationClipboard).to_string());
+                if let Some((target, limit)) = &paste_target {
+                    for warning in check_paste_limit(&processed.clipboard, target, limit) {
+                        print_warning(&warning);
+                    }
+                }
             }
         }
 
-        if let Some(output_path) = &config.output {
-            write_to_file(output_path, &rendered)?;
+        if config.writes_to_stdout() {
+            print_to_console(rendered);
+            destinations.push(message(Message::DestinationStdout).to_string());
+        } else if let Some(output_path) = config.output_file().filter(|_| !processed.parts.is_empty()) {
+            destinations.extend(write_parts(output_path, &processed.parts, config.compress)?);
+            if config.stats {
+                write_stats_file(output_path, &processed, &config)?;
+            }
+        } else if let Some(output_path) = config.output_file() {
+            let previous = read_previous_output(output_path, config.compress);
+            write_to_file(output_path, rendered, config.compress)?;
+            if let Some(previous) = previous {
+                print_output_delta(&previous, &processed);
+            }
+            // The next run overwriting this file reports the files and tokens that changed
+            let run = RunRecord::new(processed.token_count, rendered.len(), processed.paths.clone());
+            if let Err(e) = record_output(Path::new(output_path), run) {
+                tracing::warn!(error = %e, "Failed to record the output");
+            }
+            if config.stats {
+                write_stats_file(output_path, &processed, &config)?;
+            }
+            destinations.push(output_path.to_string());
         }
+
+        print_summary(&processed, config.budget, &destinations);
+    }
+
+    if let Some(timings) = timings {
+        print_timings(&timings.stages(), start.elapsed());
     }
 
     Ok(())
@@ -0,0 +1,1412 @@
+//! This module contains the catalog of user-facing messages and the locale selection.
+//!
+//! Messages are identified by a `Message` ID and looked up in the catalog of the current
+//! locale, falling back to English when a translation is missing. Placeholders such as
+//! `{path}` are filled in by `message_with`.
+
+use once_cell::sync::OnceCell;
+use std::env;
+use std::fmt::Display;
+
+/// The locale in use, set once at startup.
+static LOCALE: OnceCell<Locale> = OnceCell::new();
+
+/// The locales with a message catalog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    /// English.
+    En,
+    /// Spanish.
+    Es,
+    /// German.
+    De,
+}
+
+impl Locale {
+    /// Parses a locale tag such as `es`, `de-DE` or `es_ES.UTF-8`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The locale tag.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Locale>` - The matching locale, or `None` if there is no catalog for it.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag
+            .split(|c| c == '_' || c == '-' || c == '.' || c == '@')
+            .next()
+            .unwrap_or("")
+            .to_lowercase();
+        match language.as_str() {
+            "en" | "c" | "posix" => Some(Locale::En),
+            "es" => Some(Locale::Es),
+            "de" => Some(Locale::De),
+            _ => None,
+        }
+    }
+
+    /// Detects the locale from the `LC_ALL`, `LC_MESSAGES` and `LANG` environment variables.
+    ///
+    /// # Returns
+    ///
+    /// * `Locale` - The detected locale, or English if none is supported.
+    pub fn detect() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Locale::from_tag(&value))
+            .unwrap_or(Locale::En)
+    }
+}
+
+/// The IDs of the user-facing messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message {
+    /// The prompt was copied to the clipboard.
+    CopiedToClipboard,
+    /// The clipboard could not be initialized. Placeholder: `error`.
+    ClipboardInitFailed,
+    /// The prompt could not be copied to the clipboard. Placeholder: `error`.
+    ClipboardCopyFailed,
+    /// A clipboard command could not be run or failed. Placeholders: `command`, `error`.
+    ClipboardCommandFailed,
+    /// The program of a clipboard command backend is not installed. Placeholder: `command`.
+    ClipboardCommandMissing,
+    /// OSC 52 was selected but no terminal is attached.
+    ClipboardNoTerminal,
+    /// The clipboard content exceeds the character limit of a target. Placeholders: `count`, `limit`, `target`.
+    PasteCharactersExceeded,
+    /// The clipboard content exceeds the byte limit of a target. Placeholders: `count`, `limit`, `target`.
+    PasteBytesExceeded,
+    /// The paste target is neither built in nor configured. Placeholder: `target`.
+    UnknownPasteTarget,
+    /// The prompt was written to a file. Placeholder: `path`.
+    PromptWrittenToFile,
+    /// The token count and model information. Placeholders: `count`, `model`.
+    TokenInfo,
+    /// The changes compared to the previous output. Placeholder: `changes`.
+    ChangesSinceLastOutput,
+    /// Files added and removed since the previous output. Placeholders: `added`, `removed`.
+    FilesDelta,
+    /// Lines added or removed since the previous output. Placeholder: `delta`.
+    LinesDelta,
+    /// Tokens added or removed since the previous output. Placeholder: `delta`.
+    TokensDelta,
+    /// The summary line ending a run. Placeholder: `details`.
+    Summary,
+    /// The file counts of the summary line. Placeholders: `included`, `skipped`.
+    SummaryFiles,
+    /// The token count of the summary line. Placeholder: `count`.
+    SummaryTokens,
+    /// The share of the token budget used. Placeholders: `percent`, `budget`.
+    SummaryBudget,
+    /// Where the prompt was sent. Placeholder: `destinations`.
+    SummaryDestinations,
+    /// The clipboard as an output destination.
+    DestinationClipboard,
+    /// The console as an output destination.
+    DestinationStdout,
+    /// The header of the `--timings` breakdown.
+    Timings,
+    /// The stage column of the `--timings` breakdown.
+    TimingsStage,
+    /// The calls column of the `--timings` breakdown.
+    TimingsCalls,
+    /// The time column of the `--timings` breakdown.
+    TimingsTime,
+    /// The wall-clock total row of the `--timings` breakdown.
+    TimingsTotal,
+    /// The size of the prompt analyzed by `suggest`. Placeholders: `tokens`, `files`.
+    SuggestTotal,
+    /// The prompt is above the budget. Placeholders: `excess`, `budget`.
+    SuggestOverBudget,
+    /// The prompt fits the budget. Placeholder: `budget`.
+    SuggestWithinBudget,
+    /// The heading of the largest directories.
+    SuggestDirectories,
+    /// The heading of the token counts by file extension.
+    SuggestExtensions,
+    /// The heading of the largest files.
+    SuggestLargestFiles,
+    /// The heading of the files that look generated.
+    SuggestGenerated,
+    /// A group of files in the report. Placeholders: `name`, `tokens`, `files`.
+    SuggestGroup,
+    /// The heading of the suggested options.
+    SuggestOptions,
+    /// The estimated size with the suggested options. Placeholder: `tokens`.
+    SuggestRemaining,
+    /// Nothing needs to be excluded.
+    SuggestNoChanges,
+    /// The suggested options do not reach the budget.
+    SuggestStillOverBudget,
+    /// The heading of the token column of `--list`.
+    ListTokens,
+    /// The heading of the size column of `--list`.
+    ListSize,
+    /// The heading of the file column of `--list`.
+    ListFile,
+    /// The totals of `--list`. Placeholders: `files`, `size`, `tokens`.
+    ListTotal,
+    /// The stats sidecar file could not be written. Placeholder: `path`.
+    StatsWriteFailed,
+    /// Spinner message while traversing the directory.
+    TraversingDirectory,
+    /// The bytes read so far, shown next to the progress bar of the files. Placeholder: `size`.
+    FilesReadBytes,
+    /// The files read, logged instead of the progress bar when stderr is not a terminal. Placeholders: `files`, `size`, `elapsed`.
+    FilesRead,
+    /// Spinner message while generating the git diff.
+    GeneratingGitDiff,
+    /// Spinner message while generating the git diff between branches.
+    GeneratingGitDiffBranches,
+    /// Spinner message while retrieving the git log between branches.
+    GeneratingGitLogBranches,
+    /// Spinner message while gathering the changes of a release. Placeholders: `from`, `to`.
+    GatheringReleaseChanges,
+    /// Spinner message while gathering the commits of `bisect-context`. Placeholders: `good`, `bad`.
+    GatheringSuspectCommits,
+    /// Spinner message once processing is complete.
+    Done,
+    /// A branch option did not name exactly two branches.
+    ExpectedTwoBranches,
+    /// A branch does not exist. Placeholder: `branch`.
+    BranchNotFound,
+    /// A revision given to `release-notes` does not exist. Placeholder: `revision`.
+    RevisionNotFound,
+    /// A remote repository could not be cloned. Placeholder: `url`.
+    CloneFailed,
+    /// A branch or tag does not exist in a remote repository. Placeholder: `reference`.
+    ReferenceNotFound,
+    /// A `--github` specification is not of the form `owner/repo[@ref]`. Placeholder: `spec`.
+    InvalidGithubSpec,
+    /// A content pattern is not a valid regular expression. Placeholders: `option`, `error`.
+    InvalidContentPattern,
+    /// A selection expression could not be parsed. Placeholders: `expression`, `column`, `reason`.
+    InvalidSelection,
+    /// A selection expression has an unexpected character. Placeholder: `token`.
+    SelectionUnexpectedToken,
+    /// A selection expression ends too early.
+    SelectionUnexpectedEnd,
+    /// A selection expression uses an unknown filter. Placeholder: `key`.
+    SelectionUnknownKey,
+    /// A selection filter has an invalid value. Placeholders: `key`, `value`.
+    SelectionInvalidValue,
+    /// The git history could not be read for `changed:` filters. Placeholder: `error`.
+    GitChangesUnavailable,
+    /// The files of the git diff could not be read for `--line-number-diff`. Placeholder: `error`.
+    LineNumberDiffUnavailable,
+    /// Secrets were redacted from the packed files. Placeholders: `count`, `files`.
+    SecretsRedacted,
+    /// Secrets were redacted from the git diffs, logs and conflicts. Placeholder: `count`.
+    SecretsRedactedOutsideFiles,
+    /// The files of a priority tier were summarized to fit the token budget. Placeholders: `name`, `files`.
+    TierSummarized,
+    /// The files of a priority tier were left out to fit the token budget. Placeholders: `name`, `files`.
+    TierDropped,
+    /// Files were left out unread as their estimated tokens exceed the budget. Placeholders: `count`, `budget`.
+    EstimatedOverBudget,
+    /// A priority tier is not named `tier` followed by a number. Placeholder: `name`.
+    InvalidTierName,
+    /// A file matched by an include pattern or listed was skipped as sensitive. Placeholder: `path`.
+    SensitiveFileSkipped,
+    /// A snapshot could not be downloaded. Placeholder: `url`.
+    DownloadFailed,
+    /// A source archive could not be extracted. Placeholder: `path`.
+    ExtractFailed,
+    /// A nested git repository was left out. Placeholder: `path`.
+    NestedRepositoryExcluded,
+    /// A nested git repository was packed. Placeholder: `path`.
+    NestedRepositoryIncluded,
+    /// A file was skipped for its size. Placeholders: `path`, `size`, `limit`.
+    FileTooLarge,
+    /// A file embedded with `include_file` cannot be read. Placeholders: `path`, `error`.
+    IncludeFileUnreadable,
+    /// A file embedded with `include_file` is not text. Placeholder: `path`.
+    IncludeFileBinary,
+    /// A file embedded with `include_file` holds credentials. Placeholder: `path`.
+    IncludeFileSensitive,
+    /// A file embedded with `include_file` is above the maximum file size. Placeholders: `path`, `size`, `limit`.
+    IncludeFileTooLarge,
+    /// A file embedded with `include_file` is outside the codebase paths. Placeholder: `path`.
+    IncludeFileOutsideRoots,
+    /// The files embedded with `include_file` exceed the token budget. Placeholders: `path`, `tokens`, `budget`.
+    IncludeFileOverBudget,
+    /// A symlinked file pointing outside the root was skipped. Placeholders: `path`, `target`.
+    ExternalSymlinkSkipped,
+    /// A file changed while it was being packed. Placeholder: `path`.
+    FileModified,
+    /// A file was deleted while it was being packed. Placeholder: `path`.
+    FileVanished,
+    /// A packed file contains git conflict markers. Placeholder: `path`.
+    ConflictMarkersFound,
+    /// A merge, rebase, cherry-pick or revert is in progress with conflicted files. Placeholders: `operation`, `count`.
+    MergeInProgress,
+    /// `--conflicts` found no conflicted file. Placeholder: `path`.
+    NoConflicts,
+    /// `--system-section all` leaves nothing for the user message of a chat format.
+    SystemSectionAll,
+    /// The conflicts of `--conflicts` could not be read. Placeholder: `error`.
+    ConflictsUnavailable,
+    /// A root path was dropped because git did not check it out. Placeholder: `path`.
+    SparsePathSkipped,
+    /// The number of files git did not check out. Placeholder: `count`.
+    SparseReport,
+    /// The git diff could not be generated. Placeholder: `error`.
+    GitDiffFailed,
+    /// The git diff between branches could not be generated. Placeholder: `error`.
+    GitDiffBranchesFailed,
+    /// The git log between branches could not be retrieved. Placeholder: `error`.
+    GitLogBranchesFailed,
+    /// The custom template file could not be read.
+    TemplateReadFailed,
+    /// The template could not be registered. Placeholder: `error`.
+    TemplateRegisterFailed,
+    /// The template could not be rendered. Placeholder: `error`.
+    TemplateRenderFailed,
+    /// Location of a template error. Placeholder: `location`.
+    TemplateErrorLocation,
+    /// The variable that caused a template error. Placeholder: `name`.
+    TemplateErrorVariable,
+    /// The helper that caused a template error. Placeholder: `name`.
+    TemplateErrorHelper,
+    /// The partial that caused a template error. Placeholder: `name`.
+    TemplateErrorPartial,
+    /// Interactive prompt for a template variable. Placeholder: `name`.
+    EnterValueFor,
+    /// Help message of the interactive prompt for template variables.
+    FillTemplateVariable,
+    /// Help message of the interactive prompt for a list field of a template form.
+    FillFormList,
+    /// A required field of a template form was left empty.
+    FormFieldRequired,
+    /// A form declared in a template cannot be parsed. Placeholder: `error`.
+    FormInvalid,
+    /// A template variable or required form field is missing from the answers file. Placeholder: `name`.
+    AnswerMissing,
+    /// A template variable or form has no value and `--quiet` keeps it from being asked for. Placeholder: `name`.
+    VariableUnanswered,
+    /// The answers file cannot be read or parsed. Placeholder: `path`.
+    AnswersReadFailed,
+    /// A `--var` value is not of the form `NAME=VALUE`. Placeholder: `value`.
+    VarInvalid,
+    /// A pattern or path references a variable that neither `--var` nor the answers file gives. Placeholders: `name`, `pattern`.
+    PatternVariableMissing,
+    /// `codexio again` was run without a saved invocation.
+    NoPreviousInvocation,
+    /// No prompt was recorded for the repository of `codexio stats`. Placeholder: `path`.
+    StatsNoRuns,
+    /// The number of recorded prompts and the days they span. Placeholders: `runs`, `first`, `last`.
+    StatsOverview,
+    /// The sizes of the recorded prompts. Placeholders: `latest`, `average`, `peak`.
+    StatsTokens,
+    /// How the average size of the prompts changed. Placeholders: `percent`, `day`.
+    StatsGrowth,
+    /// The heading of the token trend by day.
+    StatsTrend,
+    /// The heading of the files included most often.
+    StatsTopFiles,
+    /// The column heading of the days of the token trend.
+    StatsDay,
+    /// The column heading of the number of prompts.
+    StatsRuns,
+    /// The column heading of the average number of tokens.
+    StatsAverageTokens,
+    /// The HTML dashboard was written. Placeholder: `path`.
+    StatsHtmlWritten,
+    /// The HTML dashboard could not be written. Placeholder: `path`.
+    StatsHtmlWriteFailed,
+    /// The HTML dashboard is being served. Placeholder: `address`.
+    StatsServing,
+    /// The HTML dashboard could not be served. Placeholder: `address`.
+    StatsServeFailed,
+    /// The selected profile does not exist. Placeholder: `name`.
+    ProfileNotFound,
+    /// The selected saved selection does not exist. Placeholder: `name`.
+    SelectionNotFound,
+    /// The project configuration file could not be updated. Placeholder: `path`.
+    ConfigWriteFailed,
+    /// A selection was saved. Placeholders: `name`, `path`.
+    SelectionSaved,
+    /// A batch file cannot be read or parsed. Placeholder: `path`.
+    BatchReadFailed,
+    /// A workspace file cannot be read or parsed. Placeholder: `path`.
+    WorkspaceFileReadFailed,
+    /// A workspace file lists no roots. Placeholder: `path`.
+    WorkspaceFileEmpty,
+    /// `--selection`, `--profile` or `--preset` was given with `batch`.
+    BatchLayersUnsupported,
+    /// A batch job has no output file. Placeholder: `job`.
+    BatchJobWithoutOutput,
+    /// The heading of the report of a batch job. Placeholders: `job`, `index`, `total`.
+    BatchJobStarted,
+    /// A batch job failed. Placeholders: `job`, `error`.
+    BatchJobFailed,
+    /// Some batch jobs failed. Placeholders: `failed`, `total`.
+    BatchFailed,
+    /// The verdict of `--explain` for a packed file. Placeholder: `path`.
+    ExplainIncluded,
+    /// The verdict of `--explain` for a file left out. Placeholder: `path`.
+    ExplainExcluded,
+    /// The explained path does not exist.
+    ExplainMissing,
+    /// The explained path is outside the codebase paths.
+    ExplainOutsideRoots,
+    /// The explained path is a directory.
+    ExplainDirectory,
+    /// The explained path is written by the run.
+    ExplainOutputFile,
+    /// The explained path was not checked out by git.
+    ExplainSkipWorktree,
+    /// The explained path is inside a `.git` directory.
+    ExplainGitDirectory,
+    /// The explained path is inside a default excluded directory. Placeholder: `directory`.
+    ExplainDefaultExcludedDirectory,
+    /// The explained path is hidden. Placeholder: `name`.
+    ExplainHidden,
+    /// The explained path is inside a nested git repository. Placeholder: `directory`.
+    ExplainNestedRepo,
+    /// The explained path is below `--max-depth`. Placeholders: `depth`, `max_depth`.
+    ExplainMaxDepth,
+    /// An ignore file rule matches the explained path. Placeholders: `pattern`, `file`.
+    ExplainIgnored,
+    /// A negated ignore file rule matches the explained path. Placeholders: `pattern`, `file`.
+    ExplainWhitelisted,
+    /// No ignore file rule matches the explained path.
+    ExplainNotIgnored,
+    /// Ignore files are disabled with `--no-ignore`.
+    ExplainIgnoreDisabled,
+    /// An include pattern matches the explained path. Placeholder: `pattern`.
+    ExplainIncludeMatched,
+    /// A negated include pattern matches the explained path. Placeholder: `pattern`.
+    ExplainIncludeNegated,
+    /// Include patterns were given but none matches the explained path.
+    ExplainIncludeMissed,
+    /// An exclude pattern matches the explained path. Placeholder: `pattern`.
+    ExplainExcludeMatched,
+    /// A negated exclude pattern matches the explained path. Placeholder: `pattern`.
+    ExplainExcludeNegated,
+    /// An exclude pattern matches the explained path as well, and `--include-priority` is set. Placeholder: `pattern`.
+    ExplainIncludePriority,
+    /// An exclude pattern matches the explained path as well, without `--include-priority`. Placeholder: `pattern`.
+    ExplainExcludePriority,
+    /// The explained path is a lock file.
+    ExplainLockFile,
+    /// The explained path is marked by linguist in `.gitattributes`. Placeholder: `attribute`.
+    ExplainLinguist,
+    /// The name or content of the explained path looks generated.
+    ExplainGenerated,
+    /// The explained path may hold credentials.
+    ExplainSensitive,
+    /// The explained path links outside its root. Placeholder: `target`.
+    ExplainExternalSymlink,
+    /// The explained path is above the maximum file size. Placeholders: `size`, `limit`.
+    ExplainTooLarge,
+    /// The explained path is binary.
+    ExplainBinary,
+    /// The encoding of the explained path cannot be detected.
+    ExplainUndetectedEncoding,
+    /// The explained path is empty.
+    ExplainEmpty,
+    /// The content of the explained path does not match `--include-containing`.
+    ExplainContentMissing,
+    /// The content of the explained path matches `--exclude-containing`.
+    ExplainContentExcluded,
+    /// The language of the explained path is not selected. Placeholder: `language`.
+    ExplainLanguage,
+    /// The language of the explained path is not detected while `--lang` is given.
+    ExplainNoLanguage,
+    /// The explained path is not selected by `--select`.
+    ExplainNotSelected,
+    /// A pattern file could not be read. Placeholder: `path`.
+    PatternFileReadFailed,
+    /// The configuration files could not be loaded.
+    ConfigLoadFailed,
+    /// A theme color is not recognized. Placeholder: `color`.
+    UnknownColor,
+    /// The response or a patched file could not be read. Placeholder: `path`.
+    ApplyReadFailed,
+    /// The response of `codexio apply` holds no unified diff.
+    ApplyNoPatches,
+    /// A hunk header cannot be parsed. Placeholders: `path`, `header`.
+    ApplyInvalidHunk,
+    /// A hunk header names no anchor. Placeholders: `path`, `line`.
+    ApplyAnchorMissing,
+    /// The anchor of a hunk does not match the file. Placeholders: `path`, `anchor`.
+    ApplyAnchorMismatch,
+    /// The lines of a hunk do not match the file. Placeholders: `path`, `line`.
+    ApplyContextMismatch,
+    /// The hunks of a file overlap or are out of order. Placeholders: `path`, `line`.
+    ApplyHunksOverlap,
+    /// A patched file does not exist. Placeholder: `path`.
+    ApplyFileNotFound,
+    /// A file created by the response already exists. Placeholder: `path`.
+    ApplyFileExists,
+    /// A patched path is outside the codebase. Placeholder: `path`.
+    ApplyUnsafePath,
+    /// A patched file could not be written. Placeholder: `path`.
+    ApplyWriteFailed,
+    /// Every hunk matches its anchors with `--check`. Placeholders: `hunks`, `files`.
+    ApplyChecked,
+    /// The hunks of the response were applied. Placeholders: `hunks`, `files`.
+    ApplyApplied,
+    /// The suggested fix of a failed `codexio doctor` check. Placeholder: `fix`.
+    DoctorFix,
+    /// The summary of `codexio doctor`. Placeholders: `passed`, `warned`, `failed`.
+    DoctorSummary,
+    /// The codebase is in a git repository. Placeholders: `path`, `branch`.
+    DoctorGitRepository,
+    /// The codebase is not in a git repository. Placeholder: `path`.
+    DoctorGitNotRepository,
+    /// How to use the git options outside a repository.
+    DoctorGitNotRepositoryFix,
+    /// The git repository of the codebase cannot be opened. Placeholder: `error`.
+    DoctorGitBroken,
+    /// How to repair a git repository that cannot be opened.
+    DoctorGitBrokenFix,
+    /// The clipboard backend can copy. Placeholder: `backend`.
+    DoctorClipboardReady,
+    /// Copying to the clipboard is turned off.
+    DoctorClipboardDisabled,
+    /// How to copy when the clipboard backend is unavailable.
+    DoctorClipboardFix,
+    /// The terminal is interactive. Placeholder: `term`.
+    DoctorTerminalReady,
+    /// Standard input or error is not a terminal, so nothing can be asked.
+    DoctorTerminalNotInteractive,
+    /// How to give template variables without a terminal.
+    DoctorTerminalNotInteractiveFix,
+    /// The terminal does not support escape sequences.
+    DoctorTerminalDumb,
+    /// How to avoid escape sequences in a dumb terminal.
+    DoctorTerminalDumbFix,
+    /// The configuration files are valid. Placeholder: `paths`.
+    DoctorConfigLoaded,
+    /// No configuration file exists.
+    DoctorConfigDefaults,
+    /// How to find what is wrong with a configuration file.
+    DoctorConfigFix,
+    /// The built-in template is used. Placeholder: `name`.
+    DoctorTemplateBuiltin,
+    /// The custom template compiles. Placeholder: `path`.
+    DoctorTemplateValid,
+    /// The custom template cannot be read or compiled. Placeholders: `path`, `error`.
+    DoctorTemplateInvalid,
+    /// How to find what is wrong with a custom template. Placeholder: `path`.
+    DoctorTemplateFix,
+    /// The cache directory is writable and its files parse. Placeholder: `path`.
+    DoctorCacheHealthy,
+    /// How to reset a broken cache directory. Placeholder: `path`.
+    DoctorCacheFix,
+    /// `--split-tokens` is given without an output file to name the parts after.
+    SplitTokensWithoutOutput,
+    /// `--split-tokens` is given with another format or section than the whole Markdown prompt.
+    SplitTokensUnsupported,
+    /// The prompt without its files does not fit in a part. Placeholders: `tokens`, `cap`.
+    SplitTokensTooSmall,
+    /// A git repository cannot be opened. Placeholder: `path`.
+    GitOpenFailed,
+    /// The head of a repository cannot be read.
+    GitHeadFailed,
+    /// A repository is bare.
+    GitNoWorkdir,
+    /// The working directory of a repository cannot be resolved.
+    GitWorkdirFailed,
+    /// A git diff cannot be computed.
+    GitDiffGenerateFailed,
+    /// A git diff cannot be printed as a patch.
+    GitDiffPrintFailed,
+    /// The commits of a repository cannot be walked.
+    GitHistoryFailed,
+    /// The index of a repository cannot be read.
+    GitIndexFailed,
+    /// The status of a repository cannot be read.
+    GitStatusFailed,
+    /// A repository cannot be cloned into a directory. Placeholder: `path`.
+    GitCloneIntoFailed,
+    /// A repository cannot be created to fetch a reference into. Placeholder: `path`.
+    GitInitFailed,
+    /// The remote of a repository cannot be added. Placeholder: `url`.
+    GitRemoteFailed,
+    /// A reference cannot be fetched. Placeholder: `reference`.
+    GitFetchFailed,
+    /// A fetched reference cannot be checked out. Placeholder: `reference`.
+    GitCheckoutFailed,
+    /// A source archive cannot be opened. Placeholder: `path`.
+    ArchiveOpenFailed,
+    /// A source archive cannot be unpacked. Placeholder: `path`.
+    ArchiveUnpackFailed,
+    /// The files extracted from an archive cannot be listed. Placeholder: `path`.
+    ArchiveReadFailed,
+    /// The files extracted from an archive cannot be moved. Placeholder: `path`.
+    ArchiveMoveFailed,
+    /// No cache directory can be determined from the environment.
+    CacheDirUnknown,
+    /// The cache directory cannot be created. Placeholder: `path`.
+    CacheDirCreateFailed,
+    /// The cache directory cannot be written to. Placeholder: `path`.
+    CacheWriteFailed,
+    /// A file of the cache directory cannot be read. Placeholder: `path`.
+    HistoryReadFailed,
+    /// A file of the cache directory cannot be parsed. Placeholder: `path`.
+    HistoryParseFailed,
+    /// A file of the cache directory cannot be written. Placeholder: `path`.
+    HistoryWriteFailed,
+    /// A partial of the template cannot be compiled. Placeholders: `name`, `error`.
+    PartialRegisterFailed,
+    /// The diagnostic logs cannot be set up. Placeholder: `error`.
+    SubscriberInstallFailed,
+    /// A path cannot be made absolute. Placeholder: `path`.
+    CanonicalizeFailed,
+    /// A file cannot be deleted. Placeholder: `path`.
+    RemoveFailed,
+    /// The temporary workspace of the run cannot be created.
+    WorkspaceCreateFailed,
+    /// A directory of the temporary workspace cannot be created. Placeholder: `path`.
+    WorkspaceDirCreateFailed,
+    /// The temporary file of an atomic write cannot be created. Placeholder: `path`.
+    TempFileCreateFailed,
+    /// A file cannot be written atomically. Placeholder: `path`.
+    FileWriteFailed,
+    /// The lock file of a shared file cannot be opened. Placeholder: `path`.
+    LockOpenFailed,
+    /// A shared file cannot be locked. Placeholder: `path`.
+    LockFailed,
+}
+
+/// Sets the locale used for messages. Only the first call has an effect.
+///
+/// # Arguments
+///
+/// * `locale` - The locale to use.
+pub fn set_locale(locale: Locale) {
+    let _ = LOCALE.set(locale);
+}
+
+/// Returns the locale used for messages.
+///
+/// # Returns
+///
+/// * `Locale` - The locale set at startup, or the one detected from the environment.
+pub fn locale() -> Locale {
+    *LOCALE.get_or_init(Locale::detect)
+}
+
+/// Returns the text of a message in the current locale.
+///
+/// # Arguments
+///
+/// * `id` - The message ID.
+///
+/// # Returns
+///
+/// * `&'static str` - The message text, with its placeholders left unfilled.
+pub fn message(id: Message) -> &'static str {
+    let translated = match locale() {
+        Locale::En => None,
+        Locale::Es => spanish(id),
+        Locale::De => german(id),
+    };
+    translated.unwrap_or_else(|| english(id))
+}
+
+/// Returns the text of a message in the current locale with its placeholders filled in.
+///
+/// # Arguments
+///
+/// * `id` - The message ID.
+/// * `args` - The placeholder names and their values.
+///
+/// # Returns
+///
+/// * `String` - The formatted message.
+pub fn message_with(id: Message, args: &[(&str, &dyn Display)]) -> String {
+    args.iter().fold(message(id).to_string(), |text, (name, value)| {
+        text.replace(&format!("{{{}}}", name), &value.to_string())
+    })
+}
+
+/// The English catalog, which every message must be part of.
+fn english(id: Message) -> &'static str {
+    match id {
+        Message::CopiedToClipboard => "Copied to clipboard successfully.",
+        Message::ClipboardInitFailed => "Failed to initialize clipboard: {error}",
+        Message::ClipboardCopyFailed => "Failed to copy to clipboard: {error}",
+        Message::ClipboardCommandFailed => "Failed to copy with {command}: {error}",
+        Message::ClipboardCommandMissing => "{command} is not installed or not on PATH",
+        Message::ClipboardNoTerminal => "No terminal to send the OSC 52 sequence to",
+        Message::PasteCharactersExceeded => "Copied {count} characters, above the {limit} character paste limit of {target}; the paste may be truncated.",
+        Message::PasteBytesExceeded => "Copied {count} bytes, above the {limit} byte paste limit of {target}; the paste may be truncated.",
+        Message::UnknownPasteTarget => "Unknown paste target '{target}'; add it to [paste_limits] in the configuration file",
+        Message::PromptWrittenToFile => "Prompt written to file: {path}",
+        Message::TokenInfo => "Token count: {count}, Model info: {model}",
+        Message::ChangesSinceLastOutput => "Changes since last output: {changes}",
+        Message::FilesDelta => "+{added}/-{removed} files",
+        Message::LinesDelta => "{delta} lines",
+        Message::TokensDelta => "{delta} tokens",
+        Message::Summary => "Summary: {details}",
+        Message::SummaryFiles => "{included} files included, {skipped} skipped",
+        Message::SummaryTokens => "{count} tokens",
+        Message::SummaryBudget => "{percent}% of {budget} token budget",
+        Message::SummaryDestinations => "sent to {destinations}",
+        Message::DestinationClipboard => "clipboard",
+        Message::DestinationStdout => "stdout",
+        Message::Timings => "Timings",
+        Message::TimingsStage => "Stage",
+        Message::TimingsCalls => "Calls",
+        Message::TimingsTime => "Time",
+        Message::TimingsTotal => "total",
+        Message::SuggestTotal => "The prompt has {tokens} tokens across {files} files.",
+        Message::SuggestOverBudget => "It is {excess} tokens over the {budget} token budget.",
+        Message::SuggestWithinBudget => "It fits the {budget} token budget.",
+        Message::SuggestDirectories => "Largest directories:",
+        Message::SuggestExtensions => "Tokens by file extension:",
+        Message::SuggestLargestFiles => "Largest files:",
+        Message::SuggestGenerated => "Files that look generated:",
+        Message::SuggestGroup => "{name}: {tokens} tokens in {files} files",
+        Message::SuggestOptions => "Suggested options:",
+        Message::SuggestRemaining => "Estimated tokens with the suggested options: {tokens}",
+        Message::SuggestNoChanges => "No changes suggested.",
+        Message::SuggestStillOverBudget => "Still above the budget; narrow the selection with --include.",
+        Message::ListTokens => "Tokens",
+        Message::ListSize => "Size",
+        Message::ListFile => "File",
+        Message::ListTotal => "Total: {files} files, {size}, {tokens} tokens",
+        Message::StatsWriteFailed => "Failed to write stats file: {path}",
+        Message::TraversingDirectory => "Traversing directory and building tree...",
+        Message::FilesReadBytes => "files, {size}",
+        Message::FilesRead => "Read {files} files, {size}, in {elapsed}",
+        Message::GeneratingGitDiff => "Generating git diff...",
+        Message::GeneratingGitDiffBranches => "Generating git diff between two branches...",
+        Message::GeneratingGitLogBranches => "Generating git log between two branches...",
+        Message::GatheringReleaseChanges => "Gathering the changes from {from} to {to}...",
+        Message::GatheringSuspectCommits => "Gathering the commits from {good} to {bad}...",
+        Message::Done => "Done!",
+        Message::ExpectedTwoBranches => "Please provide exactly two branches separated by a comma.",
+        Message::BranchNotFound => "Branch {branch} doesn't exist!",
+        Message::RevisionNotFound => "Revision {revision} doesn't exist!",
+        Message::CloneFailed => "Failed to clone repository: {url}",
+        Message::ReferenceNotFound => "Branch or tag {reference} doesn't exist in the repository",
+        Message::InvalidGithubSpec => "Invalid GitHub repository '{spec}', expected owner/repo[@ref]",
+        Message::InvalidContentPattern => "Invalid regular expression for --{option}: {error}",
+        Message::InvalidSelection => "Invalid selection expression '{expression}' at column {column}: {reason}",
+        Message::SelectionUnexpectedToken => "unexpected '{token}'",
+        Message::SelectionUnexpectedEnd => "unexpected end of expression",
+        Message::SelectionUnknownKey => "unknown filter '{key}', expected path, lang, ext, contains or changed",
+        Message::SelectionInvalidValue => "invalid value '{value}' for {key}",
+        Message::GitChangesUnavailable => "Could not read the git history for changed: filters, no file counts as changed: {error}",
+        Message::LineNumberDiffUnavailable => "Could not read the git diff for --line-number-diff, no file is numbered for it: {error}",
+        Message::SecretsRedacted => "Redacted {count} secrets in {files} files; pass --no-redact to keep them",
+        Message::SecretsRedactedOutsideFiles => "Redacted {count} secrets in the git output; pass --no-redact to keep them",
+        Message::TierSummarized => "Summarized the {files} files of {name} to fit the token budget",
+        Message::TierDropped => "Left out the {files} files of {name} to fit the token budget",
+        Message::EstimatedOverBudget => "Left out {count} files without reading them, as their estimated tokens exceed the {budget} token budget",
+        Message::InvalidTierName => "Invalid priority tier '{name}': tiers are named tier1, tier2 and so on",
+        Message::SensitiveFileSkipped => "Skipped sensitive file {path}; pass --allow-sensitive to pack it",
+        Message::DownloadFailed => "Failed to download {url}",
+        Message::ExtractFailed => "Failed to extract archive: {path}",
+        Message::NestedRepositoryExcluded => "Skipped nested git repository {path}; pass --include-nested-repos to pack it",
+        Message::NestedRepositoryIncluded => "Packed nested git repository {path}; its ignore rules may differ from the parent repository",
+        Message::FileTooLarge => "Skipped {path}: {size} is above the maximum file size of {limit}",
+        Message::IncludeFileUnreadable => "Cannot include {path}: {error}",
+        Message::IncludeFileBinary => "Cannot include {path}: it is not a text file",
+        Message::IncludeFileSensitive => "Cannot include sensitive file {path}; pass --allow-sensitive to include it",
+        Message::IncludeFileTooLarge => "Cannot include {path}: {size} is above the maximum file size of {limit}",
+        Message::IncludeFileOutsideRoots => "Cannot include {path}: it is outside the codebase paths",
+        Message::IncludeFileOverBudget => "Cannot include {path}: the included files take {tokens} tokens, over the budget of {budget}",
+        Message::ExternalSymlinkSkipped => "Skipped {path}: it links to {target} outside the codebase; pass --include-external-symlinks to pack it",
+        Message::FileModified => "{path} changed while it was being packed; its latest content was packed",
+        Message::FileVanished => "{path} was deleted while it was being packed and was left out of the tree and the files",
+        Message::ConflictMarkersFound => "{path} contains git conflict markers",
+        Message::MergeInProgress => "A {operation} is in progress with {count} conflicted files; run codexio resolve to pack them with both sides of each conflict",
+        Message::NoConflicts => "No file has merge conflicts in the repository of {path}",
+        Message::SystemSectionAll => "--system-section cannot be all, which leaves nothing for the user message",
+        Message::ConflictsUnavailable => "Could not read the merge conflicts for --conflicts: {error}",
+        Message::SparsePathSkipped => "Skipped {path}, which is outside the sparse checkout",
+        Message::SparseReport => "{count} files outside the sparse checkout or marked skip-worktree were not packed",
+        Message::GitDiffFailed => "Failed to generate git diff: {error}",
+        Message::GitDiffBranchesFailed => "Failed to generate git diff between branches: {error}",
+        Message::GitLogBranchesFailed => "Failed to retrieve git log between branches: {error}",
+        Message::TemplateReadFailed => "Failed to read custom template file",
+        Message::TemplateRegisterFailed => "Failed to register template: {error}",
+        Message::TemplateRenderFailed => "Failed to render template: {error}",
+        Message::TemplateErrorLocation => "at {location}",
+        Message::TemplateErrorVariable => "offending variable: {name}",
+        Message::TemplateErrorHelper => "offending helper: {name}",
+        Message::TemplateErrorPartial => "offending partial: {name}",
+        Message::EnterValueFor => "Enter value for '{name}': ",
+        Message::FillTemplateVariable => "Fill user defined variable in template",
+        Message::FillFormList => "Enter one item per line, and an empty line to finish.",
+        Message::FormFieldRequired => "This field is required.",
+        Message::FormInvalid => "Invalid form in template: {error}",
+        Message::AnswerMissing => "The answers file has no value for '{name}'",
+        Message::VariableUnanswered => "No value for '{name}', which --quiet does not ask for; pass --var {name}=VALUE or --answers",
+        Message::AnswersReadFailed => "Failed to read answers file: {path}",
+        Message::VarInvalid => "Invalid --var '{value}', expected NAME=VALUE",
+        Message::PatternVariableMissing => "'{pattern}' uses the variable '{name}', which has no value; pass --var {name}=VALUE",
+        Message::NoPreviousInvocation => "No previous invocation recorded for this directory",
+        Message::StatsNoRuns => "No prompts recorded for {path} yet",
+        Message::StatsOverview => "{runs} prompts generated from {first} to {last}.",
+        Message::StatsTokens => "Latest {latest} tokens, average {average}, peak {peak}.",
+        Message::StatsGrowth => "The daily average changed by {percent}% since {day}.",
+        Message::StatsTrend => "Token trend",
+        Message::StatsTopFiles => "Most included files",
+        Message::StatsDay => "Day",
+        Message::StatsRuns => "Prompts",
+        Message::StatsAverageTokens => "Avg tokens",
+        Message::StatsHtmlWritten => "Wrote the dashboard to {path}",
+        Message::StatsHtmlWriteFailed => "Failed to write the dashboard: {path}",
+        Message::StatsServing => "Serving the dashboard at http://{address}, press Ctrl+C to stop",
+        Message::StatsServeFailed => "Failed to serve the dashboard at {address}",
+        Message::ProfileNotFound => "Profile '{name}' is not defined in the configuration files",
+        Message::SelectionNotFound => "Selection '{name}' is not saved in the configuration files",
+        Message::ConfigWriteFailed => "Failed to update the configuration file {path}",
+        Message::SelectionSaved => "Saved selection '{name}' to {path}; reuse it with --selection {name}",
+        Message::BatchReadFailed => "Failed to read batch file {path}",
+        Message::WorkspaceFileReadFailed => "Failed to read workspace file {path}",
+        Message::WorkspaceFileEmpty => "Workspace file {path} lists no roots",
+        Message::BatchLayersUnsupported => "--selection, --profile and --preset cannot be used with batch; set them per job in the batch file",
+        Message::BatchJobWithoutOutput => "Job {job} has no output file",
+        Message::BatchJobStarted => "Job {index}/{total}: {job}",
+        Message::BatchJobFailed => "Job {job} failed: {error}",
+        Message::BatchFailed => "{failed} of {total} jobs failed",
+        Message::ExplainIncluded => "{path} is packed",
+        Message::ExplainExcluded => "{path} is left out",
+        Message::ExplainMissing => "The path does not exist",
+        Message::ExplainOutsideRoots => "It is outside the codebase paths",
+        Message::ExplainDirectory => "It is a directory; only the files inside it are packed",
+        Message::ExplainOutputFile => "It is an output file of this run",
+        Message::ExplainSkipWorktree => "git did not check it out",
+        Message::ExplainGitDirectory => "It is inside a .git directory",
+        Message::ExplainDefaultExcludedDirectory => "It is inside {directory}, a default excluded directory; pass --no-default-excludes to pack it",
+        Message::ExplainHidden => "{name} is hidden; pass --hidden to pack it",
+        Message::ExplainNestedRepo => "It is inside {directory}, a nested git repository; pass --include-nested-repos to pack it",
+        Message::ExplainMaxDepth => "It is {depth} levels deep, below --max-depth {max_depth}",
+        Message::ExplainIgnored => "Ignored by `{pattern}` in {file}; pass --no-ignore to pack it",
+        Message::ExplainWhitelisted => "Kept by `{pattern}` in {file}",
+        Message::ExplainNotIgnored => "No ignore file matches it",
+        Message::ExplainIgnoreDisabled => "Ignore files are disabled by --no-ignore",
+        Message::ExplainIncludeMatched => "Include pattern `{pattern}` matches it",
+        Message::ExplainIncludeNegated => "Negated include pattern `{pattern}` matches it",
+        Message::ExplainIncludeMissed => "No include pattern matches it",
+        Message::ExplainExcludeMatched => "Exclude pattern `{pattern}` matches it",
+        Message::ExplainExcludeNegated => "Negated exclude pattern `{pattern}` keeps it",
+        Message::ExplainIncludePriority => "Exclude pattern `{pattern}` matches too, and --include-priority makes the include pattern win",
+        Message::ExplainExcludePriority => "Exclude pattern `{pattern}` matches too, and wins without --include-priority",
+        Message::ExplainLockFile => "It is a lock file, skipped by default; pass --no-default-excludes or include it explicitly",
+        Message::ExplainLinguist => "It is marked {attribute} in .gitattributes; pass --no-gitattributes to pack it",
+        Message::ExplainGenerated => "It looks generated or minified; pass --include-generated to pack it",
+        Message::ExplainSensitive => "It may hold credentials; pass --allow-sensitive to pack it",
+        Message::ExplainExternalSymlink => "It links to {target}, outside its root; pass --include-external-symlinks to pack it",
+        Message::ExplainTooLarge => "{size} is above the maximum file size of {limit}",
+        Message::ExplainBinary => "It is a binary file, packed as a placeholder",
+        Message::ExplainUndetectedEncoding => "Its encoding cannot be detected; pass --lossy to pack it",
+        Message::ExplainEmpty => "It is empty",
+        Message::ExplainContentMissing => "Its content does not match --include-containing",
+        Message::ExplainContentExcluded => "Its content matches --exclude-containing",
+        Message::ExplainLanguage => "Its language, {language}, is not selected by --lang",
+        Message::ExplainNoLanguage => "Its language is not detected, and --lang is given",
+        Message::ExplainNotSelected => "It is not selected by --select",
+        Message::PatternFileReadFailed => "Failed to read pattern file: {path}",
+        Message::ConfigLoadFailed => "Failed to load configuration file",
+        Message::UnknownColor => "Unknown color in theme: {color}",
+        Message::ApplyReadFailed => "Failed to read {path}",
+        Message::ApplyNoPatches => "The response holds no unified diff",
+        Message::ApplyInvalidHunk => "Invalid hunk header for {path}: {header}",
+        Message::ApplyAnchorMissing => "The hunk of {path} at line {line} names no anchor",
+        Message::ApplyAnchorMismatch => "The anchor {anchor} does not match {path}, which changed since the prompt or was misquoted",
+        Message::ApplyContextMismatch => "The hunk of {path} at line {line} does not match the file",
+        Message::ApplyHunksOverlap => "The hunk of {path} at line {line} overlaps the one before it",
+        Message::ApplyFileNotFound => "The patched file does not exist: {path}",
+        Message::ApplyFileExists => "The created file already exists: {path}",
+        Message::ApplyUnsafePath => "The patched path is outside the codebase: {path}",
+        Message::ApplyWriteFailed => "Failed to write {path}",
+        Message::ApplyChecked => "{hunks} hunks in {files} files match their anchors",
+        Message::ApplyApplied => "Applied {hunks} hunks to {files} files",
+        Message::DoctorFix => "Fix: {fix}",
+        Message::DoctorSummary => "{passed} passed, {warned} warnings, {failed} failed",
+        Message::DoctorGitRepository => "Repository at {path}, on {branch}",
+        Message::DoctorGitNotRepository => "{path} is not in a git repository",
+        Message::DoctorGitNotRepositoryFix => "Run codexio inside a git repository to use --diff, --git-diff-branch, --git-log-branch and the git subcommands",
+        Message::DoctorGitBroken => "The git repository cannot be opened: {error}",
+        Message::DoctorGitBrokenFix => "Run git status in the codebase to see what is wrong with the repository",
+        Message::DoctorClipboardReady => "Copying with {backend}",
+        Message::DoctorClipboardDisabled => "Copying is turned off",
+        Message::DoctorClipboardFix => "Pick another backend with --clipboard, such as osc52 over SSH or wl-copy on Wayland, or pass --no-clipboard with --output",
+        Message::DoctorTerminalReady => "Interactive terminal ({term})",
+        Message::DoctorTerminalNotInteractive => "Not an interactive terminal; template variables cannot be asked for",
+        Message::DoctorTerminalNotInteractiveFix => "Give the template variables with --var or --answers",
+        Message::DoctorTerminalDumb => "TERM is dumb or unset; colors and the spinner may show as escape codes",
+        Message::DoctorTerminalDumbFix => "Set NO_COLOR and pass --ascii",
+        Message::DoctorConfigLoaded => "Loaded {paths}",
+        Message::DoctorConfigDefaults => "No configuration file, using the defaults",
+        Message::DoctorConfigFix => "Validate .codexio.toml against the schema printed by codexio config schema",
+        Message::DoctorTemplateBuiltin => "Built-in {name} template",
+        Message::DoctorTemplateValid => "{path} compiles",
+        Message::DoctorTemplateInvalid => "{path}: {error}",
+        Message::DoctorTemplateFix => "Check the template path, relative to the working directory, and preview it with codexio template preview {path}",
+        Message::DoctorCacheHealthy => "{path} is writable",
+        Message::DoctorCacheFix => "Make {path} writable, or delete it; only the history of codexio again and codexio stats is lost",
+        Message::SplitTokensWithoutOutput => "--split-tokens needs --output to name the parts after, such as --output prompt.md",
+        Message::SplitTokensUnsupported => "--split-tokens only splits the whole Markdown prompt, not other formats or sections",
+        Message::SplitTokensTooSmall => "The prompt takes {tokens} tokens without its files, more than the {cap} tokens of a part; raise --split-tokens",
+        Message::GitOpenFailed => "Failed to open the git repository at {path}",
+        Message::GitHeadFailed => "Failed to read the head of the repository",
+        Message::GitNoWorkdir => "The repository has no working directory",
+        Message::GitWorkdirFailed => "Failed to resolve the working directory of the repository",
+        Message::GitDiffGenerateFailed => "Failed to generate the git diff",
+        Message::GitDiffPrintFailed => "Failed to print the git diff",
+        Message::GitHistoryFailed => "Failed to walk the git history",
+        Message::GitIndexFailed => "Failed to read the git index",
+        Message::GitStatusFailed => "Failed to read the status of the repository",
+        Message::GitCloneIntoFailed => "Failed to clone the repository into {path}",
+        Message::GitInitFailed => "Failed to initialize a git repository in {path}",
+        Message::GitRemoteFailed => "Failed to add the remote {url}",
+        Message::GitFetchFailed => "Failed to fetch {reference} from the remote",
+        Message::GitCheckoutFailed => "Failed to check out {reference}",
+        Message::ArchiveOpenFailed => "Failed to open the archive {path}",
+        Message::ArchiveUnpackFailed => "Failed to unpack the archive into {path}",
+        Message::ArchiveReadFailed => "Failed to read the extracted files in {path}",
+        Message::ArchiveMoveFailed => "Failed to move the extracted files to {path}",
+        Message::CacheDirUnknown => "Failed to determine the cache directory",
+        Message::CacheDirCreateFailed => "Failed to create the cache directory {path}",
+        Message::CacheWriteFailed => "Failed to write to the cache directory {path}",
+        Message::HistoryReadFailed => "Failed to read the history file {path}",
+        Message::HistoryParseFailed => "Failed to parse the history file {path}",
+        Message::HistoryWriteFailed => "Failed to write the history file {path}",
+        Message::PartialRegisterFailed => "Failed to register partial {name}: {error}",
+        Message::SubscriberInstallFailed => "Failed to install the tracing subscriber: {error}",
+        Message::CanonicalizeFailed => "Failed to resolve the path {path}",
+        Message::RemoveFailed => "Failed to remove {path}",
+        Message::WorkspaceCreateFailed => "Failed to create the temporary workspace",
+        Message::WorkspaceDirCreateFailed => "Failed to create the directory {path} in the workspace",
+        Message::TempFileCreateFailed => "Failed to create a temporary file in {path}",
+        Message::FileWriteFailed => "Failed to write file: {path}",
+        Message::LockOpenFailed => "Failed to open lock file: {path}",
+        Message::LockFailed => "Failed to lock {path}",
+    }
+}
+
+/// The Spanish catalog.
+fn spanish(id: Message) -> Option<&'static str> {
+    Some(match id {
+        Message::CopiedToClipboard => "Copiado al portapapeles correctamente.",
+        Message::ClipboardInitFailed => "No se pudo inicializar el portapapeles: {error}",
+        Message::ClipboardCopyFailed => "No se pudo copiar al portapapeles: {error}",
+        Message::ClipboardCommandFailed => "No se pudo copiar con {command}: {error}",
+        Message::ClipboardCommandMissing => "{command} no está instalado o no está en el PATH",
+        Message::ClipboardNoTerminal => "No hay terminal a la que enviar la secuencia OSC 52",
+        Message::PasteCharactersExceeded => "Se copiaron {count} caracteres, por encima del límite de {limit} caracteres de {target}; el texto pegado puede quedar truncado.",
+        Message::PasteBytesExceeded => "Se copiaron {count} bytes, por encima del límite de {limit} bytes de {target}; el texto pegado puede quedar truncado.",
+        Message::UnknownPasteTarget => "Destino de pegado desconocido '{target}'; añádelo a [paste_limits] en el archivo de configuración",
+        Message::PromptWrittenToFile => "Prompt escrito en el archivo: {path}",
+        Message::TokenInfo => "Número de tokens: {count}, Modelo: {model}",
+        Message::ChangesSinceLastOutput => "Cambios desde la última salida: {changes}",
+        Message::FilesDelta => "+{added}/-{removed} archivos",
+        Message::LinesDelta => "{delta} líneas",
+        Message::TokensDelta => "{delta} tokens",
+        Message::Summary => "Resumen: {details}",
+        Message::SummaryFiles => "{included} archivos incluidos, {skipped} omitidos",
+        Message::SummaryTokens => "{count} tokens",
+        Message::SummaryBudget => "{percent}% del presupuesto de {budget} tokens",
+        Message::SummaryDestinations => "enviado a {destinations}",
+        Message::DestinationClipboard => "portapapeles",
+        Message::DestinationStdout => "salida estándar",
+        Message::Timings => "Tiempos",
+        Message::TimingsStage => "Etapa",
+        Message::TimingsCalls => "Llamadas",
+        Message::TimingsTime => "Tiempo",
+        Message::TimingsTotal => "total",
+        Message::SuggestTotal => "El prompt tiene {tokens} tokens en {files} archivos.",
+        Message::SuggestOverBudget => "Supera en {excess} tokens el presupuesto de {budget} tokens.",
+        Message::SuggestWithinBudget => "Cabe en el presupuesto de {budget} tokens.",
+        Message::SuggestDirectories => "Directorios más grandes:",
+        Message::SuggestExtensions => "Tokens por extensión de archivo:",
+        Message::SuggestLargestFiles => "Archivos más grandes:",
+        Message::SuggestGenerated => "Archivos que parecen generados:",
+        Message::SuggestGroup => "{name}: {tokens} tokens en {files} archivos",
+        Message::SuggestOptions => "Opciones sugeridas:",
+        Message::SuggestRemaining => "Tokens estimados con las opciones sugeridas: {tokens}",
+        Message::SuggestNoChanges => "No se sugieren cambios.",
+        Message::SuggestStillOverBudget => "Sigue por encima del presupuesto; limita la selección con --include.",
+        Message::ListTokens => "Tokens",
+        Message::ListSize => "Tamaño",
+        Message::ListFile => "Archivo",
+        Message::ListTotal => "Total: {files} archivos, {size}, {tokens} tokens",
+        Message::StatsWriteFailed => "No se pudo escribir el archivo de estadísticas: {path}",
+        Message::TraversingDirectory => "Recorriendo el directorio y construyendo el árbol...",
+        Message::FilesReadBytes => "archivos, {size}",
+        Message::FilesRead => "Leídos {files} archivos, {size}, en {elapsed}",
+        Message::GeneratingGitDiff => "Generando el git diff...",
+        Message::GeneratingGitDiffBranches => "Generando el git diff entre dos ramas...",
+        Message::GeneratingGitLogBranches => "Generando el git log entre dos ramas...",
+        Message::GatheringReleaseChanges => "Reuniendo los cambios de {from} a {to}...",
+        Message::GatheringSuspectCommits => "Recopilando los commits de {good} a {bad}...",
+        Message::Done => "¡Listo!",
+        Message::ExpectedTwoBranches => "Indica exactamente dos ramas separadas por una coma.",
+        Message::BranchNotFound => "¡La rama {branch} no existe!",
+        Message::RevisionNotFound => "¡La revisión {revision} no existe!",
+        Message::CloneFailed => "No se pudo clonar el repositorio: {url}",
+        Message::ReferenceNotFound => "La rama o etiqueta {reference} no existe en el repositorio",
+        Message::InvalidGithubSpec => "Repositorio de GitHub no válido '{spec}', se esperaba owner/repo[@ref]",
+        Message::InvalidContentPattern => "Expresión regular no válida para --{option}: {error}",
+        Message::InvalidSelection => "Expresión de selección no válida '{expression}' en la columna {column}: {reason}",
+        Message::SelectionUnexpectedToken => "'{token}' inesperado",
+        Message::SelectionUnexpectedEnd => "final inesperado de la expresión",
+        Message::SelectionUnknownKey => "filtro desconocido '{key}', se esperaba path, lang, ext, contains o changed",
+        Message::SelectionInvalidValue => "valor no válido '{value}' para {key}",
+        Message::GitChangesUnavailable => "No se pudo leer el historial de git para los filtros changed:, ningún archivo cuenta como modificado: {error}",
+        Message::LineNumberDiffUnavailable => "No se pudo leer el git diff para --line-number-diff, ningún archivo se numera por él: {error}",
+        Message::SecretsRedacted => "Se ocultaron {count} secretos en {files} archivos; use --no-redact para conservarlos",
+        Message::SecretsRedactedOutsideFiles => "Se ocultaron {count} secretos en la salida de git; use --no-redact para conservarlos",
+        Message::TierSummarized => "Se resumieron los {files} archivos de {name} para ajustarse al presupuesto de tokens",
+        Message::TierDropped => "Se omitieron los {files} archivos de {name} para ajustarse al presupuesto de tokens",
+        Message::EstimatedOverBudget => "Se omitieron {count} archivos sin leerlos, ya que sus tokens estimados superan el presupuesto de {budget} tokens",
+        Message::InvalidTierName => "Nivel de prioridad no válido '{name}': los niveles se llaman tier1, tier2, etc.",
+        Message::SensitiveFileSkipped => "Se omitió el archivo sensible {path}; use --allow-sensitive para incluirlo",
+        Message::DownloadFailed => "No se pudo descargar {url}",
+        Message::ExtractFailed => "No se pudo extraer el archivo comprimido: {path}",
+        Message::NestedRepositoryExcluded => "Se omitió el repositorio git anidado {path}; usa --include-nested-repos para incluirlo",
+        Message::NestedRepositoryIncluded => "Se incluyó el repositorio git anidado {path}; sus reglas de exclusión pueden diferir de las del repositorio padre",
+        Message::FileTooLarge => "Se omitió {path}: {size} supera el tamaño máximo de archivo de {limit}",
+        Message::IncludeFileUnreadable => "No se puede incluir {path}: {error}",
+        Message::IncludeFileBinary => "No se puede incluir {path}: no es un archivo de texto",
+        Message::IncludeFileSensitive => "No se puede incluir el archivo sensible {path}; use --allow-sensitive para incluirlo",
+        Message::IncludeFileTooLarge => "No se puede incluir {path}: {size} supera el tamaño máximo de archivo de {limit}",
+        Message::IncludeFileOutsideRoots => "No se puede incluir {path}: está fuera de las rutas del código",
+        Message::IncludeFileOverBudget => "No se puede incluir {path}: los archivos incluidos ocupan {tokens} tokens, más que el presupuesto de {budget}",
+        Message::ExternalSymlinkSkipped => "Se omitió {path}: enlaza a {target} fuera del código; usa --include-external-symlinks para incluirlo",
+        Message::FileModified => "{path} cambió mientras se empaquetaba; se incluyó su contenido más reciente",
+        Message::FileVanished => "{path} se eliminó mientras se empaquetaba y se omitió del árbol y de los archivos",
+        Message::ConflictMarkersFound => "{path} contiene marcadores de conflicto de git",
+        Message::MergeInProgress => "Hay un {operation} en curso con {count} archivos en conflicto; ejecute codexio resolve para empaquetarlos con ambos lados de cada conflicto",
+        Message::NoConflicts => "Ningún archivo tiene conflictos de fusión en el repositorio de {path}",
+        Message::SystemSectionAll => "--system-section no puede ser all, que no deja nada para el mensaje del usuario",
+        Message::ConflictsUnavailable => "No se pudieron leer los conflictos de fusión para --conflicts: {error}",
+        Message::SparsePathSkipped => "Se omitió {path}, que está fuera del sparse checkout",
+        Message::SparseReport => "No se incluyeron {count} archivos fuera del sparse checkout o marcados como skip-worktree",
+        Message::GitDiffFailed => "No se pudo generar el git diff: {error}",
+        Message::GitDiffBranchesFailed => "No se pudo generar el git diff entre ramas: {error}",
+        Message::GitLogBranchesFailed => "No se pudo obtener el git log entre ramas: {error}",
+        Message::TemplateReadFailed => "No se pudo leer el archivo de plantilla personalizada",
+        Message::TemplateRegisterFailed => "No se pudo registrar la plantilla: {error}",
+        Message::TemplateRenderFailed => "No se pudo renderizar la plantilla: {error}",
+        Message::TemplateErrorLocation => "en {location}",
+        Message::TemplateErrorVariable => "variable causante: {name}",
+        Message::TemplateErrorHelper => "helper causante: {name}",
+        Message::TemplateErrorPartial => "parcial causante: {name}",
+        Message::EnterValueFor => "Introduce un valor para '{name}': ",
+        Message::FillTemplateVariable => "Rellena la variable definida por el usuario en la plantilla",
+        Message::FillFormList => "Introduzca un elemento por línea y una línea vacía para terminar.",
+        Message::FormFieldRequired => "Este campo es obligatorio.",
+        Message::FormInvalid => "Formulario no válido en la plantilla: {error}",
+        Message::AnswerMissing => "El archivo de respuestas no tiene valor para '{name}'",
+        Message::VariableUnanswered => "No hay valor para '{name}', que --quiet no pregunta; indique --var {name}=VALOR o --answers",
+        Message::AnswersReadFailed => "No se pudo leer el archivo de respuestas: {path}",
+        Message::VarInvalid => "--var '{value}' no válido, se esperaba NOMBRE=VALOR",
+        Message::PatternVariableMissing => "'{pattern}' usa la variable '{name}', que no tiene valor; indique --var {name}=VALOR",
+        Message::NoPreviousInvocation => "No hay ninguna ejecución anterior registrada para este directorio",
+        Message::StatsNoRuns => "Todavía no hay prompts registrados para {path}",
+        Message::StatsOverview => "{runs} prompts generados del {first} al {last}.",
+        Message::StatsTokens => "Último {latest} tokens, media {average}, máximo {peak}.",
+        Message::StatsGrowth => "La media diaria cambió un {percent}% desde el {day}.",
+        Message::StatsTrend => "Tendencia de tokens",
+        Message::StatsTopFiles => "Archivos incluidos más a menudo",
+        Message::StatsDay => "Día",
+        Message::StatsRuns => "Prompts",
+        Message::StatsAverageTokens => "Media",
+        Message::StatsHtmlWritten => "Panel escrito en {path}",
+        Message::StatsHtmlWriteFailed => "No se pudo escribir el panel: {path}",
+        Message::StatsServing => "Sirviendo el panel en http://{address}, pulsa Ctrl+C para parar",
+        Message::StatsServeFailed => "No se pudo servir el panel en {address}",
+        Message::ProfileNotFound => "El perfil '{name}' no está definido en los archivos de configuración",
+        Message::SelectionNotFound => "La selección '{name}' no está guardada en los archivos de configuración",
+        Message::ConfigWriteFailed => "No se pudo actualizar el archivo de configuración {path}",
+        Message::SelectionSaved => "Selección '{name}' guardada en {path}; reutilícela con --selection {name}",
+        Message::BatchReadFailed => "No se pudo leer el archivo de lotes {path}",
+        Message::WorkspaceFileReadFailed => "No se pudo leer el archivo de espacio de trabajo {path}",
+        Message::WorkspaceFileEmpty => "El archivo de espacio de trabajo {path} no enumera ninguna raíz",
+        Message::BatchLayersUnsupported => "--selection, --profile y --preset no se pueden usar con batch; establézcalos por trabajo en el archivo de lotes",
+        Message::BatchJobWithoutOutput => "El trabajo {job} no tiene archivo de salida",
+        Message::BatchJobStarted => "Trabajo {index}/{total}: {job}",
+        Message::BatchJobFailed => "El trabajo {job} falló: {error}",
+        Message::BatchFailed => "Fallaron {failed} de {total} trabajos",
+        Message::ExplainIncluded => "{path} se incluye",
+        Message::ExplainExcluded => "{path} se omite",
+        Message::ExplainMissing => "La ruta no existe",
+        Message::ExplainOutsideRoots => "Está fuera de las rutas del código",
+        Message::ExplainDirectory => "Es un directorio; solo se incluyen los archivos que contiene",
+        Message::ExplainOutputFile => "Es un archivo de salida de esta ejecución",
+        Message::ExplainSkipWorktree => "git no lo extrajo",
+        Message::ExplainGitDirectory => "Está dentro de un directorio .git",
+        Message::ExplainDefaultExcludedDirectory => "Está dentro de {directory}, un directorio excluido por defecto; use --no-default-excludes para incluirlo",
+        Message::ExplainHidden => "{name} está oculto; use --hidden para incluirlo",
+        Message::ExplainNestedRepo => "Está dentro de {directory}, un repositorio git anidado; use --include-nested-repos para incluirlo",
+        Message::ExplainMaxDepth => "Está a {depth} niveles de profundidad, por debajo de --max-depth {max_depth}",
+        Message::ExplainIgnored => "Ignorado por `{pattern}` en {file}; use --no-ignore para incluirlo",
+        Message::ExplainWhitelisted => "Conservado por `{pattern}` en {file}",
+        Message::ExplainNotIgnored => "Ningún archivo de exclusión coincide",
+        Message::ExplainIgnoreDisabled => "Los archivos de exclusión están desactivados por --no-ignore",
+        Message::ExplainIncludeMatched => "El patrón de inclusión `{pattern}` coincide",
+        Message::ExplainIncludeNegated => "El patrón de inclusión negado `{pattern}` coincide",
+        Message::ExplainIncludeMissed => "Ningún patrón de inclusión coincide",
+        Message::ExplainExcludeMatched => "El patrón de exclusión `{pattern}` coincide",
+        Message::ExplainExcludeNegated => "El patrón de exclusión negado `{pattern}` lo conserva",
+        Message::ExplainIncludePriority => "El patrón de exclusión `{pattern}` también coincide, y --include-priority da prioridad al patrón de inclusión",
+        Message::ExplainExcludePriority => "El patrón de exclusión `{pattern}` también coincide, y gana sin --include-priority",
+        Message::ExplainLockFile => "Es un archivo de bloqueo, omitido por defecto; use --no-default-excludes o inclúyalo explícitamente",
+        Message::ExplainLinguist => "Está marcado como {attribute} en .gitattributes; use --no-gitattributes para incluirlo",
+        Message::ExplainGenerated => "Parece generado o minificado; use --include-generated para incluirlo",
+        Message::ExplainSensitive => "Puede contener credenciales; use --allow-sensitive para incluirlo",
+        Message::ExplainExternalSymlink => "Enlaza a {target}, fuera de su raíz; use --include-external-symlinks para incluirlo",
+        Message::ExplainTooLarge => "{size} supera el tamaño máximo de archivo de {limit}",
+        Message::ExplainBinary => "Es un archivo binario, incluido como marcador",
+        Message::ExplainUndetectedEncoding => "No se puede detectar su codificación; use --lossy para incluirlo",
+        Message::ExplainEmpty => "Está vacío",
+        Message::ExplainContentMissing => "Su contenido no coincide con --include-containing",
+        Message::ExplainContentExcluded => "Su contenido coincide con --exclude-containing",
+        Message::ExplainLanguage => "Su lenguaje, {language}, no está seleccionado por --lang",
+        Message::ExplainNoLanguage => "No se detecta su lenguaje, y se indicó --lang",
+        Message::ExplainNotSelected => "No está seleccionado por --select",
+        Message::PatternFileReadFailed => "No se pudo leer el archivo de patrones: {path}",
+        Message::ConfigLoadFailed => "No se pudo cargar el archivo de configuración",
+        Message::UnknownColor => "Color desconocido en el tema: {color}",
+        Message::ApplyReadFailed => "No se pudo leer {path}",
+        Message::ApplyNoPatches => "La respuesta no contiene ningún diff unificado",
+        Message::ApplyInvalidHunk => "Cabecera de fragmento no válida para {path}: {header}",
+        Message::ApplyAnchorMissing => "El fragmento de {path} en la línea {line} no indica ningún ancla",
+        Message::ApplyAnchorMismatch => "El ancla {anchor} no coincide con {path}, que cambió desde el prompt o se citó mal",
+        Message::ApplyContextMismatch => "El fragmento de {path} en la línea {line} no coincide con el archivo",
+        Message::ApplyHunksOverlap => "El fragmento de {path} en la línea {line} se solapa con el anterior",
+        Message::ApplyFileNotFound => "El archivo a modificar no existe: {path}",
+        Message::ApplyFileExists => "El archivo a crear ya existe: {path}",
+        Message::ApplyUnsafePath => "La ruta a modificar está fuera del código: {path}",
+        Message::ApplyWriteFailed => "No se pudo escribir {path}",
+        Message::ApplyChecked => "{hunks} fragmentos en {files} archivos coinciden con sus anclas",
+        Message::ApplyApplied => "Se aplicaron {hunks} fragmentos a {files} archivos",
+        Message::DoctorFix => "Solución: {fix}",
+        Message::DoctorSummary => "{passed} correctas, {warned} avisos, {failed} fallidas",
+        Message::DoctorGitRepository => "Repositorio en {path}, en {branch}",
+        Message::DoctorGitNotRepository => "{path} no está en un repositorio git",
+        Message::DoctorGitNotRepositoryFix => "Ejecute codexio dentro de un repositorio git para usar --diff, --git-diff-branch, --git-log-branch y los subcomandos de git",
+        Message::DoctorGitBroken => "No se puede abrir el repositorio git: {error}",
+        Message::DoctorGitBrokenFix => "Ejecute git status en el código para ver qué le pasa al repositorio",
+        Message::DoctorClipboardReady => "Se copia con {backend}",
+        Message::DoctorClipboardDisabled => "La copia está desactivada",
+        Message::DoctorClipboardFix => "Elija otro backend con --clipboard, como osc52 por SSH o wl-copy en Wayland, o indique --no-clipboard con --output",
+        Message::DoctorTerminalReady => "Terminal interactiva ({term})",
+        Message::DoctorTerminalNotInteractive => "No es una terminal interactiva; no se pueden pedir las variables de la plantilla",
+        Message::DoctorTerminalNotInteractiveFix => "Indique las variables de la plantilla con --var o --answers",
+        Message::DoctorTerminalDumb => "TERM es dumb o no está definido; los colores y el indicador pueden verse como códigos de escape",
+        Message::DoctorTerminalDumbFix => "Defina NO_COLOR e indique --ascii",
+        Message::DoctorConfigLoaded => "Cargado {paths}",
+        Message::DoctorConfigDefaults => "Ningún archivo de configuración, se usan los valores por defecto",
+        Message::DoctorConfigFix => "Valide .codexio.toml con el esquema que imprime codexio config schema",
+        Message::DoctorTemplateBuiltin => "Plantilla integrada {name}",
+        Message::DoctorTemplateValid => "{path} compila",
+        Message::DoctorTemplateInvalid => "{path}: {error}",
+        Message::DoctorTemplateFix => "Compruebe la ruta de la plantilla, relativa al directorio de trabajo, y previsualícela con codexio template preview {path}",
+        Message::DoctorCacheHealthy => "{path} admite escritura",
+        Message::DoctorCacheFix => "Haga que {path} admita escritura, o bórrelo; solo se pierde el historial de codexio again y codexio stats",
+        Message::SplitTokensWithoutOutput => "--split-tokens necesita --output para nombrar las partes, por ejemplo --output prompt.md",
+        Message::SplitTokensUnsupported => "--split-tokens solo divide el prompt Markdown completo, no otros formatos ni secciones",
+        Message::SplitTokensTooSmall => "El prompt ocupa {tokens} tokens sin sus archivos, más que los {cap} tokens de una parte; aumente --split-tokens",
+        Message::GitOpenFailed => "No se pudo abrir el repositorio git en {path}",
+        Message::GitHeadFailed => "No se pudo leer el HEAD del repositorio",
+        Message::GitNoWorkdir => "El repositorio no tiene directorio de trabajo",
+        Message::GitWorkdirFailed => "No se pudo resolver el directorio de trabajo del repositorio",
+        Message::GitDiffGenerateFailed => "No se pudo generar el diff de git",
+        Message::GitDiffPrintFailed => "No se pudo imprimir el diff de git",
+        Message::GitHistoryFailed => "No se pudo recorrer el historial de git",
+        Message::GitIndexFailed => "No se pudo leer el índice de git",
+        Message::GitStatusFailed => "No se pudo leer el estado del repositorio",
+        Message::GitCloneIntoFailed => "No se pudo clonar el repositorio en {path}",
+        Message::GitInitFailed => "No se pudo inicializar un repositorio git en {path}",
+        Message::GitRemoteFailed => "No se pudo añadir el remoto {url}",
+        Message::GitFetchFailed => "No se pudo obtener {reference} del remoto",
+        Message::GitCheckoutFailed => "No se pudo extraer {reference}",
+        Message::ArchiveOpenFailed => "No se pudo abrir el archivo comprimido {path}",
+        Message::ArchiveUnpackFailed => "No se pudo desempaquetar el archivo comprimido en {path}",
+        Message::ArchiveReadFailed => "No se pudieron leer los archivos extraídos en {path}",
+        Message::ArchiveMoveFailed => "No se pudieron mover los archivos extraídos a {path}",
+        Message::CacheDirUnknown => "No se pudo determinar el directorio de caché",
+        Message::CacheDirCreateFailed => "No se pudo crear el directorio de caché {path}",
+        Message::CacheWriteFailed => "No se pudo escribir en el directorio de caché {path}",
+        Message::HistoryReadFailed => "No se pudo leer el archivo de historial {path}",
+        Message::HistoryParseFailed => "No se pudo analizar el archivo de historial {path}",
+        Message::HistoryWriteFailed => "No se pudo escribir el archivo de historial {path}",
+        Message::PartialRegisterFailed => "No se pudo registrar el parcial {name}: {error}",
+        Message::SubscriberInstallFailed => "No se pudo instalar el suscriptor de trazas: {error}",
+        Message::CanonicalizeFailed => "No se pudo resolver la ruta {path}",
+        Message::RemoveFailed => "No se pudo eliminar {path}",
+        Message::WorkspaceCreateFailed => "No se pudo crear el espacio de trabajo temporal",
+        Message::WorkspaceDirCreateFailed => "No se pudo crear el directorio {path} en el espacio de trabajo",
+        Message::TempFileCreateFailed => "No se pudo crear un archivo temporal en {path}",
+        Message::FileWriteFailed => "No se pudo escribir el archivo: {path}",
+        Message::LockOpenFailed => "No se pudo abrir el archivo de bloqueo: {path}",
+        Message::LockFailed => "No se pudo bloquear {path}",
+    })
+}
+
+/// The German catalog.
+fn german(id: Message) -> Option<&'static str> {
+    Some(match id {
+        Message::CopiedToClipboard => "Erfolgreich in die Zwischenablage kopiert.",
+        Message::ClipboardInitFailed => "Zwischenablage konnte nicht initialisiert werden: {error}",
+        Message::ClipboardCopyFailed => "Kopieren in die Zwischenablage fehlgeschlagen: {error}",
+        Message::ClipboardCommandFailed => "Kopieren mit {command} fehlgeschlagen: {error}",
+        Message::ClipboardCommandMissing => "{command} ist nicht installiert oder nicht im PATH",
+        Message::ClipboardNoTerminal => "Kein Terminal, an das die OSC-52-Sequenz gesendet werden kann",
+        Message::PasteCharactersExceeded => "{count} Zeichen kopiert, mehr als das Einfügelimit von {limit} Zeichen für {target}; der eingefügte Text wird möglicherweise abgeschnitten.",
+        Message::PasteBytesExceeded => "{count} Bytes kopiert, mehr als das Einfügelimit von {limit} Bytes für {target}; der eingefügte Text wird möglicherweise abgeschnitten.",
+        Message::UnknownPasteTarget => "Unbekanntes Einfügeziel '{target}'; in [paste_limits] der Konfigurationsdatei ergänzen",
+        Message::PromptWrittenToFile => "Prompt in Datei geschrieben: {path}",
+        Message::TokenInfo => "Anzahl Tokens: {count}, Modell: {model}",
+        Message::ChangesSinceLastOutput => "Änderungen seit der letzten Ausgabe: {changes}",
+        Message::FilesDelta => "+{added}/-{removed} Dateien",
+        Message::LinesDelta => "{delta} Zeilen",
+        Message::TokensDelta => "{delta} Tokens",
+        Message::Summary => "Zusammenfassung: {details}",
+        Message::SummaryFiles => "{included} Dateien eingeschlossen, {skipped} übersprungen",
+        Message::SummaryTokens => "{count} Tokens",
+        Message::SummaryBudget => "{percent}% des Budgets von {budget} Tokens",
+        Message::SummaryDestinations => "gesendet an {destinations}",
+        Message::DestinationClipboard => "Zwischenablage",
+        Message::DestinationStdout => "Standardausgabe",
+        Message::Timings => "Zeiten",
+        Message::TimingsStage => "Phase",
+        Message::TimingsCalls => "Aufrufe",
+        Message::TimingsTime => "Zeit",
+        Message::TimingsTotal => "gesamt",
+        Message::SuggestTotal => "Der Prompt hat {tokens} Tokens in {files} Dateien.",
+        Message::SuggestOverBudget => "Er liegt {excess} Tokens über dem Budget von {budget} Tokens.",
+        Message::SuggestWithinBudget => "Er passt in das Budget von {budget} Tokens.",
+        Message::SuggestDirectories => "Größte Verzeichnisse:",
+        Message::SuggestExtensions => "Tokens nach Dateiendung:",
+        Message::SuggestLargestFiles => "Größte Dateien:",
+        Message::SuggestGenerated => "Dateien, die generiert aussehen:",
+        Message::SuggestGroup => "{name}: {tokens} Tokens in {files} Dateien",
+        Message::SuggestOptions => "Vorgeschlagene Optionen:",
+        Message::SuggestRemaining => "Geschätzte Tokens mit den vorgeschlagenen Optionen: {tokens}",
+        Message::SuggestNoChanges => "Keine Änderungen vorgeschlagen.",
+        Message::SuggestStillOverBudget => "Immer noch über dem Budget; schränke die Auswahl mit --include ein.",
+        Message::ListTokens => "Tokens",
+        Message::ListSize => "Größe",
+        Message::ListFile => "Datei",
+        Message::ListTotal => "Gesamt: {files} Dateien, {size}, {tokens} Tokens",
+        Message::StatsWriteFailed => "Statistikdatei konnte nicht geschrieben werden: {path}",
+        Message::TraversingDirectory => "Verzeichnis wird durchlaufen und Baum wird erstellt...",
+        Message::FilesReadBytes => "Dateien, {size}",
+        Message::FilesRead => "{files} Dateien gelesen, {size}, in {elapsed}",
+        Message::GeneratingGitDiff => "Git-Diff wird erzeugt...",
+        Message::GeneratingGitDiffBranches => "Git-Diff zwischen zwei Branches wird erzeugt...",
+        Message::GeneratingGitLogBranches => "Git-Log zwischen zwei Branches wird erzeugt...",
+        Message::GatheringReleaseChanges => "Änderungen von {from} bis {to} werden gesammelt...",
+        Message::GatheringSuspectCommits => "Sammle die Commits von {good} bis {bad}...",
+        Message::Done => "Fertig!",
+        Message::ExpectedTwoBranches => "Bitte genau zwei durch ein Komma getrennte Branches angeben.",
+        Message::BranchNotFound => "Branch {branch} existiert nicht!",
+        Message::RevisionNotFound => "Revision {revision} existiert nicht!",
+        Message::CloneFailed => "Repository konnte nicht geklont werden: {url}",
+        Message::ReferenceNotFound => "Branch oder Tag {reference} existiert im Repository nicht",
+        Message::InvalidGithubSpec => "Ungültiges GitHub-Repository '{spec}', erwartet wird owner/repo[@ref]",
+        Message::InvalidContentPattern => "Ungültiger regulärer Ausdruck für --{option}: {error}",
+        Message::InvalidSelection => "Ungültiger Auswahlausdruck '{expression}' in Spalte {column}: {reason}",
+        Message::SelectionUnexpectedToken => "unerwartetes '{token}'",
+        Message::SelectionUnexpectedEnd => "unerwartetes Ende des Ausdrucks",
+        Message::SelectionUnknownKey => "unbekannter Filter '{key}', erwartet wird path, lang, ext, contains oder changed",
+        Message::SelectionInvalidValue => "ungültiger Wert '{value}' für {key}",
+        Message::GitChangesUnavailable => "Der Git-Verlauf für changed:-Filter konnte nicht gelesen werden, keine Datei gilt als geändert: {error}",
+        Message::LineNumberDiffUnavailable => "Der Git-Diff für --line-number-diff konnte nicht gelesen werden, keine Datei wird dafür nummeriert: {error}",
+        Message::SecretsRedacted => "{count} Geheimnisse in {files} Dateien geschwärzt; --no-redact behält sie bei",
+        Message::SecretsRedactedOutsideFiles => "{count} Geheimnisse in der Git-Ausgabe geschwärzt; --no-redact behält sie bei",
+        Message::TierSummarized => "Die {files} Dateien von {name} wurden zusammengefasst, um in das Token-Budget zu passen",
+        Message::TierDropped => "Die {files} Dateien von {name} wurden weggelassen, um in das Token-Budget zu passen",
+        Message::EstimatedOverBudget => "{count} Dateien wurden ungelesen weggelassen, da ihre geschätzten Tokens das Budget von {budget} Tokens überschreiten",
+        Message::InvalidTierName => "Ungültige Prioritätsstufe '{name}': Stufen heißen tier1, tier2 usw.",
+        Message::SensitiveFileSkipped => "Sensible Datei {path} übersprungen; --allow-sensitive packt sie trotzdem",
+        Message::DownloadFailed => "{url} konnte nicht heruntergeladen werden",
+        Message::ExtractFailed => "Archiv konnte nicht entpackt werden: {path}",
+        Message::NestedRepositoryExcluded => "Verschachteltes Git-Repository {path} übersprungen; --include-nested-repos packt es mit ein",
+        Message::NestedRepositoryIncluded => "Verschachteltes Git-Repository {path} gepackt; seine Ignore-Regeln können vom übergeordneten Repository abweichen",
+        Message::FileTooLarge => "{path} übersprungen: {size} überschreitet die maximale Dateigröße von {limit}",
+        Message::IncludeFileUnreadable => "{path} kann nicht eingebunden werden: {error}",
+        Message::IncludeFileBinary => "{path} kann nicht eingebunden werden: keine Textdatei",
+        Message::IncludeFileSensitive => "Sensible Datei {path} kann nicht eingebunden werden; --allow-sensitive bindet sie trotzdem ein",
+        Message::IncludeFileTooLarge => "{path} kann nicht eingebunden werden: {size} überschreitet die maximale Dateigröße von {limit}",
+        Message::IncludeFileOutsideRoots => "{path} kann nicht eingebunden werden: die Datei liegt außerhalb der Codebasis-Pfade",
+        Message::IncludeFileOverBudget => "{path} kann nicht eingebunden werden: die eingebundenen Dateien umfassen {tokens} Tokens, mehr als das Budget von {budget}",
+        Message::ExternalSymlinkSkipped => "{path} übersprungen: verweist auf {target} außerhalb der Codebasis; --include-external-symlinks packt es mit ein",
+        Message::FileModified => "{path} hat sich beim Packen geändert; sein neuester Inhalt wurde gepackt",
+        Message::FileVanished => "{path} wurde beim Packen gelöscht und aus dem Baum und den Dateien entfernt",
+        Message::ConflictMarkersFound => "{path} enthält Git-Konfliktmarkierungen",
+        Message::MergeInProgress => "Ein {operation} läuft mit {count} Dateien in Konflikt; codexio resolve packt sie mit beiden Seiten jedes Konflikts",
+        Message::NoConflicts => "Keine Datei hat Merge-Konflikte im Repository von {path}",
+        Message::SystemSectionAll => "--system-section kann nicht all sein, da dann nichts für die Benutzernachricht bleibt",
+        Message::ConflictsUnavailable => "Die Merge-Konflikte für --conflicts konnten nicht gelesen werden: {error}",
+        Message::SparsePathSkipped => "{path} übersprungen, da es außerhalb des Sparse-Checkouts liegt",
+        Message::SparseReport => "{count} Dateien außerhalb des Sparse-Checkouts oder mit skip-worktree wurden nicht gepackt",
+        Message::GitDiffFailed => "Git-Diff konnte nicht erzeugt werden: {error}",
+        Message::GitDiffBranchesFailed => "Git-Diff zwischen Branches konnte nicht erzeugt werden: {error}",
+        Message::GitLogBranchesFailed => "Git-Log zwischen Branches konnte nicht abgerufen werden: {error}",
+        Message::TemplateReadFailed => "Benutzerdefinierte Vorlagendatei konnte nicht gelesen werden",
+        Message::TemplateRegisterFailed => "Vorlage konnte nicht registriert werden: {error}",
+        Message::TemplateRenderFailed => "Vorlage konnte nicht gerendert werden: {error}",
+        Message::TemplateErrorLocation => "bei {location}",
+        Message::TemplateErrorVariable => "verursachende Variable: {name}",
+        Message::TemplateErrorHelper => "verursachender Helper: {name}",
+        Message::TemplateErrorPartial => "verursachendes Partial: {name}",
+        Message::EnterValueFor => "Wert für '{name}' eingeben: ",
+        Message::FillTemplateVariable => "Benutzerdefinierte Variable der Vorlage ausfüllen",
+        Message::FillFormList => "Ein Eintrag pro Zeile, eine leere Zeile zum Beenden.",
+        Message::FormFieldRequired => "Dieses Feld ist erforderlich.",
+        Message::FormInvalid => "Ungültiges Formular in der Vorlage: {error}",
+        Message::AnswerMissing => "Die Antwortdatei enthält keinen Wert für '{name}'",
+        Message::VariableUnanswered => "Kein Wert für '{name}', den --quiet nicht abfragt; geben Sie --var {name}=WERT oder --answers an",
+        Message::AnswersReadFailed => "Antwortdatei konnte nicht gelesen werden: {path}",
+        Message::VarInvalid => "Ungültiges --var '{value}', erwartet wird NAME=WERT",
+        Message::PatternVariableMissing => "'{pattern}' verwendet die Variable '{name}', die keinen Wert hat; geben Sie --var {name}=WERT an",
+        Message::NoPreviousInvocation => "Für dieses Verzeichnis wurde kein vorheriger Aufruf gespeichert",
+        Message::StatsNoRuns => "Für {path} wurden noch keine Prompts gespeichert",
+        Message::StatsOverview => "{runs} Prompts erzeugt vom {first} bis {last}.",
+        Message::StatsTokens => "Zuletzt {latest} Tokens, im Schnitt {average}, höchstens {peak}.",
+        Message::StatsGrowth => "Der Tagesdurchschnitt hat sich seit dem {day} um {percent}% verändert.",
+        Message::StatsTrend => "Token-Verlauf",
+        Message::StatsTopFiles => "Am häufigsten eingeschlossene Dateien",
+        Message::StatsDay => "Tag",
+        Message::StatsRuns => "Prompts",
+        Message::StatsAverageTokens => "Ø Tokens",
+        Message::StatsHtmlWritten => "Übersicht nach {path} geschrieben",
+        Message::StatsHtmlWriteFailed => "Übersicht konnte nicht geschrieben werden: {path}",
+        Message::StatsServing => "Übersicht wird unter http://{address} bereitgestellt, mit Strg+C beenden",
+        Message::StatsServeFailed => "Übersicht konnte nicht unter {address} bereitgestellt werden",
+        Message::ProfileNotFound => "Profil '{name}' ist in den Konfigurationsdateien nicht definiert",
+        Message::SelectionNotFound => "Auswahl '{name}' ist in den Konfigurationsdateien nicht gespeichert",
+        Message::ConfigWriteFailed => "Konfigurationsdatei {path} konnte nicht aktualisiert werden",
+        Message::SelectionSaved => "Auswahl '{name}' in {path} gespeichert; mit --selection {name} wiederverwenden",
+        Message::BatchReadFailed => "Batch-Datei {path} konnte nicht gelesen werden",
+        Message::WorkspaceFileReadFailed => "Workspace-Datei {path} konnte nicht gelesen werden",
+        Message::WorkspaceFileEmpty => "Workspace-Datei {path} enthält keine Wurzelverzeichnisse",
+        Message::BatchLayersUnsupported => "--selection, --profile und --preset können nicht mit batch verwendet werden; setzen Sie sie pro Job in der Batch-Datei",
+        Message::BatchJobWithoutOutput => "Job {job} hat keine Ausgabedatei",
+        Message::BatchJobStarted => "Job {index}/{total}: {job}",
+        Message::BatchJobFailed => "Job {job} fehlgeschlagen: {error}",
+        Message::BatchFailed => "{failed} von {total} Jobs fehlgeschlagen",
+        Message::ExplainIncluded => "{path} wird gepackt",
+        Message::ExplainExcluded => "{path} wird ausgelassen",
+        Message::ExplainMissing => "Der Pfad existiert nicht",
+        Message::ExplainOutsideRoots => "Er liegt außerhalb der Codebasis-Pfade",
+        Message::ExplainDirectory => "Es ist ein Verzeichnis; nur die Dateien darin werden gepackt",
+        Message::ExplainOutputFile => "Es ist eine Ausgabedatei dieses Laufs",
+        Message::ExplainSkipWorktree => "git hat es nicht ausgecheckt",
+        Message::ExplainGitDirectory => "Es liegt in einem .git-Verzeichnis",
+        Message::ExplainDefaultExcludedDirectory => "Es liegt in {directory}, einem standardmäßig ausgeschlossenen Verzeichnis; --no-default-excludes packt es",
+        Message::ExplainHidden => "{name} ist versteckt; --hidden packt es",
+        Message::ExplainNestedRepo => "Es liegt in {directory}, einem verschachtelten git-Repository; --include-nested-repos packt es",
+        Message::ExplainMaxDepth => "Es liegt {depth} Ebenen tief, unterhalb von --max-depth {max_depth}",
+        Message::ExplainIgnored => "Ignoriert durch `{pattern}` in {file}; --no-ignore packt es",
+        Message::ExplainWhitelisted => "Beibehalten durch `{pattern}` in {file}",
+        Message::ExplainNotIgnored => "Keine Ignore-Datei passt darauf",
+        Message::ExplainIgnoreDisabled => "Ignore-Dateien sind durch --no-ignore deaktiviert",
+        Message::ExplainIncludeMatched => "Das Einschlussmuster `{pattern}` passt darauf",
+        Message::ExplainIncludeNegated => "Das negierte Einschlussmuster `{pattern}` passt darauf",
+        Message::ExplainIncludeMissed => "Kein Einschlussmuster passt darauf",
+        Message::ExplainExcludeMatched => "Das Ausschlussmuster `{pattern}` passt darauf",
+        Message::ExplainExcludeNegated => "Das negierte Ausschlussmuster `{pattern}` behält es bei",
+        Message::ExplainIncludePriority => "Das Ausschlussmuster `{pattern}` passt ebenfalls, und --include-priority lässt das Einschlussmuster gewinnen",
+        Message::ExplainExcludePriority => "Das Ausschlussmuster `{pattern}` passt ebenfalls und gewinnt ohne --include-priority",
+        Message::ExplainLockFile => "Es ist eine Lock-Datei, standardmäßig übersprungen; --no-default-excludes oder ein ausdrückliches Einschlussmuster packt sie",
+        Message::ExplainLinguist => "Es ist in .gitattributes als {attribute} markiert; --no-gitattributes packt es",
+        Message::ExplainGenerated => "Es sieht generiert oder minifiziert aus; --include-generated packt es",
+        Message::ExplainSensitive => "Es kann Zugangsdaten enthalten; --allow-sensitive packt es",
+        Message::ExplainExternalSymlink => "Es verweist auf {target} außerhalb seiner Wurzel; --include-external-symlinks packt es",
+        Message::ExplainTooLarge => "{size} überschreitet die maximale Dateigröße von {limit}",
+        Message::ExplainBinary => "Es ist eine Binärdatei und wird als Platzhalter gepackt",
+        Message::ExplainUndetectedEncoding => "Seine Kodierung kann nicht erkannt werden; --lossy packt es",
+        Message::ExplainEmpty => "Es ist leer",
+        Message::ExplainContentMissing => "Sein Inhalt passt nicht zu --include-containing",
+        Message::ExplainContentExcluded => "Sein Inhalt passt zu --exclude-containing",
+        Message::ExplainLanguage => "Seine Sprache, {language}, ist nicht durch --lang ausgewählt",
+        Message::ExplainNoLanguage => "Seine Sprache wird nicht erkannt, und --lang ist angegeben",
+        Message::ExplainNotSelected => "Es ist nicht durch --select ausgewählt",
+        Message::PatternFileReadFailed => "Musterdatei konnte nicht gelesen werden: {path}",
+        Message::ConfigLoadFailed => "Konfigurationsdatei konnte nicht geladen werden",
+        Message::UnknownColor => "Unbekannte Farbe im Theme: {color}",
+        Message::ApplyReadFailed => "{path} konnte nicht gelesen werden",
+        Message::ApplyNoPatches => "Die Antwort enthält keinen Unified Diff",
+        Message::ApplyInvalidHunk => "Ungültiger Hunk-Kopf für {path}: {header}",
+        Message::ApplyAnchorMissing => "Der Hunk von {path} in Zeile {line} nennt keinen Anker",
+        Message::ApplyAnchorMismatch => "Der Anker {anchor} passt nicht zu {path}, das sich seit dem Prompt geändert hat oder falsch zitiert wurde",
+        Message::ApplyContextMismatch => "Der Hunk von {path} in Zeile {line} passt nicht zur Datei",
+        Message::ApplyHunksOverlap => "Der Hunk von {path} in Zeile {line} überschneidet sich mit dem vorherigen",
+        Message::ApplyFileNotFound => "Die zu ändernde Datei existiert nicht: {path}",
+        Message::ApplyFileExists => "Die anzulegende Datei existiert bereits: {path}",
+        Message::ApplyUnsafePath => "Der zu ändernde Pfad liegt außerhalb der Codebasis: {path}",
+        Message::ApplyWriteFailed => "{path} konnte nicht geschrieben werden",
+        Message::ApplyChecked => "{hunks} Hunks in {files} Dateien passen zu ihren Ankern",
+        Message::ApplyApplied => "{hunks} Hunks auf {files} Dateien angewendet",
+        Message::DoctorFix => "Abhilfe: {fix}",
+        Message::DoctorSummary => "{passed} bestanden, {warned} Warnungen, {failed} fehlgeschlagen",
+        Message::DoctorGitRepository => "Repository unter {path}, auf {branch}",
+        Message::DoctorGitNotRepository => "{path} liegt in keinem Git-Repository",
+        Message::DoctorGitNotRepositoryFix => "Führen Sie codexio in einem Git-Repository aus, um --diff, --git-diff-branch, --git-log-branch und die Git-Unterbefehle zu nutzen",
+        Message::DoctorGitBroken => "Das Git-Repository kann nicht geöffnet werden: {error}",
+        Message::DoctorGitBrokenFix => "Führen Sie git status in der Codebasis aus, um zu sehen, was mit dem Repository nicht stimmt",
+        Message::DoctorClipboardReady => "Kopiert wird mit {backend}",
+        Message::DoctorClipboardDisabled => "Kopieren ist ausgeschaltet",
+        Message::DoctorClipboardFix => "Wählen Sie mit --clipboard ein anderes Backend, etwa osc52 über SSH oder wl-copy unter Wayland, oder geben Sie --no-clipboard mit --output an",
+        Message::DoctorTerminalReady => "Interaktives Terminal ({term})",
+        Message::DoctorTerminalNotInteractive => "Kein interaktives Terminal; Vorlagenvariablen können nicht abgefragt werden",
+        Message::DoctorTerminalNotInteractiveFix => "Geben Sie die Vorlagenvariablen mit --var oder --answers an",
+        Message::DoctorTerminalDumb => "TERM ist dumb oder nicht gesetzt; Farben und der Spinner können als Escape-Codes erscheinen",
+        Message::DoctorTerminalDumbFix => "Setzen Sie NO_COLOR und geben Sie --ascii an",
+        Message::DoctorConfigLoaded => "Geladen: {paths}",
+        Message::DoctorConfigDefaults => "Keine Konfigurationsdatei, es gelten die Standardwerte",
+        Message::DoctorConfigFix => "Prüfen Sie .codexio.toml gegen das Schema, das codexio config schema ausgibt",
+        Message::DoctorTemplateBuiltin => "Eingebaute Vorlage {name}",
+        Message::DoctorTemplateValid => "{path} lässt sich kompilieren",
+        Message::DoctorTemplateInvalid => "{path}: {error}",
+        Message::DoctorTemplateFix => "Prüfen Sie den Vorlagenpfad relativ zum Arbeitsverzeichnis und sehen Sie sich die Vorlage mit codexio template preview {path} an",
+        Message::DoctorCacheHealthy => "{path} ist beschreibbar",
+        Message::DoctorCacheFix => "Machen Sie {path} beschreibbar oder löschen Sie es; nur der Verlauf von codexio again und codexio stats geht verloren",
+        Message::SplitTokensWithoutOutput => "--split-tokens benötigt --output, um die Teile zu benennen, zum Beispiel --output prompt.md",
+        Message::SplitTokensUnsupported => "--split-tokens teilt nur den vollständigen Markdown-Prompt, keine anderen Formate oder Abschnitte",
+        Message::SplitTokensTooSmall => "Der Prompt umfasst ohne seine Dateien {tokens} Tokens, mehr als die {cap} Tokens eines Teils; erhöhen Sie --split-tokens",
+        Message::GitOpenFailed => "Das Git-Repository unter {path} konnte nicht geöffnet werden",
+        Message::GitHeadFailed => "HEAD des Repositorys konnte nicht gelesen werden",
+        Message::GitNoWorkdir => "Das Repository hat kein Arbeitsverzeichnis",
+        Message::GitWorkdirFailed => "Das Arbeitsverzeichnis des Repositorys konnte nicht aufgelöst werden",
+        Message::GitDiffGenerateFailed => "Git-Diff konnte nicht erstellt werden",
+        Message::GitDiffPrintFailed => "Git-Diff konnte nicht ausgegeben werden",
+        Message::GitHistoryFailed => "Git-Verlauf konnte nicht durchlaufen werden",
+        Message::GitIndexFailed => "Git-Index konnte nicht gelesen werden",
+        Message::GitStatusFailed => "Status des Repositorys konnte nicht gelesen werden",
+        Message::GitCloneIntoFailed => "Repository konnte nicht nach {path} geklont werden",
+        Message::GitInitFailed => "Git-Repository in {path} konnte nicht initialisiert werden",
+        Message::GitRemoteFailed => "Remote {url} konnte nicht hinzugefügt werden",
+        Message::GitFetchFailed => "{reference} konnte nicht vom Remote abgerufen werden",
+        Message::GitCheckoutFailed => "{reference} konnte nicht ausgecheckt werden",
+        Message::ArchiveOpenFailed => "Archiv {path} konnte nicht geöffnet werden",
+        Message::ArchiveUnpackFailed => "Archiv konnte nicht nach {path} entpackt werden",
+        Message::ArchiveReadFailed => "Entpackte Dateien in {path} konnten nicht gelesen werden",
+        Message::ArchiveMoveFailed => "Entpackte Dateien konnten nicht nach {path} verschoben werden",
+        Message::CacheDirUnknown => "Cache-Verzeichnis konnte nicht ermittelt werden",
+        Message::CacheDirCreateFailed => "Cache-Verzeichnis {path} konnte nicht erstellt werden",
+        Message::CacheWriteFailed => "In das Cache-Verzeichnis {path} konnte nicht geschrieben werden",
+        Message::HistoryReadFailed => "Verlaufsdatei {path} konnte nicht gelesen werden",
+        Message::HistoryParseFailed => "Verlaufsdatei {path} konnte nicht geparst werden",
+        Message::HistoryWriteFailed => "Verlaufsdatei {path} konnte nicht geschrieben werden",
+        Message::PartialRegisterFailed => "Partial {name} konnte nicht registriert werden: {error}",
+        Message::SubscriberInstallFailed => "Tracing-Subscriber konnte nicht installiert werden: {error}",
+        Message::CanonicalizeFailed => "Pfad {path} konnte nicht aufgelöst werden",
+        Message::RemoveFailed => "{path} konnte nicht entfernt werden",
+        Message::WorkspaceCreateFailed => "Temporärer Arbeitsbereich konnte nicht erstellt werden",
+        Message::WorkspaceDirCreateFailed => "Verzeichnis {path} im Arbeitsbereich konnte nicht erstellt werden",
+        Message::TempFileCreateFailed => "Temporäre Datei in {path} konnte nicht erstellt werden",
+        Message::FileWriteFailed => "Datei konnte nicht geschrieben werden: {path}",
+        Message::LockOpenFailed => "Sperrdatei konnte nicht geöffnet werden: {path}",
+        Message::LockFailed => "{path} konnte nicht gesperrt werden",
+    })
+}
@@ -0,0 +1,628 @@
+//! This module defines the options of a run, shared by the command-line parser, the
+//! configuration file loader and library users.
+//!
+//! `Options` holds plain data with no command-line parsing attached, so it can be built
+//! programmatically with `Options::builder()`, deserialized, or mapped from the parsed
+//! command line.
+
+use crate::config::{
+    BisectRange, ClipboardBackend, ColorChoice, ColorsConfig, Compression, FileConfig, FileSize, HtmlTheme, LogFormat, OutputFormat, PasteLimit, PresetName, ReleaseRange, Section,
+    SortOrder, ThemeName, TreeStyle,
+};
+use crate::workspace_file::WorkspaceRoot;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// The `--output` value writing the prompt to stdout.
+pub const STDOUT_OUTPUT: &str = "-";
+
+/// The options of a run.
+///
+/// Unset options keep their default: the current directory is packed with every optional
+/// feature turned off.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Options {
+    /// Paths to the codebase directories or files.
+    pub path: Vec<PathBuf>,
+    /// URL of a remote git repository to clone and use instead of the paths.
+    pub remote: Option<String>,
+    /// GitHub repository to download as a snapshot, without requiring git.
+    pub github: Option<String>,
+    /// Branch or tag to check out when cloning remote repositories.
+    pub branch: Option<String>,
+    /// File listing the paths to pack, one per line, or `-` to read them from stdin.
+    pub files_from: Option<String>,
+    /// Workspace file listing several roots to pack together, each with its own patterns.
+    pub workspace_file: Option<PathBuf>,
+    /// The roots of the workspace file, with their own patterns.
+    pub workspace_roots: Vec<WorkspaceRoot>,
+    /// Patterns to include.
+    pub include: Option<String>,
+    /// Patterns to exclude.
+    pub exclude: Option<String>,
+    /// Files of patterns to include, one glob per line, with `#` starting a comment line.
+    pub include_from: Vec<PathBuf>,
+    /// Files of patterns to exclude, one glob per line, with `#` starting a comment line.
+    pub exclude_from: Vec<PathBuf>,
+    /// Include files in case of conflict between include and exclude patterns.
+    pub include_priority: bool,
+    /// Only pack files whose content matches this regular expression, such as `TODO|FIXME` or a function name.
+    pub include_containing: Option<String>,
+    /// Skip files whose content matches this regular expression, such as `DO NOT SUBMIT` or `PRIVATE KEY`, even if they match the include patterns.
+    pub exclude_containing: Option<String>,
+    /// Only pack files in these languages or with these extensions (comma-separated), such as `rust,python,makefile`.
+    pub lang: Option<String>,
+    /// Only pack files selected by this expression, such as `(lang:rust & changed:7d) | path:docs/**`.
+    pub select: Option<String>,
+    /// Pack files matched by any ignore file, including `.gitignore`, `.ignore` and `.codexioignore`.
+    pub no_ignore: bool,
+    /// Pack files ignored by git, while still honoring `.ignore` and `.codexioignore`.
+    pub no_gitignore: bool,
+    /// Pack files marked `linguist-generated` or `linguist-vendored` in `.gitattributes`, which are skipped by default.
+    pub no_gitattributes: bool,
+    /// Pack generated and minified files, such as `*.min.js`, `*.pb.go` and files marked `Code generated ... DO NOT EDIT`, which are skipped by default.
+    pub include_generated: bool,
+    /// Pack dependency, build output and cache directories such as `node_modules` and `target`, and lock files, which are skipped by default.
+    pub no_default_excludes: bool,
+    /// Pack `.env` files, private keys, keystores and cloud credentials, which are skipped even when an include pattern matches them.
+    pub allow_sensitive: bool,
+    /// Pack hidden files and directories, such as dotfiles. The `.git` directory is always skipped.
+    pub hidden: bool,
+    /// Follow symlinked directories during traversal, skipping symlink cycles.
+    pub follow_symlinks: bool,
+    /// Pack the targets of symlinked files pointing outside the root, under the path of the link.
+    pub include_external_symlinks: bool,
+    /// Only traverse this many levels below each root, in both the tree and the files.
+    pub max_depth: Option<usize>,
+    /// Skip files larger than this size, such as `200KB` or `1MB`, reporting them as warnings.
+    pub max_file_size: Option<FileSize>,
+    /// Pack files that are not valid UTF-8 by replacing their invalid bytes, instead of detecting their encoding.
+    pub lossy: bool,
+    /// Render the license headers and other leading comment blocks shared by several files once, as common headers referenced by the files.
+    pub dedupe_headers: bool,
+    /// Whether to replace the secrets found in files with placeholders, or `None` for the default, which redacts them.
+    pub redact: Option<bool>,
+    /// Pack git repositories nested inside the codebase, which are skipped by default.
+    pub include_nested_repos: bool,
+    /// Report how many files were left out because git did not check them out.
+    pub report_sparse: bool,
+    /// Exclude files/folders from the source tree based on exclude patterns.
+    pub exclude_from_tree: bool,
+    /// Show the size and token count of each file in the source tree, and their totals on directories.
+    pub annotate_tree: bool,
+    /// Render only the source tree, without the file contents.
+    pub tree_only: bool,
+    /// Leave the source tree out of the prompt, to save its tokens on large codebases.
+    pub no_tree: bool,
+    /// How to draw the source tree, defaulting to `ascii` with `--ascii` and to `unicode` otherwise.
+    pub tree_style: Option<TreeStyle>,
+    /// Display the token count of the generated prompt.
+    pub tokens: bool,
+    /// Tokenizer to use for token count.
+    pub encoding: Option<String>,
+    /// Output file path.
+    pub output: Option<String>,
+    /// Compression of the output file, whose path ends with the extension of the compression.
+    pub compress: Option<Compression>,
+    /// Token cap of each part the prompt is split into, written next to the output file.
+    pub split_tokens: Option<usize>,
+    /// Token budget the prompt is measured against in the summary line.
+    pub budget: Option<usize>,
+    /// Estimate the token count of each file from its size before reading it.
+    pub estimate: bool,
+    /// Write a `<output>.stats.json` file with the file list, per-file tokens and warnings next to the output file.
+    pub stats: bool,
+    /// Print how long each stage of the run took to stderr.
+    pub timings: bool,
+    /// Include git diff.
+    pub diff: bool,
+    /// Generate git diff between two branches.
+    pub git_diff_branch: Option<String>,
+    /// Retrieve git log between two branches.
+    pub git_log_branch: Option<String>,
+    /// Add line numbers to the source code.
+    pub line_number: bool,
+    /// Add line numbers only to the files matching these patterns (comma-separated), such as `**/src/**`.
+    pub line_number_for: Option<String>,
+    /// Add line numbers only to the files changed in the git diff of the prompt, between the `--git-diff-branch` branches if given, or staged otherwise.
+    pub line_number_diff: bool,
+    /// Only pack the files with merge conflicts, with the versions of each from the git index.
+    pub conflicts: bool,
+    /// Prefix each line of the files with a stable anchor, its number and a hash of its content, which `codexio apply` checks the hunks of a response against.
+    pub line_anchors: bool,
+    /// Disable wrapping code inside markdown code blocks.
+    pub no_codeblock: bool,
+    /// Format of the prompt: the Markdown of the template, XML documents, or JSON Lines with one object per file.
+    pub format: Option<OutputFormat>,
+    /// Section of the template rendered as the system message of the chat formats.
+    pub system_section: Option<Section>,
+    /// Model named in the payloads of the chat formats.
+    pub model: Option<String>,
+    /// Section of the template after which `--format anthropic` places its prompt caching breakpoint.
+    pub cache_breakpoint: Option<Section>,
+    /// Use relative paths instead of absolute paths, including the parent directory.
+    pub relative_paths: bool,
+    /// Order of the files in the prompt: `path`, `size`, `modified` or `tokens`, ascending, or descending with `-desc`, e.g. `tokens-desc`.
+    pub sort: Option<SortOrder>,
+    /// Disable copying to clipboard.
+    pub no_clipboard: bool,
+    /// Print only warnings and errors, without the spinner or status lines, and never ask for template variables.
+    pub quiet: bool,
+    /// When to color the status output, on terminals without `NO_COLOR` unless `always` or `never`.
+    pub color: Option<ColorChoice>,
+    /// Clipboard backend to copy the prompt with: `auto`, `arboard`, `osc52`, `wl-copy`, `xclip` or `none`.
+    pub clipboard: Option<ClipboardBackend>,
+    /// Chat interface the prompt will be pasted into, e.g. `chatgpt` or `claude-web`.
+    pub target: Option<String>,
+    /// Paste limits by target, overriding the built-in ones.
+    pub paste_limits: HashMap<String, PasteLimit>,
+    /// Patterns of priority tiers, by name from `tier1`; under a `budget`, lower tiers are summarized or dropped first.
+    pub tiers: HashMap<String, Vec<String>>,
+    /// Copy only the selected section of the prompt to the clipboard.
+    pub copy_section: Section,
+    /// Path to a custom Handlebars template.
+    pub template: Option<PathBuf>,
+    /// Optional path to a YAML or JSON file answering the variables and forms of the template, instead of asking for them.
+    pub answers: Option<PathBuf>,
+    /// Values of template variables, as `NAME=VALUE`, also replacing `{{NAME}}` in patterns and paths.
+    pub var: Vec<String>,
+    /// Color theme for status output and HTML pages, `default` unless set.
+    pub theme: Option<ThemeName>,
+    /// Color scheme of HTML pages, following the browser unless set.
+    pub html_theme: Option<HtmlTheme>,
+    /// Color overrides for status output, by role.
+    pub colors: Option<ColorsConfig>,
+    /// Use ASCII characters only in status output, the spinner and the source tree.
+    pub ascii: bool,
+    /// Print output as JSON.
+    pub json: bool,
+    /// Format of the diagnostic logs written to stderr.
+    pub log_format: LogFormat,
+    /// List the files that would be packed instead of rendering the prompt.
+    pub list: bool,
+    /// Paths to explain the inclusion of instead of rendering the prompt.
+    pub explain: Vec<PathBuf>,
+    /// The revisions to generate release notes between, set by the `release-notes` subcommand.
+    pub release: Option<ReleaseRange>,
+    /// The revisions to look for a regression between, set by the `bisect-context` subcommand.
+    pub bisect: Option<BisectRange>,
+    /// The built-in preset applied to the run, whose template and data are used unless a template is given.
+    pub preset: Option<PresetName>,
+    /// Language of status messages, e.g. `es` or `de`.
+    pub locale: Option<String>,
+    /// Instructions appended to the prompt.
+    pub instructions: Option<String>,
+    /// The section of the prompt to render.
+    pub section: Section,
+    /// Count the tokens of every file, even without `tokens` or `stats`.
+    pub count_file_tokens: bool,
+    /// The boolean options already set by a configuration layer, which later layers keep.
+    #[serde(skip)]
+    pub settled_flags: HashSet<&'static str>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            path: vec![PathBuf::from(".")],
+            remote: None,
+            github: None,
+            branch: None,
+            files_from: None,
+            workspace_file: None,
+            workspace_roots: Vec::new(),
+            include: None,
+            exclude: None,
+            include_from: Vec::new(),
+            exclude_from: Vec::new(),
+            include_priority: false,
+            include_containing: None,
+            exclude_containing: None,
+            lang: None,
+            select: None,
+            no_ignore: false,
+            no_gitignore: false,
+            no_gitattributes: false,
+            include_generated: false,
+            no_default_excludes: false,
+            allow_sensitive: false,
+            hidden: false,
+            follow_symlinks: false,
+            include_external_symlinks: false,
+            max_depth: None,
+            max_file_size: None,
+            lossy: false,
+            dedupe_headers: false,
+            redact: None,
+            include_nested_repos: false,
+            report_sparse: false,
+            exclude_from_tree: false,
+            annotate_tree: false,
+            tree_only: false,
+            no_tree: false,
+            tree_style: None,
+            tokens: false,
+            encoding: None,
+            output: None,
+            compress: None,
+            split_tokens: None,
+            budget: None,
+            estimate: false,
+            stats: false,
+            timings: false,
+            diff: false,
+            git_diff_branch: None,
+            git_log_branch: None,
+            line_number: false,
+            line_number_for: None,
+            line_number_diff: false,
+            conflicts: false,
+            line_anchors: false,
+            no_codeblock: false,
+            format: None,
+            system_section: None,
+            model: None,
+            cache_breakpoint: None,
+            relative_paths: false,
+            sort: None,
+            no_clipboard: false,
+            quiet: false,
+            color: None,
+            clipboard: None,
+            target: None,
+            paste_limits: HashMap::new(),
+            tiers: HashMap::new(),
+            copy_section: Section::All,
+            template: None,
+            answers: None,
+            var: Vec::new(),
+            theme: None,
+            html_theme: None,
+            colors: None,
+            ascii: false,
+            json: false,
+            log_format: LogFormat::Text,
+            list: false,
+            explain: Vec::new(),
+            release: None,
+            bisect: None,
+            preset: None,
+            locale: None,
+            instructions: None,
+            section: Section::All,
+            count_file_tokens: false,
+            settled_flags: HashSet::new(),
+        }
+    }
+}
+
+/// Fills in boolean options from a configuration layer, unless the command line turned them on
+/// or an earlier layer set them, so a profile can turn off a flag of the configuration file.
+macro_rules! layer_flags {
+    ($options:ident, $file_config:ident, $($name:ident),* $(,)?) => {
+        $(
+            if let Some(value) = $file_config.$name {
+                if !$options.$name && $options.settled_flags.insert(stringify!($name)) {
+                    $options.$name = value;
+                }
+            }
+        )*
+    };
+}
+
+impl Options {
+    /// Returns a builder starting from the default options.
+    ///
+    /// # Returns
+    ///
+    /// * `OptionsBuilder` - The builder.
+    pub fn builder() -> OptionsBuilder {
+        OptionsBuilder::default()
+    }
+
+    /// Returns the directory of the first codebase path, used for git operations and the
+    /// project configuration file.
+    ///
+    /// When the first path is a file, its parent directory is returned.
+    ///
+    /// # Returns
+    ///
+    /// * `&Path` - The directory of the first path given on the command line.
+    pub fn primary_dir(&self) -> &Path {
+        match self.path.first() {
+            Some(path) if path.is_file() => path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or(Path::new(".")),
+            Some(path) => path,
+            None => Path::new("."),
+        }
+    }
+
+    /// Returns the file the prompt is written to.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&str>` - The `--output` path, or `None` without one and with `-`, which writes to stdout.
+    pub fn output_file(&self) -> Option<&str> {
+        self.output.as_deref().filter(|output| *output != STDOUT_OUTPUT)
+    }
+
+    /// Checks whether the prompt is written to stdout, with `--output -`.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the status lines go to stderr so stdout only holds the prompt.
+    pub fn writes_to_stdout(&self) -> bool {
+        self.output.as_deref() == Some(STDOUT_OUTPUT)
+    }
+
+    /// Returns the section of the prompt this invocation renders.
+    ///
+    /// # Returns
+    ///
+    /// * `Section` - The source tree with `--tree-only`, or the requested section otherwise.
+    pub fn rendered_section(&self) -> Section {
+        if self.tree_only {
+            Section::Tree
+        } else {
+            self.section
+        }
+    }
+
+    /// Returns the format the prompt is rendered in.
+    ///
+    /// # Returns
+    ///
+    /// * `OutputFormat` - The format given with `--format`, or `json` with `--json` and `markdown` otherwise.
+    pub fn output_format(&self) -> OutputFormat {
+        self.format.unwrap_or(if self.json { OutputFormat::Json } else { OutputFormat::Markdown })
+    }
+
+    /// Checks whether the code of the files is wrapped in Markdown code blocks.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `false` with `--no-codeblock`, or with the `xml` and `jsonl` formats, which hold the raw code.
+    pub fn wraps_code_blocks(&self) -> bool {
+        !self.no_codeblock && self.output_format().renders_template()
+    }
+
+    /// Checks whether the given section is rendered or copied by this invocation.
+    ///
+    /// # Arguments
+    ///
+    /// * `section` - The section to check.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the section is rendered or copied on its own.
+    pub fn requests_section(&self, section: Section) -> bool {
+        self.rendered_section() == section || self.copy_section == section
+    }
+
+    /// Checks whether this invocation reports token counts, so the tokenizer is only loaded when needed.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the prompt or its files are tokenized.
+    pub fn counts_tokens(&self) -> bool {
+        self.tokens
+            || self.split_tokens.is_some()
+            || self.json
+            || self.stats
+            || self.list
+            || self.count_file_tokens
+            || matches!(self.output_format(), OutputFormat::Json | OutputFormat::Yaml | OutputFormat::Jsonl)
+            || self.annotate_tree
+            || self.budget.is_some()
+            || self.sort.is_some_and(SortOrder::needs_tokens)
+    }
+
+    /// Fills in the options that were not given on the command line from a configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_config` - The defaults loaded from the configuration files.
+    pub fn merge_file_config(&mut self, file_config: FileConfig) {
+        self.include = self.include.take().or(file_config.include.map(|p| p.join(",")));
+        self.exclude = self.exclude.take().or(file_config.exclude.map(|p| p.join(",")));
+        if self.include_from.is_empty() {
+            self.include_from = file_config.include_from.unwrap_or_default();
+        }
+        if self.exclude_from.is_empty() {
+            self.exclude_from = file_config.exclude_from.unwrap_or_default();
+        }
+        self.include_containing = self.include_containing.take().or(file_config.include_containing);
+        self.exclude_containing = self.exclude_containing.take().or(file_config.exclude_containing);
+        self.lang = self.lang.take().or(file_config.lang);
+        self.select = self.select.take().or(file_config.select);
+        self.max_depth = self.max_depth.take().or(file_config.max_depth);
+        self.max_file_size = self.max_file_size.take().or(file_config.max_file_size);
+        self.redact = self.redact.take().or(file_config.redact);
+        self.tree_style = self.tree_style.take().or(file_config.tree_style);
+        self.encoding = self.encoding.take().or(file_config.encoding);
+        self.output = self.output.take().or(file_config.output);
+        self.compress = self.compress.take().or(file_config.compress);
+        self.split_tokens = self.split_tokens.take().or(file_config.split_tokens);
+        self.budget = self.budget.take().or(file_config.budget);
+        self.line_number_for = self.line_number_for.take().or(file_config.line_number_for);
+        self.format = self.format.take().or(file_config.format);
+        self.system_section = self.system_section.take().or(file_config.system_section);
+        self.model = self.model.take().or(file_config.model);
+        self.cache_breakpoint = self.cache_breakpoint.take().or(file_config.cache_breakpoint);
+        self.sort = self.sort.take().or(file_config.sort);
+        self.color = self.color.take().or(file_config.color);
+        self.clipboard = self.clipboard.take().or(file_config.clipboard);
+        self.target = self.target.take().or(file_config.target);
+        for (target, limit) in file_config.paste_limits {
+            self.paste_limits.entry(target).or_insert(limit);
+        }
+        for (name, patterns) in file_config.tiers {
+            self.tiers.entry(name).or_insert(patterns);
+        }
+        self.template = self.template.take().or(file_config.template);
+        self.answers = self.answers.take().or(file_config.answers);
+        self.instructions = self.instructions.take().or(file_config.instructions);
+        self.theme = self.theme.take().or(file_config.theme);
+        self.html_theme = self.html_theme.take().or(file_config.html_theme);
+        self.colors = self.colors.take().or(file_config.colors);
+        self.locale = self.locale.take().or(file_config.locale);
+        layer_flags!(
+            self,
+            file_config,
+            include_priority,
+            no_ignore,
+            no_gitignore,
+            no_gitattributes,
+            include_generated,
+            no_default_excludes,
+            allow_sensitive,
+            hidden,
+            follow_symlinks,
+            include_external_symlinks,
+            lossy,
+            dedupe_headers,
+            include_nested_repos,
+            exclude_from_tree,
+            annotate_tree,
+            tree_only,
+            no_tree,
+            tokens,
+            estimate,
+            line_number,
+            line_number_diff,
+            conflicts,
+            line_anchors,
+            no_codeblock,
+            relative_paths,
+            no_clipboard,
+            quiet,
+        );
+    }
+}
+
+
+/// Builds `Options` field by field, starting from the defaults.
+///
+/// Setters of optional values take the value itself, e.g. `.output("prompt.md")`.
+#[derive(Debug, Default, Clone)]
+pub struct OptionsBuilder {
+    options: Options,
+}
+
+/// Generates one setter per option.
+macro_rules! setters {
+    ($($name:ident: $value:ty),* $(,)?) => {
+        $(
+            #[doc = concat!("Sets the `", stringify!($name), "` option.")]
+            pub fn $name(mut self, $name: impl Into<$value>) -> Self {
+                let value: $value = $name.into();
+                self.options.$name = value.into();
+                self
+            }
+        )*
+    };
+}
+
+impl OptionsBuilder {
+    setters! {
+        path: Vec<PathBuf>,
+        remote: String,
+        github: String,
+        branch: String,
+        files_from: String,
+        workspace_file: PathBuf,
+        include: String,
+        exclude: String,
+        include_from: Vec<PathBuf>,
+        exclude_from: Vec<PathBuf>,
+        include_priority: bool,
+        include_containing: String,
+        exclude_containing: String,
+        lang: String,
+        select: String,
+        no_ignore: bool,
+        no_gitignore: bool,
+        no_gitattributes: bool,
+        include_generated: bool,
+        no_default_excludes: bool,
+        allow_sensitive: bool,
+        hidden: bool,
+        follow_symlinks: bool,
+        include_external_symlinks: bool,
+        max_depth: usize,
+        max_file_size: FileSize,
+        lossy: bool,
+        dedupe_headers: bool,
+        redact: bool,
+        include_nested_repos: bool,
+        report_sparse: bool,
+        exclude_from_tree: bool,
+        annotate_tree: bool,
+        tree_only: bool,
+        no_tree: bool,
+        tree_style: TreeStyle,
+        tokens: bool,
+        encoding: String,
+        output: String,
+        compress: Compression,
+        split_tokens: usize,
+        budget: usize,
+        estimate: bool,
+        stats: bool,
+        timings: bool,
+        diff: bool,
+        git_diff_branch: String,
+        git_log_branch: String,
+        line_number: bool,
+        line_number_for: String,
+        line_number_diff: bool,
+        conflicts: bool,
+        line_anchors: bool,
+        no_codeblock: bool,
+        format: OutputFormat,
+        system_section: Section,
+        model: String,
+        cache_breakpoint: Section,
+        relative_paths: bool,
+        sort: SortOrder,
+        no_clipboard: bool,
+        quiet: bool,
+        color: ColorChoice,
+        clipboard: ClipboardBackend,
+        target: String,
+        paste_limits: HashMap<String, PasteLimit>,
+        tiers: HashMap<String, Vec<String>>,
+        copy_section: Section,
+        template: PathBuf,
+        answers: PathBuf,
+        var: Vec<String>,
+        theme: ThemeName,
+        html_theme: HtmlTheme,
+        colors: ColorsConfig,
+        ascii: bool,
+        json: bool,
+        log_format: LogFormat,
+        list: bool,
+        explain: Vec<PathBuf>,
+        release: ReleaseRange,
+        bisect: BisectRange,
+        preset: PresetName,
+        locale: String,
+        instructions: String,
+        section: Section,
+        count_file_tokens: bool,
+    }
+
+    /// Returns the built options.
+    ///
+    /// # Returns
+    ///
+    /// * `Options` - The options set on the builder, and the defaults for the others.
+    pub fn build(self) -> Options {
+        self.options
+    }
+}
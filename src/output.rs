@@ -1,12 +1,392 @@
 //! This module handles the output operations, including printing to the console,
 //! copying to the clipboard, and writing to a file.
 
+use crate::clipboard::ClipboardSink;
+use crate::config::{ColorChoice, Compression, FileSize, OutputFormat};
+use crate::dashboard::{overview, token_summary, Dashboard};
+use crate::doctor::{Check, Status};
+use crate::explain::Explanation;
+use crate::options::Options;
+use crate::messages::{message, message_with, Message};
+use crate::history::load_output;
+use crate::processing::ProcessedCodebase;
+use crate::suggest::Suggestions;
+use crate::template::TemplateError;
+use crate::text::{display_width, escape_xml, pad_left, pad_right, xml_text};
+use crate::theme::theme;
+use crate::timings::StageTiming;
+use crate::workspace::write_atomic;
 use anyhow::{Context, Result};
-use arboard::Clipboard;
 use colored::*;
-use std::fs::File;
-use std::io::Write;
-use serde_json::json;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use serde_json::{json, Value};
+
+/// Whether status output is restricted to ASCII characters.
+static ASCII_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+/// Whether the status output is colored.
+static COLORS: AtomicBool = AtomicBool::new(true);
+
+/// Whether the colors follow the terminals, with `--color auto`.
+static AUTO_COLORS: AtomicBool = AtomicBool::new(true);
+
+/// Whether only warnings and errors are printed, with `--quiet`.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Whether status lines are printed to stderr, keeping stdout for the prompt.
+static STATUS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Whether the progress spinner is hidden.
+static SPINNER_HIDDEN: AtomicBool = AtomicBool::new(false);
+
+/// The number of characters of the longest bar of the token trend of `codexio stats`.
+const DASHBOARD_BAR_WIDTH: usize = 30;
+
+/// The model of `--format openai` unless `--model` is given.
+pub const DEFAULT_OPENAI_MODEL: &str = "gpt-4o";
+
+/// The model of `--format anthropic` unless `--model` is given.
+pub const DEFAULT_ANTHROPIC_MODEL: &str = "claude-sonnet-4-5";
+
+/// The `max_tokens` of `--format anthropic`, which the Messages API requires.
+pub const ANTHROPIC_MAX_TOKENS: u32 = 8192;
+
+/// The content and file list of an output file written by a previous run.
+#[derive(Debug)]
+pub struct PreviousOutput {
+    /// The previous content of the output file.
+    pub content: String,
+    /// The files of the previous prompt, if it was recorded or had a stats sidecar file.
+    pub paths: Option<Vec<String>>,
+    /// The number of tokens of the previous prompt, if they were counted.
+    pub token_count: Option<usize>,
+}
+
+/// Restricts status output, including the progress spinner, to ASCII characters.
+///
+/// # Arguments
+///
+/// * `ascii` - Whether to use ASCII characters only.
+pub fn set_ascii_output(ascii: bool) {
+    ASCII_OUTPUT.store(ascii, Ordering::Relaxed);
+}
+
+/// Returns whether status output is restricted to ASCII characters.
+///
+/// # Returns
+///
+/// * `bool` - `true` if only ASCII characters should be printed.
+pub fn ascii_output() -> bool {
+    ASCII_OUTPUT.load(Ordering::Relaxed)
+}
+
+/// Hides the progress spinner, e.g. while several batch jobs run at once.
+///
+/// # Arguments
+///
+/// * `hidden` - Whether to hide the spinner.
+pub fn set_spinner_hidden(hidden: bool) {
+    SPINNER_HIDDEN.store(hidden, Ordering::Relaxed);
+}
+
+/// Returns whether the progress spinner is hidden.
+///
+/// # Returns
+///
+/// * `bool` - `true` if no spinner should be drawn.
+pub fn spinner_hidden() -> bool {
+    SPINNER_HIDDEN.load(Ordering::Relaxed)
+}
+
+/// Leaves out the success and informational status lines, keeping warnings and errors, and hides
+/// the progress spinner.
+///
+/// # Arguments
+///
+/// * `quiet` - Whether to print only warnings and errors.
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+    if quiet {
+        set_spinner_hidden(true);
+    }
+}
+
+/// Returns whether only warnings and errors are printed.
+///
+/// # Returns
+///
+/// * `bool` - `true` with `--quiet`.
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Prints the status lines that stdout would get to stderr instead, e.g. with `--output -`.
+///
+/// With `--color auto`, the colors are chosen again for the streams the status lines now go to.
+///
+/// # Arguments
+///
+/// * `to_stderr` - Whether to print status lines to stderr.
+pub fn set_status_to_stderr(to_stderr: bool) {
+    STATUS_TO_STDERR.store(to_stderr, Ordering::Relaxed);
+    if AUTO_COLORS.load(Ordering::Relaxed) {
+        set_color_choice(ColorChoice::Auto);
+    }
+}
+
+/// Turns the colors of the status output, including the spinner, on or off.
+///
+/// With `auto`, the status output is colored unless `NO_COLOR` is set or `TERM` is `dumb`, and
+/// only when the streams status lines are printed to are terminals: stderr, and stdout unless the
+/// status lines were moved to stderr.
+///
+/// # Arguments
+///
+/// * `choice` - The `--color` choice.
+pub fn set_color_choice(choice: ColorChoice) {
+    AUTO_COLORS.store(choice == ColorChoice::Auto, Ordering::Relaxed);
+    let enabled = match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+            let dumb = env::var("TERM").is_ok_and(|term| term == "dumb");
+            let stdout = STATUS_TO_STDERR.load(Ordering::Relaxed) || io::stdout().is_terminal();
+            !no_color && !dumb && stdout && io::stderr().is_terminal()
+        }
+    };
+    COLORS.store(enabled, Ordering::Relaxed);
+    colored::control::set_override(enabled);
+}
+
+/// Returns whether the status output is colored.
+///
+/// # Returns
+///
+/// * `bool` - `false` with `--color never`, or with `auto` outside a terminal or with `NO_COLOR`.
+pub fn colors_enabled() -> bool {
+    COLORS.load(Ordering::Relaxed)
+}
+
+/// Checks whether stdout is piped or redirected to a file instead of shown in a terminal.
+///
+/// # Returns
+///
+/// * `bool` - `true` if stdout is not a terminal.
+pub fn stdout_piped() -> bool {
+    !io::stdout().is_terminal()
+}
+
+/// Prints a status line to stdout, or to stderr when stdout is kept for the prompt.
+///
+/// # Arguments
+///
+/// * `line` - The status line.
+fn print_status_line(line: &str) {
+    if quiet() {
+        return;
+    }
+    if STATUS_TO_STDERR.load(Ordering::Relaxed) {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
+}
+
+/// Returns the style of the progress spinner.
+///
+/// # Returns
+///
+/// * `ProgressStyle` - The spinner drawn before the current step, in ASCII with `--ascii`.
+pub fn spinner_style() -> ProgressStyle {
+    let tick_strings: &[&str] = if ascii_output() {
+        &[".....", "o....", ".o...", "..o..", "...o.", "....o"]
+    } else {
+        &["▹▹▹▹▹", "▸▹▹▹▹", "▹▸▹▹▹", "▹▹▸▹▹", "▹▹▹▸▹", "▹▹▹▹▸"]
+    };
+    ProgressStyle::default_spinner()
+        .tick_strings(tick_strings)
+        .template(if colors_enabled() { "{spinner:.blue} {msg}" } else { "{spinner} {msg}" })
+        .unwrap()
+}
+
+/// The progress of reading the files of a traversal.
+///
+/// On a terminal, the spinner turns into a bar of the files read out of the total, with the
+/// bytes read and the elapsed time. Otherwise a single line is printed once all are read.
+#[derive(Debug, Clone)]
+pub struct ReadProgress {
+    /// The spinner of the run, drawn as a bar while the files are read.
+    bar: ProgressBar,
+    /// The number of bytes read so far, across the threads reading the files.
+    bytes: Arc<AtomicU64>,
+    /// When the traversal started.
+    started: Instant,
+}
+
+impl ReadProgress {
+    /// Creates the progress of reading the files, drawn with the spinner of the run, when the
+    /// traversal starts.
+    ///
+    /// # Arguments
+    ///
+    /// * `bar` - The spinner of the run.
+    ///
+    /// # Returns
+    ///
+    /// * `ReadProgress` - The progress, with nothing read yet.
+    pub fn new(bar: ProgressBar) -> Self {
+        ReadProgress {
+            bar,
+            bytes: Arc::new(AtomicU64::new(0)),
+            started: Instant::now(),
+        }
+    }
+
+    /// Turns the spinner into a bar, once the number of files to read is known.
+    ///
+    /// # Arguments
+    ///
+    /// * `total` - The number of files to read.
+    pub fn start(&self, total: usize) {
+        let (filled, empty) = if ascii_output() { ("#", "-") } else { ("█", "░") };
+        self.bar.set_style(
+            ProgressStyle::with_template(if colors_enabled() {
+                "{bar:30.blue} {pos}/{len} {msg} {elapsed:.dim}"
+            } else {
+                "{bar:30} {pos}/{len} {msg} {elapsed}"
+            })
+                .unwrap()
+                .progress_chars(&format!("{}{}", filled, empty)),
+        );
+        self.bar.set_length(total as u64);
+        self.bar.set_position(0);
+    }
+
+    /// Counts a file as read.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The size of the file.
+    pub fn advance(&self, bytes: u64) {
+        let read = self.bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.bar.set_message(message_with(Message::FilesReadBytes, &[("size", &FileSize(read))]));
+        self.bar.inc(1);
+    }
+
+    /// Turns the bar back into the spinner, printing the files and bytes read when the bar was
+    /// not drawn because stderr is not a terminal.
+    pub fn finish(&self) {
+        self.bar.set_style(spinner_style());
+        if self.bar.is_hidden() {
+            print_info(&message_with(
+                Message::FilesRead,
+                &[
+                    ("files", &self.bar.position()),
+                    ("size", &FileSize(self.bytes.load(Ordering::Relaxed))),
+                    ("elapsed", &format!("{:.1} s", self.started.elapsed().as_secs_f64())),
+                ],
+            ));
+        }
+    }
+}
+
+/// Returns the symbol marking a successful operation in status lines.
+///
+/// # Returns
+///
+/// * `&'static str` - `✓`, or `+` when status output is restricted to ASCII.
+fn success_symbol() -> &'static str {
+    if ascii_output() {
+        "+"
+    } else {
+        "✓"
+    }
+}
+
+/// Formats the `[x]` prefix of a status line with the current theme.
+///
+/// # Arguments
+///
+/// * `symbol` - The symbol between the brackets.
+/// * `color` - The color of the symbol.
+///
+/// # Returns
+///
+/// * `String` - The formatted prefix.
+fn status_prefix(symbol: &str, color: Color) -> String {
+    let theme = theme();
+    format!(
+        "{}{}{}",
+        "[".bold().color(theme.bracket),
+        symbol.bold().color(color),
+        "]".bold().color(theme.bracket)
+    )
+}
+
+/// Prints an error status line to stderr.
+///
+/// # Arguments
+///
+/// * `message` - The error message.
+pub fn print_error(message: &str) {
+    eprintln!(
+        "{} {}",
+        status_prefix("!", theme().error),
+        message.color(theme().error)
+    );
+}
+
+/// Prints a success status line to stderr.
+///
+/// # Arguments
+///
+/// * `message` - The success message.
+pub fn print_success(message: &str) {
+    if quiet() {
+        return;
+    }
+    eprintln!(
+        "{} {}",
+        status_prefix(success_symbol(), theme().success),
+        message.color(theme().success)
+    );
+}
+
+/// Prints an informational status line to stderr.
+///
+/// # Arguments
+///
+/// * `message` - The informational message.
+pub fn print_info(message: &str) {
+    if quiet() {
+        return;
+    }
+    eprintln!("{} {}", status_prefix("i", theme().info), message);
+}
+
+/// Prints a warning status line to stderr, and logs the warning for `--log-format json`.
+///
+/// # Arguments
+///
+/// * `message` - The warning message.
+pub fn print_warning(message: &str) {
+    tracing::warn!("{}", message);
+    eprintln!(
+        "{} {}",
+        status_prefix("!", theme().highlight),
+        message.color(theme().highlight)
+    );
+}
 
 /// Prints the rendered template to the console.
 ///
@@ -17,32 +397,251 @@ pub fn print_to_console(rendered: &str) {
     println!("{}", rendered);
 }
 
+/// An output format of the prompt, selected with `--format`.
+///
+/// The `xml` and `jsonl` formats render the data of the template in place of the template, the
+/// `json` and `yaml` formats render the document wrapping the Markdown prompt, see
+/// `print_structured_output`, and the chat formats render the `model`, `system` and `user`
+/// messages split from it, with the `cache_prefix` of the user message ending at the caching
+/// breakpoint, or `null` for a breakpoint ending the system message.
+pub trait Formatter {
+    /// Renders a document in the format.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The JSON data of the prompt, or the document wrapping or splitting it.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - The rendered document, or an error if it cannot be serialized.
+    fn render(&self, document: &Value) -> Result<String>;
+}
+
+/// Renders the `<documents>` of `--format xml`.
+pub struct XmlFormatter;
+
+impl Formatter for XmlFormatter {
+    fn render(&self, document: &Value) -> Result<String> {
+        Ok(render_xml(document))
+    }
+}
+
+/// Renders the lines of `--format jsonl`.
+pub struct JsonlFormatter;
+
+impl Formatter for JsonlFormatter {
+    fn render(&self, document: &Value) -> Result<String> {
+        render_jsonl(document)
+    }
+}
+
+/// Renders the pretty-printed JSON of `--format json` and `--json`.
+pub struct JsonFormatter;
+
+impl Formatter for JsonFormatter {
+    fn render(&self, document: &Value) -> Result<String> {
+        Ok(serde_json::to_string_pretty(document)?)
+    }
+}
+
+/// Renders the YAML of `--format yaml`.
+pub struct YamlFormatter;
+
+impl Formatter for YamlFormatter {
+    fn render(&self, document: &Value) -> Result<String> {
+        Ok(serde_yaml::to_string(document)?)
+    }
+}
+
+/// Renders the chat completions request of `--format openai`.
+pub struct OpenaiFormatter;
+
+impl Formatter for OpenaiFormatter {
+    fn render(&self, document: &Value) -> Result<String> {
+        let mut messages = Vec::new();
+        if let Some(system) = document["system"].as_str().filter(|system| !system.is_empty()) {
+            messages.push(json!({ "role": "system", "content": system }));
+        }
+        messages.push(json!({ "role": "user", "content": document["user"] }));
+        let request = json!({
+            "model": document["model"].as_str().unwrap_or(DEFAULT_OPENAI_MODEL),
+            "messages": messages,
+        });
+        Ok(serde_json::to_string_pretty(&request)?)
+    }
+}
+
+/// Renders the Messages request of `--format anthropic`, marking the end of its cached prefix
+/// with `cache_control`.
+pub struct AnthropicFormatter;
+
+impl Formatter for AnthropicFormatter {
+    fn render(&self, document: &Value) -> Result<String> {
+        let cache_control = json!({ "type": "ephemeral" });
+        let system = document["system"].as_str().unwrap_or("");
+        let user = document["user"].as_str().unwrap_or("");
+        let mut request = json!({
+            "model": document["model"].as_str().unwrap_or(DEFAULT_ANTHROPIC_MODEL),
+            "max_tokens": ANTHROPIC_MAX_TOKENS,
+        });
+
+        let mut content = Vec::new();
+        match document["cache_prefix"].as_u64().map(|prefix| prefix as usize) {
+            Some(prefix) if user.is_char_boundary(prefix.min(user.len())) => {
+                let (cached, rest) = user.split_at(prefix.min(user.len()));
+                content.push(json!({ "type": "text", "text": cached, "cache_control": cache_control }));
+                if !rest.trim().is_empty() {
+                    content.push(json!({ "type": "text", "text": rest.trim_start() }));
+                }
+                if !system.is_empty() {
+                    request["system"] = json!(system);
+                }
+            }
+            _ => {
+                request["system"] = json!([{ "type": "text", "text": system, "cache_control": cache_control }]);
+                content.push(json!({ "type": "text", "text": user }));
+            }
+        }
+        request["messages"] = json!([{ "role": "user", "content": content }]);
+        Ok(serde_json::to_string_pretty(&request)?)
+    }
+}
+
+/// Returns the formatter of an output format.
+///
+/// # Arguments
+///
+/// * `format` - The output format.
+///
+/// # Returns
+///
+/// * `Option<Box<dyn Formatter>>` - The formatter, or `None` for `markdown`, which the template renders.
+pub fn formatter(format: OutputFormat) -> Option<Box<dyn Formatter>> {
+    match format {
+        OutputFormat::Markdown => None,
+        OutputFormat::Json => Some(Box::new(JsonFormatter)),
+        OutputFormat::Xml => Some(Box::new(XmlFormatter)),
+        OutputFormat::Yaml => Some(Box::new(YamlFormatter)),
+        OutputFormat::Jsonl => Some(Box::new(JsonlFormatter)),
+        OutputFormat::Openai => Some(Box::new(OpenaiFormatter)),
+        OutputFormat::Anthropic => Some(Box::new(AnthropicFormatter)),
+    }
+}
+
+/// Renders the files as the `<documents>` structure Anthropic recommends for packing documents
+/// into a prompt, followed by the instructions if there are any.
+///
+/// Each file is a `<document>` numbered from 1, with its escaped path as `<source>` and its raw
+/// code as `<document_contents>`, in a CDATA section if it holds markup. A common header removed from a file with `--dedupe-headers` is put back,
+/// so each document stands on its own.
+///
+/// # Arguments
+///
+/// * `data` - The JSON data of the prompt.
+///
+/// # Returns
+///
+/// * `String` - The rendered documents.
+pub fn render_xml(data: &Value) -> String {
+    let mut xml = String::from("<documents>\n");
+    for (index, file) in packed_files(data).enumerate() {
+        xml.push_str(&format!(
+            "<document index=\"{}\">\n<source>{}</source>\n<document_contents>\n{}\n</document_contents>\n</document>\n",
+            index + 1,
+            escape_xml(file["path"].as_str().unwrap_or("")),
+            xml_text(&file_contents(data, file)),
+        ));
+    }
+    xml.push_str("</documents>\n");
+    if let Some(instructions) = data["instructions"].as_str().filter(|instructions| !instructions.is_empty()) {
+        xml.push_str(&format!("\n{}\n", instructions.trim_end()));
+    }
+    xml
+}
+
+/// Renders the files as JSON Lines, one object per file with its `path`, `language`, `content`
+/// and `tokens`, for embedding and ingestion pipelines.
+///
+/// # Arguments
+///
+/// * `data` - The JSON data of the prompt.
+///
+/// # Returns
+///
+/// * `Result<String>` - The rendered lines, each followed by a newline.
+pub fn render_jsonl(data: &Value) -> Result<String> {
+    let mut lines = String::new();
+    for file in packed_files(data) {
+        let line = json!({
+            "path": file["path"],
+            "language": file["language"],
+            "content": file_contents(data, file),
+            "tokens": file["token_count"],
+        });
+        lines.push_str(&serde_json::to_string(&line)?);
+        lines.push('\n');
+    }
+    Ok(lines)
+}
+
+/// Returns the files of the prompt data that have content.
+///
+/// # Arguments
+///
+/// * `data` - The JSON data of the prompt.
+///
+/// # Returns
+///
+/// * `impl Iterator<Item = &Value>` - The JSON representations of the files, in order.
+fn packed_files(data: &Value) -> impl Iterator<Item = &Value> {
+    data["files"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|file| file["code"].as_str().is_some_and(|code| !code.is_empty()))
+}
+
+/// Returns the contents of a file, with the common header removed by `--dedupe-headers` put
+/// back, so each file stands on its own.
+///
+/// # Arguments
+///
+/// * `data` - The JSON data of the prompt.
+/// * `file` - The JSON representation of the file.
+///
+/// # Returns
+///
+/// * `String` - The contents of the file, without a trailing newline.
+fn file_contents(data: &Value, file: &Value) -> String {
+    let header = data["common_headers"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|header| !file["common_header"].is_null() && header["id"] == file["common_header"])
+        .and_then(|header| header["code"].as_str())
+        .map(|code| format!("{}\n", code))
+        .unwrap_or_default();
+    format!("{}{}", header, file["code"].as_str().unwrap_or("").trim_end_matches('\n'))
+}
+
 /// Copies the rendered template to the clipboard.
 ///
 /// # Arguments
 ///
 /// * `rendered` - The rendered template string.
+/// * `sink` - The clipboard backend to copy with.
 ///
 /// # Returns
 ///
 /// * `Result<()>` - An empty result indicating success or an error.
-pub fn copy_to_clipboard(rendered: &str) -> Result<()> {
-    match Clipboard::new() {
-        Ok(mut clipboard) => {
-            clipboard
-                .set_text(rendered.to_string())
-                .context("Failed to copy to clipboard")?;
-            println!(
-                "{}{}{} {}",
-                "[".bold().white(),
-                "✓".bold().green(),
-                "]".bold().white(),
-                "Copied to clipboard successfully.".green()
-            );
-            Ok(())
-        }
-        Err(e) => Err(anyhow::anyhow!("Failed to initialize clipboard: {}", e)),
-    }
+pub fn copy_to_clipboard(rendered: &str, sink: &dyn ClipboardSink) -> Result<()> {
+    sink.copy(rendered)?;
+    print_status_line(&format!(
+        "{} {}",
+        status_prefix(success_symbol(), theme().success),
+        message(Message::CopiedToClipboard).color(theme().success)
+    ));
+    Ok(())
 }
 
 /// Writes the rendered template to a specified output file.
@@ -51,24 +650,68 @@ pub fn copy_to_clipboard(rendered: &str) -> Result<()> {
 ///
 /// * `output_path` - The path to the output file.
 /// * `rendered` - The rendered template string.
+/// * `compression` - The compression of the output file, if any.
 ///
 /// # Returns
 ///
 /// * `Result<()>` - An empty result indicating success or an error.
-pub fn write_to_file(output_path: &str, rendered: &str) -> Result<()> {
-    let file = File::create(output_path)?;
-    let mut writer = std::io::BufWriter::new(file);
-    write!(writer, "{}", rendered)?;
-    println!(
-        "{}{}{} {}",
-        "[".bold().white(),
-        "✓".bold().green(),
-        "]".bold().white(),
-        format!("Prompt written to file: {}", output_path).green()
-    );
+pub fn write_to_file(output_path: &str, rendered: &str, compression: Option<Compression>) -> Result<()> {
+    let content = match compression {
+        Some(compression) => compress(rendered.as_bytes(), compression)?,
+        None => rendered.as_bytes().to_vec(),
+    };
+    write_atomic(Path::new(output_path), &content)?;
+    print_status_line(&format!(
+        "{} {}",
+        status_prefix(success_symbol(), theme().success),
+        message_with(Message::PromptWrittenToFile, &[("path", &output_path)]).color(theme().success)
+    ));
     Ok(())
 }
 
+/// Compresses the content of an output file.
+///
+/// # Arguments
+///
+/// * `content` - The content to compress.
+/// * `compression` - The compression to apply.
+///
+/// # Returns
+///
+/// * `Result<Vec<u8>>` - The compressed content, or an error if the encoder fails.
+fn compress(content: &[u8], compression: Compression) -> Result<Vec<u8>> {
+    match compression {
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(content)?;
+            encoder.finish().context("Failed to compress the output with gzip")
+        }
+        Compression::Zstd => zstd::encode_all(content, 0).context("Failed to compress the output with zstd"),
+    }
+}
+
+/// Decompresses the content of an output file.
+///
+/// # Arguments
+///
+/// * `content` - The compressed content.
+/// * `compression` - The compression the content was written with.
+///
+/// # Returns
+///
+/// * `Result<String>` - The decompressed text, or an error if the content is not compressed this way or is not UTF-8.
+fn decompress(content: &[u8], compression: Compression) -> Result<String> {
+    let bytes = match compression {
+        Compression::Gzip => {
+            let mut bytes = Vec::new();
+            GzDecoder::new(content).read_to_end(&mut bytes)?;
+            bytes
+        }
+        Compression::Zstd => zstd::decode_all(content)?,
+    };
+    Ok(String::from_utf8(bytes)?)
+}
+
 /// Prints the token count and model information to the console.
 ///
 /// # Arguments
@@ -76,43 +719,565 @@ pub fn write_to_file(output_path: &str, rendered: &str) -> Result<()> {
 /// * `token_count` - The number of tokens in the rendered template.
 /// * `model_info` - The model information string.
 pub fn print_token_info(token_count: usize, model_info: &str) {
+    let count = token_count.to_string().bold().color(theme().highlight);
+    print_status_line(&format!(
+        "{} {}",
+        status_prefix("i", theme().info),
+        message_with(Message::TokenInfo, &[("count", &count), ("model", &model_info)])
+    ));
+}
+
+/// Prints the one-line summary that ends a run.
+///
+/// The budget share is highlighted as an error when the prompt exceeds the budget. When the
+/// tokens were not counted, the total and the share are estimated and prefixed with `~`.
+///
+/// # Arguments
+///
+/// * `processed` - The processed codebase.
+/// * `budget` - The token budget, if one was given.
+/// * `destinations` - Where the prompt was sent, such as the clipboard or an output file.
+pub fn print_summary(processed: &ProcessedCodebase, budget: Option<usize>, destinations: &[String]) {
+    let mut details = vec![message_with(
+        Message::SummaryFiles,
+        &[("included", &processed.paths.len()), ("skipped", &processed.skipped)],
+    )];
+    // Without the tokenizer the total is estimated, and marked as approximate
+    let (token_count, estimated) = processed.tokens();
+    let mark = if estimated { "~" } else { "" };
+    details.push(message_with(Message::SummaryTokens, &[("count", &format!("{}{}", mark, token_count))]));
+    if let Some(budget) = budget.filter(|budget| *budget > 0) {
+        let percent = format!("{}{}", mark, token_count * 100 / budget);
+        let share = message_with(Message::SummaryBudget, &[("percent", &percent), ("budget", &budget)]);
+        let color = if token_count > budget {
+            theme().error
+        } else {
+            theme().highlight
+        };
+        details.push(share.color(color).to_string());
+    }
+    if !destinations.is_empty() {
+        details.push(message_with(
+            Message::SummaryDestinations,
+            &[("destinations", &destinations.join(", "))],
+        ));
+    }
+
+    print_status_line(&format!(
+        "{} {}",
+        status_prefix("i", theme().info),
+        message_with(Message::Summary, &[("details", &details.join(" | "))])
+    ));
+}
+
+/// Prints the per-stage breakdown of `--timings` to stderr.
+///
+/// Stages nest, so the traversal includes the filtering and reading of files, and stages
+/// running on several threads add up the time of every thread.
+///
+/// # Arguments
+///
+/// * `stages` - The recorded stage timings.
+/// * `total` - The wall-clock time of the whole run.
+pub fn print_timings(stages: &[StageTiming], total: Duration) {
+    let format_time = |duration: Duration| format!("{:.1} ms", duration.as_secs_f64() * 1000.0);
+    eprintln!("{} {}", status_prefix("i", theme().info), message(Message::Timings));
+    let row = |stage: &str, calls: &str, time: &str| {
+        eprintln!("  {} {} {}", pad_right(stage, 12), pad_left(calls, 8), pad_left(time, 12));
+    };
+    row(
+        message(Message::TimingsStage),
+        message(Message::TimingsCalls),
+        message(Message::TimingsTime),
+    );
+    for stage in stages {
+        row(stage.name, &stage.calls.to_string(), &format_time(stage.total));
+    }
+    row(message(Message::TimingsTotal), "", &format_time(total));
+}
+
+/// Prints the analysis and recommendations of `codexio suggest`.
+///
+/// # Arguments
+///
+/// * `suggestions` - The analysis of the codebase.
+pub fn print_suggestions(suggestions: &Suggestions) {
+    let mut overview = message_with(
+        Message::SuggestTotal,
+        &[("tokens", &suggestions.total_tokens), ("files", &suggestions.total_files)],
+    );
+    if let Some(budget) = suggestions.budget {
+        let fit = if suggestions.total_tokens > budget {
+            message_with(
+                Message::SuggestOverBudget,
+                &[("excess", &(suggestions.total_tokens - budget)), ("budget", &budget)],
+            )
+        } else {
+            message_with(Message::SuggestWithinBudget, &[("budget", &budget)])
+        };
+        overview = format!("{} {}", overview, fit);
+    }
+    println!("{} {}", status_prefix("i", theme().info), overview);
+
+    let sections = [
+        (Message::SuggestDirectories, &suggestions.directories),
+        (Message::SuggestExtensions, &suggestions.extensions),
+        (Message::SuggestLargestFiles, &suggestions.largest_files),
+        (Message::SuggestGenerated, &suggestions.generated),
+    ];
+    for (heading, groups) in sections {
+        if groups.is_empty() {
+            continue;
+        }
+        println!("\n{}", message(heading).bold());
+        for group in groups {
+            println!(
+                "  {}",
+                message_with(
+                    Message::SuggestGroup,
+                    &[("name", &group.name), ("tokens", &group.tokens), ("files", &group.files)],
+                )
+            );
+        }
+    }
+
+    println!("\n{}", message(Message::SuggestOptions).bold());
+    if suggestions.excludes.is_empty() {
+        println!("  {}", message(Message::SuggestNoChanges));
+        return;
+    }
+    println!(
+        "  {}",
+        format!("--exclude=\"{}\" --exclude-from-tree", suggestions.excludes.join(","))
+            .color(theme().highlight)
+    );
     println!(
-        "{}{}{} Token count: {}, Model info: {}",
-        "[".bold().white(),
-        "i".bold().blue(),
-        "]".bold().white(),
-        token_count.to_string().bold().yellow(),
-        model_info
+        "  {}",
+        message_with(Message::SuggestRemaining, &[("tokens", &suggestions.remaining_tokens)])
     );
+    if suggestions
+        .budget
+        .is_some_and(|budget| suggestions.remaining_tokens > budget)
+    {
+        println!("  {}", message(Message::SuggestStillOverBudget).color(theme().error));
+    }
 }
 
-/// Prints the output in JSON format.
+/// Prints the files of `--list`, each with its size and token count, followed by the totals.
 ///
 /// # Arguments
 ///
-/// * `rendered` - The rendered template string.
+/// * `files` - The JSON representations of the files that would be packed.
+/// * `json` - Whether to print the list as a JSON array instead of a table.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+pub fn print_file_list(files: &[Value], json: bool) -> Result<()> {
+    let entries: Vec<(&str, u64, u64)> = files
+        .iter()
+        .filter_map(|file| {
+            Some((
+                file["path"].as_str()?,
+                file["size"].as_u64().unwrap_or(0),
+                file["token_count"].as_u64().unwrap_or(0),
+            ))
+        })
+        .collect();
+    if json {
+        let listed: Vec<Value> = entries
+            .iter()
+            .map(|(path, size, tokens)| json!({ "path": path, "size": size, "token_count": tokens }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&listed)?);
+        return Ok(());
+    }
+
+    let row = |tokens: &str, size: &str, path: &str| {
+        println!("  {} {}  {}", pad_left(tokens, 8), pad_left(size, 10), path);
+    };
+    row(message(Message::ListTokens), message(Message::ListSize), message(Message::ListFile));
+    for (path, size, tokens) in &entries {
+        row(&tokens.to_string(), &FileSize(*size).to_string(), path);
+    }
+    let size: u64 = entries.iter().map(|(_, size, _)| size).sum();
+    let tokens: u64 = entries.iter().map(|(_, _, tokens)| tokens).sum();
+    println!(
+        "{} {}",
+        status_prefix("i", theme().info),
+        message_with(
+            Message::ListTotal,
+            &[("files", &entries.len()), ("size", &FileSize(size)), ("tokens", &tokens)],
+        )
+    );
+    Ok(())
+}
+
+/// Prints the dashboard of `codexio stats`: the sizes of the recorded prompts, their token trend
+/// by day, and the files included most often.
+///
+/// # Arguments
+///
+/// * `dashboard` - The dashboard of the recorded prompts.
+/// * `json` - Whether to print the dashboard as JSON instead of text.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+pub fn print_dashboard(dashboard: &Dashboard, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(dashboard)?);
+        return Ok(());
+    }
+
+    println!("{} {}", status_prefix("i", theme().info), overview(dashboard));
+    println!("{} {}", status_prefix("i", theme().info), token_summary(dashboard));
+
+    println!("\n{}", message(Message::StatsTrend).bold());
+    let row = |day: &str, runs: &str, tokens: &str, bar: &str| {
+        println!("  {} {} {}  {}", pad_right(day, 10), pad_left(runs, 6), pad_left(tokens, 10), bar);
+    };
+    row(
+        message(Message::StatsDay),
+        message(Message::StatsRuns),
+        message(Message::StatsAverageTokens),
+        "",
+    );
+    let peak = dashboard.trend.iter().map(|day| day.average_tokens).max().unwrap_or(0).max(1);
+    let glyph = if ascii_output() { "#" } else { "█" };
+    for day in &dashboard.trend {
+        let bar = glyph.repeat((day.average_tokens * DASHBOARD_BAR_WIDTH).div_ceil(peak));
+        row(&day.day, &day.runs.to_string(), &day.average_tokens.to_string(), &bar.color(theme().highlight).to_string());
+    }
+
+    println!("\n{}", message(Message::StatsTopFiles).bold());
+    for file in &dashboard.top_files {
+        println!("  {}  {}", pad_left(&file.runs.to_string(), 6), file.path);
+    }
+    Ok(())
+}
+
+/// Prints why each path given to `--explain` is packed or left out, with the checks it went through.
+///
+/// # Arguments
+///
+/// * `explanations` - The explanations, in the order the paths were given.
+/// * `json` - Whether to print the explanations as a JSON array instead of text.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+pub fn print_explanations(explanations: &[Explanation], json: bool) -> Result<()> {
+    if json {
+        let explained: Vec<Value> = explanations
+            .iter()
+            .map(|explanation| {
+                let steps: Vec<Value> = explanation
+                    .steps
+                    .iter()
+                    .map(|step| json!({ "passed": step.passed, "detail": step.detail }))
+                    .collect();
+                json!({
+                    "path": explanation.path.display().to_string(),
+                    "included": explanation.included,
+                    "steps": steps,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&explained)?);
+        return Ok(());
+    }
+
+    let failure_symbol = if ascii_output() { "x" } else { "✗" };
+    for explanation in explanations {
+        let path = explanation.path.display();
+        let (symbol, color, verdict) = if explanation.included {
+            (success_symbol(), theme().success, message_with(Message::ExplainIncluded, &[("path", &path)]))
+        } else {
+            (failure_symbol, theme().error, message_with(Message::ExplainExcluded, &[("path", &path)]))
+        };
+        println!("{} {}", status_prefix(symbol, color), verdict.bold());
+        for step in &explanation.steps {
+            let (symbol, color) = if step.passed {
+                (success_symbol(), theme().success)
+            } else {
+                (failure_symbol, theme().error)
+            };
+            println!("    {} {}", symbol.color(color), step.detail);
+        }
+    }
+    Ok(())
+}
+
+/// Prints the checks of `codexio doctor`, with the fix of each warning and failure.
+///
+/// # Arguments
+///
+/// * `checks` - The checks, in the order they were run.
+/// * `json` - Whether to print the checks as a JSON array instead of text.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+pub fn print_checks(checks: &[Check], json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(checks)?);
+        return Ok(());
+    }
+
+    let failure_symbol = if ascii_output() { "x" } else { "✗" };
+    let count = |status: Status| checks.iter().filter(|check| check.status == status).count();
+    let width = checks.iter().map(|check| display_width(check.name)).max().unwrap_or(0);
+    for check in checks {
+        let (symbol, color) = match check.status {
+            Status::Pass => (success_symbol(), theme().success),
+            Status::Warn => ("!", theme().highlight),
+            Status::Fail => (failure_symbol, theme().error),
+        };
+        println!("{} {}  {}", status_prefix(symbol, color), pad_right(check.name, width).bold(), check.detail);
+        if let Some(fix) = &check.fix {
+            println!("    {}", message_with(Message::DoctorFix, &[("fix", fix)]).color(theme().info));
+        }
+    }
+    println!(
+        "\n{}",
+        message_with(
+            Message::DoctorSummary,
+            &[("passed", &count(Status::Pass)), ("warned", &count(Status::Warn)), ("failed", &count(Status::Fail))],
+        )
+    );
+    Ok(())
+}
+
+/// Prints the document of the `json` and `yaml` formats, wrapping the prompt with its token
+/// count, its files, the template and options it was generated with, and how long it took.
+///
+/// Each file is listed with its path, extension, size, token count, the SHA-256 digest of the
+/// file and its content, so downstream tools do not have to read the codebase again.
+///
+/// # Arguments
+///
+/// * `formatter` - The formatter of the format.
+/// * `processed` - The processed codebase.
+/// * `config` - The application configuration.
 /// * `directory_name` - The name of the directory.
-/// * `token_count` - The number of tokens in the rendered template.
-/// * `model_info` - The model information string.
-/// * `files` - A vector of file paths.
+/// * `elapsed` - The time the run took so far.
+/// * `stages` - The time spent in each stage, recorded with `--timings`.
 ///
 /// # Returns
 ///
 /// * `Result<()>` - An empty result indicating success or an error.
-pub fn print_json_output(
-    rendered: &str,
+pub fn print_structured_output(
+    formatter: &dyn Formatter,
+    processed: &ProcessedCodebase,
+    config: &Options,
     directory_name: &str,
-    token_count: usize,
-    model_info: &str,
-    files: Vec<String>,
+    elapsed: Duration,
+    stages: &[StageTiming],
 ) -> Result<()> {
-    let json_output = json!({
-        "prompt": rendered,
+    let milliseconds = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    let files: Vec<Value> = processed
+        .data["files"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|file| {
+            json!({
+                "path": file["path"],
+                "extension": file["extension"],
+                "size": file["size"],
+                "token_count": file["token_count"],
+                "sha256": file["sha256"],
+                "content": file["content"],
+            })
+        })
+        .collect();
+    let stages: Vec<Value> = stages
+        .iter()
+        .map(|stage| json!({ "name": stage.name, "calls": stage.calls, "ms": milliseconds(stage.total) }))
+        .collect();
+    let template = match &config.template {
+        Some(path) => path.display().to_string(),
+        None => processed.template.clone(),
+    };
+    let document = json!({
+        "prompt": processed.rendered,
         "directory_name": directory_name,
-        "token_count": token_count,
-        "model_info": model_info,
+        "token_count": processed.token_count.unwrap_or(0),
+        "model_info": processed.model_info,
+        "template": template,
         "files": files,
+        "config": config,
+        "timing": { "total_ms": milliseconds(elapsed), "stages": stages },
     });
-    println!("{}", serde_json::to_string_pretty(&json_output)?);
+    println!("{}", formatter.render(&document)?.trim_end());
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Prints an error as JSON to stdout, for runs with `--json`.
+///
+/// Template errors include their template, line, column and offending variable or helper.
+///
+/// # Arguments
+///
+/// * `error` - The error that stopped the run.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+pub fn print_json_error(error: &anyhow::Error) -> Result<()> {
+    let mut json_error = json!({ "message": error.to_string() });
+    if let Some(template_error) = error.downcast_ref::<TemplateError>() {
+        json_error["message"] = json!(template_error.reason);
+        json_error["template"] = serde_json::to_value(template_error)?;
+    }
+    println!("{}", serde_json::to_string_pretty(&json!({ "error": json_error }))?);
+    Ok(())
+}
+
+/// Returns the path of the stats sidecar file for an output file.
+///
+/// # Arguments
+///
+/// * `output_path` - The path to the output file.
+///
+/// # Returns
+///
+/// * `String` - The path to `<output>.stats.json`.
+pub fn stats_path(output_path: &str) -> String {
+    format!("{}.stats.json", output_path)
+}
+
+/// Writes the stats sidecar file describing a generated prompt.
+///
+/// The file contains the included files with their token counts, the warnings raised
+/// while processing, and a snapshot of the configuration used.
+///
+/// # Arguments
+///
+/// * `output_path` - The path to the output file the stats describe.
+/// * `processed` - The processed codebase.
+/// * `config` - The application configuration.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+pub fn write_stats_file(output_path: &str, processed: &ProcessedCodebase, config: &Options) -> Result<()> {
+    let files: Vec<Value> = processed
+        .data
+        .get("files")
+        .and_then(|files| files.as_array())
+        .map(|files| {
+            files
+                .iter()
+                .map(|file| {
+                    json!({
+                        "path": file.get("path"),
+                        "token_count": file.get("token_count"),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let stats = json!({
+        "output": output_path,
+        "token_count": processed.token_count,
+        "model_info": processed.model_info,
+        "files": files,
+        "skipped": processed.skipped,
+        "warnings": processed.warnings,
+        "config": config,
+    });
+
+    let path = stats_path(output_path);
+    write_atomic(Path::new(&path), serde_json::to_string_pretty(&stats)?.as_bytes())
+        .with_context(|| message_with(Message::StatsWriteFailed, &[("path", &path)]))?;
+    Ok(())
+}
+
+/// Reads the output file left by a previous run, along with the record of its prompt, or its
+/// stats sidecar file for a prompt written before outputs were recorded.
+///
+/// # Arguments
+///
+/// * `output_path` - The path to the output file.
+/// * `compression` - The compression of the output file, if any.
+///
+/// # Returns
+///
+/// * `Option<PreviousOutput>` - The previous output, or `None` if the file does not exist.
+pub fn read_previous_output(output_path: &str, compression: Option<Compression>) -> Option<PreviousOutput> {
+    let content = match compression {
+        Some(compression) => decompress(&fs::read(output_path).ok()?, compression).ok()?,
+        None => fs::read_to_string(output_path).ok()?,
+    };
+    if let Ok(Some(run)) = load_output(Path::new(output_path)) {
+        return Some(PreviousOutput {
+            content,
+            paths: Some(run.files),
+            token_count: run.token_count,
+        });
+    }
+    let stats = fs::read_to_string(stats_path(output_path))
+        .ok()
+        .and_then(|stats| serde_json::from_str::<Value>(&stats).ok());
+    let paths = stats.as_ref().and_then(|stats| {
+        stats.get("files").and_then(|files| files.as_array()).map(|files| {
+            files
+                .iter()
+                .filter_map(|file| file.get("path").and_then(|p| p.as_str()).map(String::from))
+                .collect()
+        })
+    });
+    let token_count = stats
+        .as_ref()
+        .and_then(|stats| stats["token_count"].as_u64())
+        .map(|count| count as usize);
+    Some(PreviousOutput {
+        content,
+        paths,
+        token_count,
+    })
+}
+
+/// Prints how the new output differs from the previous one.
+///
+/// File additions and removals are reported when the previous prompt was recorded or had a
+/// stats sidecar file. The token change is estimated, and prefixed with `~`, when either
+/// prompt had its tokens estimated.
+///
+/// # Arguments
+///
+/// * `previous` - The output written by the previous run.
+/// * `processed` - The processed codebase of the new output.
+pub fn print_output_delta(previous: &PreviousOutput, processed: &ProcessedCodebase) {
+    let (rendered, paths) = (&processed.rendered, &processed.paths);
+    let line_delta = rendered.lines().count() as i64 - previous.content.lines().count() as i64;
+
+    let mut changes = Vec::new();
+    if let Some(previous_paths) = &previous.paths {
+        let added = paths.iter().filter(|p| !previous_paths.contains(p)).count();
+        let removed = previous_paths.iter().filter(|p| !paths.contains(p)).count();
+        changes.push(message_with(
+            Message::FilesDelta,
+            &[("added", &added), ("removed", &removed)],
+        ));
+    }
+    changes.push(message_with(Message::LinesDelta, &[("delta", &format!("{:+}", line_delta))]));
+    let (tokens, estimated) = processed.tokens();
+    let (previous_tokens, previous_estimated) = match previous.token_count {
+        Some(token_count) => (token_count, false),
+        None => (previous.content.len().div_ceil(4), true),
+    };
+    let mark = if estimated || previous_estimated { "~" } else { "" };
+    let token_delta = tokens as i64 - previous_tokens as i64;
+    changes.push(message_with(Message::TokensDelta, &[("delta", &format!("{}{:+}", mark, token_delta))]));
+
+    let changes = changes.join(", ").bold().color(theme().highlight);
+    print_status_line(&format!(
+        "{} {}",
+        status_prefix("i", theme().info),
+        message_with(Message::ChangesSinceLastOutput, &[("changes", &changes)])
+    ));
+}
@@ -0,0 +1,88 @@
+//! This module checks the clipboard content against the paste limits of chat interfaces,
+//! which may truncate or reject pastes above a certain size.
+
+use crate::config::PasteLimit;
+use crate::messages::{message_with, Message};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// Returns the built-in paste limit of a target.
+///
+/// The limits are approximate and can be overridden in the `[paste_limits]` table of the
+/// configuration files.
+///
+/// # Arguments
+///
+/// * `target` - The name of the target.
+///
+/// # Returns
+///
+/// * `Option<PasteLimit>` - The limit, or `None` if the target is not built in.
+pub fn builtin_paste_limit(target: &str) -> Option<PasteLimit> {
+    match target {
+        "chatgpt" => Some(PasteLimit {
+            bytes: None,
+            characters: Some(100_000),
+        }),
+        "claude-web" => Some(PasteLimit {
+            bytes: None,
+            characters: Some(200_000),
+        }),
+        "gemini" => Some(PasteLimit {
+            bytes: None,
+            characters: Some(30_000),
+        }),
+        _ => None,
+    }
+}
+
+/// Resolves the paste limit of a target, preferring the limits from the configuration files.
+///
+/// # Arguments
+///
+/// * `target` - The name of the target.
+/// * `overrides` - The limits loaded from the configuration files, by target.
+///
+/// # Returns
+///
+/// * `Result<PasteLimit>` - The limit, or an error if the target is unknown.
+pub fn paste_limit(target: &str, overrides: &HashMap<String, PasteLimit>) -> Result<PasteLimit> {
+    overrides
+        .get(target)
+        .copied()
+        .or_else(|| builtin_paste_limit(target))
+        .ok_or_else(|| anyhow!(message_with(Message::UnknownPasteTarget, &[("target", &target)])))
+}
+
+/// Checks content against a paste limit.
+///
+/// # Arguments
+///
+/// * `content` - The content copied to the clipboard.
+/// * `target` - The name of the target, used in the warnings.
+/// * `limit` - The paste limit of the target.
+///
+/// # Returns
+///
+/// * `Vec<String>` - One warning per exceeded threshold.
+pub fn check_paste_limit(content: &str, target: &str, limit: &PasteLimit) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(max) = limit.characters {
+        let count = content.chars().count();
+        if count > max {
+            warnings.push(message_with(
+                Message::PasteCharactersExceeded,
+                &[("count", &count), ("limit", &max), ("target", &target)],
+            ));
+        }
+    }
+    if let Some(max) = limit.bytes {
+        if content.len() > max {
+            warnings.push(message_with(
+                Message::PasteBytesExceeded,
+                &[("count", &content.len()), ("limit", &max), ("target", &target)],
+            ));
+        }
+    }
+    warnings
+}
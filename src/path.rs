@@ -1,235 +1,1431 @@
 //! This module contains the functions for traversing the directory and processing the files.
 
-use crate::filter::should_include_file;
+use crate::binary::{binary_placeholder, is_binary};
+use crate::boilerplate::{find_common_headers, strip_header, CommonHeader};
+use crate::charset::{decode_lossy, decode_text, decode_utf16};
+use crate::config::{FileSize, SortOrder, TreeStyle};
+use crate::filter::{
+    first_matching_pattern, is_generated_content, is_generated_file, is_sensitive_file, last_match, should_include_file,
+};
+use crate::gitattributes::GitAttributes;
+use crate::language::{detect_language, extension_language, matches_language};
+use crate::output::ReadProgress;
+use crate::secrets::redact_secrets;
+use crate::select::{Candidate, Selection};
+use crate::text::{anchor_lines, has_conflict_markers, number_lines_from};
+use crate::token::estimate_tokens;
 use anyhow::Result;
-use ignore::WalkBuilder;
-use log::debug;
+use encoding_rs::UTF_8;
+use glob::Pattern;
+use ignore::{Walk, WalkBuilder};
+use rayon::prelude::*;
+use regex::Regex;
 use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::fs;
-use std::path::{Path, PathBuf};
-use termtree::Tree;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use termtree::{GlyphPalette, Tree};
+use tiktoken_rs::CoreBPE;
+use tracing::{debug, instrument};
 
-/// Traverses the directory and returns the string representation of the tree and the vector of JSON file representations.
+/// ASCII replacements for the box-drawing characters used by the tree renderer.
+const ASCII_GLYPHS: GlyphPalette = GlyphPalette {
+    middle_item: "|",
+    last_item: "`",
+    item_indent: "-- ",
+    middle_skip: "|",
+    last_skip: " ",
+    skip_indent: "   ",
+};
+
+/// Glyphs drawing the tree with two spaces of indentation per level.
+const INDENT_GLYPHS: GlyphPalette = GlyphPalette {
+    middle_item: "",
+    last_item: "",
+    item_indent: "  ",
+    middle_skip: "",
+    last_skip: "",
+    skip_indent: "  ",
+};
+
+/// The number of times a file changing while being read is read again.
+const MAX_READ_ATTEMPTS: usize = 3;
+
+/// The name of the ignore files specific to codexio, using the `.gitignore` syntax.
+pub const IGNORE_FILE: &str = ".codexioignore";
+
+/// Directories holding dependencies, build output or caches, left out unless `--no-default-excludes` is given.
+pub const DEFAULT_EXCLUDED_DIRECTORIES: [&str; 12] = [
+    "node_modules",
+    "target",
+    "dist",
+    ".venv",
+    "venv",
+    "__pycache__",
+    ".tox",
+    ".mypy_cache",
+    ".pytest_cache",
+    ".next",
+    ".gradle",
+    ".terraform",
+];
+
+/// File names of lock files generated by package managers, left out unless `--no-default-excludes`
+/// is given or an include pattern matches them.
+pub const LOCK_FILES: [&str; 9] = [
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    "uv.lock",
+];
+
+/// Options controlling which files are traversed and how they are rendered.
+#[derive(Debug, Default)]
+pub struct TraversalOptions {
+    /// The patterns of files to include.
+    pub include: Vec<String>,
+    /// The patterns of files to exclude.
+    pub exclude: Vec<String>,
+    /// Whether to give priority to include patterns.
+    pub include_priority: bool,
+    /// Whether to add line numbers to the code.
+    pub line_number: bool,
+    /// The patterns of the files to add line numbers to, even without `line_number`.
+    pub line_number_for: Vec<String>,
+    /// The canonical paths of the files to add line numbers to, such as the files of the diff.
+    pub line_number_paths: HashSet<PathBuf>,
+    /// Whether to prefix each line with its anchor instead, for the `patch` preset.
+    pub line_anchors: bool,
+    /// Whether to use relative paths.
+    pub relative_paths: bool,
+    /// Whether to exclude files/folders from the tree based on exclude patterns.
+    pub exclude_from_tree: bool,
+    /// Whether to show the size and token count of the packed files and directories in the tree.
+    pub annotate_tree: bool,
+    /// Whether to disable wrapping code inside markdown code blocks.
+    pub no_codeblock: bool,
+    /// How to draw the tree.
+    pub tree_style: TreeStyle,
+    /// Canonical paths left out of the tree and the files, such as the output file.
+    pub skip_paths: Vec<PathBuf>,
+    /// Whether to disable all ignore files, including `.gitignore` and `.codexioignore`.
+    pub no_ignore: bool,
+    /// Whether to disable the git ignore rules only.
+    pub no_gitignore: bool,
+    /// Whether to traverse hidden files and directories. The `.git` directory is always skipped.
+    pub hidden: bool,
+    /// Whether to descend into symlinked directories. Symlink cycles are detected and skipped.
+    pub follow_symlinks: bool,
+    /// Whether to pack symlinked files whose target is outside the root, under the path of the link.
+    pub include_external_symlinks: bool,
+    /// The number of levels traversed below each root, or `None` for no limit.
+    pub max_depth: Option<usize>,
+    /// The size above which files are skipped, or `None` for no limit.
+    pub max_file_size: Option<FileSize>,
+    /// The progress of reading the files, if it is shown.
+    pub progress: Option<ReadProgress>,
+    /// Whether to decode files that are not valid UTF-8 lossily instead of detecting their encoding.
+    pub lossy: bool,
+    /// Whether to render the leading comment blocks shared by several files once, as common headers.
+    pub dedupe_headers: bool,
+    /// Whether to replace the secrets found in files with placeholders.
+    pub redact: bool,
+    /// Only files whose content matches this expression are packed, if set.
+    pub include_containing: Option<Regex>,
+    /// Files whose content matches this expression are skipped, if set.
+    pub exclude_containing: Option<Regex>,
+    /// Only files in these lowercase languages or extensions are packed, unless empty.
+    pub languages: Vec<String>,
+    /// Only files selected by this expression are packed, if set.
+    pub selection: Option<Selection>,
+    /// The number of seconds since each recently changed file last changed in git, for `changed:` filters.
+    pub change_ages: HashMap<PathBuf, u64>,
+    /// Whether to leave out the files marked `linguist-generated` or `linguist-vendored` in `.gitattributes`.
+    pub linguist_excludes: bool,
+    /// Whether to leave out the files that look generated or minified, from their name or content.
+    pub generated_excludes: bool,
+    /// Whether to leave out the default excluded directories and lock files.
+    pub default_excludes: bool,
+    /// Whether to pack the files holding credentials, such as `.env` files and private keys.
+    pub allow_sensitive: bool,
+    /// Whether to traverse git repositories nested inside a root.
+    pub include_nested_repos: bool,
+    /// Canonical paths of the files git does not check out, such as those outside a sparse checkout.
+    pub skip_worktree: HashSet<PathBuf>,
+    /// The order of the files, or `None` to keep the order of the tree.
+    pub sort: Option<SortOrder>,
+    /// The patterns of the roots of a workspace file, by canonical root path.
+    pub root_patterns: HashMap<PathBuf, RootPatterns>,
+    /// The patterns of the priority tiers, in order, recording on each file the first tier it matches.
+    pub tiers: Vec<Vec<String>>,
+    /// Whether to estimate the token count of each file from its size, recorded as `estimated_tokens`.
+    pub estimate: bool,
+    /// The token budget the estimates of the files are fit to before reading them, if any.
+    pub estimate_budget: Option<usize>,
+    /// Whether to record the content of each text file and the SHA-256 digest of each file, for
+    /// the documents of `--format json` and `--format yaml`.
+    pub file_contents: bool,
+}
+
+/// The patterns a root of a workspace file applies on top of the shared ones.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct RootPatterns {
+    /// The patterns of files to include, anchored at the root.
+    pub include: Vec<String>,
+    /// The patterns of files to exclude, anchored at the root.
+    pub exclude: Vec<String>,
+}
+
+impl RootPatterns {
+    /// Anchors patterns relative to a root at its canonical path, as patterns are matched
+    /// against canonical paths. Negated patterns stay negated.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The canonical path of the root.
+    /// * `include` - The patterns of files to include, relative to the root.
+    /// * `exclude` - The patterns of files to exclude, relative to the root.
+    ///
+    /// # Returns
+    ///
+    /// * `RootPatterns` - The anchored patterns.
+    pub fn anchored(root: &Path, include: &[String], exclude: &[String]) -> RootPatterns {
+        let prefix = Pattern::escape(&root.to_string_lossy());
+        let anchor = |pattern: &String| match pattern.strip_prefix('!') {
+            Some(pattern) => format!("!{}/{}", prefix, pattern),
+            None => format!("{}/{}", prefix, pattern),
+        };
+        RootPatterns {
+            include: include.iter().map(anchor).collect(),
+            exclude: exclude.iter().map(anchor).collect(),
+        }
+    }
+}
+
+/// The result of traversing the root paths.
+#[derive(Debug)]
+pub struct Traversal {
+    /// The string representation of the directory tree.
+    pub tree: String,
+    /// The JSON representations of the included files.
+    pub files: Vec<serde_json::Value>,
+    /// The number of files that were seen but left out of the prompt.
+    pub skipped: usize,
+    /// The git repositories found nested inside the roots, excluding submodules.
+    pub nested_repos: Vec<PathBuf>,
+    /// The files skipped for being larger than the maximum file size, with their sizes.
+    pub oversized: Vec<(PathBuf, FileSize)>,
+    /// The symlinked files skipped for pointing outside their root, with their targets.
+    pub external_symlinks: Vec<(PathBuf, PathBuf)>,
+    /// The files that changed while being packed, whose latest content was packed.
+    pub modified: Vec<PathBuf>,
+    /// The files deleted while being packed, which were left out of the tree and the files.
+    pub vanished: Vec<PathBuf>,
+    /// The sensitive files skipped although an include pattern matched them or they were listed.
+    pub sensitive: Vec<PathBuf>,
+    /// The headers shared by several files, which were removed from them.
+    pub common_headers: Vec<CommonHeader>,
+    /// The number of files left out without being read, as their estimated tokens exceed the budget.
+    pub over_budget: usize,
+}
+
+/// A file collected during the walk, to be read once the walk is over.
+#[derive(Debug, Clone)]
+struct CollectedFile {
+    /// The index of the root the file was collected from.
+    root: usize,
+    /// The path to the file.
+    path: PathBuf,
+    /// The path of the file in the tree of its root.
+    tree_path: PathBuf,
+    /// The size and modification time of the file when it was collected.
+    stamp: Option<FileStamp>,
+}
+
+/// The size and modification time of a file, compared to detect files changing while packed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    /// The size of the file in bytes.
+    len: u64,
+    /// The modification time of the file, if the platform reports one.
+    modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    /// Reads the stamp of a file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path to the file.
+    ///
+    /// # Returns
+    ///
+    /// * `io::Result<FileStamp>` - The size and modification time of the file.
+    fn of(path: &Path) -> io::Result<FileStamp> {
+        let metadata = fs::metadata(path)?;
+        Ok(FileStamp {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// The outcome of reading a collected file.
+#[derive(Debug)]
+enum FileOutcome {
+    /// The file is part of the prompt, and `changed` is set if it was modified while being packed.
+    Included { file: serde_json::Value, changed: bool },
+    /// The file was left out, such as an empty file.
+    Skipped,
+    /// The file was deleted between the walk and the read.
+    Vanished,
+}
+
+/// A root directory or file being traversed.
+#[derive(Debug)]
+struct Root {
+    /// The canonicalized path of the root.
+    canonical_path: PathBuf,
+    /// The label used for the root in the tree and in relative file paths.
+    label: String,
+}
+
+/// Traverses the root paths and returns the directory tree along with the included files.
+///
+/// With a single root, the tree is rooted at that directory. With several roots, each one
+/// becomes a top-level node of a merged tree, labeled so that roots sharing a name stay distinct.
 ///
 /// # Arguments
 ///
-/// * `root_path` - The path to the root directory.
-/// * `include` - The patterns of files to include.
-/// * `exclude` - The patterns of files to exclude.
-/// * `include_priority` - Whether to give priority to include patterns.
-/// * `line_number` - Whether to add line numbers to the code.
-/// * `relative_paths` - Whether to use relative paths.
-/// * `exclude_from_tree` - Whether to exclude files/folders from the tree based on exclude patterns.
-/// * `no_codeblock` - Whether to disable wrapping code inside markdown code blocks.
+/// * `root_paths` - The paths to the root directories or files.
+/// * `options` - The traversal and rendering options.
+/// * `tokenizer` - The tokenizer used to count tokens per file, if token counting is enabled.
 ///
 /// # Returns
 ///
-/// A tuple containing the string representation of the directory tree and a vector of JSON representations of the files.
+/// * `Result<Traversal>` - The directory tree, the JSON representations of the files, and the number of skipped files.
+#[instrument(name = "traversal", skip_all)]
 pub fn traverse_directory(
-    root_path: &Path,
-    include: &[String],
-    exclude: &[String],
-    include_priority: bool,
-    line_number: bool,
-    relative_paths: bool,
-    exclude_from_tree: bool,
-    no_codeblock: bool,
-) -> Result<(String, Vec<serde_json::Value>)> {
+    root_paths: &[PathBuf],
+    options: &TraversalOptions,
+    tokenizer: Option<&CoreBPE>,
+) -> Result<Traversal> {
     // ~~~ Initialization ~~~
-    let mut files = Vec::new();
-    let canonical_root_path = root_path.canonicalize()?;
-
-    // ~~~ Build the Tree ~~~
-    let tree = build_directory_tree(
-        &canonical_root_path,
-        include,
-        exclude,
-        include_priority,
-        exclude_from_tree,
-    )?;
-
-    // ~~~ Process the files ~~~
-    process_files(
-        &canonical_root_path,
-        &mut files,
-        include,
-        exclude,
-        include_priority,
-        line_number,
-        relative_paths,
-        no_codeblock,
-    )?;
-
-    Ok((tree.to_string(), files))
-}
-
-/// Builds the directory tree structure.
+    let roots = root_paths
+        .iter()
+        .zip(root_labels(root_paths))
+        .map(|(path, label)| -> Result<Root> {
+            Ok(Root {
+                canonical_path: path.canonicalize()?,
+                label,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut trees = Vec::new();
+    let mut file_paths = Vec::new();
+    let mut skipped = 0;
+    let mut oversized = Vec::new();
+    let mut external_symlinks = Vec::new();
+    let mut sensitive = Vec::new();
+    let nested_repos = Arc::new(Mutex::new(Vec::new()));
+    let mut attributes = GitAttributes::default();
+
+    // ~~~ Walk each root once, building its tree and collecting files ~~~
+    for (index, root) in roots.iter().enumerate() {
+        let mut tree = Tree::new(root.label.to_owned());
+        let root_patterns = options.root_patterns.get(&root.canonical_path);
+        for entry in build_walker(&root.canonical_path, options, Arc::clone(&nested_repos))
+            .filter_map(|entry| {
+                entry
+                    .map_err(|e| debug!(error = %e, "Skipped entry during traversal"))
+                    .ok()
+            })
+        {
+            let path = entry.path();
+            if options.skip_paths.iter().any(|skip| skip == path) {
+                debug!(path = %path.display(), reason = "output file", "Skipped file");
+                continue;
+            }
+            if options.skip_worktree.contains(path) {
+                debug!(path = %path.display(), reason = "skip-worktree", "Skipped file");
+                continue;
+            }
+            let is_file = path.is_file();
+            let included = if is_file || options.exclude_from_tree {
+                should_include_file(
+                    path,
+                    &options.include,
+                    &options.exclude,
+                    options.include_priority,
+                ) && root_patterns.map_or(true, |patterns| {
+                    should_include_file(path, &patterns.include, &patterns.exclude, options.include_priority)
+                })
+            } else {
+                true
+            };
+
+            let relative_path = path.strip_prefix(&root.canonical_path).ok();
+            if !options.exclude_from_tree || included {
+                if let Some(relative_path) = relative_path {
+                    insert_into_tree(&mut tree, relative_path);
+                }
+            }
+
+            let external_target = (is_file && included && !options.include_external_symlinks)
+                .then(|| external_symlink_target(path, &root.canonical_path))
+                .flatten();
+
+            if is_file && included && is_default_excluded_file(path, options) {
+                debug!(path = %path.display(), reason = "default-excluded", "Skipped file");
+                skipped += 1;
+            } else if is_file && included && is_linguist_excluded_file(path, options, &mut attributes) {
+                debug!(path = %path.display(), reason = "linguist", "Skipped file");
+                skipped += 1;
+            } else if is_file && included && is_generated_excluded(path, options, is_generated_file(path)) {
+                debug!(path = %path.display(), reason = "generated", "Skipped file");
+                skipped += 1;
+            } else if is_file && included && !options.allow_sensitive && is_sensitive_file(path) {
+                debug!(path = %path.display(), reason = "sensitive", "Skipped file");
+                if path.to_str().is_some_and(|path| last_match(&options.include, path) == Some(true)) {
+                    sensitive.push(path.to_path_buf());
+                }
+                skipped += 1;
+            } else if let Some(target) = external_target {
+                debug!(path = %path.display(), reason = "external symlink", "Skipped file");
+                external_symlinks.push((path.to_path_buf(), target));
+                skipped += 1;
+            } else if is_file && included {
+                if let Some(size) = oversized_file(path, options.max_file_size) {
+                    debug!(path = %path.display(), reason = "oversized", "Skipped file");
+                    oversized.push((path.to_path_buf(), size));
+                    skipped += 1;
+                } else {
+                    file_paths.push(CollectedFile {
+                        root: index,
+                        tree_path: relative_path.map(Path::to_path_buf).unwrap_or_default(),
+                        stamp: FileStamp::of(path).ok(),
+                        path: entry.into_path(),
+                    });
+                }
+            } else if is_file {
+                debug!(path = %path.display(), reason = "pattern", "Excluded file");
+                skipped += 1;
+            }
+        }
+        trees.push(tree);
+    }
+
+    // ~~~ Process the files, dropping the deleted ones from the tree ~~~
+    let processed = read_files(&roots, &file_paths, options, tokenizer, &mut trees);
+    skipped += processed.skipped;
+
+    let mut tree = if trees.len() == 1 {
+        trees.remove(0)
+    } else {
+        Tree::new(label(".")).with_leaves(trees)
+    };
+    if let Some(glyphs) = tree_glyphs(options.tree_style) {
+        tree.set_glyphs(glyphs);
+    }
+    let nested_repos = nested_repos.lock().unwrap().clone();
+
+    Ok(Traversal {
+        tree: tree.to_string(),
+        files: processed.files,
+        skipped,
+        nested_repos,
+        oversized,
+        external_symlinks,
+        modified: processed.modified,
+        vanished: processed.vanished,
+        sensitive,
+        common_headers: processed.common_headers,
+        over_budget: processed.over_budget,
+    })
+}
+
+/// Builds the directory tree and processes exactly the listed files, in the given order.
+///
+/// Patterns and the depth limit are not applied, and the order is only changed by `sort`. The tree is rooted at the current directory, and listed paths
+/// that are not files are skipped.
 ///
 /// # Arguments
 ///
-/// * `canonical_root_path` - The canonicalized path to the root directory.
-/// * `include` - The patterns of files to include.
-/// * `exclude` - The patterns of files to exclude.
-/// * `include_priority` - Whether to give priority to include patterns.
-/// * `exclude_from_tree` - Whether to exclude files/folders from the tree based on exclude patterns.
+/// * `file_list` - The paths of the files, relative to the current directory or absolute.
+/// * `options` - The traversal and rendering options.
+/// * `tokenizer` - The tokenizer used to count tokens per file, if token counting is enabled.
 ///
 /// # Returns
 ///
-/// * `Result<Tree<String>>` - The directory tree structure.
-fn build_directory_tree(
-    canonical_root_path: &PathBuf,
-    include: &[String],
-    exclude: &[String],
-    include_priority: bool,
-    exclude_from_tree: bool,
-) -> Result<Tree<String>> {
-    let parent_directory = label(canonical_root_path);
-    let tree = WalkBuilder::new(canonical_root_path)
-        .git_ignore(true)
-        .build()
-        .filter_map(|e| e.ok())
-        .fold(
-            Tree::new(parent_directory.to_owned()),
-            |mut root, entry| {
-                let path = entry.path();
-                if let Ok(relative_path) = path.strip_prefix(canonical_root_path) {
-                    let mut current_tree = &mut root;
-                    for component in relative_path.components() {
-                        let component_str =
-                            component.as_os_str().to_string_lossy().to_string();
-
-                        // Check if the current component should be excluded from the tree
-                        if exclude_from_tree
-                            && !should_include_file(
-                            path,
-                            include,
-                            exclude,
-                            include_priority,
-                        )
-                        {
-                            break;
-                        }
-
-                        current_tree = if let Some(pos) = current_tree
-                            .leaves
-                            .iter_mut()
-                            .position(|child| child.root == component_str)
-                        {
-                            &mut current_tree.leaves[pos]
-                        } else {
-                            let new_tree = Tree::new(component_str.clone());
-                            current_tree.leaves.push(new_tree);
-                            current_tree.leaves.last_mut().unwrap()
-                        };
-                    }
-                }
-                root
-            },
-        );
-    Ok(tree)
+/// * `Result<Traversal>` - The directory tree, the JSON representations of the files, and the number of skipped files.
+#[instrument(name = "traversal", skip_all)]
+pub fn traverse_file_list(
+    file_list: &[PathBuf],
+    options: &TraversalOptions,
+    tokenizer: Option<&CoreBPE>,
+) -> Result<Traversal> {
+    let root = Root {
+        canonical_path: Path::new(".").canonicalize()?,
+        label: label("."),
+    };
+    let mut trees = vec![Tree::new(root.label.to_owned())];
+    let mut file_paths = Vec::new();
+    let mut skipped = 0;
+    let mut oversized = Vec::new();
+    let mut sensitive = Vec::new();
+
+    for listed in file_list {
+        let canonical = match listed.canonicalize() {
+            Ok(canonical) if canonical.is_file() => canonical,
+            _ => {
+                debug!(path = %listed.display(), reason = "not a file", "Skipped listed path");
+                skipped += 1;
+                continue;
+            }
+        };
+        let relative = canonical
+            .strip_prefix(&root.canonical_path)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|_| listed.components().filter(|c| *c != Component::CurDir).collect());
+        insert_into_tree(&mut trees[0], &relative);
+        if !options.allow_sensitive && is_sensitive_file(&canonical) {
+            debug!(path = %canonical.display(), reason = "sensitive", "Skipped listed file");
+            sensitive.push(canonical);
+            skipped += 1;
+            continue;
+        }
+        if let Some(size) = oversized_file(&canonical, options.max_file_size) {
+            oversized.push((canonical, size));
+            skipped += 1;
+            continue;
+        }
+        file_paths.push(CollectedFile {
+            root: 0,
+            stamp: FileStamp::of(&canonical).ok(),
+            path: canonical,
+            tree_path: relative,
+        });
+    }
+
+    let roots = [root];
+    let processed = read_files(&roots, &file_paths, options, tokenizer, &mut trees);
+    skipped += processed.skipped;
+
+    let mut tree = trees.remove(0);
+    if let Some(glyphs) = tree_glyphs(options.tree_style) {
+        tree.set_glyphs(glyphs);
+    }
+
+    Ok(Traversal {
+        tree: tree.to_string(),
+        files: processed.files,
+        skipped,
+        nested_repos: Vec::new(),
+        oversized,
+        external_symlinks: Vec::new(),
+        modified: processed.modified,
+        vanished: processed.vanished,
+        sensitive,
+        common_headers: processed.common_headers,
+        over_budget: processed.over_budget,
+    })
 }
 
-/// Processes the files in the directory, adding their JSON representations to the `files` vector.
+/// Returns the labels of the root paths, disambiguating roots that share a name.
+///
+/// Roots are labeled by their file name; when several roots have the same name, they are
+/// labeled by the path as given instead.
 ///
 /// # Arguments
 ///
-/// * `canonical_root_path` - The canonicalized path to the root directory.
-/// * `files` - The vector of JSON file representations.
-/// * `include` - The patterns of files to include.
-/// * `exclude` - The patterns of files to exclude.
-/// * `include_priority` - Whether to give priority to include patterns.
-/// * `line_number` - Whether to add line numbers to the code.
-/// * `relative_paths` - Whether to use relative paths.
-/// * `no_codeblock` - Whether to disable wrapping code inside markdown code blocks.
+/// * `root_paths` - The paths to the root directories or files.
 ///
 /// # Returns
 ///
-/// * `Result<()>` - An empty result indicating success or an error.
-fn process_files(
-    canonical_root_path: &PathBuf,
-    files: &mut Vec<serde_json::Value>,
-    include: &[String],
-    exclude: &[String],
-    include_priority: bool,
-    line_number: bool,
-    relative_paths: bool,
-    no_codeblock: bool,
-) -> Result<()> {
-    let parent_directory = label(canonical_root_path);
-    for entry in WalkBuilder::new(canonical_root_path)
-        .git_ignore(true)
-        .build()
-        .filter_map(|e| e.ok())
+/// * `Vec<String>` - One label per root path.
+pub fn root_labels(root_paths: &[PathBuf]) -> Vec<String> {
+    let names: Vec<String> = root_paths.iter().map(label).collect();
+    names
+        .iter()
+        .zip(root_paths)
+        .map(|(name, path)| {
+            if names.iter().filter(|other| *other == name).count() > 1 {
+                path.display().to_string().trim_end_matches('/').to_string()
+            } else {
+                name.to_owned()
+            }
+        })
+        .collect()
+}
+
+/// Builds the walker of a root, applying the ignore options.
+///
+/// The entries of each directory are yielded sorted by name, so the tree and the files come
+/// out in the same order on every machine, whatever order the file system lists them in.
+///
+/// # Arguments
+///
+/// * `root` - The canonical path of the root.
+/// * `options` - The traversal options.
+/// * `nested_repos` - Collects the nested git repositories found during the walk.
+///
+/// # Returns
+///
+/// * `Walk` - The iterator over the entries of the root.
+fn build_walker(root: &Path, options: &TraversalOptions, nested_repos: Arc<Mutex<Vec<PathBuf>>>) -> Walk {
+    let use_gitignore = !options.no_ignore && !options.no_gitignore;
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .hidden(!options.hidden)
+        .follow_links(options.follow_symlinks)
+        .max_depth(options.max_depth)
+        .git_ignore(use_gitignore)
+        .git_global(use_gitignore)
+        .git_exclude(use_gitignore)
+        .ignore(!options.no_ignore)
+        .sort_by_file_name(|a, b| a.cmp(b));
+    if !options.no_ignore {
+        builder.add_custom_ignore_filename(IGNORE_FILE);
+    }
+
+    let include_nested_repos = options.include_nested_repos;
+    let default_excludes = options.default_excludes;
+    builder.filter_entry(move |entry| {
+        if entry.file_name() == ".git" {
+            return false;
+        }
+        if default_excludes
+            && entry.depth() > 0
+            && entry.file_type().is_some_and(|file_type| file_type.is_dir())
+            && entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| DEFAULT_EXCLUDED_DIRECTORIES.contains(&name))
+        {
+            return false;
+        }
+        // Submodules have a `.git` file, nested repositories a `.git` directory
+        if entry.depth() == 0 || !entry.path().join(".git").is_dir() {
+            return true;
+        }
+        nested_repos.lock().unwrap().push(entry.path().to_path_buf());
+        include_nested_repos
+    });
+    builder.build()
+}
+
+/// Checks whether a file is a lock file left out by default.
+///
+/// Lock files matched by an include pattern are kept.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file.
+/// * `options` - The traversal options.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the file should be left out.
+pub fn is_default_excluded_file(path: &Path, options: &TraversalOptions) -> bool {
+    options.default_excludes
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| LOCK_FILES.contains(&name))
+        && path
+            .to_str()
+            .map_or(true, |path| last_match(&options.include, path) != Some(true))
+}
+
+/// Checks whether a file is marked `linguist-generated` or `linguist-vendored` in `.gitattributes`.
+///
+/// Marked files matched by an include pattern are kept.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file.
+/// * `options` - The traversal options.
+/// * `attributes` - The attribute files read so far.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the file should be left out.
+fn is_linguist_excluded_file(path: &Path, options: &TraversalOptions, attributes: &mut GitAttributes) -> bool {
+    if !options.linguist_excludes
+        || path
+            .to_str()
+            .is_some_and(|path| last_match(&options.include, path) == Some(true))
     {
-        let path = entry.path();
-        if path.is_file()
-            && should_include_file(path, include, exclude, include_priority)
+        return false;
+    }
+    attributes.linguist(path).is_some()
+}
+
+/// Checks whether a file that looks generated should be left out.
+///
+/// Generated files matched by an include pattern are kept.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file.
+/// * `options` - The traversal options.
+/// * `generated` - Whether the name or content of the file looks generated.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the file should be left out.
+pub fn is_generated_excluded(path: &Path, options: &TraversalOptions, generated: bool) -> bool {
+    options.generated_excludes
+        && generated
+        && path
+            .to_str()
+            .map_or(true, |path| last_match(&options.include, path) != Some(true))
+}
+
+/// Returns the target of a symlinked file when it points outside its root.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file.
+/// * `root` - The canonical path of the root the file was found in.
+///
+/// # Returns
+///
+/// * `Option<PathBuf>` - The canonical target if the file is a symlink leading outside the root, `None` otherwise.
+pub fn external_symlink_target(path: &Path, root: &Path) -> Option<PathBuf> {
+    if !path.is_symlink() {
+        return None;
+    }
+    let target = path.canonicalize().ok()?;
+    (!target.starts_with(root)).then_some(target)
+}
+
+/// Checks a file against the maximum file size.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file.
+/// * `max_file_size` - The size above which files are skipped, if any.
+///
+/// # Returns
+///
+/// * `Option<FileSize>` - The size of the file if it is above the maximum, `None` otherwise.
+pub fn oversized_file(path: &Path, max_file_size: Option<FileSize>) -> Option<FileSize> {
+    let max_file_size = max_file_size?;
+    let size = FileSize(fs::metadata(path).ok()?.len());
+    (size > max_file_size).then_some(size)
+}
+
+/// Lists the files of a codebase with the default filters, for summaries of the whole codebase
+/// such as its languages and manifests, whatever the patterns of the run select.
+///
+/// Ignored and hidden files are left out, along with the default excluded directories.
+///
+/// # Arguments
+///
+/// * `root` - The directory to list, or a single file.
+///
+/// # Returns
+///
+/// * `Vec<PathBuf>` - The paths of the files, sorted by name within each directory.
+pub fn codebase_files(root: &Path) -> Vec<PathBuf> {
+    WalkBuilder::new(root)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_some_and(|file_type| file_type.is_dir())
+                || !entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| DEFAULT_EXCLUDED_DIRECTORIES.contains(&name))
+        })
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|file_type| file_type.is_file()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Returns the canonical form of a path that may not exist yet.
+///
+/// # Arguments
+///
+/// * `path` - The path, such as an output file about to be written.
+///
+/// # Returns
+///
+/// * `Option<PathBuf>` - The canonical path, or `None` if its parent directory does not exist.
+pub fn canonical_path(path: &Path) -> Option<PathBuf> {
+    if let Ok(canonical) = path.canonicalize() {
+        return Some(canonical);
+    }
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    Some(parent.canonicalize().ok()?.join(path.file_name()?))
+}
+
+/// Returns the glyphs drawing the tree in a style.
+///
+/// # Arguments
+///
+/// * `style` - The style of the tree.
+///
+/// # Returns
+///
+/// * `Option<GlyphPalette>` - The glyphs, or `None` for the default box-drawing characters.
+fn tree_glyphs(style: TreeStyle) -> Option<GlyphPalette> {
+    match style {
+        TreeStyle::Unicode => None,
+        TreeStyle::Ascii => Some(ASCII_GLYPHS),
+        TreeStyle::Indent => Some(INDENT_GLYPHS),
+    }
+}
+
+/// Inserts a path into the directory tree, creating any missing intermediate nodes.
+///
+/// # Arguments
+///
+/// * `root` - The root of the directory tree.
+/// * `relative_path` - The path to insert, relative to the root directory.
+fn insert_into_tree(root: &mut Tree<String>, relative_path: &Path) {
+    let mut current_tree = root;
+    for component in relative_path.components() {
+        let component_str = component.as_os_str().to_string_lossy().to_string();
+
+        current_tree = if let Some(pos) = current_tree
+            .leaves
+            .iter()
+            .position(|child| child.root == component_str)
         {
-            process_file(
-                path,
-                files,
-                &parent_directory,
-                relative_paths,
-                line_number,
-                no_codeblock,
-            )?;
+            &mut current_tree.leaves[pos]
+        } else {
+            current_tree.leaves.push(Tree::new(component_str));
+            current_tree.leaves.last_mut().unwrap()
+        };
+    }
+}
+
+/// Appends the size and token count of the packed files to their nodes in the directory tree,
+/// and their totals to the directories holding them, such as `main.rs (2.1 KB, 512 tok)`.
+///
+/// # Arguments
+///
+/// * `node` - The node to annotate, along with the nodes below it.
+/// * `path` - The path of the node, relative to the root directory.
+/// * `stats` - The stats of the packed files, by path relative to the root directory.
+///
+/// # Returns
+///
+/// * `Option<NodeStats>` - The stats of the node, or `None` if no packed file is below it.
+fn annotate_tree(node: &mut Tree<String>, path: &Path, stats: &HashMap<PathBuf, NodeStats>) -> Option<NodeStats> {
+    let node_stats = if node.leaves.is_empty() {
+        stats.get(path).copied()
+    } else {
+        let mut total: Option<NodeStats> = None;
+        for leaf in &mut node.leaves {
+            let leaf_path = path.join(&leaf.root);
+            if let Some(leaf_stats) = annotate_tree(leaf, &leaf_path, stats) {
+                total.get_or_insert_with(NodeStats::default).add(leaf_stats);
+            }
+        }
+        total
+    };
+    if let Some(node_stats) = node_stats {
+        node.root = format!("{} ({})", node.root, node_stats);
+    }
+    node_stats
+}
+
+/// Removes a path from the directory tree, along with the directories it leaves empty.
+///
+/// # Arguments
+///
+/// * `root` - The root of the directory tree.
+/// * `relative_path` - The path to remove, relative to the root directory.
+fn remove_from_tree(root: &mut Tree<String>, relative_path: &Path) {
+    let mut components = relative_path.components();
+    let Some(first) = components.next() else {
+        return;
+    };
+    let name = first.as_os_str().to_string_lossy();
+    let Some(pos) = root.leaves.iter().position(|child| child.root == name) else {
+        return;
+    };
+    let rest = components.as_path();
+    if rest.as_os_str().is_empty() {
+        root.leaves.remove(pos);
+    } else {
+        remove_from_tree(&mut root.leaves[pos], rest);
+        if root.leaves[pos].leaves.is_empty() {
+            root.leaves.remove(pos);
+        }
+    }
+}
+
+/// The files of a traversal, sorted by the outcome of reading them.
+#[derive(Debug, Default)]
+struct ProcessedFiles {
+    /// The JSON representations of the included files.
+    files: Vec<serde_json::Value>,
+    /// The number of files that were left out.
+    skipped: usize,
+    /// The files that changed while being packed.
+    modified: Vec<PathBuf>,
+    /// The files deleted while being packed.
+    vanished: Vec<PathBuf>,
+    /// The headers shared by several files.
+    common_headers: Vec<CommonHeader>,
+    /// The size and token count of each included file, by root and path in the tree of the root.
+    node_stats: Vec<HashMap<PathBuf, NodeStats>>,
+    /// The number of files left out without being read, as their estimated tokens exceed the budget.
+    over_budget: usize,
+}
+
+/// The size and token count of a node of the tree, summed over the packed files below directories.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct NodeStats {
+    /// The size of the packed content in bytes.
+    bytes: u64,
+    /// The number of tokens, if the files were tokenized.
+    tokens: Option<u64>,
+}
+
+impl NodeStats {
+    /// Adds the stats of another node.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The stats to add.
+    fn add(&mut self, other: NodeStats) {
+        self.bytes += other.bytes;
+        self.tokens = match (self.tokens, other.tokens) {
+            (None, None) => None,
+            (tokens, other) => Some(tokens.unwrap_or(0) + other.unwrap_or(0)),
+        };
+    }
+}
+
+impl fmt::Display for NodeStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", FileSize(self.bytes))?;
+        if let Some(tokens) = self.tokens {
+            write!(f, ", {} tok", tokens)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the collected files, factoring out their common headers if enabled.
+///
+/// With an estimate budget, the files whose estimated tokens do not fit it are left out unread.
+///
+/// # Arguments
+///
+/// * `roots` - The roots the files were collected from.
+/// * `file_paths` - The collected files to read.
+/// * `options` - The traversal and rendering options.
+/// * `tokenizer` - The tokenizer used to count tokens per file, if token counting is enabled.
+/// * `trees` - The trees of the roots, indexed like the roots.
+///
+/// # Returns
+///
+/// * `ProcessedFiles` - The included files, in the order they were collected, and the files that changed.
+fn read_files(
+    roots: &[Root],
+    file_paths: &[CollectedFile],
+    options: &TraversalOptions,
+    tokenizer: Option<&CoreBPE>,
+    trees: &mut [Tree<String>],
+) -> ProcessedFiles {
+    // Files whose estimate does not fit the budget are left out before anything is read
+    let (file_paths, over_budget) = match options.estimate_budget {
+        Some(budget) => {
+            let (kept, over_budget) = fit_estimates(file_paths, &options.tiers, budget);
+            (Cow::Owned(kept), over_budget)
+        }
+        None => (Cow::Borrowed(file_paths), 0),
+    };
+    let headers = if options.dedupe_headers {
+        let paths: Vec<&Path> = file_paths.iter().map(|file| file.path.as_path()).collect();
+        let headers = find_common_headers(&paths);
+        // The files are redacted before their header is removed, so the headers must match them
+        if options.redact {
+            headers.iter().map(|header| redact_secrets(header).0.into_owned()).collect()
+        } else {
+            headers
+        }
+    } else {
+        Vec::new()
+    };
+
+    let outcomes = process_files(roots, &file_paths, options, &headers, tokenizer);
+    let mut processed = collect_outcomes(&file_paths, outcomes, options.sort, &options.include, trees);
+    processed.over_budget = over_budget;
+    processed.skipped += over_budget;
+    if options.annotate_tree {
+        for (tree, stats) in trees.iter_mut().zip(&processed.node_stats) {
+            annotate_tree(tree, Path::new(""), stats);
+        }
+    }
+    processed.common_headers = headers
+        .iter()
+        .enumerate()
+        .filter_map(|(index, header)| {
+            let id = index + 1;
+            let sharing: Vec<&serde_json::Value> = processed
+                .files
+                .iter()
+                .filter(|file| file["common_header"] == id)
+                .collect();
+            let extension = sharing.first()?["extension"].as_str().unwrap_or("");
+            Some(CommonHeader {
+                id,
+                code: wrap_code_block(header.trim_end(), extension, options.line_number, options.no_codeblock),
+                files: sharing.len(),
+            })
+        })
+        .collect();
+    processed
+}
+
+/// Keeps the collected files whose estimated tokens fit the budget, so the others are never read.
+///
+/// Files are taken by priority tier, those matching no tier last, and in the order they were
+/// collected within a tier. Once a file does not fit, it and every file after it are left out.
+///
+/// # Arguments
+///
+/// * `file_paths` - The collected files.
+/// * `tiers` - The patterns of the priority tiers, in order.
+/// * `budget` - The number of tokens the files can take.
+///
+/// # Returns
+///
+/// * `(Vec<CollectedFile>, usize)` - The files to read, in the order they were collected, and the number left out.
+fn fit_estimates(file_paths: &[CollectedFile], tiers: &[Vec<String>], budget: usize) -> (Vec<CollectedFile>, usize) {
+    let tier_of = |file: &CollectedFile| {
+        file.path
+            .to_str()
+            .and_then(|path| tiers.iter().position(|patterns| last_match(patterns, path) == Some(true)))
+            .unwrap_or(tiers.len())
+    };
+    let mut order: Vec<usize> = (0..file_paths.len()).collect();
+    order.sort_by_key(|&index| tier_of(&file_paths[index]));
+
+    let mut kept = vec![false; file_paths.len()];
+    let mut used = 0;
+    for index in order {
+        let file = &file_paths[index];
+        used += estimate_tokens(&file.path, file.stamp.map_or(0, |stamp| stamp.len));
+        if used > budget {
+            break;
+        }
+        kept[index] = true;
+    }
+    let over_budget = kept.iter().filter(|kept| !**kept).count();
+    let files = file_paths
+        .iter()
+        .zip(kept)
+        .filter(|(_, kept)| *kept)
+        .map(|(file, _)| file.clone())
+        .collect();
+    (files, over_budget)
+}
+
+/// Sorts the outcomes of reading the collected files, removing the deleted files from the trees.
+///
+/// # Arguments
+///
+/// * `file_paths` - The collected files.
+/// * `outcomes` - The outcome of reading each collected file, in the same order.
+/// * `order` - The order of the included files, or `None` to keep the order they were collected in.
+/// * `include` - The include patterns, for the `include` order.
+/// * `trees` - The trees of the roots, indexed like the roots.
+///
+/// # Returns
+///
+/// * `ProcessedFiles` - The included files, in the requested order, and the files that changed.
+fn collect_outcomes(
+    file_paths: &[CollectedFile],
+    outcomes: Vec<FileOutcome>,
+    order: Option<SortOrder>,
+    include: &[String],
+    trees: &mut [Tree<String>],
+) -> ProcessedFiles {
+    let mut processed = ProcessedFiles::default();
+    let mut included = Vec::new();
+    for (collected, outcome) in file_paths.iter().zip(outcomes) {
+        match outcome {
+            FileOutcome::Included { file, changed } => {
+                if changed {
+                    processed.modified.push(collected.path.clone());
+                }
+                // Files matching no include pattern, such as listed files, go last
+                let pattern = (order == Some(SortOrder::Include))
+                    .then(|| collected.path.to_str().and_then(|path| first_matching_pattern(include, path)))
+                    .flatten()
+                    .unwrap_or(usize::MAX);
+                let modified = collected.stamp.and_then(|stamp| stamp.modified);
+                let stats = NodeStats {
+                    bytes: file["size"].as_u64().unwrap_or(0),
+                    tokens: file["token_count"].as_u64(),
+                };
+                if processed.node_stats.len() <= collected.root {
+                    processed.node_stats.resize_with(collected.root + 1, HashMap::new);
+                }
+                processed.node_stats[collected.root].insert(collected.tree_path.clone(), stats);
+                included.push((modified, pattern, file));
+            }
+            FileOutcome::Skipped => processed.skipped += 1,
+            FileOutcome::Vanished => {
+                remove_from_tree(&mut trees[collected.root], &collected.tree_path);
+                processed.vanished.push(collected.path.clone());
+            }
         }
     }
-    Ok(())
+    if let Some(order) = order {
+        // The sort is stable, so files comparing equal keep the order of the tree
+        let number = |file: &serde_json::Value, key: &str| file[key].as_u64().unwrap_or(0);
+        // Without a tokenizer, files are sorted by their estimated tokens
+        let tokens = |file: &serde_json::Value| file["token_count"].as_u64().or(file["estimated_tokens"].as_u64());
+        included.sort_by(|(a_modified, a_pattern, a), (b_modified, b_pattern, b)| match order {
+            SortOrder::Path => a["path"].as_str().cmp(&b["path"].as_str()),
+            SortOrder::PathDesc => b["path"].as_str().cmp(&a["path"].as_str()),
+            SortOrder::Size => number(a, "size").cmp(&number(b, "size")),
+            SortOrder::SizeDesc => number(b, "size").cmp(&number(a, "size")),
+            SortOrder::Modified => a_modified.cmp(b_modified),
+            SortOrder::ModifiedDesc => b_modified.cmp(a_modified),
+            SortOrder::Tokens => tokens(a).cmp(&tokens(b)),
+            SortOrder::TokensDesc => tokens(b).cmp(&tokens(a)),
+            SortOrder::Include => a_pattern.cmp(b_pattern),
+        });
+    }
+    processed.files = included.into_iter().map(|(_, _, file)| file).collect();
+    processed
 }
 
-/// Processes a single file, adding its JSON representation to the `files` vector.
+/// Processes the collected files in parallel, returning the outcome of reading each one.
+///
+/// Files are read and formatted across threads, but the returned vector keeps the sorted
+/// order in which the walker yielded the entries. The progress, if shown, counts the files read.
+///
+/// # Arguments
+///
+/// * `roots` - The roots the files were collected from.
+/// * `file_paths` - The collected files to process.
+/// * `options` - The traversal and rendering options.
+/// * `common_headers` - The headers to remove from the files starting with them.
+/// * `tokenizer` - The tokenizer used to count tokens per file, if token counting is enabled.
+///
+/// # Returns
+///
+/// * `Vec<FileOutcome>` - The outcome of each file, in the same order.
+fn process_files(
+    roots: &[Root],
+    file_paths: &[CollectedFile],
+    options: &TraversalOptions,
+    common_headers: &[String],
+    tokenizer: Option<&CoreBPE>,
+) -> Vec<FileOutcome> {
+    if let Some(progress) = &options.progress {
+        progress.start(file_paths.len());
+    }
+    let outcomes = file_paths
+        .par_iter()
+        .map(|collected| {
+            let outcome = process_file(collected, &roots[collected.root], options, common_headers, tokenizer);
+            if let Some(progress) = &options.progress {
+                progress.advance(collected.stamp.map_or(0, |stamp| stamp.len));
+            }
+            outcome
+        })
+        .collect();
+    if let Some(progress) = &options.progress {
+        progress.finish();
+    }
+    outcomes
+}
+
+/// Reads a file until its content is stable.
+///
+/// The file is read again when its size or modification time changes during the read, up
+/// to `MAX_READ_ATTEMPTS` times, after which the last content read is kept.
 ///
 /// # Arguments
 ///
 /// * `path` - The path to the file.
-/// * `files` - The vector of JSON file representations.
-/// * `parent_directory` - The name of the parent directory.
-/// * `relative_paths` - Whether to use relative paths.
-/// * `line_number` - Whether to add line numbers to the code.
-/// * `no_codeblock` - Whether to disable wrapping code inside markdown code blocks.
+/// * `stamp` - The stamp of the file when it was collected, if known.
+///
+/// # Returns
+///
+/// * `io::Result<(Vec<u8>, bool)>` - The content of the file, and whether it changed since it was collected.
+fn read_stable(path: &Path, stamp: Option<FileStamp>) -> io::Result<(Vec<u8>, bool)> {
+    let mut changed = false;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let before = FileStamp::of(path)?;
+        let bytes = fs::read(path)?;
+        let after = FileStamp::of(path)?;
+        changed |= stamp.is_some_and(|stamp| stamp != before) || before != after;
+        if before == after || attempts == MAX_READ_ATTEMPTS {
+            return Ok((bytes, changed));
+        }
+        debug!(path = %path.display(), "File changed while being read, reading it again");
+    }
+}
+
+/// Checks whether a file is selected by the `--select` expression.
+///
+/// # Arguments
+///
+/// * `collected` - The file collected during the walk.
+/// * `root` - The root the file was collected from.
+/// * `options` - The traversal options.
+/// * `language` - The detected language of the file, if any.
+/// * `text` - The content of the file, or `None` for binary files.
 ///
 /// # Returns
 ///
-/// * `Result<()>` - An empty result indicating success or an error.
+/// * `bool` - `true` if the file is selected, or if there is no expression.
+fn is_selected(
+    collected: &CollectedFile,
+    root: &Root,
+    options: &TraversalOptions,
+    language: Option<&str>,
+    text: Option<&str>,
+) -> bool {
+    let Some(selection) = &options.selection else {
+        return true;
+    };
+    let relative_path = collected
+        .path
+        .strip_prefix(&root.canonical_path)
+        .unwrap_or(&collected.path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    selection.matches(&Candidate {
+        relative_path: &relative_path,
+        absolute_path: &collected.path.to_string_lossy(),
+        language,
+        text,
+        age: options.change_ages.get(&collected.path).copied(),
+    })
+}
+
+/// Processes a single file, returning its JSON representation.
+///
+/// Binary files are represented by a placeholder naming their size and format, such as
+/// `<binary file, 1.2 MB, PNG>`, and are marked with `"binary": true`. Text files that are
+/// not UTF-8 are transcoded, and the encoding they were decoded from is recorded as `encoding`,
+/// unless `lossy` is set, which replaces their invalid bytes and marks them with `"lossy": true`.
+///
+/// # Arguments
+///
+/// * `collected` - The file collected during the walk.
+/// * `root` - The root the file was collected from.
+/// * `options` - The traversal and rendering options.
+/// * `common_headers` - The headers to remove from the file if it starts with one of them.
+/// * `tokenizer` - The tokenizer used to count the file's tokens, if token counting is enabled.
+///
+/// # Returns
+///
+/// * `FileOutcome` - The JSON representation of the file, or why it was left out.
+#[instrument(name = "read", skip_all)]
 fn process_file(
-    path: &Path,
-    files: &mut Vec<serde_json::Value>,
-    parent_directory: &str,
-    relative_paths: bool,
-    line_number: bool,
-    no_codeblock: bool,
-) -> Result<()> {
-    if let Ok(code_bytes) = fs::read(path) {
-        let code = String::from_utf8_lossy(&code_bytes);
-
-        let code_block = wrap_code_block(
-            &code,
-            path.extension().and_then(|ext| ext.to_str()).unwrap_or(""),
-            line_number,
-            no_codeblock,
-        );
-
-        if !code.trim().is_empty() && !code.contains(char::REPLACEMENT_CHARACTER) {
-            let file_path = if relative_paths {
-                format!("{}/{}", parent_directory, path.strip_prefix(parent_directory).unwrap().display())
+    collected: &CollectedFile,
+    root: &Root,
+    options: &TraversalOptions,
+    common_headers: &[String],
+    tokenizer: Option<&CoreBPE>,
+) -> FileOutcome {
+    let path = collected.path.as_path();
+    let (code_bytes, changed) = match read_stable(path, collected.stamp) {
+        Ok(read) => read,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            debug!(path = %path.display(), "File deleted during traversal");
+            return FileOutcome::Vanished;
+        }
+        Err(e) => {
+            debug!(path = %path.display(), error = %e, "Failed to read file");
+            return FileOutcome::Skipped;
+        }
+    };
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+
+    // UTF-16 is decoded before sniffing for binary content, as it is full of NUL bytes
+    let decoded = match decode_utf16(&code_bytes) {
+        Some(decoded) => Some(decoded),
+        None if is_binary(&code_bytes) => None,
+        None if options.lossy => Some(decode_lossy(&code_bytes)),
+        None => {
+            let Some(decoded) = decode_text(&code_bytes) else {
+                debug!(path = %path.display(), reason = "undetected encoding", "Excluded file");
+                return FileOutcome::Skipped;
+            };
+            Some(decoded)
+        }
+    };
+
+    let mut common_header = None;
+    let mut language = None;
+    let mut redactions = 0;
+    let mut content = None;
+    let (code_block, binary) = match &decoded {
+        Some(decoded) => {
+            if decoded.text.trim().is_empty() {
+                debug!(path = %path.display(), reason = "empty", "Excluded file");
+                return FileOutcome::Skipped;
+            }
+            if options
+                .include_containing
+                .as_ref()
+                .is_some_and(|pattern| !pattern.is_match(&decoded.text))
+            {
+                debug!(path = %path.display(), reason = "content does not match", "Excluded file");
+                return FileOutcome::Skipped;
+            }
+            if options
+                .exclude_containing
+                .as_ref()
+                .is_some_and(|pattern| pattern.is_match(&decoded.text))
+            {
+                debug!(path = %path.display(), reason = "content matches an exclusion", "Excluded file");
+                return FileOutcome::Skipped;
+            }
+            if is_generated_excluded(path, options, is_generated_content(&decoded.text)) {
+                debug!(path = %path.display(), reason = "generated content", "Excluded file");
+                return FileOutcome::Skipped;
+            }
+            language = detect_language(path, &decoded.text);
+            if !options.languages.is_empty()
+                && !matches_language(path, language.as_deref(), &options.languages)
+            {
+                debug!(path = %path.display(), reason = "language not selected", "Excluded file");
+                return FileOutcome::Skipped;
+            }
+            if !is_selected(collected, root, options, language.as_deref(), Some(&decoded.text)) {
+                debug!(path = %path.display(), reason = "not selected by the expression", "Excluded file");
+                return FileOutcome::Skipped;
+            }
+            let fence = code_fence(extension, language.as_deref());
+            let (redacted, count) = if options.redact {
+                redact_secrets(&decoded.text)
             } else {
-                path.display().to_string()
+                (Cow::Borrowed(decoded.text.as_ref()), 0)
             };
+            redactions = count;
+            if options.file_contents {
+                content = Some(redacted.to_string());
+            }
+            let mut code: &str = &redacted;
+            let mut first_line = 1;
+            if let Some((index, (body, removed))) = common_headers
+                .iter()
+                .enumerate()
+                .find_map(|(index, header)| Some((index, strip_header(code, header)?)))
+            {
+                common_header = Some(index + 1);
+                code = body;
+                first_line += removed;
+            }
+            let code_block = if options.line_anchors {
+                wrap_code_block(&anchor_lines(code, first_line), fence, false, options.no_codeblock)
+            } else {
+                wrap_code_block_at(code, first_line, fence, numbers_lines(path, options), options.no_codeblock)
+            };
+            (code_block, false)
+        }
+        None if options.include_containing.is_some() => {
+            debug!(path = %path.display(), reason = "content does not match", "Excluded binary file");
+            return FileOutcome::Skipped;
+        }
+        None if !options.languages.is_empty() && !matches_language(path, None, &options.languages) => {
+            debug!(path = %path.display(), reason = "language not selected", "Excluded binary file");
+            return FileOutcome::Skipped;
+        }
+        None if !is_selected(collected, root, options, detect_language(path, "").as_deref(), None) => {
+            debug!(path = %path.display(), reason = "not selected by the expression", "Excluded binary file");
+            return FileOutcome::Skipped;
+        }
+        None => (binary_placeholder(&code_bytes), true),
+    };
 
-            files.push(json!({
-                "path": file_path,
-                "extension": path.extension().and_then(|ext| ext.to_str()).unwrap_or(""),
-                "code": code_block,
-            }));
-            debug!(target: "included_files", "Included file: {}", file_path);
-        } else {
-            debug!("Excluded file (empty or invalid UTF-8): {}", path.display());
+    let file_path = if options.relative_paths {
+        match path.strip_prefix(&root.canonical_path) {
+            Ok(relative) if !relative.as_os_str().is_empty() => {
+                format!("{}/{}", root.label, relative.display())
+            }
+            _ => root.label.to_owned(),
         }
     } else {
-        debug!("Failed to read file: {}", path.display());
+        path.display().to_string()
+    };
+
+    let mut file = json!({
+        "path": file_path,
+        "extension": extension,
+        "code": code_block,
+        "size": code_bytes.len(),
+    });
+    if binary {
+        file["binary"] = json!(true);
+    }
+    if let Some(language) = language {
+        file["language"] = json!(language);
+    }
+    if let Some(id) = common_header {
+        file["common_header"] = json!(id);
     }
-    Ok(())
+    if let Some(tier) = path
+        .to_str()
+        .and_then(|path| options.tiers.iter().position(|patterns| last_match(patterns, path) == Some(true)))
+    {
+        file["tier"] = json!(tier);
+    }
+    if redactions > 0 {
+        debug!(path = %path.display(), redactions, "Redacted secrets");
+        file["redactions"] = json!(redactions);
+    }
+    if let Some(decoded) = &decoded {
+        if decoded.encoding != UTF_8 {
+            file["encoding"] = json!(decoded.encoding.name());
+        }
+        if decoded.lossy {
+            debug!(path = %path.display(), "Replaced invalid UTF-8 bytes");
+            file["lossy"] = json!(true);
+        }
+        if has_conflict_markers(&decoded.text) {
+            file["conflict_markers"] = json!(true);
+        }
+    }
+    if let Some(content) = content {
+        file["content"] = json!(content);
+    }
+    if options.file_contents {
+        file["sha256"] = json!(format!("{:x}", Sha256::digest(&code_bytes)));
+    }
+    if options.estimate {
+        file["estimated_tokens"] = json!(estimate_tokens(path, code_bytes.len() as u64));
+    }
+    if let Some(bpe) = tokenizer {
+        file["token_count"] = json!(bpe.encode_with_special_tokens(&code_block).len());
+    }
+    debug!(target: "included_files", path = %file_path, "Included file");
+    FileOutcome::Included { file, changed }
 }
 
 /// Returns the file name or the string representation of the path.
@@ -258,6 +1454,45 @@ pub fn label<P: AsRef<Path>>(p: P) -> String {
     }
 }
 
+/// Returns the info string of the code block of a file.
+///
+/// Files whose extension does not give their language, such as `Makefile` or `Dockerfile.dev`,
+/// are fenced with their detected language instead.
+///
+/// # Arguments
+///
+/// * `extension` - The file extension, empty if the file has none.
+/// * `language` - The detected language of the file, if any.
+///
+/// # Returns
+///
+/// * `&str` - The language or extension to open the code block with.
+pub fn code_fence<'a>(extension: &'a str, language: Option<&'a str>) -> &'a str {
+    match language {
+        Some(language) if extension.is_empty() || extension_language(extension) != language => language,
+        _ => extension,
+    }
+}
+
+/// Checks whether line numbers are added to a file, with `--line-number`, or because it
+/// matches `--line-number-for` or is in the diff of `--line-number-diff`.
+///
+/// # Arguments
+///
+/// * `path` - The canonical path of the file.
+/// * `options` - The traversal options.
+///
+/// # Returns
+///
+/// * `bool` - Whether the lines of the file are numbered.
+fn numbers_lines(path: &Path, options: &TraversalOptions) -> bool {
+    options.line_number
+        || options.line_number_paths.contains(path)
+        || path
+            .to_str()
+            .is_some_and(|path| last_match(&options.line_number_for, path) == Some(true))
+}
+
 /// Wraps the code block with a delimiter and adds line numbers if required.
 ///
 /// # Arguments
@@ -271,16 +1506,35 @@ pub fn label<P: AsRef<Path>>(p: P) -> String {
 ///
 /// * `String` - The wrapped code block.
 pub fn wrap_code_block(code: &str, extension: &str, line_numbers: bool, no_codeblock: bool) -> String {
-    let delimiter = "`".repeat(3);
-    let mut code_with_line_numbers = String::new();
+    wrap_code_block_at(code, 1, extension, line_numbers, no_codeblock)
+}
 
-    if line_numbers {
-        for (line_number, line) in code.lines().enumerate() {
-            code_with_line_numbers.push_str(&format!("{:4} | {}\n", line_number + 1, line));
-        }
+/// Wraps code that starts at a given line of its file, numbering lines from there if required.
+///
+/// # Arguments
+///
+/// * `code` - The code block to wrap.
+/// * `first_line` - The line of the file the code starts at.
+/// * `extension` - The file extension of the code block.
+/// * `line_numbers` - Whether to add line numbers to the code.
+/// * `no_codeblock` - Whether to not wrap the code block with a delimiter.
+///
+/// # Returns
+///
+/// * `String` - The wrapped code block.
+pub fn wrap_code_block_at(
+    code: &str,
+    first_line: usize,
+    extension: &str,
+    line_numbers: bool,
+    no_codeblock: bool,
+) -> String {
+    let delimiter = "`".repeat(3);
+    let code_with_line_numbers = if line_numbers {
+        number_lines_from(code, first_line)
     } else {
-        code_with_line_numbers = code.to_string();
-    }
+        code.to_string()
+    };
 
     if no_codeblock {
         code_with_line_numbers
@@ -0,0 +1,182 @@
+//! This module defines the built-in presets, which select the files relevant to a common kind
+//! of prompt without having to write the patterns by hand.
+
+use crate::config::{FileConfig, PresetName};
+
+/// The patterns of the files describing infrastructure: container images, Terraform modules,
+/// Kubernetes manifests and Helm charts, and CI workflows.
+const INFRA_INCLUDE: [&str; 22] = [
+    "**/Dockerfile",
+    "**/Dockerfile.*",
+    "**/*.dockerfile",
+    "**/Containerfile",
+    "**/.dockerignore",
+    "**/docker-compose*.yml",
+    "**/docker-compose*.yaml",
+    "**/compose.yml",
+    "**/compose.yaml",
+    "**/*.tf",
+    "**/*.tfvars",
+    "**/*.hcl",
+    "**/k8s/**/*.yaml",
+    "**/k8s/**/*.yml",
+    "**/kubernetes/**/*.yaml",
+    "**/kubernetes/**/*.yml",
+    "**/kustomization.yaml",
+    "**/Chart.yaml",
+    "**/charts/**",
+    "**/.github/workflows/*.yml",
+    "**/.github/workflows/*.yaml",
+    "**/.github/actions/**",
+];
+
+/// The generated files matched by the infrastructure patterns.
+const INFRA_EXCLUDE: [&str; 1] = ["**/.terraform.lock.hcl"];
+
+/// The patterns of the files a security review starts from, in the order they are listed:
+/// authentication and authorization, then input parsing, then cryptography, then the
+/// dependency manifests.
+const AUDIT_INCLUDE: [&str; 25] = [
+    "**/*auth*",
+    "**/*login*",
+    "**/*session*",
+    "**/*permission*",
+    "**/*policy*",
+    "**/*rbac*",
+    "**/*oauth*",
+    "**/*jwt*",
+    "**/*password*",
+    "**/middleware/**",
+    "**/*parse*",
+    "**/*deserializ*",
+    "**/*validat*",
+    "**/*sanitiz*",
+    "**/*upload*",
+    "**/*crypt*",
+    "**/*cipher*",
+    "**/*hash*",
+    "**/*tls*",
+    "**/Cargo.toml",
+    "**/package.json",
+    "**/pyproject.toml",
+    "**/requirements*.txt",
+    "**/go.mod",
+    "**/Gemfile",
+];
+
+/// The symbols marking security-relevant code in files whose names do not give it away, such
+/// as calls spawning processes, building queries or decoding untrusted data.
+const AUDIT_SYMBOLS: &str = r"(?i)\b(authenticat\w*|authoriz\w*|bcrypt|argon2|scrypt|pbkdf2|hmac|sha1|md5|rsa|ecdsa|jwt|csrf|set_cookie|popen|subprocess|exec|eval|pickle|unmarshal|innerHTML|query_raw|raw_sql)\b|\bunsafe\s*\{|yaml\.load\(";
+
+/// The patterns of the files a new engineer reads first, in the order they are listed: the
+/// documentation, then the build configurations, then the entry points and the top-level module
+/// of each package.
+const ONBOARD_INCLUDE: [&str; 39] = [
+    "**/README*",
+    "**/ARCHITECTURE*",
+    "**/CONTRIBUTING*",
+    "**/Cargo.toml",
+    "**/package.json",
+    "**/tsconfig.json",
+    "**/pyproject.toml",
+    "**/setup.py",
+    "**/setup.cfg",
+    "**/go.mod",
+    "**/pom.xml",
+    "**/build.gradle",
+    "**/build.gradle.kts",
+    "**/settings.gradle",
+    "**/*.csproj",
+    "**/Makefile",
+    "**/CMakeLists.txt",
+    "**/Dockerfile",
+    "**/docker-compose*.yml",
+    "**/main.rs",
+    "**/lib.rs",
+    "**/mod.rs",
+    "**/main.py",
+    "**/__main__.py",
+    "**/__init__.py",
+    "**/app.py",
+    "**/manage.py",
+    "**/main.go",
+    "**/doc.go",
+    "**/index.js",
+    "**/index.ts",
+    "**/index.tsx",
+    "**/main.js",
+    "**/main.ts",
+    "**/server.js",
+    "**/server.ts",
+    "**/Program.cs",
+    "**/main.c",
+    "**/main.cpp",
+];
+
+/// The tests and fixtures matched by the onboarding patterns, which do not describe the architecture.
+const ONBOARD_EXCLUDE: [&str; 4] = ["**/tests/**", "**/test/**", "**/__tests__/**", "**/fixtures/**"];
+
+/// Returns the configuration applied by a built-in preset.
+///
+/// # Arguments
+///
+/// * `name` - The name of the built-in preset.
+///
+/// # Returns
+///
+/// * `FileConfig` - The values of the preset, merged like a profile.
+pub fn preset_config(name: PresetName) -> FileConfig {
+    match name {
+        PresetName::Infra => FileConfig {
+            include: Some(INFRA_INCLUDE.iter().map(|pattern| pattern.to_string()).collect()),
+            exclude: Some(INFRA_EXCLUDE.iter().map(|pattern| pattern.to_string()).collect()),
+            // Workflows live in `.github`, which is skipped with the other hidden directories
+            hidden: Some(true),
+            ..Default::default()
+        },
+        PresetName::Audit => {
+            // The patterns list the security-relevant files first, and the final catch-all keeps
+            // the files selected by their symbols alone, which come last
+            let mut include: Vec<String> = AUDIT_INCLUDE.iter().map(|pattern| pattern.to_string()).collect();
+            include.push("**".to_string());
+            let paths: Vec<String> = AUDIT_INCLUDE.iter().map(|pattern| format!("path:{}", pattern)).collect();
+            FileConfig {
+                include: Some(include),
+                select: Some(format!("{} | contains:\"{}\"", paths.join(" | "), AUDIT_SYMBOLS)),
+                ..Default::default()
+            }
+        }
+        PresetName::Onboard => FileConfig {
+            include: Some(ONBOARD_INCLUDE.iter().map(|pattern| pattern.to_string()).collect()),
+            exclude: Some(ONBOARD_EXCLUDE.iter().map(|pattern| pattern.to_string()).collect()),
+            ..Default::default()
+        },
+        PresetName::Patch => FileConfig {
+            line_anchors: Some(true),
+            ..Default::default()
+        },
+        PresetName::Resolve => FileConfig {
+            conflicts: Some(true),
+            ..Default::default()
+        },
+    }
+}
+
+/// Returns the built-in template of a preset, used unless a template is given.
+///
+/// # Arguments
+///
+/// * `name` - The name of the built-in preset.
+///
+/// # Returns
+///
+/// * `Option<&'static str>` - The content of the template, or `None` if the preset uses the default template.
+pub fn preset_template(name: PresetName) -> Option<&'static str> {
+    match name {
+        PresetName::Infra => None,
+        PresetName::Audit => Some(include_str!("audit_template.hbs")),
+        PresetName::Onboard => Some(include_str!("onboard_template.hbs")),
+        PresetName::Patch => Some(include_str!("patch_template.hbs")),
+        PresetName::Resolve => Some(include_str!("resolve_template.hbs")),
+    }
+}
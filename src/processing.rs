@@ -1,21 +1,87 @@
 //! This module handles the core processing logic of the application, including directory traversal,
 //! git operations, template rendering, and token counting.
 
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use crate::git::{get_git_diff, get_git_diff_between_branches, get_git_log};
-use crate::input::{create_initial_data, parse_patterns};
-use crate::path::traverse_directory;
-use crate::template::{handlebars_setup, render_template};
+use std::path::{Path, PathBuf};
+use crate::bisect::gather_bisect;
+use crate::dependencies::dependency_summary;
+use crate::explain::{explain_path, Explanation};
+use crate::git::{
+    get_change_ages, get_diff_paths, get_git_diff, get_git_diff_between_branches, get_git_log, get_merge_state,
+    get_skip_worktree_paths, MergeState,
+};
+use crate::input::{create_initial_data, parse_patterns, parse_patterns_with_files, read_file_list};
+use crate::language::language_stats;
+use crate::messages::{message, message_with, Message};
+use crate::output::{formatter, spinner_hidden, spinner_style, stats_path, ReadProgress};
+use crate::path::{
+    canonical_path, codebase_files, traverse_directory, traverse_file_list, RootPatterns, TraversalOptions,
+};
+use crate::preset::preset_template;
+use crate::release::gather_release;
+use crate::secrets::redact_secrets;
+use crate::select::{parse_selection, Selection};
+use crate::split::{part_path, split_prompt};
+use crate::template::{handlebars_setup, render_section, without_section, IncludeFile, INCLUDE_FILE_HELPER};
+use crate::theme::theme;
+use crate::tiers::{fit_tiers, ordered_tiers, TierStatus};
 use crate::token::{get_model_info, get_tokenizer};
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use colored::*;
-use indicatif::{ProgressBar, ProgressStyle};
-use log::{debug, error};
-use crate::config::Config;
+use handlebars::Handlebars;
+use indicatif::ProgressBar;
+use regex::Regex;
+use tracing::{debug, info_span};
+use crate::config::{OutputFormat, PresetName, Section, SortOrder, TreeStyle};
+use crate::options::Options;
 
 /// Constants
 const DEFAULT_TEMPLATE_NAME: &str = "default";
 const CUSTOM_TEMPLATE_NAME: &str = "custom";
+const RELEASE_NOTES_TEMPLATE_NAME: &str = "release-notes";
+const BISECT_TEMPLATE_NAME: &str = "bisect";
+const PRESET_TEMPLATE_NAME: &str = "preset";
+
+/// The result of processing a codebase.
+#[derive(Debug)]
+pub struct ProcessedCodebase {
+    /// The rendered template, or the section requested by the `render` subcommand.
+    pub rendered: String,
+    /// The content to copy to the clipboard.
+    pub clipboard: String,
+    /// The JSON data the template was rendered with.
+    pub data: serde_json::Value,
+    /// The name of the template the prompt was rendered with, such as `default` or `custom`.
+    pub template: String,
+    /// The number of tokens in the rendered template, if this invocation counts tokens.
+    pub token_count: Option<usize>,
+    /// The model information string.
+    pub model_info: String,
+    /// The paths of the included files.
+    pub paths: Vec<String>,
+    /// The number of files that were seen but left out of the prompt.
+    pub skipped: usize,
+    /// Problems that did not abort processing but may affect the prompt.
+    pub warnings: Vec<String>,
+    /// The parts of the prompt under the cap of `--split-tokens`, or none without it.
+    pub parts: Vec<String>,
+}
+
+impl ProcessedCodebase {
+    /// Returns the number of tokens of the prompt, estimated at four bytes per token when they
+    /// were not counted.
+    ///
+    /// # Returns
+    ///
+    /// * `(usize, bool)` - The number of tokens, and whether it is an estimate.
+    pub fn tokens(&self) -> (usize, bool) {
+        match self.token_count {
+            Some(token_count) => (token_count, false),
+            None => (self.rendered.len().div_ceil(4), true),
+        }
+    }
+}
 
 /// Processes the codebase based on the provided configuration.
 ///
@@ -25,84 +91,672 @@ const CUSTOM_TEMPLATE_NAME: &str = "custom";
 ///
 /// # Returns
 ///
-/// * `Result<(String, usize, String, Vec<String>)>` - A tuple containing the rendered template, token count, model info, and file paths.
-pub fn process_codebase(config: &Config) -> Result<(String, usize, String, Vec<String>)> {
+/// * `Result<ProcessedCodebase>` - The rendered template along with its data, token count, model info, and file paths.
+pub fn process_codebase(config: &Options) -> Result<ProcessedCodebase> {
     // Handlebars Template Setup
     let (template_content, template_name) = get_template(config)?;
-    let handlebars = handlebars_setup(&template_content, template_name)?;
+    let mut handlebars = handlebars_setup(&template_content, template_name)?;
+    handlebars.register_helper(
+        INCLUDE_FILE_HELPER,
+        Box::new(IncludeFile {
+            base: config.primary_dir().to_path_buf(),
+            redact: config.redact.unwrap_or(true),
+            allow_sensitive: config.allow_sensitive,
+            max_file_size: config.max_file_size,
+            line_numbers: config.line_number,
+            no_codeblock: !config.wraps_code_blocks(),
+            roots: config.path.iter().filter_map(|path| include_root(path).canonicalize().ok()).collect(),
+            budget: config.budget,
+            encoding: config.encoding.clone(),
+            ..IncludeFile::default()
+        }),
+    );
 
     // Progress Bar Setup
-    let spinner = setup_spinner("Traversing directory and building tree...");
+    let spinner = setup_spinner(message(Message::TraversingDirectory));
 
-    // Parse Patterns
-    let include_patterns = parse_patterns(&config.include)?;
-    let exclude_patterns = parse_patterns(&config.exclude)?;
+    // Tokenizer Setup, skipped entirely when no token count is reported
+    let bpe = config.counts_tokens().then(|| get_tokenizer(&config.encoding));
+    let sort_by_tokens = config.sort.is_some_and(SortOrder::needs_tokens) && !config.estimate;
+    let tiers = ordered_tiers(&config.tiers)?;
+    let fits_tiers = config.budget.is_some() && !tiers.is_empty();
+    let file_tokenizer =
+        bpe.filter(|_| {
+            config.tokens
+                || config.stats
+                || config.count_file_tokens
+                || config.list
+                || config.annotate_tree
+                || sort_by_tokens
+                || fits_tiers
+                || matches!(config.output_format(), OutputFormat::Json | OutputFormat::Yaml | OutputFormat::Jsonl)
+        });
+    let mut warnings = Vec::new();
 
     // Traverse the directory
-    let (tree, files) = traverse_directory(
-        &config.path,
-        &include_patterns,
-        &exclude_patterns,
-        config.include_priority,
-        config.line_number,
-        config.relative_paths,
-        config.exclude_from_tree,
-        config.no_codeblock,
-    )?;
+    let (root_paths, mut options) = traversal_options(config, &mut warnings)?;
+    options.progress = (!spinner_hidden()).then(|| ReadProgress::new(spinner.clone()));
+    let mut merge_state = read_merge_state(config, &mut warnings)?;
+    let mut bisect = match &config.bisect {
+        Some(range) => {
+            spinner.set_message(message_with(
+                Message::GatheringSuspectCommits,
+                &[("good", &range.good), ("bad", &range.bad)],
+            ));
+            Some(gather_bisect(config.primary_dir(), &range.good, &range.bad)?)
+        }
+        None => None,
+    };
+
+    // The conflicted files and the files touched by the suspect commits are packed on their own
+    let file_list = match (&merge_state, &bisect) {
+        (Some(state), _) => Some(
+            state
+                .conflicts
+                .iter()
+                .map(|conflict| conflict.absolute_path.clone())
+                .filter(|path| path.exists())
+                .collect(),
+        ),
+        (None, Some(bisect)) => Some(bisect.checked_out.clone()),
+        (None, None) => config.files_from.as_deref().map(read_file_list).transpose()?,
+    };
+    let traversal = match &file_list {
+        Some(file_list) => traverse_file_list(file_list, &options, file_tokenizer)?,
+        None => traverse_directory(&root_paths, &options, file_tokenizer)?,
+    };
+    let mut files = traversal.files;
+
+    // Fit the files to the budget, summarizing and then dropping the lowest tiers first
+    let mut tier_reports = Vec::new();
+    if let (Some(budget), Some(bpe), true) = (config.budget, file_tokenizer, fits_tiers) {
+        let tree_tokens = if config.no_tree { 0 } else { bpe.encode_with_special_tokens(&traversal.tree).len() };
+        let names: Vec<String> = tiers.into_iter().map(|(name, _)| name).collect();
+        tier_reports = fit_tiers(&mut files, &names, budget.saturating_sub(tree_tokens), bpe);
+        for report in &tier_reports {
+            let id = match report.status {
+                TierStatus::Full => continue,
+                TierStatus::Summarized => Message::TierSummarized,
+                TierStatus::Dropped => Message::TierDropped,
+            };
+            warnings.push(message_with(id, &[("name", &report.name), ("files", &report.files)]));
+        }
+    }
+    if let (Some(budget), true) = (config.budget, traversal.over_budget > 0) {
+        warnings.push(message_with(
+            Message::EstimatedOverBudget,
+            &[("count", &traversal.over_budget), ("budget", &budget)],
+        ));
+    }
+    for repo in &traversal.nested_repos {
+        let id = if config.include_nested_repos {
+            Message::NestedRepositoryIncluded
+        } else {
+            Message::NestedRepositoryExcluded
+        };
+        warnings.push(message_with(id, &[("path", &repo.display())]));
+    }
+    if let Some(limit) = config.max_file_size {
+        for (path, size) in &traversal.oversized {
+            warnings.push(message_with(
+                Message::FileTooLarge,
+                &[("path", &path.display()), ("size", size), ("limit", &limit)],
+            ));
+        }
+    }
+
+    for (path, target) in &traversal.external_symlinks {
+        warnings.push(message_with(
+            Message::ExternalSymlinkSkipped,
+            &[("path", &path.display()), ("target", &target.display())],
+        ));
+    }
+
+    for path in &traversal.modified {
+        warnings.push(message_with(Message::FileModified, &[("path", &path.display())]));
+    }
+    for path in &traversal.vanished {
+        warnings.push(message_with(Message::FileVanished, &[("path", &path.display())]));
+    }
+    for path in &traversal.sensitive {
+        warnings.push(message_with(Message::SensitiveFileSkipped, &[("path", &path.display())]));
+    }
+    if !config.conflicts {
+        for file in files.iter().filter(|file| file.get("conflict_markers").is_some()) {
+            let path = file["path"].as_str().unwrap_or("");
+            warnings.push(message_with(Message::ConflictMarkersFound, &[("path", &path)]));
+        }
+    }
+    let redactions: Vec<u64> = files.iter().filter_map(|file| file["redactions"].as_u64()).collect();
+    if !redactions.is_empty() {
+        warnings.push(message_with(
+            Message::SecretsRedacted,
+            &[("count", &redactions.iter().sum::<u64>()), ("files", &redactions.len())],
+        ));
+    }
+
+    // A listing only needs the files, so the git output and the prompt are left out
+    if config.list {
+        spinner.finish_and_clear();
+        let token_count = Some(files.iter().filter_map(|file| file["token_count"].as_u64()).sum::<u64>() as usize);
+        return Ok(ProcessedCodebase {
+            rendered: String::new(),
+            clipboard: String::new(),
+            paths: file_paths(&files),
+            data: serde_json::json!({ "files": files }),
+            template: template_name.to_string(),
+            token_count,
+            model_info: get_model_info(&config.encoding).parse()?,
+            skipped: traversal.skipped,
+            warnings,
+            parts: Vec::new(),
+        });
+    }
 
     // Git Diff
-    let git_diff = if config.diff {
-        spinner.set_message("Generating git diff...");
-        get_git_diff(&config.path).unwrap_or_default()
+    let mut git_diff = if (config.diff && !config.tree_only) || config.requests_section(Section::Diff) {
+        spinner.set_message(message(Message::GeneratingGitDiff));
+        get_git_diff(config.primary_dir()).unwrap_or_else(|e| {
+            warnings.push(message_with(Message::GitDiffFailed, &[("error", &e)]));
+            String::new()
+        })
     } else {
         String::new()
     };
 
     // Git Diff Between Branches
-    let git_diff_branch = get_git_diff_between_branches_with_spinner(config, &spinner)?;
+    let mut git_diff_branch =
+        get_git_diff_between_branches_with_spinner(config, &spinner, &mut warnings)?;
 
     // Git Log Between Branches
-    let git_log_branch = get_git_log_between_branches_with_spinner(config, &spinner)?;
+    let mut git_log_branch =
+        get_git_log_between_branches_with_spinner(config, &spinner, &mut warnings)?;
 
-    spinner.finish_with_message("Done!".green().to_string());
+    // Release Changes
+    let mut release = match &config.release {
+        Some(range) => {
+            spinner.set_message(message_with(
+                Message::GatheringReleaseChanges,
+                &[("from", &range.from), ("to", &range.to)],
+            ));
+            Some(gather_release(config.primary_dir(), &range.from, &range.to)?)
+        }
+        None => None,
+    };
+
+    spinner.finish_with_message(message(Message::Done).color(theme().success).to_string());
+
+    // The files were redacted as they were read, and the other texts of the prompt are redacted here
+    if config.redact.unwrap_or(true) {
+        let mut texts: Vec<&mut String> = vec![&mut git_diff, &mut git_diff_branch, &mut git_log_branch];
+        if let Some(state) = &mut merge_state {
+            for conflict in &mut state.conflicts {
+                texts.extend([&mut conflict.base, &mut conflict.ours, &mut conflict.theirs].into_iter().flatten());
+            }
+        }
+        if let Some(release) = &mut release {
+            texts.push(&mut release.diff);
+            texts.extend(release.changelog.as_mut());
+            for commit in &mut release.commits {
+                texts.extend(commit.body.as_mut());
+            }
+        }
+        if let Some(bisect) = &mut bisect {
+            for commit in &mut bisect.commits {
+                texts.push(&mut commit.diff);
+                texts.extend(commit.body.as_mut());
+            }
+        }
+        let count: usize = texts.into_iter().map(redact_in_place).sum();
+        if count > 0 {
+            warnings.push(message_with(Message::SecretsRedactedOutsideFiles, &[("count", &count)]));
+        }
+    }
 
     // Prepare JSON Data
     let mut data = create_initial_data(
         config,
-        tree,
+        traversal.tree,
         files.clone(),
         git_diff,
         git_diff_branch,
         git_log_branch,
     );
+    if !traversal.common_headers.is_empty() {
+        data["common_headers"] = serde_json::json!(traversal.common_headers);
+    }
+    if let Some(release) = release {
+        data["release"] = serde_json::to_value(release)?;
+    }
+    if !tier_reports.is_empty() {
+        data["tiers"] = serde_json::json!(tier_reports);
+    }
+    if let Some(state) = merge_state {
+        data["merge"] = serde_json::to_value(state)?;
+    }
+    if let Some(bisect) = bisect {
+        data["bisect"] = serde_json::to_value(bisect)?;
+    }
+    // The summaries of the presets cover the whole codebase, whatever their patterns select
+    if matches!(config.preset, Some(PresetName::Audit | PresetName::Onboard)) {
+        data["dependencies"] = serde_json::json!(dependency_summary(&root_paths));
+    }
+    if config.preset == Some(PresetName::Onboard) {
+        let paths: Vec<PathBuf> = root_paths.iter().flat_map(|root| codebase_files(root)).collect();
+        data["languages"] = serde_json::json!(language_stats(&paths));
+    }
 
     debug!(
         "JSON Data: {}",
         serde_json::to_string_pretty(&data).unwrap()
     );
 
-    // Handle undefined variables
-    crate::input::prompt_for_undefined_variables(&mut data, &template_content)?;
+    // Render the template, or the formats such as XML documents which take no template variables
+    let format = config.output_format();
+    if format.renders_template() {
+        if let Some(obj) = data.as_object_mut() {
+            for (name, value) in crate::input::parse_variables(&config.var)? {
+                obj.entry(name).or_insert(serde_json::json!(value));
+            }
+        }
+        let answers = config.answers.as_deref().map(crate::input::load_answers).transpose()?;
+        crate::input::prompt_for_undefined_variables(&mut data, &template_content, answers.as_ref(), !config.quiet)?;
+    }
+    let (rendered, clipboard) = match formatter(format) {
+        Some(formatter) if !format.renders_template() => {
+            let rendered = formatter.render(&data)?;
+            (rendered.clone(), rendered)
+        }
+        Some(formatter) if format.is_chat() => {
+            let rendered = formatter.render(&chat_messages(config, &handlebars, template_name, &data)?)?;
+            (rendered.clone(), rendered)
+        }
+        _ => {
+            let rendered = render_section(&handlebars, template_name, config.rendered_section(), &data)?;
+            let clipboard = match config.copy_section {
+                Section::All => rendered.clone(),
+                section => render_section(&handlebars, template_name, section, &data)?,
+            };
+            (rendered, clipboard)
+        }
+    };
 
-    // Render the template
-    let rendered = render_template(&handlebars, template_name, &data)?;
+    // Split the prompt into parts under the token cap, between its files
+    let parts = match config.split_tokens {
+        Some(cap) => {
+            if format != OutputFormat::Markdown || config.rendered_section() != Section::All {
+                bail!(message(Message::SplitTokensUnsupported));
+            }
+            let bpe = bpe.unwrap_or_else(|| get_tokenizer(&config.encoding));
+            info_span!("split").in_scope(|| split_prompt(&handlebars, template_name, &data, bpe, cap))?
+        }
+        None => Vec::new(),
+    };
 
     // Token Count
-    let token_count = if config.tokens {
-        let bpe = get_tokenizer(&config.encoding);
-        bpe.encode_with_special_tokens(&rendered).len()
+    let token_count =
+        bpe.map(|bpe| info_span!("tokenize").in_scope(|| bpe.encode_with_special_tokens(&rendered).len()));
+
+    let paths = file_paths(&files);
+
+    let model_info = get_model_info(&config.encoding);
+
+    Ok(ProcessedCodebase {
+        rendered,
+        clipboard,
+        data,
+        template: template_name.to_string(),
+        token_count,
+        model_info: model_info.parse()?,
+        paths,
+        skipped: traversal.skipped,
+        warnings,
+        parts,
+    })
+}
+
+/// Redacts the secrets of a text of the prompt other than a file.
+///
+/// # Arguments
+///
+/// * `text` - The text, replaced by its redacted version.
+///
+/// # Returns
+///
+/// * `usize` - The number of secrets redacted.
+fn redact_in_place(text: &mut String) -> usize {
+    let (redacted, count) = redact_secrets(text);
+    if count > 0 {
+        let redacted = redacted.into_owned();
+        *text = redacted;
+    }
+    count
+}
+
+/// Returns the directory files may be embedded from with `include_file` for a codebase path.
+///
+/// # Arguments
+///
+/// * `path` - A codebase path, a directory or a file.
+///
+/// # Returns
+///
+/// * `&Path` - The directory itself, or the directory of the file.
+fn include_root(path: &Path) -> &Path {
+    if path.is_file() {
+        path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."))
     } else {
-        0
+        path
+    }
+}
+
+/// Explains why each path given to `--explain` is packed or left out of the run.
+///
+/// # Arguments
+///
+/// * `config` - The application configuration.
+///
+/// # Returns
+///
+/// * `Result<Vec<Explanation>>` - One explanation per path, in the order they were given.
+pub fn explain_paths(config: &Options) -> Result<Vec<Explanation>> {
+    let mut warnings = Vec::new();
+    let (root_paths, options) = traversal_options(config, &mut warnings)?;
+    Ok(config
+        .explain
+        .iter()
+        .map(|path| explain_path(path, &root_paths, &options))
+        .collect())
+}
+
+/// Builds the traversal options of a run from its options.
+///
+/// # Arguments
+///
+/// * `config` - The application configuration.
+/// * `warnings` - The warnings collected during processing.
+///
+/// # Returns
+///
+/// * `Result<(Vec<PathBuf>, TraversalOptions)>` - The root paths to traverse and the traversal options.
+fn traversal_options(config: &Options, warnings: &mut Vec<String>) -> Result<(Vec<PathBuf>, TraversalOptions)> {
+    // Parse Patterns
+    let include_patterns = parse_patterns_with_files(&config.include, &config.include_from)?;
+    let exclude_patterns = parse_patterns_with_files(&config.exclude, &config.exclude_from)?;
+
+    // Never pack the files this run writes, which would otherwise be picked up by the next run
+    let skip_paths = config
+        .output_file()
+        .into_iter()
+        .flat_map(|output| {
+            let parts = (1..)
+                .map(move |index| part_path(output, index))
+                .take_while(|path| Path::new(path).exists());
+            [output.to_string(), stats_path(output)].into_iter().chain(parts)
+        })
+        .filter_map(|path| canonical_path(Path::new(&path)))
+        .collect();
+
+    // Limit traversal to the files git checked out
+    let skip_worktree = get_skip_worktree_paths(config.primary_dir()).unwrap_or_else(|e| {
+        debug!("Not reading skip-worktree entries: {}", e);
+        Vec::new()
+    });
+    let root_paths = checked_out_roots(&config.path, &skip_worktree, warnings);
+    if config.report_sparse && !skip_worktree.is_empty() {
+        warnings.push(message_with(
+            Message::SparseReport,
+            &[("count", &skip_worktree.len())],
+        ));
+    }
+
+    // Read the git history only as far back as the selection needs
+    let selection = config.select.as_deref().map(parse_selection).transpose()?;
+    let mut change_ages = HashMap::new();
+    if let Some(window) = selection.as_ref().and_then(Selection::changed_window) {
+        for root in &root_paths {
+            match get_change_ages(root, window) {
+                Ok(ages) => change_ages.extend(ages),
+                Err(e) => warnings.push(message_with(Message::GitChangesUnavailable, &[("error", &e)])),
+            }
+        }
+    }
+
+    // Line numbers for the files of the diff, the ones the model is asked to patch
+    let mut line_number_paths = HashSet::new();
+    if config.line_number_diff {
+        let branches = parse_patterns(&config.git_diff_branch)?;
+        let branches = match branches.as_slice() {
+            [branch1, branch2] => Some((branch1.as_str(), branch2.as_str())),
+            _ => None,
+        };
+        match get_diff_paths(config.primary_dir(), branches) {
+            Ok(paths) => line_number_paths.extend(paths),
+            Err(e) => warnings.push(message_with(Message::LineNumberDiffUnavailable, &[("error", &e)])),
+        }
+    }
+
+    // Several include patterns order the files, so the entrypoint can be listed before the tests
+    let positive_includes = include_patterns.iter().filter(|pattern| !pattern.starts_with('!')).count();
+    let sort = config
+        .sort
+        .or_else(|| (positive_includes > 1).then_some(SortOrder::Include));
+
+    // The patterns of the roots of a workspace file only apply below their root
+    let mut root_patterns = HashMap::new();
+    for root in &config.workspace_roots {
+        if let Some(canonical) = canonical_path(&root.path) {
+            let patterns = RootPatterns::anchored(&canonical, &root.include, &root.exclude);
+            root_patterns.insert(canonical, patterns);
+        }
+    }
+
+    let options = TraversalOptions {
+        include: include_patterns,
+        exclude: exclude_patterns,
+        include_priority: config.include_priority,
+        line_number: config.line_number,
+        line_number_for: parse_patterns(&config.line_number_for)?,
+        line_number_paths,
+        line_anchors: config.line_anchors,
+        relative_paths: config.relative_paths,
+        exclude_from_tree: config.exclude_from_tree || config.tree_only,
+        annotate_tree: config.annotate_tree,
+        no_codeblock: !config.wraps_code_blocks(),
+        tree_style: config.tree_style.unwrap_or(if config.ascii { TreeStyle::Ascii } else { TreeStyle::Unicode }),
+        skip_paths,
+        no_ignore: config.no_ignore,
+        no_gitignore: config.no_gitignore,
+        hidden: config.hidden,
+        follow_symlinks: config.follow_symlinks,
+        include_external_symlinks: config.include_external_symlinks,
+        max_depth: config.max_depth,
+        max_file_size: config.max_file_size,
+        lossy: config.lossy,
+        dedupe_headers: config.dedupe_headers,
+        redact: config.redact.unwrap_or(true),
+        include_containing: content_pattern("include-containing", &config.include_containing)?,
+        exclude_containing: content_pattern("exclude-containing", &config.exclude_containing)?,
+        languages: parse_patterns(&config.lang)?
+            .into_iter()
+            .map(|language| language.to_lowercase())
+            .collect(),
+        selection,
+        change_ages,
+        linguist_excludes: !config.no_gitattributes,
+        generated_excludes: !config.include_generated,
+        default_excludes: !config.no_default_excludes,
+        allow_sensitive: config.allow_sensitive,
+        include_nested_repos: config.include_nested_repos,
+        skip_worktree: skip_worktree.into_iter().collect(),
+        sort,
+        root_patterns,
+        tiers: ordered_tiers(&config.tiers)?.into_iter().map(|(_, patterns)| patterns).collect(),
+        estimate: config.estimate,
+        estimate_budget: config.budget.filter(|_| config.estimate),
+        file_contents: matches!(config.output_format(), OutputFormat::Json | OutputFormat::Yaml),
+        progress: None,
+    };
+    Ok((root_paths, options))
+}
+
+/// Renders the messages of the chat formats: the `--system-section` of the template as the
+/// system message, and the rest of the prompt, rendered without the data of that section, as the
+/// user message, with the length of its prefix cached by `--format anthropic`.
+///
+/// # Arguments
+///
+/// * `config` - The application configuration.
+/// * `handlebars` - The configured Handlebars instance.
+/// * `template_name` - The name of the template.
+/// * `data` - The JSON data of the prompt.
+///
+/// # Returns
+///
+/// * `Result<serde_json::Value>` - The `model`, `system` and `user` messages and the `cache_prefix`, or an error if the system section is the whole prompt or a template fails to render.
+fn chat_messages(
+    config: &Options,
+    handlebars: &Handlebars,
+    template_name: &str,
+    data: &serde_json::Value,
+) -> Result<serde_json::Value> {
+    let system_section = config.system_section.unwrap_or(Section::Instructions);
+    if system_section == Section::All {
+        bail!(message(Message::SystemSectionAll));
+    }
+    let system = render_section(handlebars, template_name, system_section, data)?;
+    let system = system.trim();
+    let user_data = without_section(data, system_section);
+    let user = render_section(handlebars, template_name, config.rendered_section(), &user_data)?;
+    let user = user.trim();
+
+    // The caching breakpoint ends the system message when it follows the system section, and
+    // otherwise the rendered breakpoint section in the user message, or the whole of it
+    let breakpoint = config.cache_breakpoint.unwrap_or(Section::Files);
+    let cache_prefix = if breakpoint == system_section && !system.is_empty() {
+        None
+    } else {
+        let section = match breakpoint {
+            Section::All => String::new(),
+            section => render_section(handlebars, template_name, section, &user_data)?,
+        };
+        let section = section.trim();
+        Some(match user.find(section).filter(|_| !section.is_empty()) {
+            Some(start) => start + section.len(),
+            None => user.len(),
+        })
     };
+    Ok(serde_json::json!({
+        "model": config.model,
+        "system": system,
+        "user": user,
+        "cache_prefix": cache_prefix,
+    }))
+}
 
-    let paths: Vec<String> = files
+/// Reads the merge, rebase, cherry-pick or revert in progress, for `--conflicts`, or warns
+/// about it otherwise.
+///
+/// # Arguments
+///
+/// * `config` - The application configuration.
+/// * `warnings` - The warnings collected during processing.
+///
+/// # Returns
+///
+/// * `Result<Option<MergeState>>` - The operation in progress and its conflicts with `--conflicts`, or an error if they cannot be read or there are none.
+fn read_merge_state(config: &Options, warnings: &mut Vec<String>) -> Result<Option<MergeState>> {
+    let state = get_merge_state(config.primary_dir());
+    if !config.conflicts {
+        if let Ok(MergeState { operation: Some(operation), conflicts, .. }) = &state {
+            if !conflicts.is_empty() {
+                warnings.push(message_with(
+                    Message::MergeInProgress,
+                    &[("operation", operation), ("count", &conflicts.len())],
+                ));
+            }
+        }
+        return Ok(None);
+    }
+    let state = state.map_err(|e| anyhow::anyhow!(message_with(Message::ConflictsUnavailable, &[("error", &e)])))?;
+    if state.conflicts.is_empty() {
+        bail!(message_with(Message::NoConflicts, &[("path", &config.primary_dir().display())]));
+    }
+    Ok(Some(state))
+}
+
+/// Returns the paths of the included files.
+///
+/// # Arguments
+///
+/// * `files` - The JSON representations of the included files.
+///
+/// # Returns
+///
+/// * `Vec<String>` - The path of each file, in order.
+fn file_paths(files: &[serde_json::Value]) -> Vec<String> {
+    files
         .iter()
         .filter_map(|file| file.get("path").and_then(|p| p.as_str()).map(String::from))
-        .collect();
+        .collect()
+}
 
-    let model_info = get_model_info(&config.encoding);
+/// Compiles the regular expression of a content filter.
+///
+/// # Arguments
+///
+/// * `option` - The name of the option the expression was given with, for error messages.
+/// * `pattern` - The regular expression, if one was given.
+///
+/// # Returns
+///
+/// * `Result<Option<Regex>>` - The compiled expression, or an error if it is invalid.
+fn content_pattern(option: &str, pattern: &Option<String>) -> Result<Option<Regex>> {
+    pattern
+        .as_deref()
+        .map(|pattern| {
+            Regex::new(pattern).map_err(|e| {
+                anyhow::anyhow!(message_with(Message::InvalidContentPattern, &[("option", &option), ("error", &e)]))
+            })
+        })
+        .transpose()
+}
 
-    Ok((rendered, token_count, model_info.parse()?, paths))
+/// Drops the root paths that do not exist because git did not check them out.
+///
+/// # Arguments
+///
+/// * `paths` - The root paths given on the command line.
+/// * `skip_worktree` - The absolute paths of the files git did not check out.
+/// * `warnings` - The warnings collected during processing.
+///
+/// # Returns
+///
+/// * `Vec<PathBuf>` - The root paths to traverse.
+fn checked_out_roots(
+    paths: &[PathBuf],
+    skip_worktree: &[PathBuf],
+    warnings: &mut Vec<String>,
+) -> Vec<PathBuf> {
+    let current_dir = std::env::current_dir().unwrap_or_default();
+    paths
+        .iter()
+        .filter(|path| {
+            if path.exists() {
+                return true;
+            }
+            let absolute = current_dir.join(path);
+            let outside_checkout = skip_worktree.iter().any(|file| file.starts_with(&absolute));
+            if outside_checkout {
+                warnings.push(message_with(
+                    Message::SparsePathSkipped,
+                    &[("path", &path.display())],
+                ));
+            }
+            !outside_checkout
+        })
+        .cloned()
+        .collect()
 }
 
 /// Generates the git diff between two branches, updating the progress spinner.
@@ -111,24 +765,31 @@ pub fn process_codebase(config: &Config) -> Result<(String, usize, String, Vec<S
 ///
 /// * `config` - The application configuration.
 /// * `spinner` - The progress spinner.
+/// * `warnings` - The warnings collected during processing.
 ///
 /// # Returns
 ///
 /// * `Result<String>` - The git diff between branches string.
 fn get_git_diff_between_branches_with_spinner(
-    config: &Config,
+    config: &Options,
     spinner: &ProgressBar,
+    warnings: &mut Vec<String>,
 ) -> Result<String> {
     if let Some(branches) = &config.git_diff_branch {
-        spinner.set_message("Generating git diff between two branches...");
+        spinner.set_message(message(Message::GeneratingGitDiffBranches));
         let branches = parse_patterns(&Some(branches.to_string()))?;
 
         if branches.len() != 2 {
-            error!("Please provide exactly two branches separated by a comma.");
-            std::process::exit(1);
+            bail!(message(Message::ExpectedTwoBranches));
         }
 
-        Ok(get_git_diff_between_branches(&config.path, &branches[0], &branches[1]).unwrap_or_default())
+        Ok(
+            get_git_diff_between_branches(config.primary_dir(), &branches[0], &branches[1])
+                .unwrap_or_else(|e| {
+                    warnings.push(message_with(Message::GitDiffBranchesFailed, &[("error", &e)]));
+                    String::new()
+                }),
+        )
     } else {
         Ok(String::new())
     }
@@ -140,24 +801,28 @@ fn get_git_diff_between_branches_with_spinner(
 ///
 /// * `config` - The application configuration.
 /// * `spinner` - The progress spinner.
+/// * `warnings` - The warnings collected during processing.
 ///
 /// # Returns
 ///
 /// * `Result<String>` - The git log between branches string.
 fn get_git_log_between_branches_with_spinner(
-    config: &Config,
+    config: &Options,
     spinner: &ProgressBar,
+    warnings: &mut Vec<String>,
 ) -> Result<String> {
     if let Some(branches) = &config.git_log_branch {
-        spinner.set_message("Generating git log between two branches...");
+        spinner.set_message(message(Message::GeneratingGitLogBranches));
         let branches = parse_patterns(&Some(branches.to_string()))?;
 
         if branches.len() != 2 {
-            error!("Please provide exactly two branches separated by a comma.");
-            std::process::exit(1);
+            bail!(message(Message::ExpectedTwoBranches));
         }
 
-        Ok(get_git_log(&config.path, &branches[0], &branches[1]).unwrap_or_default())
+        Ok(get_git_log(config.primary_dir(), &branches[0], &branches[1]).unwrap_or_else(|e| {
+            warnings.push(message_with(Message::GitLogBranchesFailed, &[("error", &e)]));
+            String::new()
+        }))
     } else {
         Ok(String::new())
     }
@@ -173,14 +838,12 @@ fn get_git_log_between_branches_with_spinner(
 ///
 /// * `ProgressBar` - The configured progress spinner.
 fn setup_spinner(message: &str) -> ProgressBar {
+    if spinner_hidden() {
+        return ProgressBar::hidden();
+    }
     let spinner = ProgressBar::new_spinner();
     spinner.enable_steady_tick(std::time::Duration::from_millis(120));
-    spinner.set_style(
-        ProgressStyle::default_spinner()
-            .tick_strings(&["▹▹▹▹▹", "▸▹▹▹▹", "▹▸▹▹▹", "▹▹▸▹▹", "▹▹▹▸▹", "▹▹▹▹▸"])
-            .template("{spinner:.blue} {msg}")
-            .unwrap(),
-    );
+    spinner.set_style(spinner_style());
     spinner.set_message(message.to_string());
     spinner
 }
@@ -194,11 +857,20 @@ fn setup_spinner(message: &str) -> ProgressBar {
 /// # Returns
 ///
 /// * `Result<(String, &str)>` - A tuple containing the template content and name.
-fn get_template(config: &Config) -> Result<(String, &str)> {
+fn get_template(config: &Options) -> Result<(String, &str)> {
     if let Some(template_path) = &config.template {
         let content = fs::read_to_string(template_path)
-            .context("Failed to read custom template file")?;
+            .with_context(|| message(Message::TemplateReadFailed))?;
         Ok((content, CUSTOM_TEMPLATE_NAME))
+    } else if config.release.is_some() {
+        Ok((
+            include_str!("release_notes_template.hbs").to_string(),
+            RELEASE_NOTES_TEMPLATE_NAME,
+        ))
+    } else if config.bisect.is_some() {
+        Ok((include_str!("bisect_template.hbs").to_string(), BISECT_TEMPLATE_NAME))
+    } else if let Some(content) = config.preset.and_then(preset_template) {
+        Ok((content.to_string(), PRESET_TEMPLATE_NAME))
     } else {
         Ok((
             include_str!("default_template.hbs").to_string(),
@@ -0,0 +1,283 @@
+//! This module gathers what release notes are written from: the commits between two revisions,
+//! grouped by their conventional commit type, the files they changed, their diff, and the start
+//! of the existing changelog, so the notes follow its format.
+
+use crate::messages::{message_with, Message};
+use crate::text::truncate_lines;
+use anyhow::{anyhow, Context, Result};
+use git2::{Commit, Delta, DiffFindOptions, DiffOptions, Repository, Sort};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::path::Path;
+use tracing::instrument;
+
+/// The names of changelog files, looked up case-insensitively at the root of the repository.
+pub const CHANGELOG_FILES: [&str; 5] = ["CHANGELOG.md", "CHANGELOG", "CHANGES.md", "HISTORY.md", "RELEASES.md"];
+
+/// The number of lines of the changelog given to the model as an example of its format.
+const MAX_CHANGELOG_LINES: usize = 80;
+
+/// The groups commits are sorted into, by conventional commit type, in the order they are rendered.
+const GROUPS: [(&str, &[&str]); 6] = [
+    ("Features", &["feat", "feature"]),
+    ("Bug Fixes", &["fix", "bugfix"]),
+    ("Performance", &["perf"]),
+    ("Documentation", &["docs", "doc"]),
+    ("Refactoring", &["refactor"]),
+    ("Maintenance", &["build", "chore", "ci", "style", "test", "tests", "revert"]),
+];
+
+/// The summary line of a conventional commit, such as `feat(cli)!: add --sort`.
+static CONVENTIONAL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?P<kind>[A-Za-z]+)(?:\((?P<scope>[^)]*)\))?(?P<breaking>!)?:\s*(?P<description>.+)$").unwrap());
+
+/// A commit of a release.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReleaseCommit {
+    /// The abbreviated id of the commit.
+    pub id: String,
+    /// The first line of the commit message.
+    pub summary: String,
+    /// The summary without its conventional commit type and scope.
+    pub description: String,
+    /// The rest of the commit message, if any.
+    pub body: Option<String>,
+    /// The name of the author.
+    pub author: String,
+    /// The conventional commit type, such as `feat` or `fix`, if the summary has one.
+    pub kind: Option<String>,
+    /// The conventional commit scope, such as `cli` in `feat(cli): ...`.
+    pub scope: Option<String>,
+    /// Whether the commit is marked as a breaking change, with `!` or a `BREAKING CHANGE` footer.
+    pub breaking: bool,
+}
+
+/// The commits of a release sharing a heading.
+#[derive(Debug, Clone, Serialize)]
+pub struct CommitGroup {
+    /// The heading of the group, such as `Features`.
+    pub title: String,
+    /// The commits of the group, oldest first.
+    pub commits: Vec<ReleaseCommit>,
+}
+
+/// A file changed by a release.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangedFile {
+    /// The path of the file relative to the repository, after the release.
+    pub path: String,
+    /// How the file changed: `added`, `modified`, `deleted`, `renamed` or `changed`.
+    pub status: &'static str,
+}
+
+/// What release notes are written from.
+#[derive(Debug, Clone, Serialize)]
+pub struct Release {
+    /// The revision the release starts after.
+    pub from: String,
+    /// The revision the release ends at.
+    pub to: String,
+    /// The commits of the release, oldest first, without merge commits.
+    pub commits: Vec<ReleaseCommit>,
+    /// The commits grouped by type, starting with the breaking changes, leaving out empty groups.
+    pub groups: Vec<CommitGroup>,
+    /// The files changed between the two revisions.
+    pub changed_files: Vec<ChangedFile>,
+    /// The diff between the two revisions.
+    pub diff: String,
+    /// The start of the changelog at the end revision, if the repository has one.
+    pub changelog: Option<String>,
+}
+
+/// Gathers the commits, changed files, diff and changelog between two revisions.
+///
+/// # Arguments
+///
+/// * `repo_path` - A path inside the git repository.
+/// * `from` - The revision the release starts after, such as a tag.
+/// * `to` - The revision the release ends at.
+///
+/// # Returns
+///
+/// * `Result<Release>` - What the release notes are written from, or an error if a revision does not exist.
+#[instrument(name = "git", skip_all)]
+pub fn gather_release(repo_path: &Path, from: &str, to: &str) -> Result<Release> {
+    let repo = Repository::discover(repo_path).context("Failed to open repository")?;
+    let resolve = |revision: &str| -> Result<Commit> {
+        repo.revparse_single(revision)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|_| anyhow!(message_with(Message::RevisionNotFound, &[("revision", &revision)])))
+    };
+    let from_commit = resolve(from)?;
+    let to_commit = resolve(to)?;
+
+    // ~~~ Commits reachable from the end but not from the start ~~~
+    let mut revwalk = repo.revwalk().context("Failed to create revwalk")?;
+    revwalk.push(to_commit.id()).context("Failed to push commit to revwalk")?;
+    revwalk.hide(from_commit.id()).context("Failed to hide commit from revwalk")?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?).context("Failed to find commit")?;
+        if commit.parent_count() > 1 {
+            continue;
+        }
+        commits.push(release_commit(&commit));
+    }
+
+    // ~~~ Files and diff between the two trees ~~~
+    let from_tree = from_commit.tree()?;
+    let to_tree = to_commit.tree()?;
+    let mut diff = repo
+        .diff_tree_to_tree(Some(&from_tree), Some(&to_tree), Some(DiffOptions::new().ignore_whitespace(true)))
+        .context("Failed to generate diff between revisions")?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))?;
+    let changed_files = diff
+        .deltas()
+        .filter_map(|delta| {
+            let file = if delta.status() == Delta::Deleted {
+                delta.old_file()
+            } else {
+                delta.new_file()
+            };
+            Some(ChangedFile {
+                path: file.path()?.to_string_lossy().replace('\\', "/"),
+                status: match delta.status() {
+                    Delta::Added => "added",
+                    Delta::Deleted => "deleted",
+                    Delta::Modified => "modified",
+                    Delta::Renamed => "renamed",
+                    _ => "changed",
+                },
+            })
+        })
+        .collect();
+    let mut diff_text = Vec::new();
+    diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            diff_text.push(line.origin() as u8);
+        }
+        diff_text.extend_from_slice(line.content());
+        true
+    })
+    .context("Failed to print diff")?;
+
+    // ~~~ The changelog, as it stands at the end of the release ~~~
+    let changelog = to_tree
+        .iter()
+        .find(|entry| {
+            entry
+                .name()
+                .is_some_and(|name| CHANGELOG_FILES.iter().any(|file| file.eq_ignore_ascii_case(name)))
+        })
+        .and_then(|entry| entry.to_object(&repo).ok()?.peel_to_blob().ok())
+        .map(|blob| {
+            let content = String::from_utf8_lossy(blob.content());
+            truncate_lines(&content, MAX_CHANGELOG_LINES).to_string()
+        })
+        .filter(|changelog| !changelog.trim().is_empty());
+
+    Ok(Release {
+        from: from.to_string(),
+        to: to.to_string(),
+        groups: group_commits(&commits),
+        commits,
+        changed_files,
+        diff: String::from_utf8_lossy(&diff_text).into_owned(),
+        changelog,
+    })
+}
+
+/// Reads a commit, parsing its conventional commit type, scope and breaking marker.
+///
+/// # Arguments
+///
+/// * `commit` - The commit to read.
+///
+/// # Returns
+///
+/// * `ReleaseCommit` - The commit as rendered in release notes.
+fn release_commit(commit: &Commit) -> ReleaseCommit {
+    let summary = commit.summary().unwrap_or_default().trim().to_string();
+    let body = commit
+        .body()
+        .map(|body| body.trim().to_string())
+        .filter(|body| !body.is_empty());
+    let mut release_commit = parse_commit_message(&summary, body.as_deref());
+    release_commit.id = commit.id().to_string()[..7].to_string();
+    release_commit.author = commit.author().name().unwrap_or_default().to_string();
+    release_commit
+}
+
+/// Parses the conventional commit type, scope and breaking marker of a commit message.
+///
+/// Summaries that do not follow the conventional commit format are kept as they are, without a type.
+///
+/// # Arguments
+///
+/// * `summary` - The first line of the commit message.
+/// * `body` - The rest of the commit message, if any.
+///
+/// # Returns
+///
+/// * `ReleaseCommit` - The parsed commit, with an empty id and author.
+pub fn parse_commit_message(summary: &str, body: Option<&str>) -> ReleaseCommit {
+    let footer_breaking = body.is_some_and(|body| {
+        body.lines()
+            .any(|line| line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:"))
+    });
+    let mut commit = ReleaseCommit {
+        id: String::new(),
+        summary: summary.to_string(),
+        description: summary.to_string(),
+        body: body.map(str::to_string),
+        author: String::new(),
+        kind: None,
+        scope: None,
+        breaking: footer_breaking,
+    };
+    if let Some(captures) = CONVENTIONAL.captures(summary) {
+        commit.kind = Some(captures["kind"].to_lowercase());
+        commit.scope = captures
+            .name("scope")
+            .map(|scope| scope.as_str().trim().to_string())
+            .filter(|scope| !scope.is_empty());
+        commit.breaking |= captures.name("breaking").is_some();
+        commit.description = captures["description"].trim().to_string();
+    }
+    commit
+}
+
+/// Sorts commits into the groups of release notes.
+///
+/// Breaking changes are listed first, and also in the group of their type. Commits without a
+/// known type end up in `Other Changes`. Empty groups are left out.
+///
+/// # Arguments
+///
+/// * `commits` - The commits of the release, in order.
+///
+/// # Returns
+///
+/// * `Vec<CommitGroup>` - The groups, in the order they are rendered.
+pub fn group_commits(commits: &[ReleaseCommit]) -> Vec<CommitGroup> {
+    let group = |title: &str, filter: &dyn Fn(&ReleaseCommit) -> bool| CommitGroup {
+        title: title.to_string(),
+        commits: commits.iter().filter(|commit| filter(commit)).cloned().collect(),
+    };
+    let known = |commit: &ReleaseCommit| {
+        commit
+            .kind
+            .as_deref()
+            .is_some_and(|kind| GROUPS.iter().any(|(_, kinds)| kinds.contains(&kind)))
+    };
+
+    let mut groups = vec![group("Breaking Changes", &|commit| commit.breaking)];
+    for (title, kinds) in GROUPS {
+        groups.push(group(title, &|commit| commit.kind.as_deref().is_some_and(|kind| kinds.contains(&kind))));
+    }
+    groups.push(group("Other Changes", &|commit| !known(commit)));
+    groups.retain(|group| !group.commits.is_empty());
+    groups
+}
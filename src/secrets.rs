@@ -0,0 +1,137 @@
+//! This module detects secrets in file contents, such as API keys, tokens, private keys and
+//! connection strings, and replaces them with placeholders such as `[REDACTED:aws-access-key]`
+//! before they end up in a prompt.
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+
+/// The minimum Shannon entropy, in bits per character, of a value assigned to a secret-looking
+/// name for it to be redacted by the generic rule.
+const MIN_GENERIC_ENTROPY: f64 = 3.5;
+
+/// A rule detecting one kind of secret.
+struct Rule {
+    /// The kind of secret, named in the placeholder.
+    id: &'static str,
+    /// The expression matching the secret. When it has a `secret` group, only that group is redacted.
+    pattern: Regex,
+    /// Whether the secret must look random, to tell keys apart from ordinary words.
+    check_entropy: bool,
+}
+
+/// The rules, checked in order, from the most specific to the most generic.
+static RULES: Lazy<Vec<Rule>> = Lazy::new(|| {
+    let rule = |id, pattern: &str, check_entropy| Rule {
+        id,
+        pattern: Regex::new(pattern).unwrap(),
+        check_entropy,
+    };
+    vec![
+        rule(
+            "private-key",
+            r"-----BEGIN[A-Z ]*PRIVATE KEY( BLOCK)?-----[\s\S]*?-----END[A-Z ]*PRIVATE KEY( BLOCK)?-----",
+            false,
+        ),
+        rule("aws-access-key", r"\b(?:AKIA|ASIA|AGPA|AIDA|AROA)[0-9A-Z]{16}\b", false),
+        rule(
+            "aws-secret-key",
+            r#"(?i)aws.{0,20}secret.{0,20}?['"]?\s*[:=]\s*['"]?(?P<secret>[A-Za-z0-9/+]{40})\b"#,
+            false,
+        ),
+        rule("github-token", r"\b(?:gh[pousr]_[A-Za-z0-9]{36,255}|github_pat_[A-Za-z0-9_]{60,255})\b", false),
+        rule("gitlab-token", r"\bglpat-[A-Za-z0-9_-]{20}\b", false),
+        rule("slack-token", r"\bxox[abposr]-[A-Za-z0-9-]{10,}\b", false),
+        rule("stripe-key", r"\b[rs]k_(?:live|test)_[A-Za-z0-9]{24,}\b", false),
+        rule("google-api-key", r"\bAIza[0-9A-Za-z_-]{35}\b", false),
+        rule("anthropic-key", r"\bsk-ant-[A-Za-z0-9_-]{32,}", false),
+        rule("openai-key", r"\bsk-(?:proj-)?[A-Za-z0-9_-]{32,}", false),
+        rule(
+            "jwt",
+            r"\beyJ[A-Za-z0-9_-]{10,}\.eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}",
+            false,
+        ),
+        rule(
+            "connection-string",
+            r"\b[a-z][a-z0-9+.-]*://[^\s:/@'\x22]+:(?P<secret>[^\s@/'\x22$<{][^\s@/'\x22]*)@",
+            false,
+        ),
+        // Quoted values assigned to secret-looking names in code and configuration files
+        rule(
+            "generic-secret",
+            r#"(?i)[\w.-]*(?:secret|token|passw(?:or)?d|api[_-]?key|access[_-]?key|private[_-]?key)[\w.-]*['"]?\s*(?:[:=]|:=|=>)\s*['"](?P<secret>[A-Za-z0-9/+=_.-]{16,})['"]"#,
+            true,
+        ),
+        // Unquoted values in `.env` files and shell scripts
+        rule(
+            "generic-secret",
+            r"(?m)^\s*(?:export\s+)?[A-Z0-9_]*(?:SECRET|TOKEN|PASSWORD|PASSWD|API_KEY|ACCESS_KEY|PRIVATE_KEY)[A-Z0-9_]*=(?P<secret>[A-Za-z0-9/+=_.-]{16,})\s*$",
+            true,
+        ),
+    ]
+});
+
+/// Replaces the secrets of a text with placeholders naming their kind.
+///
+/// # Arguments
+///
+/// * `text` - The text to scan, such as the content of a file.
+///
+/// # Returns
+///
+/// * `(Cow<str>, usize)` - The text with its secrets replaced, and the number of secrets replaced.
+pub fn redact_secrets(text: &str) -> (Cow<'_, str>, usize) {
+    let mut redacted = Cow::Borrowed(text);
+    let mut count = 0;
+    for rule in RULES.iter() {
+        if !rule.pattern.is_match(&redacted) {
+            continue;
+        }
+        let replaced = rule
+            .pattern
+            .replace_all(&redacted, |captures: &Captures| {
+                let whole = captures.get(0).unwrap();
+                let secret = captures.name("secret").unwrap_or(whole);
+                if rule.check_entropy && shannon_entropy(secret.as_str()) < MIN_GENERIC_ENTROPY {
+                    return whole.as_str().to_string();
+                }
+                count += 1;
+                let start = secret.start() - whole.start();
+                let end = secret.end() - whole.start();
+                format!(
+                    "{}[REDACTED:{}]{}",
+                    &whole.as_str()[..start],
+                    rule.id,
+                    &whole.as_str()[end..]
+                )
+            })
+            .into_owned();
+        redacted = Cow::Owned(replaced);
+    }
+    (redacted, count)
+}
+
+/// Computes the Shannon entropy of a string, which is higher for random keys than for words.
+///
+/// # Arguments
+///
+/// * `value` - The string to measure.
+///
+/// # Returns
+///
+/// * `f64` - The entropy in bits per character.
+pub fn shannon_entropy(value: &str) -> f64 {
+    let mut counts = [0usize; 256];
+    for byte in value.bytes() {
+        counts[byte as usize] += 1;
+    }
+    let length = value.len() as f64;
+    counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let probability = *count as f64 / length;
+            -probability * probability.log2()
+        })
+        .sum()
+}
@@ -0,0 +1,294 @@
+//! This module parses and evaluates selection expressions, such as
+//! `(lang:rust & changed:7d) | path:docs/**`, which combine file filters with boolean operators.
+
+use crate::messages::{message, message_with, Message};
+use anyhow::{anyhow, Result};
+use glob::Pattern;
+use regex::Regex;
+
+/// A parsed selection expression.
+#[derive(Debug, Clone)]
+pub enum Selection {
+    /// Files whose path, relative to their root or absolute, matches a glob pattern.
+    Path(Pattern),
+    /// Files in a language, such as `rust` or `makefile`.
+    Lang(String),
+    /// Files with an extension, compared case-insensitively.
+    Ext(String),
+    /// Text files whose content matches a regular expression.
+    Contains(Regex),
+    /// Files changed in git within a number of seconds, including uncommitted changes.
+    Changed(u64),
+    /// Files not selected by an expression.
+    Not(Box<Selection>),
+    /// Files selected by both expressions.
+    And(Box<Selection>, Box<Selection>),
+    /// Files selected by either expression.
+    Or(Box<Selection>, Box<Selection>),
+}
+
+/// What a selection expression is evaluated against for one file.
+#[derive(Debug, Clone, Copy)]
+pub struct Candidate<'a> {
+    /// The path of the file relative to its root, with `/` separators.
+    pub relative_path: &'a str,
+    /// The absolute path of the file.
+    pub absolute_path: &'a str,
+    /// The detected language of the file, if any.
+    pub language: Option<&'a str>,
+    /// The content of the file, or `None` for binary files.
+    pub text: Option<&'a str>,
+    /// The number of seconds since the file last changed in git, if it changed recently.
+    pub age: Option<u64>,
+}
+
+impl Selection {
+    /// Checks whether a file is selected by the expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `candidate` - The file to check.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - `true` if the file is selected.
+    pub fn matches(&self, candidate: &Candidate) -> bool {
+        match self {
+            Selection::Path(pattern) => {
+                pattern.matches(candidate.relative_path) || pattern.matches(candidate.absolute_path)
+            }
+            Selection::Lang(language) => candidate.language == Some(language.as_str()),
+            Selection::Ext(extension) => candidate
+                .relative_path
+                .rsplit_once('.')
+                .is_some_and(|(stem, ext)| !stem.ends_with('/') && ext.eq_ignore_ascii_case(extension)),
+            Selection::Contains(pattern) => candidate.text.is_some_and(|text| pattern.is_match(text)),
+            Selection::Changed(window) => candidate.age.is_some_and(|age| age <= *window),
+            Selection::Not(inner) => !inner.matches(candidate),
+            Selection::And(left, right) => left.matches(candidate) && right.matches(candidate),
+            Selection::Or(left, right) => left.matches(candidate) || right.matches(candidate),
+        }
+    }
+
+    /// Returns the longest `changed:` window of the expression, so git history is only read as
+    /// far back as needed.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<u64>` - The window in seconds, or `None` if the expression does not use `changed:`.
+    pub fn changed_window(&self) -> Option<u64> {
+        match self {
+            Selection::Changed(window) => Some(*window),
+            Selection::Not(inner) => inner.changed_window(),
+            Selection::And(left, right) | Selection::Or(left, right) => {
+                left.changed_window().max(right.changed_window())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parses a selection expression.
+///
+/// Filters are written `key:value`, with the keys `path`, `lang`, `ext`, `contains` and
+/// `changed`, and combined with `&`, `|`, `!` and parentheses. `!` binds tightest and `&`
+/// binds tighter than `|`. Values containing spaces or operators can be quoted with `"`.
+///
+/// # Arguments
+///
+/// * `expression` - The selection expression.
+///
+/// # Returns
+///
+/// * `Result<Selection>` - The parsed expression, or an error naming the column it failed at.
+pub fn parse_selection(expression: &str) -> Result<Selection> {
+    let mut parser = Parser {
+        chars: expression.chars().collect(),
+        position: 0,
+    };
+    parser
+        .parse()
+        .map_err(|(position, reason)| {
+            anyhow!(message_with(
+                Message::InvalidSelection,
+                &[("expression", &expression), ("column", &(position + 1)), ("reason", &reason)],
+            ))
+        })
+}
+
+/// Parses a duration such as `30m`, `12h`, `7d` or `2w`.
+///
+/// # Arguments
+///
+/// * `value` - The duration, as a number followed by `s`, `m`, `h`, `d` or `w`.
+///
+/// # Returns
+///
+/// * `Option<u64>` - The duration in seconds, or `None` if it is invalid.
+pub fn parse_duration(value: &str) -> Option<u64> {
+    let split = value.find(|c: char| !c.is_ascii_digit())?;
+    let (number, unit) = value.split_at(split);
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    number.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// The position of a parse error and its localized reason.
+type ParseError = (usize, String);
+
+/// A recursive descent parser over the characters of an expression.
+struct Parser {
+    /// The characters of the expression.
+    chars: Vec<char>,
+    /// The index of the next character to parse.
+    position: usize,
+}
+
+impl Parser {
+    /// Parses the whole expression, failing on trailing input.
+    fn parse(&mut self) -> Result<Selection, ParseError> {
+        let selection = self.parse_or()?;
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(selection),
+            Some(c) => Err(self.unexpected(c)),
+        }
+    }
+
+    /// Parses alternatives separated by `|`.
+    fn parse_or(&mut self) -> Result<Selection, ParseError> {
+        let mut selection = self.parse_and()?;
+        while self.eat('|') {
+            selection = Selection::Or(Box::new(selection), Box::new(self.parse_and()?));
+        }
+        Ok(selection)
+    }
+
+    /// Parses terms separated by `&`.
+    fn parse_and(&mut self) -> Result<Selection, ParseError> {
+        let mut selection = self.parse_unary()?;
+        while self.eat('&') {
+            selection = Selection::And(Box::new(selection), Box::new(self.parse_unary()?));
+        }
+        Ok(selection)
+    }
+
+    /// Parses a negation, a parenthesized expression or a filter.
+    fn parse_unary(&mut self) -> Result<Selection, ParseError> {
+        if self.eat('!') {
+            return Ok(Selection::Not(Box::new(self.parse_unary()?)));
+        }
+        if self.eat('(') {
+            let selection = self.parse_or()?;
+            if !self.eat(')') {
+                return Err(self.unexpected_here());
+            }
+            return Ok(selection);
+        }
+        self.parse_filter()
+    }
+
+    /// Parses a `key:value` filter.
+    fn parse_filter(&mut self) -> Result<Selection, ParseError> {
+        self.skip_whitespace();
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+            self.position += 1;
+        }
+        let key: String = self.chars[start..self.position].iter().collect();
+        if key.is_empty() || !self.eat_raw(':') {
+            return Err(self.unexpected_here());
+        }
+        let value_start = self.position;
+        let value = self.parse_value()?;
+        let invalid = || {
+            (
+                value_start,
+                message_with(Message::SelectionInvalidValue, &[("key", &key), ("value", &value)]),
+            )
+        };
+        match key.as_str() {
+            "path" => Pattern::new(&value).map(Selection::Path).map_err(|_| invalid()),
+            "lang" => Ok(Selection::Lang(value.to_lowercase())),
+            "ext" => Ok(Selection::Ext(value.trim_start_matches('.').to_string())),
+            "contains" => Regex::new(&value).map(Selection::Contains).map_err(|_| invalid()),
+            "changed" => parse_duration(&value).map(Selection::Changed).ok_or_else(invalid),
+            _ => Err((start, message_with(Message::SelectionUnknownKey, &[("key", &key)]))),
+        }
+    }
+
+    /// Parses the value of a filter, either quoted or running up to whitespace or an operator.
+    fn parse_value(&mut self) -> Result<String, ParseError> {
+        if self.eat_raw('"') {
+            let start = self.position;
+            while self.peek().is_some_and(|c| c != '"') {
+                self.position += 1;
+            }
+            let value = self.chars[start..self.position].iter().collect();
+            if !self.eat_raw('"') {
+                return Err(self.unexpected_here());
+            }
+            return Ok(value);
+        }
+        let start = self.position;
+        while self
+            .peek()
+            .is_some_and(|c| !c.is_whitespace() && !matches!(c, '(' | ')' | '&' | '|'))
+        {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(self.unexpected_here());
+        }
+        Ok(self.chars[start..self.position].iter().collect())
+    }
+
+    /// Consumes a character after optional whitespace, if it is the next one.
+    fn eat(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        self.eat_raw(expected)
+    }
+
+    /// Consumes a character, if it is the next one.
+    fn eat_raw(&mut self, expected: char) -> bool {
+        let found = self.peek() == Some(expected);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    /// Returns the next character without consuming it.
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    /// Consumes the whitespace before the next token.
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    /// Returns the error for the character at the current position, or for the end of the expression.
+    fn unexpected_here(&self) -> ParseError {
+        match self.peek() {
+            Some(c) => self.unexpected(c),
+            None => (self.position, message(Message::SelectionUnexpectedEnd)),
+        }
+    }
+
+    /// Returns the error for an unexpected character at the current position.
+    fn unexpected(&self, token: char) -> ParseError {
+        (
+            self.position,
+            message_with(Message::SelectionUnexpectedToken, &[("token", &token)]),
+        )
+    }
+}
@@ -0,0 +1,156 @@
+//! This module saves the file selection of a run, its patterns, filters and budget, under a name
+//! in the project configuration file, so it can be shared and reused with `--selection`.
+
+use crate::input::{parse_patterns, PROJECT_CONFIG_FILE};
+use crate::messages::{message_with, Message};
+use crate::options::Options;
+use crate::workspace::{with_file_lock, write_atomic};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{value, Array, DocumentMut, Item, Table};
+
+/// Returns the keys of the file selection of a run, leaving out the options at their defaults.
+///
+/// # Arguments
+///
+/// * `options` - The effective options of the run.
+///
+/// # Returns
+///
+/// * `Result<Table>` - The selection, with the keys of the configuration files.
+pub fn selection_table(options: &Options) -> Result<Table> {
+    let mut table = Table::new();
+    let patterns = |patterns: &Option<String>| -> Result<Option<Array>> {
+        let patterns = parse_patterns(patterns)?;
+        Ok((!patterns.is_empty()).then(|| patterns.iter().map(String::as_str).collect()))
+    };
+    let paths = |paths: &[PathBuf]| -> Option<Array> {
+        (!paths.is_empty()).then(|| paths.iter().map(|path| path.to_string_lossy().into_owned()).collect())
+    };
+
+    if let Some(include) = patterns(&options.include)? {
+        table["include"] = value(include);
+    }
+    if let Some(exclude) = patterns(&options.exclude)? {
+        table["exclude"] = value(exclude);
+    }
+    if let Some(include_from) = paths(&options.include_from) {
+        table["include_from"] = value(include_from);
+    }
+    if let Some(exclude_from) = paths(&options.exclude_from) {
+        table["exclude_from"] = value(exclude_from);
+    }
+    let strings = [
+        ("include_containing", &options.include_containing),
+        ("exclude_containing", &options.exclude_containing),
+        ("lang", &options.lang),
+        ("select", &options.select),
+    ];
+    for (key, option) in strings {
+        if let Some(option) = option {
+            table[key] = value(option.as_str());
+        }
+    }
+    let flags = [
+        ("include_priority", options.include_priority),
+        ("no_ignore", options.no_ignore),
+        ("no_gitignore", options.no_gitignore),
+        ("no_default_excludes", options.no_default_excludes),
+        ("hidden", options.hidden),
+        ("follow_symlinks", options.follow_symlinks),
+        ("include_nested_repos", options.include_nested_repos),
+        ("no_gitattributes", options.no_gitattributes),
+        ("include_generated", options.include_generated),
+        ("allow_sensitive", options.allow_sensitive),
+        ("include_external_symlinks", options.include_external_symlinks),
+        ("estimate", options.estimate),
+    ];
+    for (key, flag) in flags {
+        if flag {
+            table[key] = value(true);
+        }
+    }
+    if let Some(max_depth) = options.max_depth {
+        table["max_depth"] = value(max_depth as i64);
+    }
+    if let Some(max_file_size) = options.max_file_size {
+        table["max_file_size"] = value(max_file_size.0 as i64);
+    }
+    if let Some(budget) = options.budget {
+        table["budget"] = value(budget as i64);
+    }
+    if let Some(sort) = options.sort.and_then(|sort| sort.to_possible_value()) {
+        table["sort"] = value(sort.get_name());
+    }
+    if !options.tiers.is_empty() {
+        let mut names: Vec<&String> = options.tiers.keys().collect();
+        names.sort();
+        let mut tiers = Table::new();
+        for name in names {
+            tiers[name.as_str()] = value(options.tiers[name].iter().map(String::as_str).collect::<Array>());
+        }
+        table["tiers"] = Item::Table(tiers);
+    }
+    Ok(table)
+}
+
+/// Saves the file selection of a run under a name in the project configuration file.
+///
+/// The rest of the file, including its comments and formatting, is kept, and a selection
+/// saved earlier under the same name is replaced.
+///
+/// # Arguments
+///
+/// * `root_path` - The codebase directory, where the project configuration file is stored.
+/// * `name` - The name of the selection.
+/// * `options` - The effective options of the run.
+///
+/// # Returns
+///
+/// * `Result<PathBuf>` - The path of the project configuration file, or an error if it cannot be updated.
+pub fn save_selection(root_path: &Path, name: &str, options: &Options) -> Result<PathBuf> {
+    let path = root_path.join(PROJECT_CONFIG_FILE);
+    // The file is shared with other runs, so it is rewritten under a lock and in one step
+    with_file_lock(&path, || update_selections(&path, name, options))?;
+    Ok(path)
+}
+
+/// Replaces a selection in the project configuration file, keeping the rest of the file.
+///
+/// # Arguments
+///
+/// * `path` - The path of the project configuration file.
+/// * `name` - The name of the selection.
+/// * `options` - The effective options of the run.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+fn update_selections(path: &Path, name: &str, options: &Options) -> Result<()> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(e).with_context(|| message_with(Message::ConfigWriteFailed, &[("path", &path.display())]))
+        }
+    };
+    let mut document: DocumentMut = content
+        .parse()
+        .with_context(|| message_with(Message::ConfigWriteFailed, &[("path", &path.display())]))?;
+
+    let selections = document
+        .entry("selections")
+        .or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        })
+        .as_table_mut()
+        .with_context(|| message_with(Message::ConfigWriteFailed, &[("path", &path.display())]))?;
+    selections.insert(name, Item::Table(selection_table(options)?));
+
+    write_atomic(path, document.to_string().as_bytes())
+        .with_context(|| message_with(Message::ConfigWriteFailed, &[("path", &path.display())]))
+}
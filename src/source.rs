@@ -0,0 +1,339 @@
+//! This module fetches codebases that are not plain local directories, such as remote git
+//! repositories, GitHub snapshots and source archives, into temporary directories that the
+//! rest of the pipeline can traverse.
+
+use crate::options::Options;
+use crate::messages::{message_with, Message};
+use crate::workspace::Workspace;
+use anyhow::{anyhow, Context, Result};
+use flate2::read::GzDecoder;
+use git2::build::{CheckoutBuilder, RepoBuilder};
+use git2::{FetchOptions, Repository};
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use tar::Archive;
+use tracing::{debug, instrument};
+use zip::ZipArchive;
+
+/// URL prefixes recognized as remote git repositories.
+const REMOTE_PREFIXES: [&str; 5] = ["https://", "http://", "ssh://", "git://", "file://"];
+
+/// File name suffixes of the supported source archives.
+const ARCHIVE_SUFFIXES: [&str; 4] = [".tar.gz", ".tgz", ".tar", ".zip"];
+
+/// The base URL of the GitHub API, used when `GITHUB_API_URL` is not set.
+const GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Checks whether a path argument is the URL of a remote git repository.
+///
+/// # Arguments
+///
+/// * `path` - The path argument given on the command line.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the argument is a git URL, including scp-like `git@host:org/repo` URLs.
+pub fn is_remote_url(path: &str) -> bool {
+    REMOTE_PREFIXES.iter().any(|prefix| path.starts_with(prefix))
+        || (path.starts_with("git@") && path.contains(':'))
+}
+
+/// Fetches the remote codebases of the configuration, replacing their URLs with local paths.
+///
+/// Each remote repository is shallow-cloned into its own directory of the workspace, checked
+/// out at `--branch` when given. A `--github` snapshot is downloaded as a tarball instead, which
+/// does not require git, and `.zip`, `.tar` and `.tar.gz` paths are extracted.
+///
+/// # Arguments
+///
+/// * `config` - The application configuration, whose paths are updated in place.
+/// * `workspace` - The workspace of the run, which must outlive processing.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+#[instrument(name = "fetch", skip_all)]
+pub fn fetch_sources(config: &mut Options, workspace: &Workspace) -> Result<()> {
+    if let Some(spec) = &config.github {
+        let dir = workspace.create_dir()?;
+        let destination = download_github_tarball(spec, config.branch.as_deref(), &dir)?;
+        config.path = vec![destination];
+        return Ok(());
+    }
+
+    if let Some(remote) = &config.remote {
+        config.path = vec![PathBuf::from(remote)];
+    }
+
+    for path in config.path.iter_mut() {
+        let destination = if let Some(url) = path.to_str().filter(|url| is_remote_url(url)) {
+            let destination = workspace.create_dir()?.join(repository_name(url));
+            clone_repository(url, config.branch.as_deref(), &destination)
+                .with_context(|| message_with(Message::CloneFailed, &[("url", &url)]))?;
+            destination
+        } else if let Some(name) = archive_name(path) {
+            let dir = workspace.create_dir()?;
+            extract_archive(path, &name, &dir).with_context(|| {
+                message_with(Message::ExtractFailed, &[("path", &path.display())])
+            })?
+        } else {
+            continue;
+        };
+
+        debug!("Fetched {} into {}", path.display(), destination.display());
+        *path = destination;
+    }
+    Ok(())
+}
+
+/// Parses a GitHub repository specification of the form `owner/repo[@ref]`.
+///
+/// # Arguments
+///
+/// * `spec` - The repository specification.
+///
+/// # Returns
+///
+/// * `Result<(&str, &str, Option<&str>)>` - The owner, the repository name, and the reference if one was given.
+pub fn parse_github_spec(spec: &str) -> Result<(&str, &str, Option<&str>)> {
+    let (repository, reference) = match spec.split_once('@') {
+        Some((repository, reference)) if !reference.is_empty() => (repository, Some(reference)),
+        Some((repository, _)) => (repository, None),
+        None => (spec, None),
+    };
+    let (owner, name) = repository
+        .split_once('/')
+        .filter(|(owner, name)| !owner.is_empty() && !name.is_empty() && !name.contains('/'))
+        .ok_or_else(|| anyhow!(message_with(Message::InvalidGithubSpec, &[("spec", &spec)])))?;
+    Ok((owner, name, reference))
+}
+
+/// Downloads a snapshot of a GitHub repository through the tarball API and extracts it.
+///
+/// A `GITHUB_TOKEN` environment variable is sent as a bearer token, which allows fetching
+/// private repositories.
+///
+/// # Arguments
+///
+/// * `spec` - The repository specification, `owner/repo[@ref]`.
+/// * `branch` - The reference to use when the specification does not include one.
+/// * `temp_dir` - The directory to extract the snapshot into.
+///
+/// # Returns
+///
+/// * `Result<PathBuf>` - The directory holding the extracted snapshot, named after the repository.
+fn download_github_tarball(spec: &str, branch: Option<&str>, temp_dir: &Path) -> Result<PathBuf> {
+    let (owner, name, reference) = parse_github_spec(spec)?;
+    let api_url = env::var("GITHUB_API_URL").unwrap_or_else(|_| GITHUB_API_URL.to_string());
+    let mut url = format!("{}/repos/{}/{}/tarball", api_url.trim_end_matches('/'), owner, name);
+    if let Some(reference) = reference.or(branch) {
+        url = format!("{}/{}", url, reference);
+    }
+
+    let mut request = ureq::get(&url)
+        .set("User-Agent", "codexio")
+        .set("Accept", "application/vnd.github+json");
+    if let Ok(token) = env::var("GITHUB_TOKEN") {
+        request = request.set("Authorization", &format!("Bearer {}", token));
+    }
+    let response = request
+        .call()
+        .with_context(|| message_with(Message::DownloadFailed, &[("url", &url)]))?;
+
+    // GitHub wraps the snapshot in an `owner-repo-sha` directory.
+    let extract_dir = temp_dir.join("archive");
+    unpack_tar_gz(response.into_reader(), &extract_dir)?;
+    move_extracted(&extract_dir, temp_dir.join(name))
+}
+
+/// Returns the name of the directory a source archive extracts to.
+///
+/// # Arguments
+///
+/// * `path` - The path given on the command line.
+///
+/// # Returns
+///
+/// * `Option<String>` - The file name without its archive suffix, or `None` if the path is not an archive file.
+pub fn archive_name(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    let lowercase = file_name.to_lowercase();
+    let suffix = ARCHIVE_SUFFIXES
+        .iter()
+        .find(|suffix| lowercase.ends_with(*suffix))?;
+    if !path.is_file() {
+        return None;
+    }
+    Some(file_name[..file_name.len() - suffix.len()].to_string())
+}
+
+/// Extracts a source archive into a temporary directory.
+///
+/// # Arguments
+///
+/// * `archive` - The path to the `.zip`, `.tar` or `.tar.gz` archive.
+/// * `name` - The name of the directory holding the extracted files.
+/// * `temp_dir` - The directory to extract the archive into.
+///
+/// # Returns
+///
+/// * `Result<PathBuf>` - The directory holding the extracted files.
+fn extract_archive(archive: &Path, name: &str, temp_dir: &Path) -> Result<PathBuf> {
+    let file = File::open(archive)
+        .with_context(|| message_with(Message::ArchiveOpenFailed, &[("path", &archive.display())]))?;
+    let extension = archive
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let extract_dir = temp_dir.join("archive");
+    match extension.as_str() {
+        "zip" => ZipArchive::new(file)
+            .and_then(|mut zip| zip.extract(&extract_dir))
+            .with_context(|| message_with(Message::ArchiveUnpackFailed, &[("path", &extract_dir.display())]))?,
+        "tar" => Archive::new(file)
+            .unpack(&extract_dir)
+            .with_context(|| message_with(Message::ArchiveUnpackFailed, &[("path", &extract_dir.display())]))?,
+        _ => unpack_tar_gz(file, &extract_dir)?,
+    }
+    move_extracted(&extract_dir, temp_dir.join(name))
+}
+
+/// Moves extracted files to their final directory, unwrapping a single top-level directory.
+///
+/// Archives usually wrap their contents in one directory, which would otherwise show up as
+/// an extra level in the source tree.
+///
+/// # Arguments
+///
+/// * `extract_dir` - The directory the archive was extracted into.
+/// * `destination` - The final directory, whose name labels the root of the tree.
+///
+/// # Returns
+///
+/// * `Result<PathBuf>` - The final directory.
+fn move_extracted(extract_dir: &Path, destination: PathBuf) -> Result<PathBuf> {
+    fs::rename(single_directory(extract_dir)?, &destination)
+        .with_context(|| message_with(Message::ArchiveMoveFailed, &[("path", &destination.display())]))?;
+    Ok(destination)
+}
+
+/// Extracts a gzip-compressed tar archive into a directory.
+///
+/// # Arguments
+///
+/// * `reader` - The reader of the compressed archive.
+/// * `destination` - The directory to extract into.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+fn unpack_tar_gz<R: Read>(reader: R, destination: &Path) -> Result<()> {
+    Archive::new(GzDecoder::new(reader))
+        .unpack(destination)
+        .with_context(|| message_with(Message::ArchiveUnpackFailed, &[("path", &destination.display())]))
+}
+
+/// Returns the only directory inside a directory, or the directory itself otherwise.
+///
+/// # Arguments
+///
+/// * `dir` - The directory an archive was extracted into.
+///
+/// # Returns
+///
+/// * `Result<PathBuf>` - The directory holding the extracted files.
+fn single_directory(dir: &Path) -> Result<PathBuf> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| message_with(Message::ArchiveReadFailed, &[("path", &dir.display())]))?
+        .filter_map(|entry| entry.ok())
+        .collect::<Vec<_>>();
+    match entries.as_slice() {
+        [entry] if entry.path().is_dir() => Ok(entry.path()),
+        _ => Ok(dir.to_path_buf()),
+    }
+}
+
+/// Returns the name of a repository from its URL, used as the root label of the tree.
+///
+/// # Arguments
+///
+/// * `url` - The URL of the repository.
+///
+/// # Returns
+///
+/// * `String` - The last component of the URL without its `.git` suffix.
+fn repository_name(url: &str) -> String {
+    let name = url
+        .trim_end_matches('/')
+        .rsplit(|c| c == '/' || c == ':')
+        .next()
+        .unwrap_or("");
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    if name.is_empty() {
+        "repository".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Clones a repository into a directory, shallowly when the transport supports it.
+///
+/// # Arguments
+///
+/// * `url` - The URL of the repository.
+/// * `reference` - The branch or tag to check out, or `None` for the default branch.
+/// * `destination` - The directory to clone into.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+fn clone_repository(url: &str, reference: Option<&str>, destination: &Path) -> Result<()> {
+    let mut fetch_options = FetchOptions::new();
+    // The local transport does not support shallow fetches.
+    if !url.starts_with("file://") {
+        fetch_options.depth(1);
+    }
+
+    let Some(reference) = reference else {
+        RepoBuilder::new()
+            .fetch_options(fetch_options)
+            .clone(url, destination)
+            .with_context(|| message_with(Message::GitCloneIntoFailed, &[("path", &destination.display())]))?;
+        return Ok(());
+    };
+
+    let repo = Repository::init(destination)
+        .with_context(|| message_with(Message::GitInitFailed, &[("path", &destination.display())]))?;
+    let mut remote = repo
+        .remote("origin", url)
+        .with_context(|| message_with(Message::GitRemoteFailed, &[("url", &url)]))?;
+    let refspecs = [
+        format!("+refs/heads/{0}:refs/remotes/origin/{0}", reference),
+        format!("+refs/tags/{0}:refs/tags/{0}", reference),
+    ];
+    remote
+        .fetch(&refspecs, Some(&mut fetch_options), None)
+        .with_context(|| message_with(Message::GitFetchFailed, &[("reference", &reference)]))?;
+
+    let object = repo
+        .revparse_single(&format!("refs/remotes/origin/{}", reference))
+        .or_else(|_| repo.revparse_single(&format!("refs/tags/{}", reference)))
+        .map_err(|_| {
+            anyhow!(message_with(
+                Message::ReferenceNotFound,
+                &[("reference", &reference)]
+            ))
+        })?;
+    let commit = object
+        .peel_to_commit()
+        .with_context(|| message_with(Message::GitCheckoutFailed, &[("reference", &reference)]))?;
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().force()))
+        .with_context(|| message_with(Message::GitCheckoutFailed, &[("reference", &reference)]))?;
+    repo.set_head_detached(commit.id())
+        .with_context(|| message_with(Message::GitCheckoutFailed, &[("reference", &reference)]))?;
+    Ok(())
+}
@@ -0,0 +1,323 @@
+//! This module splits a prompt into parts under a token cap, for `--split-tokens`, so a prompt
+//! too large for one message can be pasted or stored in several.
+//!
+//! The first part renders the whole template with the first files, and each next part renders
+//! the `files` partial with the files that follow, under a header telling its position in the
+//! sequence. Files are kept whole, and only a file that does not fit in a part on its own is cut
+//! between lines into pieces.
+
+use crate::config::{Compression, Section};
+use crate::messages::{message_with, Message};
+use crate::output::write_to_file;
+use crate::template::{render_section, render_template};
+use anyhow::{bail, Context, Result};
+use handlebars::Handlebars;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use tiktoken_rs::CoreBPE;
+
+/// The delimiter of the code blocks of the files.
+const CODE_FENCE: &str = "```";
+
+/// Splits a prompt into parts of at most `cap` tokens.
+///
+/// The tokens of the parts are added up file by file, so a part may exceed the cap by the few
+/// tokens merged where two files meet.
+///
+/// # Arguments
+///
+/// * `handlebars` - The Handlebars registry with the template and its partials.
+/// * `template_name` - The name of the template.
+/// * `data` - The JSON data the prompt is rendered with.
+/// * `bpe` - The tokenizer counting the tokens of the parts.
+/// * `cap` - The maximum number of tokens of a part.
+///
+/// # Returns
+///
+/// * `Result<Vec<String>>` - The parts in order, each starting with its header, or an error if
+///   the prompt does not fit in a part without its files.
+pub fn split_prompt(
+    handlebars: &Handlebars,
+    template_name: &str,
+    data: &Value,
+    bpe: &CoreBPE,
+    cap: usize,
+) -> Result<Vec<String>> {
+    let mut without_files = data.clone();
+    without_files["files"] = json!([]);
+    let splitter = Splitter {
+        handlebars,
+        template_name,
+        data: without_files,
+        bpe,
+    };
+    let header = [(1, 999), (998, 999), (999, 999)]
+        .iter()
+        .map(|&(index, total)| splitter.count(&part_header(index, total)))
+        .max()
+        .unwrap_or(0);
+    let empty_files = splitter.count(&splitter.render_next(&[])?);
+    let first_base = header + splitter.count(&splitter.render_first(&[])?);
+    let next_base = header + empty_files;
+    let base = first_base.max(next_base);
+    if base >= cap {
+        bail!(message_with(Message::SplitTokensTooSmall, &[("tokens", &base), ("cap", &cap)]));
+    }
+    let available = cap - next_base;
+
+    // ~~~ Fill the parts file by file, starting a new part when the next file does not fit ~~~
+    let mut groups: Vec<Vec<Value>> = vec![Vec::new()];
+    let mut used = first_base;
+    for file in data["files"].as_array().into_iter().flatten() {
+        let tokens = splitter.file_tokens(file, empty_files)?;
+        let pieces = if tokens > available {
+            splitter.cut_file(file, available, empty_files)?
+        } else {
+            vec![(file.clone(), tokens)]
+        };
+        for (piece, tokens) in pieces {
+            let fresh = groups.len() > 1 && groups.last().is_some_and(Vec::is_empty);
+            if used + tokens > cap && !fresh {
+                groups.push(Vec::new());
+                used = next_base;
+            }
+            used += tokens;
+            groups.last_mut().unwrap().push(piece);
+        }
+    }
+
+    let total = groups.len();
+    groups
+        .iter()
+        .enumerate()
+        .map(|(index, files)| {
+            let body = if index == 0 {
+                splitter.render_first(files)?
+            } else {
+                splitter.render_next(files)?
+            };
+            Ok(format!("{}{}", part_header(index + 1, total), body))
+        })
+        .collect()
+}
+
+/// Returns the path of a part of the prompt, numbering it before the extension of the output
+/// file, so `prompt.md.gz` has the parts `prompt.part1.md.gz`, `prompt.part2.md.gz`, and so on.
+///
+/// # Arguments
+///
+/// * `output_path` - The path of the output file.
+/// * `index` - The position of the part, from 1.
+///
+/// # Returns
+///
+/// * `String` - The path of the part.
+pub fn part_path(output_path: &str, index: usize) -> String {
+    let (path, compressed) = match Compression::from_path(output_path) {
+        Some(compression) => output_path.split_at(output_path.len() - compression.extension().len() - 1),
+        None => (output_path, ""),
+    };
+    let name_start = path.rfind(['/', '\\']).map_or(0, |separator| separator + 1);
+    let (stem, extension) = match path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => path.split_at(name_start + dot),
+        _ => (path, ""),
+    };
+    format!("{}.part{}{}{}", stem, index, extension, compressed)
+}
+
+/// Writes the parts of a prompt next to its output file, and removes the parts left by a
+/// previous run split into more parts, which would read as the end of this prompt.
+///
+/// # Arguments
+///
+/// * `output_path` - The path of the output file the parts are named after.
+/// * `parts` - The parts of the prompt.
+/// * `compression` - The compression of the output file, if any.
+///
+/// # Returns
+///
+/// * `Result<Vec<String>>` - The paths of the parts written, or an error if one cannot be written or a stale one removed.
+pub fn write_parts(output_path: &str, parts: &[String], compression: Option<Compression>) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for (index, part) in parts.iter().enumerate() {
+        let path = part_path(output_path, index + 1);
+        write_to_file(&path, part, compression)?;
+        paths.push(path);
+    }
+    let stale = (parts.len() + 1..)
+        .map(|index| part_path(output_path, index))
+        .take_while(|path| Path::new(path).exists());
+    for path in stale {
+        fs::remove_file(&path).with_context(|| message_with(Message::RemoveFailed, &[("path", &path)]))?;
+    }
+    Ok(paths)
+}
+
+/// Returns the header of a part, telling its position in the sequence.
+///
+/// # Arguments
+///
+/// * `index` - The position of the part, from 1.
+/// * `total` - The number of parts.
+///
+/// # Returns
+///
+/// * `String` - The header, followed by a blank line.
+fn part_header(index: usize, total: usize) -> String {
+    if index == 1 {
+        format!(
+            "> Part 1 of {}. This prompt is split into {} parts; read them all before answering.\n\n",
+            total, total
+        )
+    } else if index < total {
+        format!(
+            "> Part {} of {}, continuing the files of part {}. More parts follow.\n\n",
+            index,
+            total,
+            index - 1
+        )
+    } else {
+        format!(
+            "> Part {} of {}, continuing the files of part {}. This is the last part.\n\n",
+            index,
+            total,
+            index - 1
+        )
+    }
+}
+
+/// What the parts of a prompt are rendered and measured with.
+struct Splitter<'a, 'reg> {
+    handlebars: &'a Handlebars<'reg>,
+    template_name: &'a str,
+    /// The JSON data of the prompt without its files, which each part fills in.
+    data: Value,
+    bpe: &'a CoreBPE,
+}
+
+impl Splitter<'_, '_> {
+    /// Counts the tokens of a text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to count.
+    ///
+    /// # Returns
+    ///
+    /// * `usize` - The number of tokens.
+    fn count(&self, text: &str) -> usize {
+        self.bpe.encode_with_special_tokens(text).len()
+    }
+
+    /// Renders the first part: the whole template with some of the files.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The files of the part.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - The rendered part, without its header.
+    fn render_first(&self, files: &[Value]) -> Result<String> {
+        render_template(self.handlebars, self.template_name, &self.with_files(files))
+    }
+
+    /// Renders a part after the first: the `files` partial with some of the files.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The files of the part.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String>` - The rendered part, without its header.
+    fn render_next(&self, files: &[Value]) -> Result<String> {
+        render_section(self.handlebars, self.template_name, Section::Files, &self.with_files(files))
+    }
+
+    /// Returns the data of the prompt with some of its files.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The files to render.
+    ///
+    /// # Returns
+    ///
+    /// * `Value` - The JSON data object with these files.
+    fn with_files(&self, files: &[Value]) -> Value {
+        let mut data = self.data.clone();
+        data["files"] = Value::Array(files.to_vec());
+        data
+    }
+
+    /// Counts the tokens a file adds to a part.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The file.
+    /// * `empty_files` - The tokens of the `files` partial without files.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize>` - The number of tokens of the file.
+    fn file_tokens(&self, file: &Value, empty_files: usize) -> Result<usize> {
+        let rendered = self.render_next(std::slice::from_ref(file))?;
+        Ok(self.count(&rendered).saturating_sub(empty_files))
+    }
+
+    /// Cuts a file that does not fit in a part on its own into pieces that do, between lines.
+    ///
+    /// Each piece keeps the code fences of the file, and its path names the lines it holds.
+    /// A single line larger than a part is left in a piece of its own.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The file to cut.
+    /// * `available` - The tokens a part has room for after its header.
+    /// * `empty_files` - The tokens of the `files` partial without files.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<(Value, usize)>>` - The pieces in order, with their number of tokens.
+    fn cut_file(&self, file: &Value, available: usize, empty_files: usize) -> Result<Vec<(Value, usize)>> {
+        let path = file["path"].as_str().unwrap_or_default();
+        let lines: Vec<&str> = file["code"].as_str().unwrap_or_default().lines().collect();
+        let (fence, body) = match lines.as_slice() {
+            [first, body @ .., last] if first.starts_with(CODE_FENCE) && *last == CODE_FENCE => (Some(*first), body),
+            _ => (None, lines.as_slice()),
+        };
+        let piece = |start: usize, end: usize| {
+            let code = body[start..end].join("\n");
+            let mut piece = file.clone();
+            piece["code"] = match fence {
+                Some(fence) => json!(format!("{}\n{}\n{}", fence, code, CODE_FENCE)),
+                None => json!(code),
+            };
+            piece["path"] = json!(format!("{} (lines {}-{})", path, start + 1, end));
+            piece
+        };
+
+        let overhead = self.file_tokens(&piece(0, 0), empty_files)?;
+        let mut ranges = Vec::new();
+        let (mut start, mut tokens) = (0, overhead);
+        for (index, line) in body.iter().enumerate() {
+            let line_tokens = self.count(line) + 1;
+            if tokens + line_tokens > available && index > start {
+                ranges.push((start, index));
+                start = index;
+                tokens = overhead;
+            }
+            tokens += line_tokens;
+        }
+        ranges.push((start, body.len()));
+        ranges
+            .into_iter()
+            .map(|(start, end)| {
+                let piece = piece(start, end);
+                let tokens = self.file_tokens(&piece, empty_files)?;
+                Ok((piece, tokens))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,275 @@
+//! This module implements `codexio suggest`, which analyzes the files of a codebase and
+//! recommends exclude patterns so the prompt fits a token budget.
+
+use crate::path::LOCK_FILES;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Component, Path};
+
+/// File name suffixes of minified, compiled or generated files.
+const GENERATED_SUFFIXES: [&str; 6] = [".min.js", ".min.css", ".map", ".pb.go", "_pb2.py", ".snap"];
+
+/// Directory names that usually hold build output or vendored code.
+const GENERATED_DIRECTORIES: [&str; 7] = [
+    "dist",
+    "build",
+    "generated",
+    "vendor",
+    "node_modules",
+    "target",
+    "__pycache__",
+];
+
+/// The number of entries listed per category of the report.
+const TOP_ENTRIES: usize = 5;
+
+/// The token count of a group of files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenGroup {
+    /// The name of the group, such as a directory, an extension or a file path.
+    pub name: String,
+    /// The number of files in the group.
+    pub files: usize,
+    /// The number of tokens of the files in the group.
+    pub tokens: usize,
+}
+
+/// The analysis of a codebase and the recommended selection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestions {
+    /// The number of tokens of the whole prompt.
+    pub total_tokens: usize,
+    /// The number of files in the prompt.
+    pub total_files: usize,
+    /// The token budget, if one was given.
+    pub budget: Option<usize>,
+    /// The top-level directories with the most tokens.
+    pub directories: Vec<TokenGroup>,
+    /// The file extensions with the most tokens.
+    pub extensions: Vec<TokenGroup>,
+    /// The files with the most tokens.
+    pub largest_files: Vec<TokenGroup>,
+    /// The groups of files that look generated, by exclude pattern.
+    pub generated: Vec<TokenGroup>,
+    /// The recommended exclude patterns.
+    pub excludes: Vec<String>,
+    /// The estimated number of tokens left once the patterns are excluded.
+    pub remaining_tokens: usize,
+}
+
+/// A file of the prompt, as seen by the analysis.
+#[derive(Debug)]
+struct AnalyzedFile<'a> {
+    /// The path of the file relative to its root, without the root label.
+    relative: &'a Path,
+    /// The number of tokens of the file.
+    tokens: usize,
+}
+
+/// Analyzes the files of a prompt and recommends exclude patterns to fit a budget.
+///
+/// Files that look generated, such as lock files and minified assets, are always recommended
+/// for exclusion. When the prompt is still above the budget, the largest top-level directories
+/// and then the largest files are excluded until it fits, keeping at least one file.
+///
+/// # Arguments
+///
+/// * `files` - The JSON representations of the files, with relative paths and token counts.
+/// * `total_tokens` - The number of tokens of the whole prompt.
+/// * `budget` - The token budget to fit, if one was given.
+///
+/// # Returns
+///
+/// * `Suggestions` - The analysis and the recommended exclude patterns.
+pub fn suggest(files: &[Value], total_tokens: usize, budget: Option<usize>) -> Suggestions {
+    let analyzed: Vec<AnalyzedFile> = files
+        .iter()
+        .filter_map(|file| {
+            let path = Path::new(file.get("path")?.as_str()?);
+            Some(AnalyzedFile {
+                relative: strip_root_label(path),
+                tokens: file.get("token_count").and_then(Value::as_u64).unwrap_or(0) as usize,
+            })
+        })
+        .collect();
+
+    let directories = group_by(&analyzed, |file| top_level_directory(file.relative));
+    let extensions = group_by(&analyzed, |file| {
+        file.relative
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| format!(".{}", ext))
+    });
+    let largest_files = group_by(&analyzed, |file| Some(file.relative.display().to_string()));
+    let generated = group_by(&analyzed, |file| generated_pattern(file.relative));
+
+    // ~~~ Choose the patterns to exclude ~~~
+    let mut excluded = HashSet::new();
+    let mut excludes = Vec::new();
+    let mut remaining = total_tokens;
+
+    for group in &generated {
+        let removed = exclude_matching(&analyzed, &mut excluded, |file| {
+            generated_pattern(file.relative).as_deref() == Some(group.name.as_str())
+        });
+        remaining = remaining.saturating_sub(removed);
+        excludes.push(group.name.clone());
+    }
+
+    let over_budget = |remaining: usize| budget.is_some_and(|budget| remaining > budget);
+    for group in &directories {
+        if !over_budget(remaining) {
+            break;
+        }
+        if group.files >= analyzed.len() {
+            continue;
+        }
+        let removed = exclude_matching(&analyzed, &mut excluded, |file| {
+            top_level_directory(file.relative).as_deref() == Some(group.name.as_str())
+        });
+        remaining = remaining.saturating_sub(removed);
+        excludes.push(format!("**/{}/**", group.name));
+    }
+    for group in &largest_files {
+        if !over_budget(remaining) || excluded.len() + 1 >= analyzed.len() {
+            break;
+        }
+        let removed = exclude_matching(&analyzed, &mut excluded, |file| {
+            file.relative.display().to_string() == group.name
+        });
+        if removed > 0 {
+            remaining = remaining.saturating_sub(removed);
+            excludes.push(format!("**/{}", group.name));
+        }
+    }
+
+    Suggestions {
+        total_tokens,
+        total_files: analyzed.len(),
+        budget,
+        directories: directories.into_iter().take(TOP_ENTRIES).collect(),
+        extensions: extensions.into_iter().take(TOP_ENTRIES).collect(),
+        largest_files: largest_files.into_iter().take(TOP_ENTRIES).collect(),
+        generated,
+        excludes,
+        remaining_tokens: remaining,
+    }
+}
+
+/// Returns the exclude pattern matching a file that looks generated.
+///
+/// # Arguments
+///
+/// * `relative` - The path of the file relative to its root.
+///
+/// # Returns
+///
+/// * `Option<String>` - The pattern, or `None` if the file does not look generated.
+pub fn generated_pattern(relative: &Path) -> Option<String> {
+    let file_name = relative.file_name()?.to_str()?;
+    if LOCK_FILES.contains(&file_name) {
+        return Some(format!("**/{}", file_name));
+    }
+    if let Some(suffix) = GENERATED_SUFFIXES
+        .iter()
+        .find(|suffix| file_name.ends_with(*suffix))
+    {
+        return Some(format!("*{}", suffix));
+    }
+    relative
+        .parent()?
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .find(|name| GENERATED_DIRECTORIES.contains(name))
+        .map(|name| format!("**/{}/**", name))
+}
+
+/// Marks the files matching a predicate as excluded.
+///
+/// # Arguments
+///
+/// * `files` - The analyzed files.
+/// * `excluded` - The indices of the files excluded so far.
+/// * `matches` - Whether a file is matched by the pattern being excluded.
+///
+/// # Returns
+///
+/// * `usize` - The number of tokens of the files that were not excluded before.
+fn exclude_matching(
+    files: &[AnalyzedFile],
+    excluded: &mut HashSet<usize>,
+    matches: impl Fn(&AnalyzedFile) -> bool,
+) -> usize {
+    files
+        .iter()
+        .enumerate()
+        .filter(|(index, file)| matches(file) && excluded.insert(*index))
+        .map(|(_, file)| file.tokens)
+        .sum()
+}
+
+/// Groups files by a key and sums their tokens, largest groups first.
+///
+/// # Arguments
+///
+/// * `files` - The analyzed files.
+/// * `key` - Returns the group of a file, or `None` to leave it out.
+///
+/// # Returns
+///
+/// * `Vec<TokenGroup>` - The groups, sorted by decreasing token count, then by name.
+fn group_by(files: &[AnalyzedFile], key: impl Fn(&AnalyzedFile) -> Option<String>) -> Vec<TokenGroup> {
+    let mut groups: BTreeMap<String, TokenGroup> = BTreeMap::new();
+    for file in files {
+        let Some(name) = key(file) else {
+            continue;
+        };
+        let group = groups.entry(name.clone()).or_insert(TokenGroup {
+            name,
+            files: 0,
+            tokens: 0,
+        });
+        group.files += 1;
+        group.tokens += file.tokens;
+    }
+    let mut groups: Vec<TokenGroup> = groups.into_values().collect();
+    groups.sort_by(|a, b| b.tokens.cmp(&a.tokens).then_with(|| a.name.cmp(&b.name)));
+    groups
+}
+
+/// Removes the root label from a relative file path.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file, starting with the label of its root.
+///
+/// # Returns
+///
+/// * `&Path` - The path relative to the root.
+fn strip_root_label(path: &Path) -> &Path {
+    let mut components = path.components();
+    components.next();
+    match components.as_path() {
+        rest if rest.as_os_str().is_empty() => path,
+        rest => rest,
+    }
+}
+
+/// Returns the top-level directory of a path relative to its root.
+///
+/// # Arguments
+///
+/// * `relative` - The path of the file relative to its root.
+///
+/// # Returns
+///
+/// * `Option<String>` - The first directory of the path, or `None` for files at the root.
+fn top_level_directory(relative: &Path) -> Option<String> {
+    let mut components = relative.components();
+    let first = components.next()?;
+    components.next()?;
+    Some(first.as_os_str().to_string_lossy().to_string())
+}
@@ -1,12 +1,335 @@
 //! This module contains the functions to set up the Handlebars template engine and render the template with the provided data.
 //! It also includes functions for handling user-defined variables, copying the rendered output to the clipboard, and writing it to a file.
 
-use anyhow::{Result};
-use handlebars::{no_escape, Handlebars};
+use crate::binary::is_binary;
+use crate::charset::{decode_text, decode_utf16};
+use crate::config::{FileSize, Section};
+use crate::filter::is_sensitive_file;
+use crate::form::{parse_forms, placeholder_form};
+use crate::language::detect_language;
+use crate::messages::{message_with, Message};
+use crate::path::{code_fence, wrap_code_block};
+use crate::secrets::redact_secrets;
+use crate::token::get_tokenizer;
+use anyhow::Result;
+use handlebars::{
+    no_escape, Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError,
+    RenderErrorReason, TemplateErrorReason,
+};
+use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::instrument;
+
+/// The partials the default template is built from, available to custom templates as well.
+const PARTIALS: [(&str, &str); 4] = [
+    ("tree", include_str!("partials/tree.hbs")),
+    ("files", include_str!("partials/files.hbs")),
+    ("diff", include_str!("partials/diff.hbs")),
+    ("instructions", include_str!("partials/instructions.hbs")),
+];
+
+/// The name the template is registered under by `codexio template preview`.
+const PREVIEW_TEMPLATE_NAME: &str = "preview";
+
+/// The name of the helper embedding a file, as in `{{include_file "docs/spec.md"}}`.
+pub const INCLUDE_FILE_HELPER: &str = "include_file";
+
+/// The template engines set up so far, by template name and content, shared by the jobs of a batch.
+static REGISTRIES: Lazy<Mutex<HashMap<(String, String), Handlebars<'static>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The stage of the template engine at which a template error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateStage {
+    /// The template could not be parsed when it was registered.
+    Register,
+    /// The template could not be rendered with the prompt data.
+    Render,
+}
+
+/// The variable, helper or partial of a template that caused an error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "name", rename_all = "lowercase")]
+pub enum TemplateSubject {
+    /// A variable or JSON path of the template.
+    Variable(String),
+    /// A helper called by the template.
+    Helper(String),
+    /// A partial included by the template.
+    Partial(String),
+}
+
+/// An error of the template engine, with the location in the template that caused it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TemplateError {
+    /// The stage at which the error occurred.
+    pub stage: TemplateStage,
+    /// The name of the template or partial the error occurred in.
+    pub template: String,
+    /// The line of the template the error occurred at, starting at 1.
+    pub line: Option<usize>,
+    /// The column of the template the error occurred at, starting at 1.
+    pub column: Option<usize>,
+    /// The variable, helper or partial that caused the error, if known.
+    pub subject: Option<TemplateSubject>,
+    /// The reason reported by Handlebars.
+    pub reason: String,
+}
+
+impl TemplateError {
+    /// Builds the error from a Handlebars template error, raised while registering a template.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The Handlebars template error.
+    /// * `template_name` - The name of the template being registered.
+    ///
+    /// # Returns
+    ///
+    /// * `TemplateError` - The error with its location.
+    pub fn from_register(error: &handlebars::TemplateError, template_name: &str) -> Self {
+        let subject = match error.reason() {
+            TemplateErrorReason::MismatchingClosedHelper(open, _) => {
+                Some(TemplateSubject::Helper(open.clone()))
+            }
+            _ => None,
+        };
+        TemplateError {
+            stage: TemplateStage::Register,
+            template: error
+                .template_name
+                .clone()
+                .unwrap_or_else(|| template_name.to_string()),
+            line: error.line_no,
+            column: error.column_no,
+            subject,
+            reason: error.reason().to_string(),
+        }
+    }
+
+    /// Builds the error from a Handlebars render error.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The Handlebars render error.
+    /// * `template_name` - The name of the template being rendered.
+    ///
+    /// # Returns
+    ///
+    /// * `TemplateError` - The error with its location.
+    pub fn from_render(error: &RenderError, template_name: &str) -> Self {
+        let subject = match error.reason() {
+            RenderErrorReason::MissingVariable(Some(path))
+            | RenderErrorReason::InvalidJsonPath(path)
+            | RenderErrorReason::InvalidJsonIndex(path) => Some(TemplateSubject::Variable(path.clone())),
+            RenderErrorReason::HelperNotFound(name) => Some(TemplateSubject::Helper(name.clone())),
+            RenderErrorReason::ParamNotFoundForIndex(helper, _)
+            | RenderErrorReason::ParamNotFoundForName(helper, _)
+            | RenderErrorReason::ParamTypeMismatchForName(helper, _, _)
+            | RenderErrorReason::HashTypeMismatchForName(helper, _, _) => {
+                Some(TemplateSubject::Helper(helper.to_string()))
+            }
+            RenderErrorReason::PartialNotFound(name) => Some(TemplateSubject::Partial(name.clone())),
+            _ => None,
+        };
+        TemplateError {
+            stage: TemplateStage::Render,
+            template: error
+                .template_name
+                .clone()
+                .unwrap_or_else(|| template_name.to_string()),
+            line: error.line_no,
+            column: error.column_no,
+            subject,
+            reason: error.reason().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failed = match self.stage {
+            TemplateStage::Register => Message::TemplateRegisterFailed,
+            TemplateStage::Render => Message::TemplateRenderFailed,
+        };
+        write!(f, "{}", message_with(failed, &[("error", &self.reason)]))?;
+
+        let location = match (self.line, self.column) {
+            (Some(line), Some(column)) => format!("{}:{}:{}", self.template, line, column),
+            (Some(line), None) => format!("{}:{}", self.template, line),
+            _ => self.template.clone(),
+        };
+        write!(f, "\n  {}", message_with(Message::TemplateErrorLocation, &[("location", &location)]))?;
+
+        if let Some(subject) = &self.subject {
+            let (kind, name) = match subject {
+                TemplateSubject::Variable(name) => (Message::TemplateErrorVariable, name),
+                TemplateSubject::Helper(name) => (Message::TemplateErrorHelper, name),
+                TemplateSubject::Partial(name) => (Message::TemplateErrorPartial, name),
+            };
+            write!(f, "\n  {}", message_with(kind, &[("name", name)]))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// The `include_file` helper, which reads a file when the template is rendered and wraps it in a
+/// code block like the packed files.
+#[derive(Debug, Clone)]
+pub struct IncludeFile {
+    /// The directory relative paths are resolved against, usually the first codebase path.
+    pub base: PathBuf,
+    /// Whether to replace secrets in the file with placeholders.
+    pub redact: bool,
+    /// Whether files holding credentials, such as `.env` files, may be included.
+    pub allow_sensitive: bool,
+    /// The size above which files are refused, if any.
+    pub max_file_size: Option<FileSize>,
+    /// Whether to add line numbers to the file.
+    pub line_numbers: bool,
+    /// Whether to leave the file out of a code block.
+    pub no_codeblock: bool,
+    /// The canonical directories files may be included from, or only the base directory if empty.
+    pub roots: Vec<PathBuf>,
+    /// The token budget the included files are counted against, if any.
+    pub budget: Option<usize>,
+    /// The tokenizer counting the tokens of the included files against the budget.
+    pub encoding: Option<String>,
+    /// The tokens of each file included so far, shared by the clones of the helper, so a file
+    /// embedded by several renders of the template is counted once.
+    pub included_tokens: Arc<Mutex<HashMap<PathBuf, usize>>>,
+}
+
+impl Default for IncludeFile {
+    fn default() -> Self {
+        IncludeFile {
+            base: PathBuf::from("."),
+            redact: true,
+            allow_sensitive: false,
+            max_file_size: None,
+            line_numbers: false,
+            no_codeblock: false,
+            roots: Vec::new(),
+            budget: None,
+            encoding: None,
+            included_tokens: Arc::default(),
+        }
+    }
+}
+
+impl IncludeFile {
+    /// Reads a file and wraps it in a code block.
+    ///
+    /// Files outside the roots are refused, so a template cannot embed any readable file of the
+    /// machine, and the included files fail the render once they exceed the token budget together.
+    ///
+    /// # Arguments
+    ///
+    /// * `relative` - The path to the file, relative to the base directory or absolute.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, String>` - The wrapped file, or the reason it cannot be included.
+    pub fn render_file(&self, relative: &str) -> Result<String, String> {
+        let shown = Path::new(relative).display();
+        let path = self
+            .base
+            .join(relative)
+            .canonicalize()
+            .map_err(|e| message_with(Message::IncludeFileUnreadable, &[("path", &shown), ("error", &e)]))?;
+        let inside = if self.roots.is_empty() {
+            self.base.canonicalize().is_ok_and(|base| path.starts_with(base))
+        } else {
+            self.roots.iter().any(|root| path.starts_with(root))
+        };
+        if !inside {
+            return Err(message_with(Message::IncludeFileOutsideRoots, &[("path", &shown)]));
+        }
+        if !self.allow_sensitive && is_sensitive_file(&path) {
+            return Err(message_with(Message::IncludeFileSensitive, &[("path", &shown)]));
+        }
+        let bytes = fs::read(&path)
+            .map_err(|e| message_with(Message::IncludeFileUnreadable, &[("path", &shown), ("error", &e)]))?;
+        let size = FileSize(bytes.len() as u64);
+        if let Some(limit) = self.max_file_size.filter(|limit| size > *limit) {
+            return Err(message_with(
+                Message::IncludeFileTooLarge,
+                &[("path", &shown), ("size", &size), ("limit", &limit)],
+            ));
+        }
+        let decoded = decode_utf16(&bytes)
+            .or_else(|| if is_binary(&bytes) { None } else { decode_text(&bytes) })
+            .ok_or_else(|| message_with(Message::IncludeFileBinary, &[("path", &shown)]))?;
+
+        let text = if self.redact {
+            redact_secrets(&decoded.text).0
+        } else {
+            decoded.text
+        };
+        if let Some(budget) = self.budget {
+            let tokens = get_tokenizer(&self.encoding).encode_with_special_tokens(&text).len();
+            let mut included = self.included_tokens.lock().unwrap();
+            included.insert(path.clone(), tokens);
+            let total: usize = included.values().sum();
+            if total > budget {
+                return Err(message_with(
+                    Message::IncludeFileOverBudget,
+                    &[("path", &shown), ("tokens", &total), ("budget", &budget)],
+                ));
+            }
+        }
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or("");
+        let language = detect_language(&path, &text);
+        Ok(wrap_code_block(
+            &text,
+            code_fence(extension, language.as_deref()),
+            self.line_numbers,
+            self.no_codeblock,
+        ))
+    }
+}
+
+impl HelperDef for IncludeFile {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        helper: &Helper<'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let param = helper
+            .param(0)
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex(INCLUDE_FILE_HELPER, 0))?;
+        let relative = param.value().as_str().ok_or_else(|| {
+            RenderErrorReason::ParamTypeMismatchForName(
+                INCLUDE_FILE_HELPER,
+                "0".to_string(),
+                "string".to_string(),
+            )
+        })?;
+        let rendered = self.render_file(relative).map_err(RenderErrorReason::Other)?;
+        out.write(&rendered)?;
+        Ok(())
+    }
+}
 
 /// Set up the Handlebars template engine with a template string and a template name.
 ///
+/// The section partials (`tree`, `files`, `diff` and `instructions`) are registered as well, and
+/// the `include_file` helper, resolving paths against the current directory with secrets redacted.
+/// Engines are cached by template name and content, so the jobs of a batch parse each template once.
+///
 /// # Arguments
 ///
 /// * `template_str` - The Handlebars template string.
@@ -16,13 +339,27 @@ use regex::Regex;
 ///
 /// * `Result<Handlebars<'static>>` - The configured Handlebars instance.
 pub fn handlebars_setup(template_str: &str, template_name: &str) -> Result<Handlebars<'static>> {
+    let key = (template_name.to_string(), template_str.to_string());
+    if let Some(handlebars) = REGISTRIES.lock().unwrap().get(&key) {
+        return Ok(handlebars.clone());
+    }
+
     let mut handlebars = Handlebars::new();
     handlebars.register_escape_fn(no_escape);
 
+    for (name, partial) in PARTIALS {
+        handlebars
+            .register_partial(name, partial)
+            .map_err(|e| anyhow::anyhow!(message_with(Message::PartialRegisterFailed, &[("name", &name), ("error", &e)])))?;
+    }
+
+    handlebars.register_helper(INCLUDE_FILE_HELPER, Box::new(IncludeFile::default()));
+
     handlebars
         .register_template_string(template_name, template_str)
-        .map_err(|e| anyhow::anyhow!("Failed to register template: {}", e))?;
+        .map_err(|e| TemplateError::from_register(&e, template_name))?;
 
+    REGISTRIES.lock().unwrap().insert(key, handlebars.clone());
     Ok(handlebars)
 }
 
@@ -46,6 +383,8 @@ pub fn extract_undefined_variables(template: &str) -> Vec<String> {
 
 /// Renders the template with the provided data.
 ///
+/// Failures are reported as a [`TemplateError`] carrying the template, line and column.
+///
 /// # Arguments
 ///
 /// * `handlebars` - The configured Handlebars instance.
@@ -62,6 +401,115 @@ pub fn render_template(
 ) -> Result<String> {
     let rendered = handlebars
         .render(template_name, data)
-        .map_err(|e| anyhow::anyhow!("Failed to render template: {}", e))?;
+        .map_err(|e| TemplateError::from_render(&e, template_name))?;
     Ok(rendered.trim().to_string())
-}
\ No newline at end of file
+}
+
+/// Renders a single section of the prompt using its partial.
+///
+/// # Arguments
+///
+/// * `handlebars` - The configured Handlebars instance.
+/// * `template_name` - The name of the template, used when the whole prompt is requested.
+/// * `section` - The section to render.
+/// * `data` - The JSON data object.
+///
+/// # Returns
+///
+/// * `Result<String>` - The rendered section as a string.
+#[instrument(name = "render", skip_all)]
+pub fn render_section(
+    handlebars: &Handlebars,
+    template_name: &str,
+    section: Section,
+    data: &Value,
+) -> Result<String> {
+    match section.partial_name() {
+        Some(partial) => render_template(handlebars, partial, data),
+        None => render_template(handlebars, template_name, data),
+    }
+}
+
+/// Returns the data of the prompt without the data a section renders, so the rest of the prompt
+/// can be rendered without it.
+///
+/// # Arguments
+///
+/// * `data` - The JSON data object.
+/// * `section` - The section to leave out.
+///
+/// # Returns
+///
+/// * `Value` - The JSON data object without the fields of the section, or unchanged for the whole prompt.
+pub fn without_section(data: &Value, section: Section) -> Value {
+    let fields: &[&str] = match section {
+        Section::Files => &["files", "common_headers", "tiers"],
+        Section::Diff => &["git_diff", "git_diff_branch", "git_log_branch"],
+        Section::Tree => &["source_tree"],
+        Section::Instructions => &["instructions"],
+        Section::All => &[],
+    };
+    let mut data = data.clone();
+    if let Some(object) = data.as_object_mut() {
+        for field in fields {
+            object.remove(*field);
+        }
+    }
+    data
+}
+
+/// Returns small sample data shaped like the data of a real run, with two files and a diff.
+///
+/// # Returns
+///
+/// * `Value` - The sample JSON data object.
+pub fn sample_data() -> Value {
+    json!({
+        "absolute_code_path": "example",
+        "source_tree": "example\n├── Cargo.toml\n└── src\n    └── main.rs",
+        "files": [
+            {
+                "path": "example/Cargo.toml",
+                "extension": "toml",
+                "code": wrap_code_block("[package]\nname = \"example\"\nversion = \"0.1.0\"", "toml", false, false),
+            },
+            {
+                "path": "example/src/main.rs",
+                "extension": "rs",
+                "code": wrap_code_block("fn main() {\n    println!(\"Hello, world!\");\n}", "rs", false, false),
+            },
+        ],
+        "git_diff": "diff --git a/src/main.rs b/src/main.rs\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,3 +1,3 @@\n fn main() {\n-    println!(\"Hello\");\n+    println!(\"Hello, world!\");\n }",
+        "git_diff_branch": "",
+        "git_log_branch": "",
+        "instructions": "Explain what this program does."
+    })
+}
+
+/// Renders a template against the sample data, for `codexio template preview`.
+///
+/// User-defined variables and the fields of declared forms are filled with a `<name>` placeholder
+/// instead of being asked for.
+///
+/// # Arguments
+///
+/// * `template_str` - The Handlebars template string.
+///
+/// # Returns
+///
+/// * `Result<String>` - The rendered preview.
+pub fn preview_template(template_str: &str) -> Result<String> {
+    let handlebars = handlebars_setup(template_str, PREVIEW_TEMPLATE_NAME)?;
+    let mut data = sample_data();
+    for form in parse_forms(template_str)? {
+        if data.get(&form.name).is_none() {
+            data[&form.name] = placeholder_form(&form);
+        }
+    }
+    for variable in extract_undefined_variables(template_str) {
+        if data.get(&variable).is_none() {
+            data[&variable] = json!(format!("<{}>", variable));
+        }
+    }
+    render_template(&handlebars, PREVIEW_TEMPLATE_NAME, &data)
+}
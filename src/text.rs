@@ -0,0 +1,203 @@
+//! This module contains the text helpers shared by rendering and truncation, which measure
+//! strings by display width so multi-byte and wide characters are never misaligned.
+
+use std::borrow::Cow;
+use unicode_width::UnicodeWidthStr;
+
+/// The minimum width of the line numbers added by `--line-number`.
+const MIN_LINE_NUMBER_WIDTH: usize = 4;
+
+/// Returns the number of terminal columns a string takes, counting wide characters as two.
+///
+/// # Arguments
+///
+/// * `text` - The string to measure.
+///
+/// # Returns
+///
+/// * `usize` - The display width of the string.
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// Cuts a string to at most a number of lines.
+///
+/// # Arguments
+///
+/// * `text` - The string to cut.
+/// * `max_lines` - The number of lines to keep.
+///
+/// # Returns
+///
+/// * `&str` - The first `max_lines` lines, without the newline ending the last one.
+pub fn truncate_lines(text: &str, max_lines: usize) -> &str {
+    if max_lines == 0 {
+        return "";
+    }
+    match text.match_indices('\n').nth(max_lines - 1) {
+        Some((index, _)) => &text[..index],
+        None => text,
+    }
+}
+
+/// Pads a string with spaces on the right up to a display width.
+///
+/// # Arguments
+///
+/// * `text` - The string to pad.
+/// * `width` - The display width to reach.
+///
+/// # Returns
+///
+/// * `String` - The padded string, unchanged if it is already wider.
+pub fn pad_right(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(display_width(text))))
+}
+
+/// Pads a string with spaces on the left up to a display width.
+///
+/// # Arguments
+///
+/// * `text` - The string to pad.
+/// * `width` - The display width to reach.
+///
+/// # Returns
+///
+/// * `String` - The padded string, unchanged if it is already wider.
+pub fn pad_left(text: &str, width: usize) -> String {
+    format!("{}{}", " ".repeat(width.saturating_sub(display_width(text))), text)
+}
+
+/// Prefixes each line of code with its line number.
+///
+/// Line numbers are right-aligned to the widest one, and at least four columns wide.
+///
+/// # Arguments
+///
+/// * `code` - The code to number.
+///
+/// # Returns
+///
+/// * `String` - The numbered lines, each followed by a newline.
+pub fn number_lines(code: &str) -> String {
+    number_lines_from(code, 1)
+}
+
+/// Prefixes each line of code with its line number, starting at a given line.
+///
+/// # Arguments
+///
+/// * `code` - The code to number.
+/// * `first_line` - The number of the first line, for code that does not start at the top of its file.
+///
+/// # Returns
+///
+/// * `String` - The numbered lines, each followed by a newline.
+pub fn number_lines_from(code: &str, first_line: usize) -> String {
+    let last_line = first_line + code.lines().count().saturating_sub(1);
+    let width = last_line.to_string().len().max(MIN_LINE_NUMBER_WIDTH);
+    code.lines()
+        .enumerate()
+        .map(|(index, line)| format!("{:>width$} | {}\n", first_line + index, line, width = width))
+        .collect()
+}
+
+/// Returns the anchor of a line: its number and a short hash of its content, such as `12:3fa`.
+///
+/// The hash is FNV-1a, so anchors are the same on every machine and release, and ignores
+/// trailing whitespace, including the carriage returns of Windows line endings.
+///
+/// # Arguments
+///
+/// * `number` - The number of the line, starting at 1.
+/// * `line` - The content of the line.
+///
+/// # Returns
+///
+/// * `String` - The anchor of the line.
+pub fn line_anchor(number: usize, line: &str) -> String {
+    let hash = line
+        .trim_end()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3));
+    format!("{}:{:03x}", number, hash & 0xfff)
+}
+
+/// Prefixes each line of code with its anchor, starting at a given line, for the `patch` preset.
+///
+/// # Arguments
+///
+/// * `code` - The code to anchor.
+/// * `first_line` - The number of the first line, for code that does not start at the top of its file.
+///
+/// # Returns
+///
+/// * `String` - The anchored lines, each followed by a newline.
+pub fn anchor_lines(code: &str, first_line: usize) -> String {
+    let last_line = first_line + code.lines().count().saturating_sub(1);
+    let width = last_line.to_string().len().max(MIN_LINE_NUMBER_WIDTH) + 4;
+    code.lines()
+        .enumerate()
+        .map(|(index, line)| format!("{:>width$} | {}\n", line_anchor(first_line + index, line), line, width = width))
+        .collect()
+}
+
+/// Escapes the characters with a meaning in XML and HTML text and attributes.
+///
+/// # Arguments
+///
+/// * `text` - The text to escape.
+///
+/// # Returns
+///
+/// * `String` - The escaped text.
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Makes text safe to put in an XML element as is, wrapping it in a CDATA section if it holds
+/// markup, with any `]]>` in it split across two sections.
+///
+/// # Arguments
+///
+/// * `text` - The text to put in the element.
+///
+/// # Returns
+///
+/// * `Cow<str>` - The text itself if it holds no markup, or its CDATA sections.
+pub fn xml_text(text: &str) -> Cow<'_, str> {
+    if text.contains(['<', '&']) {
+        Cow::Owned(format!("<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>")))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Checks whether text contains git conflict markers: a `<<<<<<<` line followed by a `=======`
+/// line and a `>>>>>>>` line.
+///
+/// # Arguments
+///
+/// * `text` - The text to check.
+///
+/// # Returns
+///
+/// * `bool` - `true` if the text has at least one complete conflict.
+pub fn has_conflict_markers(text: &str) -> bool {
+    let markers = ["<<<<<<<", "=======", ">>>>>>>"];
+    let mut next = 0;
+    for line in text.lines() {
+        let rest = line.trim_end().strip_prefix(markers[next]);
+        if rest.is_some_and(|rest| rest.is_empty() || rest.starts_with(' ')) {
+            next += 1;
+            if next == markers.len() {
+                return true;
+            }
+        }
+    }
+    false
+}
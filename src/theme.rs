@@ -0,0 +1,200 @@
+//! This module defines the color themes used for console status output and HTML pages.
+
+use crate::config::{ColorsConfig, HtmlTheme, ThemeName};
+use crate::messages::{message_with, Message};
+use anyhow::{anyhow, Result};
+use colored::Color;
+use once_cell::sync::OnceCell;
+
+/// The theme in use, set once at startup.
+static THEME: OnceCell<Theme> = OnceCell::new();
+
+/// The colors used for the parts of a status line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// The color of the brackets around status symbols.
+    pub bracket: Color,
+    /// The color of success symbols and messages.
+    pub success: Color,
+    /// The color of informational symbols.
+    pub info: Color,
+    /// The color of error symbols and messages.
+    pub error: Color,
+    /// The color of highlighted values, such as token counts.
+    pub highlight: Color,
+}
+
+impl Theme {
+    /// Returns one of the built-in themes.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the built-in theme.
+    ///
+    /// # Returns
+    ///
+    /// * `Theme` - The built-in theme.
+    pub fn builtin(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Default => Theme {
+                bracket: Color::White,
+                success: Color::Green,
+                info: Color::Blue,
+                error: Color::Red,
+                highlight: Color::Yellow,
+            },
+            ThemeName::HighContrast => Theme {
+                bracket: Color::BrightWhite,
+                success: Color::BrightGreen,
+                info: Color::BrightCyan,
+                error: Color::BrightRed,
+                highlight: Color::BrightYellow,
+            },
+        }
+    }
+
+    /// Builds a theme from a built-in theme and the color overrides of the configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the built-in theme to start from.
+    /// * `colors` - The color overrides, if any.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Theme>` - The resulting theme, or an error if a color name is not recognized.
+    pub fn from_config(name: ThemeName, colors: Option<&ColorsConfig>) -> Result<Self> {
+        let mut theme = Theme::builtin(name);
+        if let Some(colors) = colors {
+            for (slot, value) in [
+                (&mut theme.bracket, &colors.bracket),
+                (&mut theme.success, &colors.success),
+                (&mut theme.info, &colors.info),
+                (&mut theme.error, &colors.error),
+                (&mut theme.highlight, &colors.highlight),
+            ] {
+                if let Some(value) = value {
+                    *slot = value
+                        .parse()
+                        .map_err(|_| anyhow!(message_with(Message::UnknownColor, &[("color", &value)])))?;
+                }
+            }
+        }
+        Ok(theme)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::builtin(ThemeName::Default)
+    }
+}
+
+/// The colors of an HTML page, as CSS colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HtmlPalette {
+    /// The page background.
+    pub background: &'static str,
+    /// The text.
+    pub text: &'static str,
+    /// The table borders.
+    pub border: &'static str,
+    /// The bars of charts.
+    pub bar: &'static str,
+}
+
+impl HtmlPalette {
+    /// Returns the light or dark palette of a built-in theme.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the built-in theme.
+    /// * `dark` - Whether to return the dark palette instead of the light one.
+    ///
+    /// # Returns
+    ///
+    /// * `HtmlPalette` - The palette.
+    pub fn builtin(name: ThemeName, dark: bool) -> Self {
+        match (name, dark) {
+            (ThemeName::Default, false) => HtmlPalette {
+                background: "#ffffff",
+                text: "#1f2328",
+                border: "#d0d7de",
+                bar: "#4a7bd0",
+            },
+            (ThemeName::Default, true) => HtmlPalette {
+                background: "#0d1117",
+                text: "#e6edf3",
+                border: "#30363d",
+                bar: "#58a6ff",
+            },
+            (ThemeName::HighContrast, false) => HtmlPalette {
+                background: "#ffffff",
+                text: "#000000",
+                border: "#000000",
+                bar: "#0030a0",
+            },
+            (ThemeName::HighContrast, true) => HtmlPalette {
+                background: "#000000",
+                text: "#ffffff",
+                border: "#ffffff",
+                bar: "#ffd700",
+            },
+        }
+    }
+
+    /// Returns the CSS declarations setting the variables of the palette.
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The `--background`, `--text`, `--border` and `--bar` declarations.
+    fn variables(&self) -> String {
+        format!(
+            "--background: {}; --text: {}; --border: {}; --bar: {};",
+            self.background, self.text, self.border, self.bar
+        )
+    }
+}
+
+/// Returns the CSS rules defining the colors of an HTML page as variables of `:root`.
+///
+/// # Arguments
+///
+/// * `html_theme` - Whether to use the light or dark palette, or to follow `prefers-color-scheme`.
+/// * `name` - The built-in theme the palettes are taken from.
+///
+/// # Returns
+///
+/// * `String` - The CSS rules, used as `var(--background)`, `var(--text)`, `var(--border)` and
+///   `var(--bar)`.
+pub fn html_theme_css(html_theme: HtmlTheme, name: ThemeName) -> String {
+    let light = HtmlPalette::builtin(name, false);
+    let dark = HtmlPalette::builtin(name, true);
+    match html_theme {
+        HtmlTheme::Light => format!(":root {{ color-scheme: light; {} }}\n", light.variables()),
+        HtmlTheme::Dark => format!(":root {{ color-scheme: dark; {} }}\n", dark.variables()),
+        HtmlTheme::Auto => format!(
+            ":root {{ color-scheme: light dark; {} }}\n@media (prefers-color-scheme: dark) {{ :root {{ {} }} }}\n",
+            light.variables(),
+            dark.variables()
+        ),
+    }
+}
+
+/// Sets the theme used for status output. Only the first call has an effect.
+///
+/// # Arguments
+///
+/// * `theme` - The theme to use.
+pub fn set_theme(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// Returns the theme used for status output.
+///
+/// # Returns
+///
+/// * `&'static Theme` - The theme set at startup, or the default theme.
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
@@ -0,0 +1,160 @@
+//! This module fits the files of a prompt to its token budget by priority tier.
+//!
+//! Tiers are configured as lists of patterns, `tier1` holding the files that matter most:
+//!
+//! ```toml
+//! [tiers]
+//! tier1 = ["**/src/core/**"]
+//! tier2 = ["**/src/**"]
+//! ```
+//!
+//! Files matching no tier come last. Under a budget, the tiers are kept in full in order while
+//! they fit. From the first one that does not, each tier is summarized while its placeholders
+//! fit, and from the first one that does not fit even summarized, it and every tier after it are
+//! dropped, so a lower tier never keeps more than a higher one and what a budget keeps is
+//! predictable.
+
+use crate::config::FileSize;
+use crate::messages::{message_with, Message};
+use anyhow::{bail, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tiktoken_rs::CoreBPE;
+
+/// The name the files matching no tier are reported under.
+pub const UNTIERED: &str = "untiered";
+
+/// What a tier kept of its files under the token budget.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TierStatus {
+    /// The files are packed in full.
+    Full,
+    /// The files are listed with a placeholder instead of their content.
+    Summarized,
+    /// The files are left out.
+    Dropped,
+}
+
+/// What a tier kept of its files, recorded in the prompt data as `tiers`.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TierReport {
+    /// The name of the tier, such as `tier1`, or `untiered`.
+    pub name: String,
+    /// What the tier kept of its files.
+    pub status: TierStatus,
+    /// The number of files of the tier.
+    pub files: usize,
+    /// The number of tokens of the files of the tier, as packed.
+    pub tokens: usize,
+}
+
+/// Orders the configured tiers by their number, `tier1` first.
+///
+/// # Arguments
+///
+/// * `tiers` - The patterns of each tier, by name.
+///
+/// # Returns
+///
+/// * `Result<Vec<(String, Vec<String>)>>` - The names and patterns of the tiers in order, or an error if a name is not `tier` followed by a number.
+pub fn ordered_tiers(tiers: &HashMap<String, Vec<String>>) -> Result<Vec<(String, Vec<String>)>> {
+    let mut ordered = Vec::new();
+    for (name, patterns) in tiers {
+        let Some(number) = name.strip_prefix("tier").and_then(|number| number.parse::<u32>().ok()) else {
+            bail!(message_with(Message::InvalidTierName, &[("name", name)]));
+        };
+        ordered.push((number, name.clone(), patterns.clone()));
+    }
+    ordered.sort();
+    Ok(ordered.into_iter().map(|(_, name, patterns)| (name, patterns)).collect())
+}
+
+/// Fits the files to the token budget, tier by tier.
+///
+/// Each file is in the tier given by its `tier` position, or comes last without one. Its tokens
+/// are taken from its `token_count`. Summarized files keep their path, with a placeholder naming
+/// their size and token count as their `code`, and are marked with `"summarized": true`.
+///
+/// # Arguments
+///
+/// * `files` - The JSON representations of the files, updated in place.
+/// * `names` - The names of the tiers, in order.
+/// * `budget` - The number of tokens the files can take.
+/// * `bpe` - The tokenizer counting the tokens of the placeholders.
+///
+/// # Returns
+///
+/// * `Vec<TierReport>` - What each tier kept, in order, including the untiered files if there are any.
+pub fn fit_tiers(files: &mut Vec<Value>, names: &[String], budget: usize, bpe: &CoreBPE) -> Vec<TierReport> {
+    let tier_of = |file: &Value| {
+        file["tier"]
+            .as_u64()
+            .map_or(names.len(), |tier| (tier as usize).min(names.len()))
+    };
+    let tokens_of = |file: &Value| file["token_count"].as_u64().unwrap_or(0) as usize;
+
+    let mut reports = Vec::new();
+    let mut used = 0;
+    let mut degraded = false;
+    let mut dropping = false;
+    for tier in 0..=names.len() {
+        let members: Vec<usize> = (0..files.len()).filter(|&index| tier_of(&files[index]) == tier).collect();
+        if tier == names.len() && members.is_empty() {
+            break;
+        }
+        let full: usize = members.iter().map(|&index| tokens_of(&files[index])).sum();
+        let placeholders: Vec<String> = members.iter().map(|&index| summary_placeholder(&files[index])).collect();
+        let summarized: Vec<usize> = placeholders
+            .iter()
+            .map(|placeholder| bpe.encode_with_special_tokens(placeholder).len())
+            .collect();
+
+        let (status, tokens) = if !degraded && used + full <= budget {
+            (TierStatus::Full, full)
+        } else if !dropping && used + summarized.iter().sum::<usize>() <= budget {
+            degraded = true;
+            for ((&index, placeholder), tokens) in members.iter().zip(placeholders).zip(&summarized) {
+                let file = &mut files[index];
+                file["code"] = json!(placeholder);
+                file["token_count"] = json!(tokens);
+                file["summarized"] = json!(true);
+            }
+            (TierStatus::Summarized, summarized.iter().sum())
+        } else {
+            degraded = true;
+            dropping = true;
+            for &index in &members {
+                files[index]["dropped"] = json!(true);
+            }
+            (TierStatus::Dropped, 0)
+        };
+        used += tokens;
+        reports.push(TierReport {
+            name: names.get(tier).cloned().unwrap_or_else(|| UNTIERED.to_string()),
+            status,
+            files: members.len(),
+            tokens,
+        });
+    }
+    files.retain(|file| file.get("dropped").is_none());
+    reports
+}
+
+/// Returns the placeholder a summarized file is listed with.
+///
+/// # Arguments
+///
+/// * `file` - The JSON representation of the file.
+///
+/// # Returns
+///
+/// * `String` - The placeholder, such as `<summarized for the token budget, 3.4 KB, 812 tokens>`.
+fn summary_placeholder(file: &Value) -> String {
+    let size = FileSize(file["size"].as_u64().unwrap_or(0));
+    match file["token_count"].as_u64() {
+        Some(tokens) => format!("<summarized for the token budget, {}, {} tokens>", size, tokens),
+        None => format!("<summarized for the token budget, {}>", size),
+    }
+}
@@ -0,0 +1,103 @@
+//! This module records how long each stage of a run takes, for the `--timings` breakdown.
+//!
+//! The stages are instrumented with `tracing` spans, which cost next to nothing when no layer
+//! records them. With `--timings`, a layer of the subscriber installed by `crate::logging` adds
+//! up the time spent in each span by name.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// The time spent in one stage of a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageTiming {
+    /// The name of the span instrumenting the stage.
+    pub name: &'static str,
+    /// The number of times the stage ran.
+    pub calls: usize,
+    /// The time spent in the stage, summed over all calls and threads.
+    pub total: Duration,
+}
+
+/// The timings recorded by the subscriber, in the order the stages first completed.
+#[derive(Debug, Clone, Default)]
+pub struct Timings {
+    stages: Arc<Mutex<Vec<StageTiming>>>,
+}
+
+impl Timings {
+    /// Records one run of a stage.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the stage.
+    /// * `elapsed` - The time the run took.
+    pub fn record(&self, name: &'static str, elapsed: Duration) {
+        let mut stages = self.stages.lock().unwrap();
+        match stages.iter_mut().find(|stage| stage.name == name) {
+            Some(stage) => {
+                stage.calls += 1;
+                stage.total += elapsed;
+            }
+            None => stages.push(StageTiming {
+                name,
+                calls: 1,
+                total: elapsed,
+            }),
+        }
+    }
+
+    /// Returns the timings recorded so far.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<StageTiming>` - One entry per stage, in the order the stages first completed.
+    pub fn stages(&self) -> Vec<StageTiming> {
+        self.stages.lock().unwrap().clone()
+    }
+}
+
+/// The instant a span was created, stored in the span's extensions.
+struct SpanStart(Instant);
+
+/// A subscriber layer adding up the time spent in each span.
+pub(crate) struct TimingsLayer {
+    timings: Timings,
+}
+
+impl TimingsLayer {
+    /// Creates the layer.
+    ///
+    /// # Arguments
+    ///
+    /// * `timings` - The timings to record the spans into.
+    ///
+    /// # Returns
+    ///
+    /// * `TimingsLayer` - The layer, to install with the subscriber of `crate::logging`.
+    pub(crate) fn new(timings: Timings) -> Self {
+        TimingsLayer { timings }
+    }
+}
+
+impl<S> Layer<S> for TimingsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanStart(Instant::now()));
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(&id) {
+            if let Some(start) = span.extensions().get::<SpanStart>() {
+                self.timings.record(span.name(), start.0.elapsed());
+            }
+        }
+    }
+}
@@ -1,9 +1,22 @@
 //! This module encapsulates the logic for counting the tokens in the rendered text.
 
+use once_cell::sync::Lazy;
+use std::path::Path;
 use tiktoken_rs::{cl100k_base, p50k_base, p50k_edit, r50k_base, CoreBPE};
+use tracing::instrument;
+
+// Building a tokenizer decodes its whole vocabulary, so each one is built the first time it is
+// needed and shared by every later call, including the jobs of a batch.
+static CL100K: Lazy<CoreBPE> = Lazy::new(|| cl100k_base().unwrap());
+static P50K: Lazy<CoreBPE> = Lazy::new(|| p50k_base().unwrap());
+static P50K_EDIT: Lazy<CoreBPE> = Lazy::new(|| p50k_edit().unwrap());
+static R50K: Lazy<CoreBPE> = Lazy::new(|| r50k_base().unwrap());
 
 /// Returns the appropriate tokenizer based on the provided encoding.
 ///
+/// Each tokenizer is built once, the first time it is asked for, so runs that do not count
+/// tokens never load one.
+///
 /// # Arguments
 ///
 /// * `encoding` - An optional string specifying the encoding to use for tokenization.
@@ -11,14 +24,14 @@ use tiktoken_rs::{cl100k_base, p50k_base, p50k_edit, r50k_base, CoreBPE};
 ///
 /// # Returns
 ///
-/// * `CoreBPE` - The tokenizer corresponding to the specified encoding.
-pub fn get_tokenizer(encoding: &Option<String>) -> CoreBPE {
+/// * `&'static CoreBPE` - The tokenizer corresponding to the specified encoding.
+#[instrument(name = "tokenize", skip_all)]
+pub fn get_tokenizer(encoding: &Option<String>) -> &'static CoreBPE {
     match encoding.as_deref().unwrap_or("cl100k") {
-        "cl100k" => cl100k_base().unwrap(),
-        "p50k" => p50k_base().unwrap(),
-        "p50k_edit" => p50k_edit().unwrap(),
-        "r50k" | "gpt2" => r50k_base().unwrap(),
-        _ => cl100k_base().unwrap(),
+        "p50k" => &P50K,
+        "p50k_edit" => &P50K_EDIT,
+        "r50k" | "gpt2" => &R50K,
+        _ => &CL100K,
     }
 }
 
@@ -40,4 +53,37 @@ pub fn get_model_info(encoding: &Option<String>) -> &'static str {
         "r50k" | "gpt2" => "GPT-3 models like davinci",
         _ => "ChatGPT models, text-embedding-ada-002",
     }
-}
\ No newline at end of file
+}
+
+/// The number of bytes per token assumed for files whose extension is not more specific.
+const DEFAULT_BYTES_PER_TOKEN: f64 = 4.0;
+
+/// Estimates the token count of a file from its size, without reading it.
+///
+/// Prose tokenizes into longer tokens than code, and markup and data formats, full of
+/// punctuation, into shorter ones, so the usual four bytes per token is refined by extension.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file, whose extension refines the estimate.
+/// * `bytes` - The size of the file in bytes.
+///
+/// # Returns
+///
+/// * `usize` - The estimated number of tokens.
+pub fn estimate_tokens(path: &Path, bytes: u64) -> usize {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+    let bytes_per_token = match extension.as_str() {
+        "md" | "markdown" | "txt" | "rst" | "adoc" | "org" | "tex" => 4.5,
+        "rs" | "go" | "java" | "kt" | "scala" | "swift" | "c" | "h" | "cc" | "cpp" | "hpp" | "cs" | "py" | "rb"
+        | "php" => 3.6,
+        "js" | "jsx" | "ts" | "tsx" | "css" | "scss" | "sh" | "sql" => 3.4,
+        "json" | "yaml" | "yml" | "toml" | "xml" | "html" | "htm" | "svg" | "csv" | "lock" => 3.0,
+        _ => DEFAULT_BYTES_PER_TOKEN,
+    };
+    (bytes as f64 / bytes_per_token).ceil() as usize
+}
@@ -0,0 +1,171 @@
+//! This module provides the per-run temporary workspace and crash-safe file writes.
+//!
+//! Remote clones and extracted archives live in a workspace that is deleted when the run
+//! ends, including when it panics. Files are written to a temporary file first and renamed
+//! into place, so an interrupted run never leaves a half-written file behind, and shared
+//! files are updated under a lock so that concurrent invocations do not lose each other's changes.
+
+use crate::messages::{message, message_with, Message};
+use anyhow::{Context, Result};
+use fs2::FileExt;
+use once_cell::sync::Lazy;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, Once};
+use tempfile::TempDir;
+
+/// The workspaces of the runs in progress, deleted by the panic hook.
+static ACTIVE_WORKSPACES: Lazy<Mutex<Vec<PathBuf>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Guards the installation of the panic hook.
+static PANIC_HOOK: Once = Once::new();
+
+/// A temporary directory holding the files fetched during a run.
+///
+/// The directory is deleted when the workspace is dropped, or by a panic hook when the run
+/// panics, since release builds abort without running destructors.
+#[derive(Debug)]
+pub struct Workspace {
+    /// The temporary directory of the workspace.
+    dir: TempDir,
+    /// The number of directories created inside the workspace.
+    created: AtomicUsize,
+}
+
+impl Workspace {
+    /// Creates a workspace in the system temporary directory.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Workspace>` - The new workspace, or an error if it cannot be created.
+    pub fn new() -> Result<Self> {
+        install_panic_hook();
+        let dir = tempfile::Builder::new()
+            .prefix("codexio-")
+            .tempdir()
+            .with_context(|| message(Message::WorkspaceCreateFailed))?;
+        ACTIVE_WORKSPACES.lock().unwrap().push(dir.path().to_path_buf());
+        Ok(Workspace {
+            dir,
+            created: AtomicUsize::new(0),
+        })
+    }
+
+    /// Returns the path of the workspace.
+    ///
+    /// # Returns
+    ///
+    /// * `&Path` - The path of the temporary directory.
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Creates a new, uniquely named directory inside the workspace.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PathBuf>` - The path of the new directory.
+    pub fn create_dir(&self) -> Result<PathBuf> {
+        let index = self.created.fetch_add(1, Ordering::Relaxed);
+        let dir = self.path().join(format!("source-{}", index));
+        fs::create_dir(&dir)
+            .with_context(|| message_with(Message::WorkspaceDirCreateFailed, &[("path", &dir.display())]))?;
+        Ok(dir)
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        if let Ok(mut workspaces) = ACTIVE_WORKSPACES.lock() {
+            workspaces.retain(|path| path != self.dir.path());
+        }
+    }
+}
+
+/// Installs a panic hook deleting the active workspaces before the default hook runs.
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if let Ok(workspaces) = ACTIVE_WORKSPACES.try_lock() {
+                for path in workspaces.iter() {
+                    let _ = fs::remove_dir_all(path);
+                }
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// Writes a file atomically, by writing a temporary file next to it and renaming it into place.
+///
+/// A replaced file keeps its permissions, and a new file gets the default ones.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to write.
+/// * `content` - The content of the file.
+///
+/// # Returns
+///
+/// * `Result<()>` - An empty result indicating success or an error.
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(".codexio-").suffix(".tmp");
+    // Temporary files are private, while a new file should get the default mode left by the umask
+    #[cfg(unix)]
+    builder.permissions(fs::Permissions::from_mode(0o666));
+    let mut file = builder
+        .tempfile_in(dir)
+        .with_context(|| message_with(Message::TempFileCreateFailed, &[("path", &dir.display())]))?;
+    // Replacing a file keeps its mode, which the umask would otherwise have narrowed
+    if let Ok(metadata) = fs::metadata(path) {
+        file.as_file().set_permissions(metadata.permissions())?;
+    }
+    file.write_all(content)?;
+    file.as_file().sync_all()?;
+    file.persist(path)
+        .map_err(|e| e.error)
+        .with_context(|| message_with(Message::FileWriteFailed, &[("path", &path.display())]))?;
+    Ok(())
+}
+
+/// Runs a read-modify-write operation on a file shared between invocations while holding
+/// an exclusive lock on it.
+///
+/// The lock is taken on a `<file>.lock` file next to it and blocks until other invocations
+/// release it. Combined with `write_atomic`, readers never need the lock.
+///
+/// # Arguments
+///
+/// * `path` - The path of the shared file.
+/// * `operation` - The operation to run while the lock is held.
+///
+/// # Returns
+///
+/// * `Result<T>` - The result of the operation.
+pub fn with_file_lock<T>(path: &Path, operation: impl FnOnce() -> Result<T>) -> Result<T> {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let lock_file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| message_with(Message::LockOpenFailed, &[("path", &Path::new(&lock_path).display())]))?;
+    lock_file
+        .lock_exclusive()
+        .with_context(|| message_with(Message::LockFailed, &[("path", &path.display())]))?;
+    let result = operation();
+    let _ = lock_file.unlock();
+    result
+}
@@ -0,0 +1,93 @@
+//! This module reads workspace files, which list the roots of a codebase spread over several
+//! repositories or directories, with patterns of their own, so they are packed into one prompt.
+//!
+//! ```yaml
+//! roots:
+//!   - path: ../api
+//!     include: ["src/**", "proto/**"]
+//!   - path: ../web
+//!     exclude: ["**/*.snap"]
+//! tokens: true
+//! ```
+
+use crate::config::FileConfig;
+use crate::messages::{message_with, Message};
+use crate::options::Options;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A workspace file, listing the roots to pack together.
+///
+/// Besides `roots`, a workspace file takes every key of the configuration files, such as
+/// `include` or `template`, applied to all the roots.
+#[derive(Deserialize, Debug)]
+pub struct WorkspaceFile {
+    /// The roots of the workspace, in the order they are packed.
+    pub roots: Vec<WorkspaceRoot>,
+    /// The options of the workspace.
+    #[serde(flatten)]
+    pub config: FileConfig,
+}
+
+/// A root of a workspace file, with its own patterns.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceRoot {
+    /// The path of the root, relative to the workspace file.
+    pub path: PathBuf,
+    /// Patterns to include, relative to the root.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Patterns to exclude, relative to the root.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// Reads and parses a workspace file, resolving the paths of its roots against its directory.
+///
+/// # Arguments
+///
+/// * `path` - The path to the YAML workspace file.
+///
+/// # Returns
+///
+/// * `Result<WorkspaceFile>` - The roots and options of the workspace, or an error if the file cannot be read or parsed, or lists no roots.
+pub fn load_workspace_file(path: &Path) -> Result<WorkspaceFile> {
+    let read_failed = || message_with(Message::WorkspaceFileReadFailed, &[("path", &path.display())]);
+    let content = fs::read_to_string(path).with_context(read_failed)?;
+    let mut workspace: WorkspaceFile = serde_yaml::from_str(&content).with_context(read_failed)?;
+    if workspace.roots.is_empty() {
+        bail!(message_with(Message::WorkspaceFileEmpty, &[("path", &path.display())]));
+    }
+    let dir = path.parent().unwrap_or(Path::new("."));
+    for root in &mut workspace.roots {
+        root.path = dir.join(&root.path);
+    }
+    let template = workspace.config.template.take();
+    workspace.config.template = template.map(|template| dir.join(template));
+    Ok(workspace)
+}
+
+/// Applies a workspace file to the options, packing its roots with their patterns.
+///
+/// The options of the workspace file come right after the command-line options, and files are
+/// listed under the label of their root, as with `--relative-paths`.
+///
+/// # Arguments
+///
+/// * `options` - The options to apply the workspace file to.
+/// * `path` - The path to the YAML workspace file.
+///
+/// # Returns
+///
+/// * `Result<()>` - An error if the workspace file cannot be read or parsed.
+pub fn apply_workspace_file(options: &mut Options, path: &Path) -> Result<()> {
+    let workspace = load_workspace_file(path)?;
+    options.path = workspace.roots.iter().map(|root| root.path.clone()).collect();
+    options.workspace_roots = workspace.roots;
+    options.relative_paths = true;
+    options.merge_file_config(workspace.config);
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use codexio::apply::{apply_patch, parse_patches, HunkLine};
+use codexio::text::line_anchor;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = "fn main() {\n    let x = 1;\n    println!(\"{}\", x);\n}\n";
+
+    /// Returns a response changing the second line of `CONTENT`, anchored with the given anchor.
+    fn response(anchor: &str) -> String {
+        format!(
+            "Here is the fix:\n\n```diff\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,3 +1,3 @@ {}\n fn main() {{\n-    let x = 1;\n+    let x = 2;\n     println!(\"{{}}\", x);\n```\n\nThis sets x to 2.\n",
+            anchor
+        )
+    }
+
+    #[test]
+    fn test_parse_patches() {
+        let patches = parse_patches(&response(&line_anchor(1, "fn main() {"))).unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].path, "src/main.rs");
+        assert!(!patches[0].creates);
+        let hunk = &patches[0].hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_count, hunk.new_count), (1, 3, 3));
+        assert_eq!(hunk.anchor, Some(line_anchor(1, "fn main() {")));
+        assert_eq!(hunk.lines[1], HunkLine::Remove("    let x = 1;".to_string()));
+        assert_eq!(hunk.lines.len(), 4);
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let patches = parse_patches(&response(&line_anchor(1, "fn main() {"))).unwrap();
+        let patched = apply_patch(&patches[0], CONTENT).unwrap();
+        assert_eq!(patched, "fn main() {\n    let x = 2;\n    println!(\"{}\", x);\n}\n");
+    }
+
+    #[test]
+    fn test_apply_patch_rejects_stale_anchors() {
+        let patches = parse_patches(&response(&line_anchor(1, "fn start() {"))).unwrap();
+        assert!(apply_patch(&patches[0], CONTENT).is_err());
+
+        let patches = parse_patches(&response("")).unwrap();
+        assert!(apply_patch(&patches[0], CONTENT).is_err());
+    }
+
+    #[test]
+    fn test_apply_patch_rejects_mismatched_context() {
+        let patches = parse_patches(&response(&line_anchor(1, "fn main() {"))).unwrap();
+        let changed = CONTENT.replace("let x = 1", "let x = 3");
+        assert!(apply_patch(&patches[0], &changed).is_err());
+    }
+
+    #[test]
+    fn test_apply_patch_creates_files() {
+        let response = "--- /dev/null\n+++ b/src/new.rs\n@@ -0,0 +1,2 @@\n+// New module\n+pub fn new() {}\n";
+        let patches = parse_patches(response).unwrap();
+        assert!(patches[0].creates);
+        assert_eq!(apply_patch(&patches[0], "").unwrap(), "// New module\npub fn new() {}\n");
+    }
+}
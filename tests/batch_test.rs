@@ -0,0 +1,44 @@
+use codexio::batch::{BatchFile, JobPaths};
+use codexio::options::Options;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_batch_jobs() {
+        let batch: BatchFile = serde_yaml::from_str(
+            "jobs:\n  - name: api\n    path: services/api\n    include: [\"**/*.rs\"]\n    output: out/api.md\n  - path: [services/web, shared]\n    template: prompt.hbs\n    output: out/web.md\n",
+        )
+        .unwrap();
+        assert_eq!(batch.jobs.len(), 2);
+        assert_eq!(batch.jobs[0].label(0), "api");
+        assert_eq!(batch.jobs[1].label(1), "out/web.md");
+        assert_eq!(
+            batch.jobs[1].path,
+            Some(JobPaths::Many(vec![PathBuf::from("services/web"), PathBuf::from("shared")]))
+        );
+
+        let mut jobs = batch.jobs.into_iter();
+        let base = Options {
+            budget: Some(1000),
+            ..Options::default()
+        };
+        let api = jobs.next().unwrap().options(&base, Path::new("/ci")).unwrap();
+        assert_eq!(api.path, vec![PathBuf::from("/ci/services/api")]);
+        assert_eq!(api.include.as_deref(), Some("**/*.rs"));
+        assert_eq!(api.output.as_deref(), Some("/ci/out/api.md"));
+        assert_eq!(api.budget, Some(1000));
+        assert!(api.no_clipboard);
+
+        let web = jobs.next().unwrap().options(&base, Path::new("/ci")).unwrap();
+        assert_eq!(web.path, vec![PathBuf::from("/ci/services/web"), PathBuf::from("/ci/shared")]);
+        assert_eq!(web.template, Some(PathBuf::from("/ci/prompt.hbs")));
+    }
+
+    #[test]
+    fn test_batch_rejects_unknown_keys() {
+        assert!(serde_yaml::from_str::<BatchFile>("jobs: []\nretries: 3\n").is_err());
+    }
+}
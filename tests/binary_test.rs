@@ -0,0 +1,24 @@
+use codexio::binary::{binary_kind, binary_placeholder, is_binary};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_binary() {
+        assert!(is_binary(b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR"));
+        assert!(is_binary(b"text with a \x00 byte"));
+        assert!(!is_binary("fn main() {}\n".as_bytes()));
+        // Text files may start like a magic number
+        assert!(!is_binary(b"MZ is the start of this sentence"));
+    }
+
+    #[test]
+    fn test_binary_placeholder() {
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.resize(1_258_291, 0);
+        assert_eq!(binary_kind(&png), Some("PNG"));
+        assert_eq!(binary_placeholder(&png), "<binary file, 1.2 MB, PNG>");
+        assert_eq!(binary_placeholder(b"\x00\x01\x02"), "<binary file, 3 B>");
+    }
+}
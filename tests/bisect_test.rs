@@ -0,0 +1,63 @@
+use codexio::bisect::gather_bisect;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Repository, Signature};
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    /// Writes a file, or deletes it without content, and commits it, returning the id of the commit.
+    fn commit_file(repo: &Repository, path: &str, content: Option<&str>, message: &str) -> git2::Oid {
+        let root = repo.workdir().unwrap();
+        let mut index = repo.index().expect("Failed to get repository index");
+        match content {
+            Some(content) => {
+                fs::write(root.join(path), content).expect("Failed to write file");
+                index.add_path(Path::new(path)).expect("Failed to add file to index");
+            }
+            None => {
+                fs::remove_file(root.join(path)).expect("Failed to delete file");
+                index.remove_path(Path::new(path)).expect("Failed to remove file from index");
+            }
+        }
+        index.write().expect("Failed to write index");
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parents = match repo.head() {
+            Ok(head) => vec![head.peel_to_commit().unwrap()],
+            Err(_) => Vec::new(),
+        };
+        let parents: Vec<_> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+            .expect("Failed to commit")
+    }
+
+    #[test]
+    fn test_gather_bisect() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo = Repository::init(temp_dir.path()).expect("Failed to initialize repository");
+        commit_file(&repo, "parser.rs", Some("fn parse() {}\n"), "Add the parser");
+        commit_file(&repo, "scratch.txt", Some("notes\n"), "Add notes");
+        let good = commit_file(&repo, "README.md", Some("# Parser\n"), "Document the parser");
+        commit_file(&repo, "parser.rs", Some("fn parse() { todo!() }\n"), "Rewrite the parser");
+        commit_file(&repo, "scratch.txt", None, "Remove notes");
+
+        let bisect = gather_bisect(temp_dir.path(), &good.to_string(), "HEAD").unwrap();
+        assert_eq!(bisect.bad, "HEAD");
+        let summaries: Vec<_> = bisect.commits.iter().map(|commit| commit.summary.as_str()).collect();
+        assert_eq!(summaries, ["Rewrite the parser", "Remove notes"]);
+        assert!(bisect.commits[0].diff.contains("+fn parse() { todo!() }"));
+        assert_eq!(bisect.commits[0].changed_files[0].status, "modified");
+        assert_eq!(bisect.commits[1].changed_files[0].status, "deleted");
+        assert_eq!(bisect.commits[0].date.len(), "2024-05-01".len());
+
+        // Deleted files are listed as touched, but only the checked out ones are packed
+        assert_eq!(bisect.touched_files, ["parser.rs", "scratch.txt"]);
+        let root = temp_dir.path().canonicalize().unwrap();
+        assert_eq!(bisect.checked_out, vec![root.join("parser.rs")]);
+
+        assert!(gather_bisect(temp_dir.path(), "missing", "HEAD").is_err());
+    }
+}
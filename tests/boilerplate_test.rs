@@ -0,0 +1,46 @@
+use codexio::boilerplate::{find_common_headers, leading_comment_block, strip_header};
+use std::fs;
+use tempfile::tempdir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LICENSE: &str = "// Copyright 2024 Example Corp.\n// SPDX-License-Identifier: MIT\n// See LICENSE for details.\n";
+
+    #[test]
+    fn test_leading_comment_block() {
+        let text = format!("{}\nfn main() {{}}\n", LICENSE);
+        assert_eq!(leading_comment_block(&text), Some(LICENSE));
+
+        let python = "# Generated by protoc.\n# Do not edit.\n#\nimport os\n";
+        assert_eq!(leading_comment_block(python), Some("# Generated by protoc.\n# Do not edit.\n#\n"));
+
+        assert_eq!(leading_comment_block("// One line\nfn main() {}\n"), None);
+        assert_eq!(leading_comment_block("#include <stdio.h>\n#include <stdlib.h>\n#define X 1\n"), None);
+    }
+
+    #[test]
+    fn test_strip_header() {
+        let text = format!("{}\n\nfn main() {{}}\n", LICENSE);
+        assert_eq!(strip_header(&text, LICENSE), Some(("fn main() {}\n", 5)));
+        assert_eq!(strip_header("fn main() {}\n", LICENSE), None);
+    }
+
+    #[test]
+    fn test_find_common_headers() {
+        let dir = tempdir().unwrap();
+        let mut paths = Vec::new();
+        for name in ["a.rs", "b.rs", "c.rs"] {
+            let path = dir.path().join(name);
+            fs::write(&path, format!("{}\nfn {}() {{}}\n", LICENSE, &name[..1])).unwrap();
+            paths.push(path);
+        }
+        let other = dir.path().join("d.rs");
+        fs::write(&other, "// Other\n// header\n// here\nfn d() {}\n").unwrap();
+        paths.push(other);
+
+        assert_eq!(find_common_headers(&paths), vec![LICENSE.to_string()]);
+        assert!(find_common_headers(&paths[..2]).is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+use codexio::charset::{decode_lossy, decode_text, decode_utf16, detect_utf16};
+use encoding_rs::{SHIFT_JIS, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encodes text as UTF-16, optionally with a byte order mark.
+    fn utf16(text: &str, big_endian: bool, bom: bool) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if bom {
+            bytes.extend(if big_endian { [0xfe, 0xff] } else { [0xff, 0xfe] });
+        }
+        for unit in text.encode_utf16() {
+            bytes.extend(if big_endian { unit.to_be_bytes() } else { unit.to_le_bytes() });
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_detect_utf16() {
+        assert_eq!(detect_utf16(&utf16("fn main() {}", false, true)), Some(UTF_16LE));
+        assert_eq!(detect_utf16(&utf16("fn main() {}", true, true)), Some(UTF_16BE));
+        assert_eq!(detect_utf16(&utf16("fn main() {}", false, false)), Some(UTF_16LE));
+        assert_eq!(detect_utf16(&utf16("fn main() {}", true, false)), Some(UTF_16BE));
+        assert_eq!(detect_utf16(b"fn main() {}"), None);
+        assert_eq!(detect_utf16(b"\x00\x01\x02\x03"), None);
+    }
+
+    #[test]
+    fn test_decode_utf16() {
+        let decoded = decode_utf16(&utf16("// été\nfn main() {}", false, true)).unwrap();
+        assert_eq!(decoded.text, "// été\nfn main() {}");
+        assert_eq!(decoded.encoding, UTF_16LE);
+    }
+
+    #[test]
+    fn test_decode_text() {
+        let decoded = decode_text("fn main() {}".as_bytes()).unwrap();
+        assert_eq!(decoded.text, "fn main() {}");
+        assert_eq!(decoded.encoding, UTF_8);
+
+        let (latin1, _, _) = WINDOWS_1252.encode("# Le café est prêt, déjà servi à la fenêtre\n");
+        let decoded = decode_text(&latin1).unwrap();
+        assert_eq!(decoded.text, "# Le café est prêt, déjà servi à la fenêtre\n");
+        assert_eq!(decoded.encoding, WINDOWS_1252);
+
+        let japanese = "// これは日本語のコメントです。ファイルはシフトJISで保存されています。\n";
+        let (shift_jis, _, _) = SHIFT_JIS.encode(japanese);
+        let decoded = decode_text(&shift_jis).unwrap();
+        assert_eq!(decoded.text, japanese);
+        assert_eq!(decoded.encoding, SHIFT_JIS);
+    }
+
+    #[test]
+    fn test_decode_lossy() {
+        let decoded = decode_lossy("naïve ".as_bytes());
+        assert_eq!(decoded.text, "naïve ");
+        assert!(!decoded.lossy);
+
+        let decoded = decode_lossy(b"na\xc3\xafve \xff");
+        assert_eq!(decoded.text, "naïve \u{fffd}");
+        assert_eq!(decoded.encoding, UTF_8);
+        assert!(decoded.lossy);
+    }
+}
@@ -0,0 +1,26 @@
+use codexio::clipboard::{clipboard_sink, osc52_sequence, ClipboardSink, NoopSink};
+use codexio::config::ClipboardBackend;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("hello", false), "\x1b]52;c;aGVsbG8=\x07");
+        assert_eq!(
+            osc52_sequence("hello", true),
+            "\x1bPtmux;\x1b\x1b]52;c;aGVsbG8=\x07\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_clipboard_sink() {
+        assert_eq!(clipboard_sink(ClipboardBackend::Osc52).name(), "osc52");
+        assert_eq!(clipboard_sink(ClipboardBackend::WlCopy).name(), "wl-copy");
+        assert_eq!(clipboard_sink(ClipboardBackend::Xclip).name(), "xclip");
+        assert_eq!(clipboard_sink(ClipboardBackend::None).name(), "none");
+        assert_ne!(clipboard_sink(ClipboardBackend::Auto).name(), "none");
+        assert!(NoopSink.copy("prompt").is_ok());
+    }
+}
@@ -0,0 +1,23 @@
+use codexio::config::FileSize;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_size() {
+        assert_eq!("512".parse::<FileSize>().unwrap(), FileSize(512));
+        assert_eq!("200KB".parse::<FileSize>().unwrap(), FileSize(200 * 1024));
+        assert_eq!("1.5 mb".parse::<FileSize>().unwrap(), FileSize(1024 * 1024 * 3 / 2));
+        assert_eq!("2G".parse::<FileSize>().unwrap(), FileSize(2 << 30));
+        assert!("ten".parse::<FileSize>().is_err());
+        assert!("10XB".parse::<FileSize>().is_err());
+    }
+
+    #[test]
+    fn test_display_file_size() {
+        assert_eq!(FileSize(512).to_string(), "512 B");
+        assert_eq!(FileSize(200 * 1024).to_string(), "200.0 KB");
+        assert_eq!(FileSize(3 << 20).to_string(), "3.0 MB");
+    }
+}
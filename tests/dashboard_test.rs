@@ -0,0 +1,83 @@
+use codexio::dashboard::{build_dashboard, format_day, render_dashboard_html};
+use codexio::config::{HtmlTheme, ThemeName};
+use codexio::history::RunRecord;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A day of May 2024, in seconds since the Unix epoch.
+    const MAY_6: u64 = 1_714_953_600;
+
+    /// Returns the record of a prompt generated at a time with a token count.
+    fn run(timestamp: u64, token_count: Option<usize>, files: &[&str]) -> RunRecord {
+        RunRecord {
+            timestamp,
+            token_count,
+            bytes: 4_000,
+            files: files.iter().map(|file| file.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_format_day() {
+        assert_eq!(format_day(0), "1970-01-01");
+        assert_eq!(format_day(MAY_6 + 3_600), "2024-05-06");
+        assert_eq!(format_day(951_782_400), "2000-02-29");
+    }
+
+    #[test]
+    fn test_build_dashboard() {
+        let runs = vec![
+            run(MAY_6, Some(100), &["src/main.rs", "src/lib.rs"]),
+            run(MAY_6 + 60, Some(300), &["src/main.rs"]),
+            run(MAY_6 + 86_400, None, &["src/main.rs", "README.md"]),
+        ];
+        let dashboard = build_dashboard(&runs);
+
+        assert_eq!(dashboard.runs, 3);
+        assert_eq!(dashboard.first_day.as_deref(), Some("2024-05-06"));
+        assert_eq!(dashboard.last_day.as_deref(), Some("2024-05-07"));
+        // The last prompt was not counted and is estimated at four bytes per token
+        assert_eq!(dashboard.latest_tokens, 1_000);
+        assert_eq!(dashboard.average_tokens, 466);
+        assert_eq!(dashboard.peak_tokens, 1_000);
+
+        assert_eq!(dashboard.trend.len(), 2);
+        assert_eq!(dashboard.trend[0].runs, 2);
+        assert_eq!(dashboard.trend[0].average_tokens, 200);
+        assert_eq!(dashboard.growth_percent, Some(400));
+
+        let top: Vec<(&str, usize)> = dashboard.top_files.iter().map(|file| (file.path.as_str(), file.runs)).collect();
+        assert_eq!(top, vec![("src/main.rs", 3), ("README.md", 1), ("src/lib.rs", 1)]);
+    }
+
+    #[test]
+    fn test_render_dashboard_html_escapes_paths() {
+        let dashboard = build_dashboard(&[run(MAY_6, Some(10), &["src/<generated>.rs"])]);
+        let html = render_dashboard_html(&dashboard, "/repo", HtmlTheme::Auto, ThemeName::Default);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<code>src/&lt;generated&gt;.rs</code>"));
+        assert!(html.contains("width: 100%"));
+    }
+
+    #[test]
+    fn test_render_dashboard_html_follows_color_scheme() {
+        let dashboard = build_dashboard(&[run(MAY_6, Some(10), &["src/main.rs"])]);
+        let html = render_dashboard_html(&dashboard, "/repo", HtmlTheme::Auto, ThemeName::Default);
+        assert!(html.contains("--background: #ffffff;"));
+        assert!(html.contains("@media (prefers-color-scheme: dark) { :root { --background: #0d1117;"));
+        assert!(html.contains("background: var(--bar);"));
+    }
+
+    #[test]
+    fn test_render_dashboard_html_with_explicit_scheme() {
+        let dashboard = build_dashboard(&[run(MAY_6, Some(10), &["src/main.rs"])]);
+        let dark = render_dashboard_html(&dashboard, "/repo", HtmlTheme::Dark, ThemeName::HighContrast);
+        assert!(dark.contains(":root { color-scheme: dark; --background: #000000;"));
+        assert!(!dark.contains("prefers-color-scheme"));
+        let light = render_dashboard_html(&dashboard, "/repo", HtmlTheme::Light, ThemeName::Default);
+        assert!(light.contains(":root { color-scheme: light; --background: #ffffff;"));
+        assert!(!light.contains("#0d1117"));
+    }
+}
@@ -0,0 +1,87 @@
+use codexio::dependencies::{is_manifest, parse_manifest, Dependency};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependency(name: &str, version: Option<&str>, kind: &'static str) -> Dependency {
+        Dependency {
+            name: name.to_string(),
+            version: version.map(str::to_string),
+            kind,
+            local: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_cargo_manifest() {
+        let content = r#"
+[package]
+name = "app"
+
+[dependencies]
+serde = { version = "1.0", features = ["derive"] }
+regex = "1.10"
+local = { path = "../local", optional = true }
+
+[dev-dependencies]
+tempfile = "3"
+"#;
+        let (ecosystem, dependencies) = parse_manifest("Cargo.toml", content).unwrap();
+        assert_eq!(ecosystem, "cargo");
+        assert_eq!(
+            dependencies,
+            [
+                dependency("serde", Some("1.0"), "runtime"),
+                dependency("regex", Some("1.10"), "runtime"),
+                Dependency {
+                    local: true,
+                    ..dependency("local", Some("../local"), "optional")
+                },
+                dependency("tempfile", Some("3"), "dev"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_other_manifests() {
+        let (_, dependencies) =
+            parse_manifest("package.json", r#"{"dependencies": {"express": "^4.19"}, "devDependencies": {"jest": "29"}}"#)
+                .unwrap();
+        assert_eq!(
+            dependencies,
+            [dependency("express", Some("^4.19"), "runtime"), dependency("jest", Some("29"), "dev")]
+        );
+
+        let (_, dependencies) = parse_manifest(
+            "pyproject.toml",
+            "[project]\ndependencies = [\"requests[socks]>=2.31; python_version > '3.8'\", \"click\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            dependencies,
+            [dependency("requests", Some(">=2.31"), "runtime"), dependency("click", None, "runtime")]
+        );
+
+        let (_, dependencies) = parse_manifest(
+            "go.mod",
+            "module app\n\nrequire github.com/pkg/errors v0.9.1\n\nrequire (\n\tgolang.org/x/crypto v0.21.0 // indirect\n)\n",
+        )
+        .unwrap();
+        assert_eq!(
+            dependencies,
+            [
+                dependency("github.com/pkg/errors", Some("v0.9.1"), "runtime"),
+                dependency("golang.org/x/crypto", Some("v0.21.0"), "runtime"),
+            ]
+        );
+
+        let (_, dependencies) =
+            parse_manifest("requirements-dev.txt", "# tools\n-r requirements.txt\npytest==8.1 # tests\n").unwrap();
+        assert_eq!(dependencies, [dependency("pytest", Some("==8.1"), "runtime")]);
+
+        assert!(is_manifest("requirements.txt"));
+        assert!(!is_manifest("README.md"));
+        assert!(parse_manifest("Cargo.toml", "not = [valid").is_none());
+    }
+}
@@ -0,0 +1,81 @@
+use codexio::form::{collect_form, parse_forms, placeholder_form, FieldKind};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const TEMPLATE: &str = r#"{{!--
+forms:
+  - name: task
+    title: Task
+    fields:
+      - name: title
+        required: true
+      - name: steps
+        label: Steps to reproduce
+        kind: steps
+      - name: criteria
+        label: Acceptance criteria
+        kind: checklist
+      - name: notes
+        kind: list
+--}}
+{{!-- An unrelated comment --}}
+{{ task.section }}"#;
+
+    #[test]
+    fn test_parse_forms() {
+        let forms = parse_forms(TEMPLATE).unwrap();
+        assert_eq!(forms.len(), 1);
+        let form = &forms[0];
+        assert_eq!(form.name, "task");
+        assert_eq!(form.title.as_deref(), Some("Task"));
+        let kinds: Vec<_> = form.fields.iter().map(|field| field.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![FieldKind::Text, FieldKind::Steps, FieldKind::Checklist, FieldKind::List]
+        );
+        assert!(form.fields[0].required);
+        assert_eq!(form.fields[1].label(), "Steps to reproduce");
+        assert_eq!(form.fields[3].label(), "notes");
+
+        assert!(parse_forms("{{ task }}").unwrap().is_empty());
+        assert!(parse_forms("{{!-- forms: [ --}}").is_err());
+    }
+
+    #[test]
+    fn test_collect_form_from_answers() {
+        let form = &parse_forms(TEMPLATE).unwrap()[0];
+        let answers = json!({
+            "title": "Crash on empty input",
+            "steps": ["Run codexio on an empty directory", "Open the output"],
+            "criteria": "No panic",
+        });
+        let values = collect_form(form, Some(&answers)).unwrap();
+
+        assert_eq!(values["title"], "Crash on empty input");
+        assert_eq!(values["criteria"], json!(["No panic"]));
+        assert_eq!(values["notes"], json!([]));
+        assert_eq!(
+            values["section"],
+            "## Task\n\n### title\n\nCrash on empty input\n\n### Steps to reproduce\n\n1. Run codexio on an empty directory\n2. Open the output\n\n### Acceptance criteria\n\n- [ ] No panic\n"
+        );
+    }
+
+    #[test]
+    fn test_collect_form_missing_required_answer() {
+        let form = &parse_forms(TEMPLATE).unwrap()[0];
+        let error = collect_form(form, Some(&json!({ "steps": ["Run it"] }))).unwrap_err();
+        assert!(error.to_string().contains("'title'"));
+    }
+
+    #[test]
+    fn test_placeholder_form() {
+        let form = &parse_forms(TEMPLATE).unwrap()[0];
+        let values = placeholder_form(form);
+        assert_eq!(values["title"], "<title>");
+        assert_eq!(values["steps"], json!(["<Steps to reproduce>"]));
+        assert!(values["section"].as_str().unwrap().contains("- [ ] <Acceptance criteria>"));
+    }
+}
@@ -1,9 +1,12 @@
-use codexio::git::{get_git_diff, get_git_diff_between_branches, get_git_log};
+use codexio::git::{
+    get_change_ages, get_diff_paths, get_git_diff, get_git_diff_between_branches, get_git_log,
+    get_merge_state, get_skip_worktree_paths,
+};
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use git2::{Repository, RepositoryInitOptions, Signature};
+    use git2::{IndexEntryExtendedFlag, Repository, RepositoryInitOptions, Signature};
     use std::fs;
     use tempfile::TempDir;
 
@@ -264,4 +267,158 @@ mod tests {
         assert!(log.contains("First commit in development"));
         assert!(log.contains("Second commit in development"));
     }
+
+    #[test]
+    fn test_get_skip_worktree_paths() {
+        // Create a temporary directory
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = temp_dir.path();
+
+        // Initialize a new Git repository
+        let repo = Repository::init(repo_path).expect("Failed to initialize repository");
+
+        // Stage two files
+        fs::write(repo_path.join("kept.txt"), "Kept content").expect("Failed to write to test file");
+        fs::write(repo_path.join("sparse.txt"), "Sparse content")
+            .expect("Failed to write to test file");
+        let mut index = repo.index().expect("Failed to get repository index");
+        index
+            .add_path(std::path::Path::new("kept.txt"))
+            .expect("Failed to add file to index");
+        index
+            .add_path(std::path::Path::new("sparse.txt"))
+            .expect("Failed to add file to index");
+
+        // Mark one of them skip-worktree, as a sparse checkout does
+        let mut entry = index
+            .get_path(std::path::Path::new("sparse.txt"), 0)
+            .expect("Failed to find index entry");
+        entry.flags_extended |= IndexEntryExtendedFlag::SKIP_WORKTREE.bits();
+        index.add(&entry).expect("Failed to update index entry");
+        index.write().expect("Failed to write index");
+
+        // Get the skip-worktree paths
+        let paths = get_skip_worktree_paths(repo_path).expect("Failed to get skip-worktree paths");
+
+        // Assert that only the marked file is reported
+        assert_eq!(paths, vec![repo_path.canonicalize().unwrap().join("sparse.txt")]);
+    }
+
+    #[test]
+    fn test_get_change_ages() {
+        // Create a temporary directory
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = temp_dir.path();
+
+        // Initialize a new Git repository and commit a file
+        let repo = Repository::init(repo_path).expect("Failed to initialize repository");
+        fs::write(repo_path.join("committed.txt"), "Committed content").expect("Failed to write to test file");
+        let mut index = repo.index().expect("Failed to get repository index");
+        index
+            .add_path(std::path::Path::new("committed.txt"))
+            .expect("Failed to add file to index");
+        index.write().expect("Failed to write index");
+        let tree_id = index.write_tree().expect("Failed to write tree");
+        let tree = repo.find_tree(tree_id).expect("Failed to find tree");
+        let signature = Signature::now("Test", "test@example.com").expect("Failed to create signature");
+        repo.commit(Some("HEAD"), &signature, &signature, "Initial commit", &tree, &[])
+            .expect("Failed to commit");
+
+        // Leave another file uncommitted
+        fs::write(repo_path.join("untracked.txt"), "Untracked content").expect("Failed to write to test file");
+
+        // Get the change ages within a day
+        let ages = get_change_ages(repo_path, 24 * 60 * 60).expect("Failed to get change ages");
+        let workdir = repo_path.canonicalize().unwrap();
+
+        // Assert that both files changed recently, the uncommitted one just now
+        assert!(ages[&workdir.join("committed.txt")] < 60);
+        assert_eq!(ages[&workdir.join("untracked.txt")], 0);
+    }
+
+    #[test]
+    fn test_get_diff_paths() {
+        // Create a temporary directory
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = temp_dir.path();
+
+        // Initialize a new Git repository and commit two files
+        let repo = Repository::init(repo_path).expect("Failed to initialize repository");
+        fs::write(repo_path.join("changed.txt"), "Initial content").expect("Failed to write to test file");
+        fs::write(repo_path.join("unchanged.txt"), "Unchanged content").expect("Failed to write to test file");
+        let mut index = repo.index().expect("Failed to get repository index");
+        index
+            .add_path(std::path::Path::new("changed.txt"))
+            .expect("Failed to add file to index");
+        index
+            .add_path(std::path::Path::new("unchanged.txt"))
+            .expect("Failed to add file to index");
+        index.write().expect("Failed to write index");
+        let tree_id = index.write_tree().expect("Failed to write tree");
+        let tree = repo.find_tree(tree_id).expect("Failed to find tree");
+        let signature = Signature::now("Test", "test@example.com").expect("Failed to create signature");
+        repo.commit(Some("HEAD"), &signature, &signature, "Initial commit", &tree, &[])
+            .expect("Failed to commit");
+
+        // Stage a change to one of them
+        fs::write(repo_path.join("changed.txt"), "Modified content").expect("Failed to modify test file");
+        let mut index = repo.index().expect("Failed to get repository index");
+        index
+            .add_path(std::path::Path::new("changed.txt"))
+            .expect("Failed to add file to index");
+        index.write().expect("Failed to write index");
+
+        // Assert that only the staged file is in the diff
+        let paths = get_diff_paths(repo_path, None).expect("Failed to get diff paths");
+        assert_eq!(paths, vec![repo_path.canonicalize().unwrap().join("changed.txt")]);
+    }
+
+    #[test]
+    fn test_get_merge_state() {
+        // Create a temporary directory
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = temp_dir.path();
+
+        // Initialize a new Git repository
+        let mut binding = RepositoryInitOptions::new();
+        let init_options = binding.initial_head("master");
+        let repo = Repository::init_opts(repo_path, init_options)
+            .expect("Failed to initialize repository");
+        let signature = Signature::now("Test", "test@example.com").expect("Failed to create signature");
+        let tree_with = |content: &str| {
+            let blob = repo.blob(content.as_bytes()).expect("Failed to write blob");
+            let mut builder = repo.treebuilder(None).expect("Failed to create tree builder");
+            builder.insert("file.txt", blob, 0o100644).expect("Failed to insert file");
+            repo.find_tree(builder.write().expect("Failed to write tree")).expect("Failed to find tree")
+        };
+
+        // Change the same line on two branches
+        let base = repo
+            .commit(Some("HEAD"), &signature, &signature, "Base", &tree_with("base\n"), &[])
+            .expect("Failed to commit");
+        let base = repo.find_commit(base).expect("Failed to find commit");
+        let feature = repo
+            .commit(Some("refs/heads/feature"), &signature, &signature, "Feature", &tree_with("theirs\n"), &[&base])
+            .expect("Failed to commit");
+        repo.commit(Some("HEAD"), &signature, &signature, "Ours", &tree_with("ours\n"), &[&base])
+            .expect("Failed to commit");
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force()))
+            .expect("Failed to check out head");
+
+        // Merge the branches, leaving the file conflicted
+        let annotated = repo.find_annotated_commit(feature).expect("Failed to find commit");
+        repo.merge(&[&annotated], None, None).expect("Failed to merge");
+
+        let state = get_merge_state(repo_path).expect("Failed to get merge state");
+        assert_eq!(state.operation.as_deref(), Some("merge"));
+        assert_eq!(state.head.as_deref(), Some("master"));
+        assert_eq!(state.conflicts.len(), 1);
+        let conflict = &state.conflicts[0];
+        assert_eq!(conflict.path, "file.txt");
+        assert_eq!(conflict.absolute_path, repo_path.canonicalize().unwrap().join("file.txt"));
+        assert_eq!(conflict.base.as_deref(), Some("base\n"));
+        assert_eq!(conflict.ours.as_deref(), Some("ours\n"));
+        assert_eq!(conflict.theirs.as_deref(), Some("theirs\n"));
+        assert!(fs::read_to_string(repo_path.join("file.txt")).unwrap().contains("<<<<<<< "));
+    }
 }
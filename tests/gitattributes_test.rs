@@ -0,0 +1,48 @@
+use codexio::gitattributes::{GitAttributes, Linguist, ATTRIBUTES_FILE};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, relative: &str, content: &str) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).expect("Failed to create directory");
+        fs::write(path, content).expect("Failed to write file");
+    }
+
+    #[test]
+    fn test_linguist_attributes() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let root = temp_dir.path();
+        fs::create_dir(root.join(".git")).expect("Failed to create .git");
+        write(
+            root,
+            ATTRIBUTES_FILE,
+            "# Generated code\n/gen/** linguist-generated\nvendor/** linguist-vendored=true\n*.pb.go linguist-generated\n",
+        );
+
+        let mut attributes = GitAttributes::default();
+        assert_eq!(attributes.linguist(&root.join("gen/api.rs")), Some(Linguist::Generated));
+        assert_eq!(attributes.linguist(&root.join("vendor/lib/x.js")), Some(Linguist::Vendored));
+        assert_eq!(attributes.linguist(&root.join("proto/deep/api.pb.go")), Some(Linguist::Generated));
+        assert_eq!(attributes.linguist(&root.join("src/gen/api.rs")), None);
+        assert_eq!(attributes.linguist(&root.join("src/main.rs")), None);
+    }
+
+    #[test]
+    fn test_linguist_attributes_precedence() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let root = temp_dir.path();
+        write(root, ".git/info/attributes", "keep.pb.go -linguist-generated\n");
+        write(root, ATTRIBUTES_FILE, "*.pb.go linguist-generated\n");
+        write(root, &format!("api/{}", ATTRIBUTES_FILE), "manual.pb.go linguist-generated=false\n");
+
+        let mut attributes = GitAttributes::default();
+        assert_eq!(attributes.linguist(&root.join("api/auto.pb.go")), Some(Linguist::Generated));
+        assert_eq!(attributes.linguist(&root.join("api/manual.pb.go")), None);
+        assert_eq!(attributes.linguist(&root.join("keep.pb.go")), None);
+    }
+}
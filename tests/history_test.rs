@@ -0,0 +1,85 @@
+use codexio::history::{
+    load_invocation_in, load_output_in, load_runs_in, record_output_in, record_run_in, save_invocation_in, RunRecord,
+};
+use std::path::PathBuf;
+use std::thread;
+use tempfile::TempDir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrent_saves_are_all_recorded() {
+        let cache_dir = TempDir::new().expect("Failed to create temp dir");
+
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let cache = cache_dir.path().to_path_buf();
+                thread::spawn(move || {
+                    let directory = PathBuf::from(format!("/project/package-{}", i));
+                    save_invocation_in(&cache, &directory, &[format!("--include=package-{}", i)])
+                        .expect("Failed to save invocation");
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        for i in 0..8 {
+            let directory = PathBuf::from(format!("/project/package-{}", i));
+            assert_eq!(
+                load_invocation_in(cache_dir.path(), &directory).unwrap(),
+                Some(vec![format!("--include=package-{}", i)])
+            );
+        }
+    }
+
+    #[test]
+    fn test_outputs_are_recorded_per_file() {
+        let cache_dir = TempDir::new().expect("Failed to create temp dir");
+        let output = cache_dir.path().join("prompt.md");
+        assert_eq!(load_output_in(cache_dir.path(), &output).unwrap(), None);
+
+        let first = RunRecord::new(None, 400, vec!["src/main.rs".to_string()]);
+        record_output_in(cache_dir.path(), &output, first).unwrap();
+        let second = RunRecord::new(Some(90), 360, vec!["src/lib.rs".to_string()]);
+        record_output_in(cache_dir.path(), &output, second.clone()).unwrap();
+
+        assert_eq!(load_output_in(cache_dir.path(), &output).unwrap(), Some(second));
+        assert_eq!(load_output_in(cache_dir.path(), &cache_dir.path().join("other.md")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_runs_are_recorded_per_directory() {
+        let cache_dir = TempDir::new().expect("Failed to create temp dir");
+        let api = PathBuf::from("/project/api");
+        let web = PathBuf::from("/project/web");
+        assert!(load_runs_in(cache_dir.path(), &api).unwrap().is_empty());
+
+        for bytes in [100, 200] {
+            record_run_in(cache_dir.path(), &api, RunRecord::new(None, bytes, vec!["src/main.rs".to_string()])).unwrap();
+        }
+        record_run_in(cache_dir.path(), &web, RunRecord::new(Some(5), 20, Vec::new())).unwrap();
+
+        let runs = load_runs_in(cache_dir.path(), &api).unwrap();
+        assert_eq!(runs.iter().map(|run| run.bytes).collect::<Vec<_>>(), vec![100, 200]);
+        assert_eq!(runs[0].files, vec!["src/main.rs".to_string()]);
+        assert_eq!(load_runs_in(cache_dir.path(), &web).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_only_the_last_runs_are_kept() {
+        let cache_dir = TempDir::new().expect("Failed to create temp dir");
+        let directory = PathBuf::from("/project");
+        for bytes in 0..501 {
+            record_run_in(cache_dir.path(), &directory, RunRecord::new(None, bytes, Vec::new())).unwrap();
+        }
+
+        let runs = load_runs_in(cache_dir.path(), &directory).unwrap();
+        assert_eq!(runs.len(), 500);
+        assert_eq!(runs[0].bytes, 1);
+        assert_eq!(runs[499].bytes, 500);
+    }
+}
@@ -0,0 +1,75 @@
+use codexio::language::{detect_language, language_stats, matches_language};
+use std::path::Path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language_from_name_and_extension() {
+        assert_eq!(detect_language(Path::new("src/main.rs"), ""), Some("rust".to_string()));
+        assert_eq!(detect_language(Path::new("Makefile"), "all:\n"), Some("makefile".to_string()));
+        assert_eq!(detect_language(Path::new("docker/Dockerfile"), ""), Some("dockerfile".to_string()));
+        assert_eq!(detect_language(Path::new("CMakeLists.txt"), ""), Some("cmake".to_string()));
+        assert_eq!(detect_language(Path::new("notes.custom"), ""), Some("custom".to_string()));
+        assert_eq!(detect_language(Path::new("Dockerfile.dev"), ""), Some("dockerfile".to_string()));
+        assert_eq!(detect_language(Path::new("prod.tfvars"), ""), Some("terraform".to_string()));
+    }
+
+    #[test]
+    fn test_detect_language_from_shebang() {
+        assert_eq!(detect_language(Path::new("bin/run"), "#!/bin/bash\necho hi\n"), Some("bash".to_string()));
+        assert_eq!(
+            detect_language(Path::new("bin/tool"), "#!/usr/bin/env -S python3.12 -u\nprint()\n"),
+            Some("python".to_string())
+        );
+        assert_eq!(detect_language(Path::new("bin/serve"), "#!/usr/bin/env node\n"), Some("javascript".to_string()));
+        assert_eq!(detect_language(Path::new("LICENSE"), "MIT License\n"), None);
+    }
+
+    #[test]
+    fn test_detect_language_from_modeline() {
+        assert_eq!(
+            detect_language(Path::new("config/rc"), "set x\n# vim: set ft=vim:\n"),
+            Some("vim".to_string())
+        );
+        assert_eq!(
+            detect_language(Path::new("tasks"), "# -*- mode: ruby -*-\ntask :default\n"),
+            Some("ruby".to_string())
+        );
+        assert_eq!(
+            detect_language(Path::new("script"), "# -*- coding: utf-8 -*-\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_matches_language() {
+        let languages = vec!["rust".to_string(), "toml".to_string(), "makefile".to_string()];
+        assert!(matches_language(Path::new("src/main.rs"), Some("rust"), &languages));
+        assert!(matches_language(Path::new("Cargo.toml"), Some("toml"), &languages));
+        assert!(matches_language(Path::new("Makefile"), Some("makefile"), &languages));
+        assert!(!matches_language(Path::new("main.py"), Some("python"), &languages));
+        assert!(matches_language(Path::new("lib.RS"), None, &["rs".to_string()]));
+    }
+
+    #[test]
+    fn test_language_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [("main.rs", 300), ("lib.rs", 300), ("build.py", 400)];
+        let paths: Vec<_> = files
+            .iter()
+            .map(|(name, size)| {
+                let path = dir.path().join(name);
+                std::fs::write(&path, "x".repeat(*size)).unwrap();
+                path
+            })
+            .collect();
+
+        let stats = language_stats(&paths);
+        assert_eq!(stats.len(), 2);
+        assert_eq!((stats[0].language.as_str(), stats[0].files, stats[0].bytes), ("rust", 2, 600));
+        assert_eq!(stats[0].percent, 60.0);
+        assert_eq!((stats[1].language.as_str(), stats[1].percent), ("python", 40.0));
+    }
+}
@@ -0,0 +1,22 @@
+use codexio::messages::{message_with, set_locale, Locale, Message};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locale_from_tag() {
+        assert_eq!(Locale::from_tag("es"), Some(Locale::Es));
+        assert_eq!(Locale::from_tag("de-DE"), Some(Locale::De));
+        assert_eq!(Locale::from_tag("es_ES.UTF-8"), Some(Locale::Es));
+        assert_eq!(Locale::from_tag("C"), Some(Locale::En));
+        assert_eq!(Locale::from_tag("fr_FR"), None);
+    }
+
+    #[test]
+    fn test_message_placeholders() {
+        set_locale(Locale::En);
+        let text = message_with(Message::TokenInfo, &[("count", &42), ("model", &"gpt-4")]);
+        assert_eq!(text, "Token count: 42, Model info: gpt-4");
+    }
+}
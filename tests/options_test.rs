@@ -0,0 +1,109 @@
+use clap::Parser;
+use codexio::config::{Command, Config, FileConfig, HtmlTheme, Section, ThemeName};
+use codexio::options::Options;
+use std::path::PathBuf;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_options() {
+        let options = Options::default();
+        assert_eq!(options.path, vec![PathBuf::from(".")]);
+        assert_eq!(options.section, Section::All);
+        assert!(options.output.is_none());
+        assert!(!options.tokens);
+    }
+
+    #[test]
+    fn test_builder_sets_options() {
+        let options = Options::builder()
+            .path(vec![PathBuf::from("src")])
+            .include("*.rs")
+            .max_depth(2usize)
+            .tokens(true)
+            .build();
+        assert_eq!(options.path, vec![PathBuf::from("src")]);
+        assert_eq!(options.include.as_deref(), Some("*.rs"));
+        assert_eq!(options.max_depth, Some(2));
+        assert!(options.tokens);
+        assert!(!options.diff);
+    }
+
+    #[test]
+    fn test_command_line_maps_into_options() {
+        let config = Config::parse_from(["codexio", "src", "--exclude=*.md", "render", "--section", "tree"]);
+        let (command, options) = config.into_options();
+        assert!(matches!(command, Some(Command::Render { .. })));
+        assert_eq!(options.path, vec![PathBuf::from("src")]);
+        assert_eq!(options.exclude.as_deref(), Some("*.md"));
+        assert_eq!(options.section, Section::Tree);
+    }
+
+    #[test]
+    fn test_options_deserialize_with_defaults() {
+        let options: Options = serde_json::from_str(r#"{"include": "*.rs", "no_clipboard": true}"#).unwrap();
+        assert_eq!(options.include.as_deref(), Some("*.rs"));
+        assert!(options.no_clipboard);
+        assert_eq!(options.path, vec![PathBuf::from(".")]);
+    }
+
+    #[test]
+    fn test_file_config_fills_unset_options() {
+        let mut options = Options::builder().output("prompt.md").build();
+        let file_config = FileConfig {
+            output: Some("other.md".to_string()),
+            encoding: Some("p50k".to_string()),
+            ..Default::default()
+        };
+        options.merge_file_config(file_config);
+        assert_eq!(options.output.as_deref(), Some("prompt.md"));
+        assert_eq!(options.encoding.as_deref(), Some("p50k"));
+    }
+
+    #[test]
+    fn test_file_config_sets_theme_unless_given() {
+        let mut options = Options::builder().html_theme(HtmlTheme::Light).build();
+        let file_config = FileConfig {
+            theme: Some(ThemeName::HighContrast),
+            html_theme: Some(HtmlTheme::Dark),
+            ..Default::default()
+        };
+        options.merge_file_config(file_config);
+        assert_eq!(options.theme, Some(ThemeName::HighContrast));
+        assert_eq!(options.html_theme, Some(HtmlTheme::Light));
+    }
+
+    #[test]
+    fn test_earlier_layer_turns_off_flag() {
+        let mut options = Options::default();
+        let profile = FileConfig {
+            line_number: Some(false),
+            tokens: Some(false),
+            ..Default::default()
+        };
+        let file_config = FileConfig {
+            line_number: Some(true),
+            tokens: Some(true),
+            relative_paths: Some(true),
+            ..Default::default()
+        };
+        options.merge_file_config(profile);
+        options.merge_file_config(file_config);
+        assert!(!options.line_number);
+        assert!(!options.tokens);
+        assert!(options.relative_paths);
+    }
+
+    #[test]
+    fn test_command_line_flag_wins_over_layers() {
+        let mut options = Options::builder().line_number(true).build();
+        let profile = FileConfig {
+            line_number: Some(false),
+            ..Default::default()
+        };
+        options.merge_file_config(profile);
+        assert!(options.line_number);
+    }
+}
@@ -0,0 +1,36 @@
+use codexio::config::PasteLimit;
+use codexio::messages::{set_locale, Locale};
+use codexio::paste::{check_paste_limit, paste_limit};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_limit_overrides_builtin() {
+        let limit = PasteLimit {
+            bytes: Some(10),
+            characters: None,
+        };
+        let overrides = HashMap::from([("chatgpt".to_string(), limit)]);
+        assert_eq!(paste_limit("chatgpt", &overrides).unwrap(), limit);
+        assert!(paste_limit("claude-web", &overrides).is_ok());
+        assert!(paste_limit("unknown", &overrides).is_err());
+    }
+
+    #[test]
+    fn test_check_paste_limit() {
+        set_locale(Locale::En);
+        let limit = PasteLimit {
+            bytes: Some(8),
+            characters: Some(4),
+        };
+        assert!(check_paste_limit("abc", "chat", &limit).is_empty());
+
+        let warnings = check_paste_limit("äöüäöü", "chat", &limit);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("6 characters"));
+        assert!(warnings[1].contains("12 bytes"));
+    }
+}
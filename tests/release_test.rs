@@ -0,0 +1,86 @@
+use codexio::release::{gather_release, group_commits, parse_commit_message};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Repository, Signature};
+    use std::fs;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    /// Writes a file and commits it, returning the id of the commit.
+    fn commit_file(repo: &Repository, path: &str, content: &str, message: &str) -> git2::Oid {
+        let root = repo.workdir().unwrap();
+        fs::write(root.join(path), content).expect("Failed to write file");
+        let mut index = repo.index().expect("Failed to get repository index");
+        index.add_path(Path::new(path)).expect("Failed to add file to index");
+        index.write().expect("Failed to write index");
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = Signature::now("Test", "test@example.com").unwrap();
+        let parents = match repo.head() {
+            Ok(head) => vec![head.peel_to_commit().unwrap()],
+            Err(_) => Vec::new(),
+        };
+        let parents: Vec<_> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parents)
+            .expect("Failed to commit")
+    }
+
+    #[test]
+    fn test_parse_commit_message() {
+        let commit = parse_commit_message("feat(cli)!: add --sort", None);
+        assert_eq!(commit.kind.as_deref(), Some("feat"));
+        assert_eq!(commit.scope.as_deref(), Some("cli"));
+        assert_eq!(commit.description, "add --sort");
+        assert!(commit.breaking);
+
+        let commit = parse_commit_message("fix: keep order", Some("BREAKING CHANGE: paths are sorted"));
+        assert_eq!(commit.kind.as_deref(), Some("fix"));
+        assert!(commit.scope.is_none());
+        assert!(commit.breaking);
+
+        let commit = parse_commit_message("Update README", None);
+        assert!(commit.kind.is_none());
+        assert_eq!(commit.description, "Update README");
+        assert!(!commit.breaking);
+    }
+
+    #[test]
+    fn test_group_commits() {
+        let commits = vec![
+            parse_commit_message("feat: add --sort", None),
+            parse_commit_message("fix!: drop --order", None),
+            parse_commit_message("chore: bump dependencies", None),
+            parse_commit_message("Update README", None),
+        ];
+        let groups = group_commits(&commits);
+        let titles: Vec<_> = groups.iter().map(|group| group.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            ["Breaking Changes", "Features", "Bug Fixes", "Maintenance", "Other Changes"]
+        );
+        assert_eq!(groups[0].commits[0].description, "drop --order");
+    }
+
+    #[test]
+    fn test_gather_release() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo = Repository::init(temp_dir.path()).expect("Failed to initialize repository");
+        let first = commit_file(&repo, "CHANGELOG.md", "# Changelog\n\n## 1.0.0\n", "chore: release 1.0.0");
+        repo.tag_lightweight("v1.0.0", &repo.find_object(first, None).unwrap(), false)
+            .expect("Failed to tag");
+        commit_file(&repo, "main.rs", "fn main() {}\n", "feat(cli): add main");
+        commit_file(&repo, "main.rs", "fn main() { run() }\n", "fix: call run");
+
+        let release = gather_release(temp_dir.path(), "v1.0.0", "HEAD").expect("Failed to gather release");
+        let summaries: Vec<_> = release.commits.iter().map(|commit| commit.summary.as_str()).collect();
+        assert_eq!(summaries, ["feat(cli): add main", "fix: call run"]);
+        assert_eq!(release.changed_files.len(), 1);
+        assert_eq!(release.changed_files[0].path, "main.rs");
+        assert_eq!(release.changed_files[0].status, "added");
+        assert!(release.diff.contains("+fn main() { run() }"));
+        assert!(release.changelog.unwrap().starts_with("# Changelog"));
+
+        assert!(gather_release(temp_dir.path(), "v9.9.9", "HEAD").is_err());
+    }
+}
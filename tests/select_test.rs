@@ -0,0 +1,65 @@
+use codexio::select::{parse_duration, parse_selection, Candidate};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate<'a>(relative_path: &'a str, language: Option<&'a str>, age: Option<u64>) -> Candidate<'a> {
+        Candidate {
+            relative_path,
+            absolute_path: "/",
+            language,
+            text: Some("fn main() {}\n// TODO: tests"),
+            age,
+        }
+    }
+
+    #[test]
+    fn test_selection_operators() {
+        let selection = parse_selection("(lang:rust & changed:7d) | path:docs/**").unwrap();
+        assert!(selection.matches(&candidate("src/main.rs", Some("rust"), Some(3600))));
+        assert!(!selection.matches(&candidate("src/lib.rs", Some("rust"), None)));
+        assert!(!selection.matches(&candidate("src/lib.rs", Some("rust"), Some(30 * 24 * 3600))));
+        assert!(selection.matches(&candidate("docs/guide.md", Some("markdown"), None)));
+
+        let selection = parse_selection("ext:rs & !path:tests/** & contains:\"TODO: \"").unwrap();
+        assert!(selection.matches(&candidate("src/main.rs", None, None)));
+        assert!(!selection.matches(&candidate("tests/main.rs", None, None)));
+        assert!(!selection.matches(&candidate("src/main.py", None, None)));
+    }
+
+    #[test]
+    fn test_selection_precedence() {
+        // `&` binds tighter than `|`
+        let selection = parse_selection("ext:md | ext:rs & lang:python").unwrap();
+        assert!(selection.matches(&candidate("README.md", None, None)));
+        assert!(!selection.matches(&candidate("src/main.rs", Some("rust"), None)));
+    }
+
+    #[test]
+    fn test_changed_window() {
+        let selection = parse_selection("changed:1d | !(changed:2w & ext:rs)").unwrap();
+        assert_eq!(selection.changed_window(), Some(14 * 24 * 3600));
+        assert_eq!(parse_selection("lang:rust").unwrap().changed_window(), None);
+    }
+
+    #[test]
+    fn test_invalid_selection() {
+        let error = parse_selection("lang:rust &").unwrap_err().to_string();
+        assert!(error.contains("column 12"), "{}", error);
+        assert!(parse_selection("size:10").is_err());
+        assert!(parse_selection("changed:soon").is_err());
+        assert!(parse_selection("(lang:rust").is_err());
+        assert!(parse_selection("lang:rust)").is_err());
+        assert!(parse_selection("contains:\"unclosed").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30m"), Some(1800));
+        assert_eq!(parse_duration("7d"), Some(7 * 24 * 3600));
+        assert_eq!(parse_duration("2w"), Some(14 * 24 * 3600));
+        assert_eq!(parse_duration("7"), None);
+        assert_eq!(parse_duration("d"), None);
+    }
+}
@@ -0,0 +1,86 @@
+use codexio::config::{FileSize, SortOrder};
+use codexio::input::load_file_config;
+use codexio::options::Options;
+use codexio::selection::{save_selection, selection_table};
+use std::collections::HashMap;
+use std::fs;
+use tempfile::tempdir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_table_leaves_out_defaults() {
+        let options = Options::builder()
+            .include("src/**/*.rs, Cargo.toml")
+            .lang("rust")
+            .hidden(true)
+            .budget(50_000usize)
+            .build();
+        let table = selection_table(&options).unwrap();
+        assert_eq!(
+            table.to_string(),
+            "include = [\"src/**/*.rs\", \"Cargo.toml\"]\nlang = \"rust\"\nhidden = true\nbudget = 50000\n"
+        );
+    }
+
+    #[test]
+    fn test_save_selection_keeps_the_rest_of_the_file() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join(".codexio.toml");
+        fs::write(&config_path, "# Team defaults\nencoding = \"cl100k\"\n").unwrap();
+
+        let options = Options::builder().exclude("**/tests/**").build();
+        save_selection(dir.path(), "backend-core", &options).unwrap();
+        let options = Options::builder().include("docs/**").build();
+        save_selection(dir.path(), "docs", &options).unwrap();
+        let options = Options::builder().include("api/**").build();
+        save_selection(dir.path(), "backend-core", &options).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&config_path).unwrap(),
+            "# Team defaults\nencoding = \"cl100k\"\n\n[selections.backend-core]\ninclude = [\"api/**\"]\n\n[selections.docs]\ninclude = [\"docs/**\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_saved_selection_round_trips() {
+        let dir = tempdir().unwrap();
+        let tiers = HashMap::from([
+            ("tier1".to_string(), vec!["src/core/**".to_string()]),
+            ("tier2".to_string(), vec!["src/**".to_string(), "Cargo.toml".to_string()]),
+        ]);
+        let options = Options::builder()
+            .include("src/**")
+            .exclude("**/fixtures/**")
+            .lang("rust")
+            .no_gitattributes(true)
+            .include_generated(true)
+            .allow_sensitive(true)
+            .include_external_symlinks(true)
+            .estimate(true)
+            .max_file_size(FileSize(2048))
+            .budget(8_000usize)
+            .sort(SortOrder::TokensDesc)
+            .tiers(tiers.clone())
+            .build();
+        save_selection(dir.path(), "core", &options).unwrap();
+
+        let selection = load_file_config(dir.path()).unwrap().selections.remove("core").unwrap();
+        let mut loaded = Options::builder().build();
+        loaded.merge_file_config(selection);
+        assert_eq!(loaded.include, options.include);
+        assert_eq!(loaded.exclude, options.exclude);
+        assert_eq!(loaded.lang, options.lang);
+        assert!(loaded.no_gitattributes);
+        assert!(loaded.include_generated);
+        assert!(loaded.allow_sensitive);
+        assert!(loaded.include_external_symlinks);
+        assert!(loaded.estimate);
+        assert_eq!(loaded.max_file_size, Some(FileSize(2048)));
+        assert_eq!(loaded.budget, Some(8_000));
+        assert_eq!(loaded.sort, Some(SortOrder::TokensDesc));
+        assert_eq!(loaded.tiers, tiers);
+    }
+}
@@ -0,0 +1,117 @@
+use codexio::options::Options;
+use codexio::source::{archive_name, fetch_sources, is_remote_url, parse_github_spec};
+use codexio::workspace::Workspace;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use git2::{Oid, Repository, Signature};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Commits a new file to the repository on top of the current HEAD.
+    fn commit_file(repo: &Repository, repo_path: &Path, name: &str, content: &str) -> Oid {
+        fs::write(repo_path.join(name), content).expect("Failed to write test file");
+
+        let mut index = repo.index().expect("Failed to get repository index");
+        index.add_path(Path::new(name)).expect("Failed to add file to index");
+        index.write().expect("Failed to write index");
+
+        let tree_id = index.write_tree().expect("Failed to write tree");
+        let tree = repo.find_tree(tree_id).expect("Failed to find tree");
+        let signature =
+            Signature::now("Test", "test@example.com").expect("Failed to create signature");
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+
+        repo.commit(Some("HEAD"), &signature, &signature, name, &tree, &parents)
+            .expect("Failed to commit")
+    }
+
+    #[test]
+    fn test_is_remote_url() {
+        assert!(is_remote_url("https://github.com/org/repo.git"));
+        assert!(is_remote_url("git@github.com:org/repo.git"));
+        assert!(is_remote_url("file:///tmp/repo"));
+        assert!(!is_remote_url("./src"));
+        assert!(!is_remote_url("git@notes"));
+    }
+
+    #[test]
+    fn test_parse_github_spec() {
+        assert_eq!(
+            parse_github_spec("org/repo@v1.2.0").unwrap(),
+            ("org", "repo", Some("v1.2.0"))
+        );
+        assert_eq!(parse_github_spec("org/repo").unwrap(), ("org", "repo", None));
+        assert!(parse_github_spec("repo").is_err());
+        assert!(parse_github_spec("org/repo/extra").is_err());
+    }
+
+    #[test]
+    fn test_extract_tar_gz_archive() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let archive_path = temp_dir.path().join("project-1.2.3.tar.gz");
+
+        let file = fs::File::create(&archive_path).expect("Failed to create archive");
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        let content = b"fn main() {}";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "project-1.2.3/src/main.rs", &content[..])
+            .expect("Failed to append to archive");
+        builder
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .expect("Failed to finish archive");
+
+        assert_eq!(archive_name(&archive_path).as_deref(), Some("project-1.2.3"));
+
+        let mut config = Options::builder().path(vec![archive_path.clone()]).build();
+        let workspace = Workspace::new().expect("Failed to create workspace");
+        fetch_sources(&mut config, &workspace).expect("Failed to extract archive");
+
+        let extracted = &config.path[0];
+        assert!(extracted.ends_with("project-1.2.3"));
+        assert_eq!(
+            fs::read_to_string(extracted.join("src/main.rs")).unwrap(),
+            "fn main() {}"
+        );
+    }
+
+    #[test]
+    fn test_fetch_remote_at_tag() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = temp_dir.path();
+        let repo = Repository::init(repo_path).expect("Failed to initialize repository");
+
+        let tagged = commit_file(&repo, repo_path, "tagged.txt", "Tagged content");
+        repo.tag_lightweight(
+            "v1",
+            &repo.find_object(tagged, None).expect("Failed to find commit"),
+            false,
+        )
+        .expect("Failed to create tag");
+        commit_file(&repo, repo_path, "later.txt", "Later content");
+
+        let url = format!("file://{}", repo_path.display());
+        let mut config = Options::builder().remote(url).branch("v1").build();
+        let workspace = Workspace::new().expect("Failed to create workspace");
+        fetch_sources(&mut config, &workspace).expect("Failed to fetch remote");
+
+        let clone = &config.path[0];
+        assert!(clone.starts_with(workspace.path()));
+        assert!(clone.join("tagged.txt").exists());
+        assert!(!clone.join("later.txt").exists());
+
+        let workspace_path = workspace.path().to_path_buf();
+        drop(workspace);
+        assert!(!workspace_path.exists());
+    }
+}
@@ -0,0 +1,62 @@
+use codexio::split::{part_path, split_prompt, write_parts};
+use codexio::template::handlebars_setup;
+use codexio::token::get_tokenizer;
+use serde_json::{json, Value};
+use std::fs;
+use tempfile::TempDir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a file of the prompt data whose code block has the given number of lines.
+    fn file(path: &str, lines: usize) -> Value {
+        let code: Vec<String> = (0..lines).map(|line| format!("let value_{} = {};", line, line)).collect();
+        json!({ "path": path, "code": format!("```rs\n{}\n```", code.join("\n")) })
+    }
+
+    #[test]
+    fn test_part_path() {
+        assert_eq!(part_path("prompt.md", 1), "prompt.part1.md");
+        assert_eq!(part_path("out/prompt.md.gz", 2), "out/prompt.part2.md.gz");
+        assert_eq!(part_path("dir.v2/prompt", 3), "dir.v2/prompt.part3");
+    }
+
+    #[test]
+    fn test_split_prompt() {
+        let handlebars = handlebars_setup("## Project\n\n{{> files}}", "custom").unwrap();
+        let bpe = get_tokenizer(&None);
+        let data = json!({ "files": [file("a.rs", 20), file("b.rs", 20), file("c.rs", 20), file("big.rs", 400)] });
+
+        let parts = split_prompt(&handlebars, "custom", &data, bpe, 500).unwrap();
+        assert!(parts.len() > 2);
+        for part in &parts {
+            assert!(bpe.encode_with_special_tokens(part).len() <= 510);
+            assert_eq!(part.matches("```").count() % 2, 0);
+        }
+        assert!(parts[0].starts_with(&format!("> Part 1 of {}.", parts.len())));
+        assert!(parts[0].contains("## Project"));
+        assert!(!parts[1].contains("## Project"));
+        assert!(parts.last().unwrap().contains("This is the last part."));
+
+        // Small files are kept whole, and only the large one is cut between lines
+        assert_eq!(parts.iter().filter(|part| part.contains("### `a.rs`")).count(), 1);
+        assert!(parts.iter().any(|part| part.contains("### `big.rs (lines 1-")));
+
+        assert!(split_prompt(&handlebars, "custom", &data, bpe, 5).is_err());
+    }
+
+    #[test]
+    fn test_write_parts_removes_stale_parts() {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let output = dir.path().join("prompt.md").to_string_lossy().into_owned();
+        let three: Vec<String> = (1..=3).map(|index| format!("part {}", index)).collect();
+        assert_eq!(write_parts(&output, &three, None).unwrap().len(), 3);
+
+        let written = write_parts(&output, &["only part".to_string()], None).unwrap();
+        assert_eq!(written, vec![part_path(&output, 1)]);
+        assert_eq!(fs::read_to_string(part_path(&output, 1)).unwrap(), "only part");
+        assert!(!dir.path().join("prompt.part2.md").exists());
+        assert!(!dir.path().join("prompt.part3.md").exists());
+    }
+}
@@ -0,0 +1,45 @@
+use codexio::suggest::{generated_pattern, suggest};
+use serde_json::{json, Value};
+use std::path::Path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, tokens: usize) -> Value {
+        json!({ "path": path, "extension": "", "code": "", "token_count": tokens })
+    }
+
+    #[test]
+    fn test_generated_pattern() {
+        assert_eq!(generated_pattern(Path::new("Cargo.lock")).as_deref(), Some("**/Cargo.lock"));
+        assert_eq!(generated_pattern(Path::new("web/app.min.js")).as_deref(), Some("*.min.js"));
+        assert_eq!(generated_pattern(Path::new("web/dist/app.js")).as_deref(), Some("**/dist/**"));
+        assert_eq!(generated_pattern(Path::new("src/main.rs")), None);
+    }
+
+    #[test]
+    fn test_suggest_within_budget_only_excludes_generated_files() {
+        let files = [file("repo/src/main.rs", 100), file("repo/Cargo.lock", 50)];
+        let suggestions = suggest(&files, 200, Some(1000));
+
+        assert_eq!(suggestions.excludes, vec!["**/Cargo.lock"]);
+        assert_eq!(suggestions.remaining_tokens, 150);
+        assert_eq!(suggestions.extensions[0].name, ".rs");
+    }
+
+    #[test]
+    fn test_suggest_excludes_largest_directories_to_fit_budget() {
+        let files = [
+            file("repo/src/main.rs", 100),
+            file("repo/fixtures/big.json", 5000),
+            file("repo/docs/guide.md", 800),
+            file("repo/README.md", 50),
+        ];
+        let suggestions = suggest(&files, 6000, Some(1000));
+
+        assert_eq!(suggestions.directories[0].name, "fixtures");
+        assert_eq!(suggestions.excludes, vec!["**/fixtures/**"]);
+        assert_eq!(suggestions.remaining_tokens, 1000);
+    }
+}
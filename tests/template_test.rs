@@ -1,9 +1,15 @@
-use codexio::template::{extract_undefined_variables, handlebars_setup, render_template};
+use codexio::config::{FileSize, Section};
+use codexio::template::{
+    extract_undefined_variables, handlebars_setup, preview_template, render_section, render_template,
+    without_section, IncludeFile, TemplateError, TemplateStage, TemplateSubject, INCLUDE_FILE_HELPER,
+};
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
+    use std::fs;
+    use tempfile::TempDir;
 
     #[test]
     fn test_handlebars_setup() {
@@ -49,4 +55,151 @@ mod tests {
             Err(e) => panic!("Template rendering failed: {}", e),
         }
     }
+
+    #[test]
+    fn test_render_section() {
+        let handlebars = handlebars_setup("{{> tree}}\n\n{{> files}}", "test_template").unwrap();
+        let data = json!({
+            "source_tree": "project\n└── main.rs",
+            "files": [{ "path": "project/main.rs", "code": "fn main() {}" }],
+            "git_diff": "+fn main() {}",
+            "git_diff_branch": "",
+            "git_log_branch": ""
+        });
+
+        let tree = render_section(&handlebars, "test_template", Section::Tree, &data).unwrap();
+        assert!(tree.contains("project\n└── main.rs"));
+        assert!(!tree.contains("fn main() {}"));
+
+        let diff = render_section(&handlebars, "test_template", Section::Diff, &data).unwrap();
+        assert!(diff.contains("+fn main() {}"));
+        assert!(!diff.contains("Git Log Between Branches"));
+
+        let files = render_section(&handlebars, "test_template", Section::Files, &data).unwrap();
+        assert!(files.contains("### `project/main.rs`"));
+        assert!(!files.contains("└── main.rs"));
+
+        let all = render_section(&handlebars, "test_template", Section::All, &data).unwrap();
+        assert!(all.contains("└── main.rs") && all.contains("### `project/main.rs`"));
+    }
+
+    #[test]
+    fn test_render_error_location() {
+        let template_str = "Files:\n{{shout path}}";
+        let handlebars = handlebars_setup(template_str, "custom").unwrap();
+        let data = json!({ "path": "src/main.rs" });
+
+        let error = render_template(&handlebars, "custom", &data).unwrap_err();
+        let template_error = error.downcast_ref::<TemplateError>().expect("Expected a template error");
+        assert_eq!(template_error.stage, TemplateStage::Render);
+        assert_eq!(template_error.template, "custom");
+        assert_eq!(template_error.line, Some(2));
+        assert_eq!(template_error.subject, Some(TemplateSubject::Helper("shout".to_string())));
+        assert!(error.to_string().contains("custom:2:"));
+    }
+
+    #[test]
+    fn test_register_error_location() {
+        let template_str = "Header\n{{#if path}}\n{{/each}}";
+        let error = handlebars_setup(template_str, "custom").unwrap_err();
+        let template_error = error.downcast_ref::<TemplateError>().expect("Expected a template error");
+        assert_eq!(template_error.stage, TemplateStage::Register);
+        assert_eq!(template_error.template, "custom");
+        assert!(template_error.line.is_some());
+        assert_eq!(template_error.subject, Some(TemplateSubject::Helper("if".to_string())));
+    }
+
+    #[test]
+    fn test_preview_template() {
+        let template_str = "{{> tree}}\n{{#each files}}{{path}}\n{{/each}}{{git_diff}}\nReviewer: {{reviewer}}";
+        let preview = preview_template(template_str).unwrap();
+        assert!(preview.contains("└── src"));
+        assert!(preview.contains("example/Cargo.toml\nexample/src/main.rs"));
+        assert!(preview.contains("+    println!(\"Hello, world!\");"));
+        assert!(preview.contains("Reviewer: <reviewer>"));
+    }
+
+    #[test]
+    fn test_include_file_helper() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        fs::create_dir(temp_dir.path().join("docs")).unwrap();
+        fs::write(
+            temp_dir.path().join("docs/spec.md"),
+            "# Spec\napi_key = \"Zx8qL2mN4pR7sT9vW1yB3dF6\"",
+        )
+        .unwrap();
+        fs::write(temp_dir.path().join(".env"), "TOKEN=abc").unwrap();
+
+        let template_str = "Reference:\n{{include_file \"docs/spec.md\"}}";
+        let mut handlebars = handlebars_setup(template_str, "custom").unwrap();
+        handlebars.register_helper(
+            INCLUDE_FILE_HELPER,
+            Box::new(IncludeFile {
+                base: temp_dir.path().to_path_buf(),
+                ..IncludeFile::default()
+            }),
+        );
+        let rendered = render_template(&handlebars, "custom", &json!({})).unwrap();
+        assert!(rendered.contains("```md\n# Spec\n"));
+        assert!(rendered.contains("[REDACTED:generic-secret]"));
+        assert!(!rendered.contains("Zx8qL2mN4pR7sT9vW1yB3dF6"));
+
+        let include = IncludeFile {
+            base: temp_dir.path().to_path_buf(),
+            max_file_size: Some(FileSize(4)),
+            ..IncludeFile::default()
+        };
+        assert!(include.render_file("docs/spec.md").is_err());
+        assert!(include.render_file(".env").is_err());
+        assert!(include.render_file("missing.md").is_err());
+
+        let outside_dir = TempDir::new().expect("Failed to create temp dir");
+        let outside = outside_dir.path().join("outside.md");
+        fs::write(&outside, "# Outside").unwrap();
+        let include = IncludeFile {
+            base: temp_dir.path().join("docs"),
+            roots: vec![temp_dir.path().canonicalize().unwrap()],
+            ..IncludeFile::default()
+        };
+        assert!(include.render_file("spec.md").is_ok());
+        assert!(include.render_file("../docs/spec.md").is_ok());
+        assert!(include.render_file(outside.to_str().unwrap()).is_err());
+        assert!(include
+            .render_file(&format!("../../{}/outside.md", outside_dir.path().file_name().unwrap().to_string_lossy()))
+            .is_err());
+
+        let include = IncludeFile {
+            base: temp_dir.path().to_path_buf(),
+            budget: Some(1),
+            ..IncludeFile::default()
+        };
+        assert!(include.render_file("docs/spec.md").is_err());
+        let include = IncludeFile {
+            base: temp_dir.path().to_path_buf(),
+            budget: Some(10_000),
+            ..IncludeFile::default()
+        };
+        assert!(include.render_file("docs/spec.md").is_ok());
+        assert!(include.render_file("docs/spec.md").is_ok());
+        assert_eq!(include.included_tokens.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_without_section() {
+        let data = json!({
+            "source_tree": "example",
+            "files": [{ "path": "example/main.rs", "code": "fn main() {}" }],
+            "instructions": "Review this code.",
+        });
+        let handlebars = handlebars_setup("{{> tree}}\n{{> files}}\n{{> instructions}}", "custom").unwrap();
+
+        let rendered = render_template(&handlebars, "custom", &without_section(&data, Section::Instructions)).unwrap();
+        assert!(rendered.contains("fn main() {}"));
+        assert!(!rendered.contains("Review this code."));
+
+        let rendered = render_template(&handlebars, "custom", &without_section(&data, Section::Tree)).unwrap();
+        assert!(!rendered.contains("## Source Tree"));
+        assert!(rendered.contains("Review this code."));
+        assert_eq!(without_section(&data, Section::All), data);
+    }
 }
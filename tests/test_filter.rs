@@ -1,4 +1,6 @@
-use codexio::filter::should_include_file;
+use codexio::filter::{
+    is_generated_content, is_generated_file, is_sensitive_file, last_match, should_include_file,
+};
 use colored::*;
 use once_cell::sync::Lazy;
 use std::fs::{self, File};
@@ -368,4 +370,133 @@ mod tests {
             include_priority
         ));
     }
+
+    #[test]
+    fn test_negated_exclude_patterns() {
+        let base_path = TEST_DIR.path();
+
+        let include_patterns = vec![];
+        let exclude_patterns = vec!["**/lowercase/**".to_string(), "!**/lowercase/foo.py".to_string()];
+        let include_priority = false;
+
+        assert!(should_include_file(
+            &base_path.join("lowercase/foo.py"),
+            &include_patterns,
+            &exclude_patterns,
+            include_priority
+        ));
+        for file in ["lowercase/bar.py", "lowercase/qux.txt"] {
+            assert!(!should_include_file(
+                &base_path.join(file),
+                &include_patterns,
+                &exclude_patterns,
+                include_priority
+            ));
+        }
+        assert!(should_include_file(
+            &base_path.join("uppercase/FOO.py"),
+            &include_patterns,
+            &exclude_patterns,
+            include_priority
+        ));
+    }
+
+    #[test]
+    fn test_negated_include_patterns() {
+        let base_path = TEST_DIR.path();
+
+        let include_patterns = vec!["*.py".to_string(), "!**/uppercase/*".to_string()];
+        let exclude_patterns = vec![];
+        let include_priority = false;
+
+        assert!(should_include_file(
+            &base_path.join("lowercase/foo.py"),
+            &include_patterns,
+            &exclude_patterns,
+            include_priority
+        ));
+        for file in ["uppercase/FOO.py", "lowercase/qux.txt"] {
+            assert!(!should_include_file(
+                &base_path.join(file),
+                &include_patterns,
+                &exclude_patterns,
+                include_priority
+            ));
+        }
+
+        // Only negated include patterns include everything else
+        let include_patterns = vec!["!*.txt".to_string()];
+        assert!(should_include_file(
+            &base_path.join("lowercase/foo.py"),
+            &include_patterns,
+            &exclude_patterns,
+            include_priority
+        ));
+        assert!(!should_include_file(
+            &base_path.join("lowercase/qux.txt"),
+            &include_patterns,
+            &exclude_patterns,
+            include_priority
+        ));
+    }
+
+    #[test]
+    fn test_last_match() {
+        let patterns = vec!["*.rs".to_string(), "!*/generated.rs".to_string(), "\\!*".to_string()];
+        assert_eq!(last_match(&patterns, "src/main.rs"), Some(true));
+        assert_eq!(last_match(&patterns, "src/generated.rs"), Some(false));
+        assert_eq!(last_match(&patterns, "!important"), Some(true));
+        assert_eq!(last_match(&patterns, "README.md"), None);
+    }
+
+    #[test]
+    fn test_is_sensitive_file() {
+        for path in [
+            "/project/.env",
+            "/project/config/.env.production",
+            "/project/certs/server.pem",
+            "/home/user/.ssh/id_ed25519",
+            "/home/user/.aws/credentials",
+            "/project/infra/terraform.tfstate.backup",
+            "/project/.kube/config",
+        ] {
+            assert!(is_sensitive_file(Path::new(path)), "{}", path);
+        }
+        for path in [
+            "/project/.env.example",
+            "/project/src/environment.rs",
+            "/project/docs/id_rsa_setup.md.template",
+            "/project/aws/credentials.rs",
+            "/project/src/key.rs",
+        ] {
+            assert!(!is_sensitive_file(Path::new(path)), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_is_generated_file() {
+        for path in [
+            "/project/static/app.min.js",
+            "/project/static/app.js.map",
+            "/project/api/service.pb.go",
+            "/project/proto/service_pb2.py",
+            "/project/lib/model.g.dart",
+        ] {
+            assert!(is_generated_file(Path::new(path)), "{}", path);
+        }
+        for path in ["/project/src/min.js", "/project/src/pb.go", "/project/src/main.rs"] {
+            assert!(!is_generated_file(Path::new(path)), "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_is_generated_content() {
+        assert!(is_generated_content(
+            "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n"
+        ));
+        assert!(is_generated_content("# @generated by tool\nx = 1\n"));
+        assert!(is_generated_content(&"var a=1;".repeat(1000)));
+        assert!(!is_generated_content("fn main() {\n    println!(\"Hello\");\n}\n"));
+        assert!(!is_generated_content(&"let a = 1;\n".repeat(1000)));
+    }
 }
@@ -0,0 +1,73 @@
+use codexio::text::{
+    anchor_lines, display_width, escape_xml, has_conflict_markers, line_anchor, number_lines, pad_left, pad_right,
+    truncate_lines, xml_text,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width("main.rs"), 7);
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(display_width("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn test_truncate_lines() {
+        assert_eq!(truncate_lines("one\ntwo\nthree", 2), "one\ntwo");
+        assert_eq!(truncate_lines("one\ntwo", 5), "one\ntwo");
+        assert_eq!(truncate_lines("one\ntwo", 0), "");
+    }
+
+    #[test]
+    fn test_padding() {
+        assert_eq!(pad_right("日本", 6), "日本  ");
+        assert_eq!(pad_left("日本", 6), "  日本");
+        assert_eq!(pad_right("too wide", 3), "too wide");
+    }
+
+    #[test]
+    fn test_number_lines() {
+        assert_eq!(number_lines("fn main() {\n}"), "   1 | fn main() {\n   2 | }\n");
+
+        let code = "x\n".repeat(10_000);
+        let numbered = number_lines(&code);
+        assert!(numbered.starts_with("    1 | x\n"));
+        assert!(numbered.ends_with("10000 | x\n"));
+    }
+
+    #[test]
+    fn test_line_anchors() {
+        let anchor = line_anchor(12, "let x = 1;");
+        assert!(anchor.starts_with("12:"));
+        assert_eq!(anchor.len(), 6);
+        assert_eq!(line_anchor(12, "let x = 1;\r"), anchor);
+        assert_ne!(line_anchor(12, "let x = 2;"), anchor);
+
+        let anchored = anchor_lines("fn main() {\n}", 1);
+        assert_eq!(
+            anchored,
+            format!("{:>8} | fn main() {{\n{:>8} | }}\n", line_anchor(1, "fn main() {"), line_anchor(2, "}"))
+        );
+    }
+
+    #[test]
+    fn test_escape_xml() {
+        assert_eq!(escape_xml("a<b>&\"c'"), "a&lt;b&gt;&amp;&quot;c&#39;");
+        assert_eq!(xml_text("plain text"), "plain text");
+        assert_eq!(xml_text("if a < b {}"), "<![CDATA[if a < b {}]]>");
+        assert_eq!(xml_text("x]]>y"), "x]]>y");
+        assert_eq!(xml_text("<a>]]></a>"), "<![CDATA[<a>]]]]><![CDATA[></a>]]>");
+    }
+
+    #[test]
+    fn test_has_conflict_markers() {
+        assert!(has_conflict_markers("a\n<<<<<<< HEAD\nours\n=======\ntheirs\n>>>>>>> feature\nb\n"));
+        assert!(has_conflict_markers("<<<<<<< HEAD\r\nours\r\n=======\r\ntheirs\r\n>>>>>>> 1a2b3c4\r\n"));
+        assert!(!has_conflict_markers("Title\n=======\n"));
+        assert!(!has_conflict_markers("<<<<<<< HEAD\nours\n=======\ntheirs\n"));
+        assert!(!has_conflict_markers("<<<<<<<<<< not a marker\n=======\n>>>>>>> feature\n"));
+    }
+}
@@ -0,0 +1,34 @@
+use codexio::config::{ColorsConfig, ThemeName};
+use codexio::theme::Theme;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use colored::Color;
+
+    #[test]
+    fn test_builtin_theme_without_overrides() {
+        let theme = Theme::from_config(ThemeName::HighContrast, None).unwrap();
+        assert_eq!(theme, Theme::builtin(ThemeName::HighContrast));
+    }
+
+    #[test]
+    fn test_color_overrides() {
+        let colors = ColorsConfig {
+            success: Some("bright magenta".to_string()),
+            ..Default::default()
+        };
+        let theme = Theme::from_config(ThemeName::Default, Some(&colors)).unwrap();
+        assert_eq!(theme.success, Color::BrightMagenta);
+        assert_eq!(theme.error, Color::Red);
+    }
+
+    #[test]
+    fn test_unknown_color_is_an_error() {
+        let colors = ColorsConfig {
+            info: Some("not-a-color".to_string()),
+            ..Default::default()
+        };
+        assert!(Theme::from_config(ThemeName::Default, Some(&colors)).is_err());
+    }
+}
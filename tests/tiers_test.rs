@@ -0,0 +1,106 @@
+use codexio::tiers::{fit_tiers, ordered_tiers, TierStatus, UNTIERED};
+use codexio::token::get_tokenizer;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+
+    /// Returns the JSON representation of a file of a tier with a token count.
+    fn file(path: &str, tier: Option<usize>, tokens: usize) -> Value {
+        let mut file = json!({ "path": path, "code": "code", "size": tokens * 4, "token_count": tokens });
+        if let Some(tier) = tier {
+            file["tier"] = json!(tier);
+        }
+        file
+    }
+
+    #[test]
+    fn test_ordered_tiers() {
+        let tiers = HashMap::from([
+            ("tier10".to_string(), vec!["**/docs/**".to_string()]),
+            ("tier2".to_string(), vec!["**/src/**".to_string()]),
+            ("tier1".to_string(), vec!["**/src/core/**".to_string()]),
+        ]);
+        let names: Vec<String> = ordered_tiers(&tiers).unwrap().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, vec!["tier1", "tier2", "tier10"]);
+
+        let invalid = HashMap::from([("core".to_string(), vec!["**/src/core/**".to_string()])]);
+        assert!(ordered_tiers(&invalid).unwrap_err().to_string().contains("'core'"));
+    }
+
+    #[test]
+    fn test_fit_tiers() {
+        let bpe = get_tokenizer(&None);
+        let names = vec!["tier1".to_string(), "tier2".to_string()];
+        let mut files = vec![
+            file("core.rs", Some(0), 400),
+            file("main.rs", Some(1), 800),
+            file("lib.rs", Some(1), 800),
+            file("notes.md", None, 300),
+        ];
+
+        let reports = fit_tiers(&mut files, &names, 500, bpe);
+        let statuses: Vec<_> = reports.iter().map(|report| (report.name.as_str(), report.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("tier1", TierStatus::Full),
+                ("tier2", TierStatus::Summarized),
+                (UNTIERED, TierStatus::Summarized),
+            ]
+        );
+        assert_eq!(reports[0].tokens, 400);
+        assert_eq!(files.len(), 4);
+        assert_eq!(files[0]["code"], "code");
+        assert_eq!(files[1]["summarized"], true);
+        assert!(files[1]["code"].as_str().unwrap().starts_with("<summarized for the token budget, "));
+        assert!(reports.iter().map(|report| report.tokens).sum::<usize>() <= 500);
+    }
+
+    #[test]
+    fn test_fit_tiers_summarizes_before_dropping() {
+        let bpe = get_tokenizer(&None);
+        let names: Vec<String> = (1..=4).map(|tier| format!("tier{}", tier)).collect();
+        let mut files = vec![
+            file("core.rs", Some(0), 400),
+            file("main.rs", Some(1), 800),
+            file("lib.rs", Some(2), 800),
+        ];
+        files.extend((0..10).map(|index| file(&format!("tests/test{}.rs", index), Some(3), 800)));
+        files.push(file("notes.md", None, 800));
+
+        // The untiered placeholder would fit, but nothing after a dropped tier is kept
+        let reports = fit_tiers(&mut files, &names, 500, bpe);
+        let statuses: Vec<_> = reports.iter().map(|report| (report.name.as_str(), report.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("tier1", TierStatus::Full),
+                ("tier2", TierStatus::Summarized),
+                ("tier3", TierStatus::Summarized),
+                ("tier4", TierStatus::Dropped),
+                (UNTIERED, TierStatus::Dropped),
+            ]
+        );
+        let paths: Vec<_> = files.iter().map(|file| file["path"].as_str().unwrap()).collect();
+        assert_eq!(paths, vec!["core.rs", "main.rs", "lib.rs"]);
+    }
+
+    #[test]
+    fn test_fit_tiers_drops_lowest_tiers() {
+        let bpe = get_tokenizer(&None);
+        let names = vec!["tier1".to_string(), "tier2".to_string()];
+        let mut files = vec![file("core.rs", Some(0), 400), file("main.rs", Some(1), 800)];
+
+        let reports = fit_tiers(&mut files, &names, 401, bpe);
+        assert_eq!(reports[0].status, TierStatus::Full);
+        assert_eq!(reports[1].status, TierStatus::Dropped);
+        assert_eq!(reports[1].files, 1);
+        // No files match no tier, so there is no untiered report
+        assert_eq!(reports.len(), 2);
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0]["path"], "core.rs");
+    }
+}
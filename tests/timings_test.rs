@@ -0,0 +1,22 @@
+use codexio::timings::Timings;
+use std::time::Duration;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timings_add_up_per_stage() {
+        let timings = Timings::default();
+        timings.record("traversal", Duration::from_millis(5));
+        timings.record("git", Duration::from_millis(2));
+        timings.record("traversal", Duration::from_millis(3));
+
+        let stages = timings.stages();
+        assert_eq!(stages.len(), 2);
+        assert_eq!(stages[0].name, "traversal");
+        assert_eq!(stages[0].calls, 2);
+        assert_eq!(stages[0].total, Duration::from_millis(8));
+        assert_eq!(stages[1].name, "git");
+    }
+}
@@ -0,0 +1,17 @@
+use codexio::token::estimate_tokens;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(Path::new("notes"), 400), 100);
+        assert_eq!(estimate_tokens(Path::new("README.md"), 450), 100);
+        assert_eq!(estimate_tokens(Path::new("data.JSON"), 300), 100);
+        assert_eq!(estimate_tokens(Path::new("src/main.rs"), 360), 100);
+        assert_eq!(estimate_tokens(Path::new("src/main.rs"), 1), 1);
+        assert_eq!(estimate_tokens(Path::new("empty.txt"), 0), 0);
+    }
+}
@@ -0,0 +1,64 @@
+use codexio::options::Options;
+use codexio::path::RootPatterns;
+use codexio::workspace_file::{apply_workspace_file, load_workspace_file};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_load_workspace_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("workspace.yaml");
+        fs::write(
+            &path,
+            "roots:\n  - path: ../api\n    include: [\"src/**\"]\n  - path: web\ntemplate: prompt.hbs\nexclude: [\"**/*.snap\"]\n",
+        )
+        .unwrap();
+
+        let workspace = load_workspace_file(&path).unwrap();
+        assert_eq!(workspace.roots.len(), 2);
+        assert_eq!(workspace.roots[0].path, dir.path().join("../api"));
+        assert_eq!(workspace.roots[0].include, vec!["src/**".to_string()]);
+        assert!(workspace.roots[1].exclude.is_empty());
+        assert_eq!(workspace.config.template, Some(dir.path().join("prompt.hbs")));
+
+        let mut options = Options {
+            exclude: Some("*.log".to_string()),
+            ..Options::default()
+        };
+        apply_workspace_file(&mut options, &path).unwrap();
+        assert_eq!(options.path, vec![dir.path().join("../api"), dir.path().join("web")]);
+        assert_eq!(options.workspace_roots.len(), 2);
+        assert!(options.relative_paths);
+        // The command line takes precedence over the workspace file
+        assert_eq!(options.exclude.as_deref(), Some("*.log"));
+    }
+
+    #[test]
+    fn test_load_workspace_file_errors() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("workspace.yaml");
+        assert!(load_workspace_file(&path).is_err());
+
+        fs::write(&path, "roots: []\n").unwrap();
+        assert!(load_workspace_file(&path).is_err());
+
+        fs::write(&path, "roots:\n  - path: api\n    includes: [\"src/**\"]\n").unwrap();
+        assert!(load_workspace_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_root_patterns_anchored() {
+        let patterns = RootPatterns::anchored(
+            Path::new("/work/api[v2]"),
+            &["src/**".to_string(), "!src/gen/**".to_string()],
+            &["*.snap".to_string()],
+        );
+        assert_eq!(patterns.include, vec!["/work/api[[]v2[]]/src/**", "!/work/api[[]v2[]]/src/gen/**"]);
+        assert_eq!(patterns.exclude, vec!["/work/api[[]v2[]]/*.snap"]);
+    }
+}
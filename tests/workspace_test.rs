@@ -0,0 +1,56 @@
+use codexio::workspace::{write_atomic, Workspace};
+use std::fs;
+use tempfile::TempDir;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("output.txt");
+        fs::write(&path, "old content").unwrap();
+
+        write_atomic(&path, b"new content").expect("Failed to write file");
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new content");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let path = temp_dir.path().join("deploy.sh");
+        fs::write(&path, "old content").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        write_atomic(&path, b"new content").expect("Failed to write file");
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o755);
+
+        // A new file gets the same mode as one created by `fs::write`
+        let reference = temp_dir.path().join("reference.txt");
+        fs::write(&reference, "content").unwrap();
+        let created = temp_dir.path().join("created.txt");
+        write_atomic(&created, b"content").expect("Failed to write file");
+        assert_eq!(
+            fs::metadata(&created).unwrap().permissions().mode() & 0o777,
+            fs::metadata(&reference).unwrap().permissions().mode() & 0o777
+        );
+    }
+
+    #[test]
+    fn test_workspace_is_removed_on_drop() {
+        let workspace = Workspace::new().expect("Failed to create workspace");
+        let first = workspace.create_dir().unwrap();
+        let second = workspace.create_dir().unwrap();
+        assert_ne!(first, second);
+
+        let path = workspace.path().to_path_buf();
+        drop(workspace);
+        assert!(!path.exists());
+    }
+}